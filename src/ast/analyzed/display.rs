@@ -1,7 +1,8 @@
 //! Formatting functions for analyzed PIL files.
 //!
-//! These are not meant to be 1-1 reproductions, they will have errors.
-//! Do not use this to re-generate PIL files!
+//! The output is not a 1-1 reproduction of the source (names are absolute,
+//! declarations are split and expressions are condensed), but it is valid PIL:
+//! Analyzing the printed output again results in the same printed output.
 
 use std::{
     fmt::{Display, Formatter, Result},
@@ -9,7 +10,10 @@ use std::{
 };
 
 use itertools::Itertools;
-use crate::ast::parsed::{display::format_type_args, LambdaExpression, TypedExpression};
+use crate::ast::parsed::{
+    display::{format_type_args, quote},
+    LambdaExpression, TypedExpression,
+};
 
 use crate::ast::{parsed::FunctionKind, writeln_indented, writeln_indented_by};
 
@@ -24,7 +28,7 @@ impl<T: Display> Display for Analyzed<T> {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         let (mut current_namespace, mut current_degree) = (AbsoluteSymbolPath::default(), None);
         let mut update_namespace =
-            |name: &str, degree: Option<DegreeType>, f: &mut Formatter<'_>| {
+            |name: &str, degree: Option<DegreeRange>, f: &mut Formatter<'_>| {
                 let mut namespace =
                     AbsoluteSymbolPath::default().join(SymbolPath::from_str(name).unwrap());
                 let name = namespace.pop().unwrap();
@@ -73,11 +77,17 @@ impl<T: Display> Display for Analyzed<T> {
                                     Some(FunctionValueDefinition::Expression(
                                         TypedExpression { e, type_scheme },
                                     )) => {
+                                        // Declaring the type `expr` would turn the symbol
+                                        // into an intermediate column, so we omit it.
+                                        let type_scheme = type_scheme
+                                            .as_ref()
+                                            .filter(|ts| **ts != Type::Expr.into())
+                                            .cloned();
                                         writeln_indented(
                                             f,
                                             format!(
                                                 "let{} = {e};",
-                                                format_type_scheme_around_name(&name, type_scheme)
+                                                format_type_scheme_around_name(&name, &type_scheme)
                                             ),
                                         )?;
                                     }
@@ -92,7 +102,10 @@ impl<T: Display> Display for Analyzed<T> {
                                     Some(FunctionValueDefinition::TraitDeclaration(
                                         trait_declaration,
                                     )) => {
-                                        writeln_indented(f, trait_declaration)?;
+                                        writeln_indented(
+                                            f,
+                                            trait_declaration.to_string_with_name(&name),
+                                        )?;
                                     }
                                     _ => {
                                         unreachable!("Invalid definition for symbol: {}", name)
@@ -122,7 +135,11 @@ impl<T: Display> Display for Analyzed<T> {
                 }
                 StatementIdentifier::PublicDeclaration(name) => {
                     let decl = &self.public_declarations[name];
-                    let (name, is_local) = update_namespace(&decl.name, None, f)?;
+                    let degree = self
+                        .definitions
+                        .get(&decl.polynomial.name)
+                        .and_then(|(symbol, _)| symbol.degree);
+                    let (name, is_local) = update_namespace(&decl.name, degree, f)?;
                     writeln_indented_by(
                         f,
                         format_public_declaration(&name, decl),
@@ -130,7 +147,11 @@ impl<T: Display> Display for Analyzed<T> {
                     )?;
                 }
                 StatementIdentifier::Identity(i) => {
-                    writeln_indented(f, &self.identities[*i])?;
+                    let identity = &self.identities[*i];
+                    if let Some(label) = &identity.label {
+                        writeln_indented(f, format!("#[label({})]", quote(label)))?;
+                    }
+                    writeln_indented(f, identity)?;
                 }
             }
         }
@@ -204,6 +225,20 @@ fn format_public_declaration(name: &str, decl: &PublicDeclaration) -> String {
     )
 }
 
+impl Display for CircuitStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        writeln!(f, "namespaces: {}", self.namespaces)?;
+        writeln!(f, "witness columns: {}", self.witness_columns)?;
+        writeln!(f, "fixed columns: {}", self.fixed_columns)?;
+        writeln!(f, "intermediate columns: {}", self.intermediate_columns)?;
+        writeln!(f, "polynomial identities: {}", self.polynomial_identities)?;
+        writeln!(f, "lookups: {}", self.lookups)?;
+        writeln!(f, "permutations: {}", self.permutations)?;
+        writeln!(f, "connections: {}", self.connections)?;
+        write!(f, "degree: {}", self.degrees.iter().format(", "))
+    }
+}
+
 impl Display for FunctionValueDefinition {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         match self {
@@ -511,7 +546,7 @@ mod test {
         };
 
         let (input, expected) = &(&wrap(input), &wrap(expected));
-        let analyzed = analyze_string::<GoldilocksField>(input);
+        let analyzed = analyze_string::<GoldilocksField>(input).unwrap();
         let printed = analyzed.to_string();
 
         assert_eq!(expected.trim(), printed.trim());
@@ -608,4 +643,70 @@ mod test {
             test_paren(&["x[42]"], &test_case);
         }
     }
+
+    fn assert_reanalyzes(input: &str) {
+        let printed = analyze_string::<GoldilocksField>(input)
+            .unwrap()
+            .to_string();
+        let reprinted = analyze_string::<GoldilocksField>(&printed)
+            .unwrap_or_else(|e| panic!("{e}\n{printed}"))
+            .to_string();
+        assert_eq!(printed, reprinted);
+    }
+
+    #[test]
+    fn reanalyze_printed() {
+        assert_reanalyzes(
+            r#"
+    let N: int = 8;
+    namespace Main(N);
+        let K: int = 3;
+        let f: int -> int = |i| i + K;
+        col fixed ONE = [1]*;
+        col fixed SEQ(i) { f(i) };
+        col fixed ARR = [1, 2] + [0]*;
+        col witness x, y[2];
+        col witness stage(1) z;
+        col inter = x + y[0];
+        let arr: expr[2] = [x, y[1]];
+        public out = y[1](N - 1);
+        x' = inter * ONE + arr[1];
+        [x, y[0]] in [SEQ, ONE];
+        ONE $ [x] is ONE $ [y[1]];
+        [x, y[0]] connect [y[1], z];
+    namespace Other(4);
+        enum E { A, B(int) }
+        col witness w;
+        w = Main.x;
+"#,
+        );
+        assert_reanalyzes(
+            r#"
+    namespace N(16);
+        trait Add2<T> { add2: T, T -> T, }
+        let g: int, int -> int = |a, b| match a { 0 => b, _ => if a < b { -a } else { a * b } };
+        let h: int -> string = |i| { let s = "x\n"; s };
+        col witness a, b;
+        let c: expr[2] = [a + 1, b - 2];
+        let make = constr || { let t; t = a; t };
+        make() = b;
+        -a = -(b + 2) * 3;
+        a * (1 - a) = 0;
+"#,
+        );
+        assert_reanalyzes(
+            r#"
+    namespace X(4);
+        enum Opt<T> { None, Some(T) }
+        let<T> unwrap_or: Opt<T>, T -> T = |o, d| match o { Opt::Some(x) => x, Opt::None => d };
+        let v: int = unwrap_or::<int>(Opt::Some(-3), 4);
+        let t: (int, string) = (1, "a" + "b");
+        let nested: int[][] = [[1, 2], [], [3]];
+        let p: int -> int = |x| match x { -1 => 0, 2 => 1, _ => x };
+        let<T: Add> dbl: T -> T = |x| x + x;
+        col witness w[3];
+        w[0] = dbl::<expr>(w[1]) + w[2]';
+"#,
+        );
+    }
 }
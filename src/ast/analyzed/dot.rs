@@ -0,0 +1,138 @@
+//! Export of the dependencies between columns and identities as a Graphviz DOT graph.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Write};
+
+use crate::ast::parsed::visitor::AllChildren;
+
+use super::*;
+
+impl<T: Display> Analyzed<T> {
+    /// Returns a Graphviz DOT graph with a node for every column and every identity.
+    /// Columns are grouped by namespace and array columns are a single node.
+    /// There are edges from every identity and every intermediate column
+    /// to the columns it references directly.
+    pub fn to_dot(&self) -> String {
+        let columns = self
+            .committed_polys_in_source_order()
+            .into_iter()
+            .chain(self.constant_polys_in_source_order())
+            .map(|(symbol, _)| symbol)
+            .chain(
+                self.intermediate_polys_in_source_order()
+                    .into_iter()
+                    .map(|(symbol, _)| symbol),
+            )
+            .collect::<Vec<_>>();
+        let names_by_id = columns
+            .iter()
+            .flat_map(|symbol| {
+                symbol
+                    .array_elements()
+                    .map(|(_, id)| (id, symbol.absolute_name.as_str()))
+            })
+            .collect::<HashMap<_, _>>();
+        let referenced = |e: &AlgebraicExpression<T>| match e {
+            AlgebraicExpression::Reference(r) => names_by_id.get(&r.poly_id).copied(),
+            _ => None,
+        };
+
+        let mut namespaces = BTreeMap::<&str, Vec<&Symbol>>::new();
+        for symbol in &columns {
+            let namespace = symbol
+                .absolute_name
+                .rsplit_once('.')
+                .map(|(namespace, _)| namespace)
+                .unwrap_or_default();
+            namespaces.entry(namespace).or_default().push(symbol);
+        }
+
+        let mut dot = "digraph pil {\n".to_string();
+        for (namespace, symbols) in namespaces {
+            writeln!(dot, "  subgraph \"cluster_{namespace}\" {{").unwrap();
+            writeln!(dot, "    label = \"{namespace}\";").unwrap();
+            for symbol in symbols {
+                let shape = match symbol.kind {
+                    SymbolKind::Poly(PolynomialType::Committed) => "box",
+                    SymbolKind::Poly(PolynomialType::Constant) => "box, style=rounded",
+                    _ => "ellipse",
+                };
+                writeln!(dot, "    \"{}\" [shape={shape}];", symbol.absolute_name).unwrap();
+            }
+            dot.push_str("  }\n");
+        }
+        for (symbol, values) in self.intermediate_polys_in_source_order() {
+            let targets = values
+                .iter()
+                .flat_map(|value| value.all_children())
+                .filter_map(referenced)
+                .collect::<BTreeSet<_>>();
+            for target in targets {
+                writeln!(dot, "  \"{}\" -> \"{target}\";", symbol.absolute_name).unwrap();
+            }
+        }
+        for identity in &self.identities {
+            let label = identity.to_string().replace('"', "\\\"");
+            writeln!(
+                dot,
+                "  \"identity_{}\" [shape=diamond, label=\"{label}\"];",
+                identity.id
+            )
+            .unwrap();
+            let targets = identity
+                .all_children()
+                .filter_map(referenced)
+                .collect::<BTreeSet<_>>();
+            for target in targets {
+                writeln!(dot, "  \"identity_{}\" -> \"{target}\";", identity.id).unwrap();
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::number::GoldilocksField;
+    use crate::pil_analyzer::analyze_string;
+
+    #[test]
+    fn to_dot() {
+        let input = r#"namespace A(4);
+    col witness x, y[2];
+    col fixed F = [1, 0]*;
+    col sq = x * x;
+    x' = y[1] + sq;
+namespace B(4);
+    col witness z;
+    [z] in [A.F];
+"#;
+        let dot = analyze_string::<GoldilocksField>(input).unwrap().to_dot();
+        assert_eq!(
+            dot,
+            r#"digraph pil {
+  subgraph "cluster_A" {
+    label = "A";
+    "A.x" [shape=box];
+    "A.y" [shape=box];
+    "A.F" [shape=box, style=rounded];
+    "A.sq" [shape=ellipse];
+  }
+  subgraph "cluster_B" {
+    label = "B";
+    "B.z" [shape=box];
+  }
+  "A.sq" -> "A.x";
+  "identity_0" [shape=diamond, label="A.x' = A.y[1] + A.sq;"];
+  "identity_0" -> "A.sq";
+  "identity_0" -> "A.x";
+  "identity_0" -> "A.y";
+  "identity_1" [shape=diamond, label="[B.z] in [A.F];"];
+  "identity_1" -> "A.F";
+  "identity_1" -> "B.z";
+}
+"#
+        );
+    }
+}
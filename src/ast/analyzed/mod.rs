@@ -1,22 +1,30 @@
 mod display;
+mod dot;
 pub mod visitor;
 
 use std::cmp::max;
 use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fmt::Display;
+use std::fs;
 use std::hash::{Hash, Hasher};
+use std::io;
 use std::iter::{self, empty};
 use std::ops::{self, ControlFlow};
+use std::path::Path;
+use std::str::FromStr;
 use std::sync::Arc;
 
 use itertools::Itertools;
-use crate::number::{DegreeType, FieldElement};
+use crate::number::{BigUint, DegreeType, FieldElement, LargeInt};
+use crate::parser::test_utils::ClearSourceRefs;
 use crate::parser_util::SourceRef;
 use schemars::JsonSchema;
-use serde::{Deserialize, Serialize};
+use serde::de::IgnoredAny;
+use serde::{Deserialize, Serialize, Serializer};
 
+use crate::ast::parsed::asm::{AbsoluteSymbolPath, SymbolPath};
 use crate::ast::parsed::types::{ArrayType, Type, TypeBounds, TypeScheme};
-use crate::ast::parsed::visitor::{Children, ExpressionVisitable};
+use crate::ast::parsed::visitor::{AllChildren, Children, ExpressionVisitable};
 pub use crate::ast::parsed::BinaryOperator;
 pub use crate::ast::parsed::UnaryOperator;
 use crate::ast::parsed::{
@@ -34,17 +42,145 @@ pub enum StatementIdentifier {
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct Analyzed<T> {
+    #[serde(serialize_with = "serialize_sorted_map")]
     pub definitions: HashMap<String, (Symbol, Option<FunctionValueDefinition>)>,
+    #[serde(serialize_with = "serialize_sorted_map")]
     pub public_declarations: HashMap<String, PublicDeclaration>,
+    #[serde(serialize_with = "serialize_sorted_map")]
     pub intermediate_columns: HashMap<String, (Symbol, Vec<AlgebraicExpression<T>>)>,
     pub identities: Vec<Identity<SelectedExpressions<AlgebraicExpression<T>>>>,
     /// The order in which definitions and identities
     /// appear in the source.
     pub source_order: Vec<StatementIdentifier>,
     /// Symbols from the core that were added automatically but will not be printed.
+    #[serde(serialize_with = "serialize_sorted_set")]
     pub auto_added_symbols: HashSet<String>,
 }
 
+/// Compares two analyzed files structurally, i.e. ignoring source references.
+impl<T: PartialEq + Clone> PartialEq for Analyzed<T> {
+    fn eq(&self, other: &Self) -> bool {
+        let (left, right) = (self.without_source_refs(), other.without_source_refs());
+        left.definitions == right.definitions
+            && left.public_declarations == right.public_declarations
+            && left.intermediate_columns == right.intermediate_columns
+            && left.identities == right.identities
+            && left.source_order == right.source_order
+            && left.auto_added_symbols == right.auto_added_symbols
+    }
+}
+
+/// Serializes a hash map with its entries sorted by key, so that the output is deterministic.
+fn serialize_sorted_map<S: Serializer, K: Ord + Serialize, V: Serialize>(
+    map: &HashMap<K, V>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_map(map.iter().sorted_by(|(a, _), (b, _)| a.cmp(b)))
+}
+
+/// Serializes a hash set with its elements sorted, so that the output is deterministic.
+fn serialize_sorted_set<S: Serializer, K: Ord + Serialize>(
+    set: &HashSet<K>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(set.iter().sorted())
+}
+
+/// Options for the JSON export of [`Analyzed`] via [`Analyzed::to_json`],
+/// [`Analyzed::to_json_value`] and [`Analyzed::write_json`].
+/// By default, the output is compact and exactly the serde serialization of [`Analyzed`].
+/// The additional fields can be combined freely. The result can still be deserialized,
+/// the additional fields are ignored, except for the generated labels.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct JsonExportOptions {
+    /// Pretty-print the output instead of writing it without any whitespace.
+    /// The compact form is considerably smaller for large programs.
+    pub pretty: bool,
+    /// Add a `degree` field to every identity as computed by [`Analyzed::identity_degrees`].
+    pub degrees: bool,
+    /// Add a `label` field to every identity, which is `identity_<id>` for identities
+    /// that have not been labeled in the source.
+    /// When the result is deserialized, these generated labels are kept.
+    pub labels: bool,
+    /// Add a `span` field to every identity and every column with the `file`, `line`
+    /// and `col` of its declaration, or `null` if the location is not known.
+    /// The location refers to the file the element was declared in, even if that file
+    /// was included from a different file.
+    pub spans: bool,
+    /// Add a top-level `publics` array that contains the public declarations in the
+    /// order they were declared, including their names and the column and row they reference.
+    pub publics: bool,
+    /// Add a top-level `stages` object that maps each column to its stage as computed
+    /// by [`Analyzed::column_stages`].
+    pub stages: bool,
+    /// Add a top-level `witness_definitions` object that maps the name of each witness
+    /// column that has a definition (a query function) to that definition as PIL source.
+    /// Witness columns without a definition are omitted.
+    pub witness_definitions: bool,
+}
+
+impl JsonExportOptions {
+    fn has_additional_fields(&self) -> bool {
+        let JsonExportOptions {
+            pretty: _,
+            degrees,
+            labels,
+            spans,
+            publics,
+            stages,
+            witness_definitions,
+        } = *self;
+        degrees || labels || spans || publics || stages || witness_definitions
+    }
+}
+
+/// Returns the file, line and column of the source reference as a JSON object,
+/// or `null` if the line and column are not known.
+fn span_to_json(source: &SourceRef) -> serde_json::Value {
+    let Some((line, col)) = source.line_col() else {
+        return serde_json::Value::Null;
+    };
+    serde_json::Map::from_iter([
+        ("file".to_string(), source.file_name.as_deref().into()),
+        ("line".to_string(), line.into()),
+        ("col".to_string(), col.into()),
+    ])
+    .into()
+}
+
+fn json_value_to_string(value: &serde_json::Value, pretty: bool) -> Result<String, String> {
+    if pretty {
+        serde_json::to_string_pretty(value)
+    } else {
+        serde_json::to_string(value)
+    }
+    .map_err(|e| format!("Failed to serialize analyzed to JSON: {e}"))
+}
+
+impl<T: Clone> Analyzed<T> {
+    /// Returns a copy where all source references are replaced by unknown ones.
+    fn without_source_refs(&self) -> Self {
+        let mut result = self.clone();
+        for (symbol, value) in result.definitions.values_mut() {
+            symbol.source = SourceRef::unknown();
+            value
+                .iter_mut()
+                .flat_map(|value| value.children_mut())
+                .for_each(|e| e.clear_source_refs());
+        }
+        for decl in result.public_declarations.values_mut() {
+            decl.source = SourceRef::unknown();
+        }
+        for (symbol, _) in result.intermediate_columns.values_mut() {
+            symbol.source = SourceRef::unknown();
+        }
+        for identity in &mut result.identities {
+            identity.source = SourceRef::unknown();
+        }
+        result
+    }
+}
+
 impl<T> Analyzed<T> {
     /// Returns the degree common among all symbols that have an explicit degree.
     ///
@@ -52,16 +188,25 @@ impl<T> Analyzed<T> {
     ///
     /// Panics if there is no common degree or if there are no symbols
     pub fn degree(&self) -> DegreeType {
+        self.common_degree().unwrap()
+    }
+
+    /// Returns the degree (number of rows) common among all symbols that have
+    /// an explicit degree, i.e. the value `std::prover::degree()` evaluates to.
+    /// Returns `None` if no symbol has an explicit degree, if the degrees
+    /// of different namespaces differ or if the common degree is a range.
+    pub fn common_degree(&self) -> Option<DegreeType> {
         self.definitions
             .values()
             .filter_map(|(symbol, _)| symbol.degree)
             .unique()
             .exactly_one()
-            .unwrap()
+            .ok()?
+            .try_into_unique()
     }
 
-    /// Returns the set of all explicit degrees in this [`Analyzed<T>`].
-    pub fn degrees(&self) -> HashSet<DegreeType> {
+    /// Returns the set of all explicit degrees (or degree ranges) in this [`Analyzed<T>`].
+    pub fn degrees(&self) -> HashSet<DegreeRange> {
         self.definitions
             .values()
             .filter_map(|(symbol, _)| symbol.degree)
@@ -156,6 +301,42 @@ impl<T> Analyzed<T> {
             .collect()
     }
 
+    /// Returns the declared or inferred type scheme of the symbol with the given
+    /// absolute name, or `None` if there is no such symbol or it is a type or trait
+    /// declaration.
+    pub fn symbol_type_scheme(&self, name: &str) -> Option<TypeScheme> {
+        if let Some((symbol, _)) = self.intermediate_columns.get(name) {
+            let ty = if symbol.is_array() {
+                Type::Array(ArrayType {
+                    base: Box::new(Type::Expr),
+                    length: None,
+                })
+            } else {
+                Type::Expr
+            };
+            return Some(ty.into());
+        }
+        match self.definitions.get(name)? {
+            (
+                _,
+                Some(
+                    FunctionValueDefinition::TypeDeclaration(_)
+                    | FunctionValueDefinition::TraitDeclaration(_),
+                ),
+            ) => None,
+            (symbol, value) => type_from_definition(symbol, value),
+        }
+    }
+
+    /// Returns the type of the symbol with the given absolute name, like
+    /// [`Analyzed::symbol_type_scheme`]. Returns `None` for generic symbols,
+    /// use [`Analyzed::symbol_type_scheme`] for those.
+    pub fn symbol_type(&self, name: &str) -> Option<Type> {
+        self.symbol_type_scheme(name)
+            .filter(|scheme| scheme.vars.is_empty())
+            .map(|scheme| scheme.ty)
+    }
+
     fn declaration_type_count(&self, poly_type: PolynomialType) -> usize {
         self.definitions
             .iter()
@@ -168,6 +349,107 @@ impl<T> Analyzed<T> {
             .sum()
     }
 
+    /// Checks the structural consistency of this [`Analyzed<T>`], which is useful
+    /// for data that was deserialized or modified after analysis:
+    /// All polynomial IDs referenced in identities, intermediate columns and
+    /// public declarations have to exist, all degrees have to be powers of two
+    /// and the rows of public declarations have to be in range.
+    ///
+    /// Returns all problems found.
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors = vec![];
+
+        let poly_symbols = self
+            .committed_polys_in_source_order()
+            .into_iter()
+            .chain(self.constant_polys_in_source_order())
+            .map(|(symbol, _)| symbol)
+            .chain(
+                self.intermediate_polys_in_source_order()
+                    .into_iter()
+                    .map(|(symbol, _)| symbol),
+            )
+            .collect::<Vec<_>>();
+        let poly_ids = poly_symbols
+            .iter()
+            .flat_map(|symbol| symbol.array_elements().map(|(_, poly_id)| poly_id))
+            .collect::<BTreeSet<_>>();
+
+        for symbol in &poly_symbols {
+            if let Some(degree) = symbol.degree {
+                if !degree.min.is_power_of_two() || !degree.max.is_power_of_two() {
+                    errors.push(format!(
+                        "Degree of {} is not a power of two: {degree}",
+                        symbol.absolute_name
+                    ));
+                }
+            }
+        }
+
+        let mut check_reference = |reference: &AlgebraicReference, context: &str| {
+            if !poly_ids.contains(&reference.poly_id) {
+                errors.push(format!(
+                    "Reference to unknown polynomial {} ({:?} with ID {}) in {context}",
+                    reference.name, reference.poly_id.ptype, reference.poly_id.id
+                ));
+            }
+        };
+        for identity in &self.identities {
+            for e in identity.all_children() {
+                if let AlgebraicExpression::Reference(reference) = e {
+                    check_reference(reference, &format!("identity {}", identity.id));
+                }
+            }
+        }
+        for (symbol, values) in self.intermediate_polys_in_source_order() {
+            for e in values.iter().flat_map(|v| v.all_children()) {
+                if let AlgebraicExpression::Reference(reference) = e {
+                    let context = format!("intermediate column {}", symbol.absolute_name);
+                    check_reference(reference, &context);
+                }
+            }
+        }
+
+        for (name, public) in self.public_declarations_in_source_order() {
+            let Some(poly_id) = public.polynomial.poly_id else {
+                errors.push(format!(
+                    "Public declaration {name} references unresolved polynomial {}",
+                    public.polynomial.name
+                ));
+                continue;
+            };
+            let poly_id = PolyID {
+                id: poly_id.id + public.array_index.unwrap_or_default() as u64,
+                ..poly_id
+            };
+            let Some(symbol) = poly_symbols
+                .iter()
+                .find(|symbol| symbol.array_elements().any(|(_, id)| id == poly_id))
+            else {
+                errors.push(format!(
+                    "Public declaration {name} references unknown polynomial {}",
+                    public.referenced_poly_name()
+                ));
+                continue;
+            };
+            // The row has to exist for every degree in the range.
+            if let Some(degree) = symbol.degree {
+                if public.index >= degree.min {
+                    errors.push(format!(
+                        "Public declaration {name} refers to row {} but the degree of {} is {degree}",
+                        public.index, symbol.absolute_name
+                    ));
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
     /// Returns the type (scheme) of a symbol with the given name.
     pub fn type_of_symbol(&self, name: &str) -> TypeScheme {
         let (sym, value) = &self.definitions[name];
@@ -322,6 +604,42 @@ impl<T> Analyzed<T> {
             .filter_map(|(_poly, definition)| definition.as_mut())
             .for_each(|definition| definition.post_visit_expressions_mut(f))
     }
+
+    /// Returns the ID of every column by name, where array elements are named `name[i]`.
+    /// See [`PolyID`] for how the IDs are assigned.
+    pub fn poly_ids(&self) -> BTreeMap<String, PolyID> {
+        self.committed_polys_in_source_order()
+            .into_iter()
+            .chain(self.constant_polys_in_source_order())
+            .map(|(symbol, _)| symbol)
+            .chain(
+                self.intermediate_polys_in_source_order()
+                    .into_iter()
+                    .map(|(symbol, _)| symbol),
+            )
+            .flat_map(|symbol| symbol.array_elements())
+            .collect()
+    }
+
+    /// Returns a summary of every identity in source order, with its kind
+    /// and the columns it references directly (intermediate columns are not inlined).
+    pub fn identities(&self) -> impl Iterator<Item = IdentityInfo> + '_ {
+        self.identities
+            .iter()
+            .enumerate()
+            .map(|(index, identity)| IdentityInfo {
+                index,
+                id: identity.id,
+                kind: identity.kind,
+                columns: identity
+                    .all_children()
+                    .filter_map(|e| match e {
+                        AlgebraicExpression::Reference(r) => Some(r.name.clone()),
+                        _ => None,
+                    })
+                    .collect(),
+            })
+    }
 }
 
 impl<T: FieldElement> Analyzed<T> {
@@ -354,6 +672,258 @@ impl<T: FieldElement> Analyzed<T> {
     pub fn deserialize(bytes: &[u8]) -> Result<Self, String> {
         serde_cbor::from_slice(bytes).map_err(|e| format!("Failed to deserialize analyzed: {e}"))
     }
+
+    /// Writes the analyzed file to `path` in a binary format, so that it can be loaded
+    /// with [`Analyzed::read_from_file`] without parsing and analyzing the source again.
+    /// The file also contains the field modulus, which is checked when loading.
+    /// Unlike the JSON export, this format is not meant to be consumed by backends.
+    pub fn write_to_file(&self, path: &Path) -> Result<(), String> {
+        let bytes = serde_cbor::to_vec(&(T::modulus().to_arbitrary_integer(), self))
+            .map_err(|e| format!("Failed to serialize analyzed: {e}"))?;
+        fs::write(path, bytes).map_err(|e| format!("Failed to write {}: {e}", path.display()))
+    }
+
+    /// Loads an analyzed file written by [`Analyzed::write_to_file`].
+    /// Fails if it was written for a different field.
+    pub fn read_from_file(path: &Path) -> Result<Self, String> {
+        let bytes =
+            fs::read(path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+        let error = |e| format!("Failed to deserialize {}: {e}", path.display());
+        let (modulus, IgnoredAny): (BigUint, IgnoredAny) =
+            serde_cbor::from_slice(&bytes).map_err(error)?;
+        if modulus != T::modulus().to_arbitrary_integer() {
+            return Err(format!(
+                "{} was written for a field with modulus {modulus}, not {}.",
+                path.display(),
+                T::modulus()
+            ));
+        }
+        let (_, analyzed): (BigUint, Self) = serde_cbor::from_slice(&bytes).map_err(error)?;
+        Ok(analyzed)
+    }
+
+    /// Serializes to JSON, see [`JsonExportOptions`] for the output format
+    /// and the fields that can be added.
+    pub fn to_json(&self, options: &JsonExportOptions) -> Result<String, String> {
+        if options.has_additional_fields() {
+            return json_value_to_string(&self.to_json_value(options)?, options.pretty);
+        }
+        if options.pretty {
+            serde_json::to_string_pretty(self)
+        } else {
+            serde_json::to_string(self)
+        }
+        .map_err(|e| format!("Failed to serialize analyzed to JSON: {e}"))
+    }
+
+    /// Writes the same JSON as [`Analyzed::to_json`] to `writer`. Without additional
+    /// fields, the output is streamed without building the whole string in memory first.
+    /// Use this for large exports and wrap files in an [`io::BufWriter`].
+    pub fn write_json<W: io::Write>(
+        &self,
+        writer: W,
+        options: &JsonExportOptions,
+    ) -> Result<(), String> {
+        match (options.has_additional_fields(), options.pretty) {
+            (true, true) => serde_json::to_writer_pretty(writer, &self.to_json_value(options)?),
+            (true, false) => serde_json::to_writer(writer, &self.to_json_value(options)?),
+            (false, true) => serde_json::to_writer_pretty(writer, self),
+            (false, false) => serde_json::to_writer(writer, self),
+        }
+        .map_err(|e| format!("Failed to write analyzed as JSON: {e}"))
+    }
+
+    /// Serializes to a JSON value with the same structure as [`Analyzed::to_json`],
+    /// for embedding into a larger JSON document without re-parsing.
+    /// [`JsonExportOptions::pretty`] is ignored.
+    pub fn to_json_value(&self, options: &JsonExportOptions) -> Result<serde_json::Value, String> {
+        let mut value = serde_json::to_value(self)
+            .map_err(|e| format!("Failed to serialize analyzed to JSON: {e}"))?;
+        let identities = value["identities"].as_array_mut().unwrap();
+        let degrees = options.degrees.then(|| self.identity_degrees());
+        for (identity, json) in self.identities.iter().zip(identities) {
+            let json = json.as_object_mut().unwrap();
+            if let Some(degrees) = &degrees {
+                json.insert("degree".to_string(), degrees[&identity.id].into());
+            }
+            if options.labels {
+                json.insert("label".to_string(), identity.label_or_default().into());
+            }
+            if options.spans {
+                json.insert("span".to_string(), span_to_json(&identity.source));
+            }
+        }
+        if options.spans {
+            for (name, (symbol, _)) in &self.definitions {
+                if matches!(symbol.kind, SymbolKind::Poly(_)) {
+                    value["definitions"][name][0]
+                        .as_object_mut()
+                        .unwrap()
+                        .insert("span".to_string(), span_to_json(&symbol.source));
+                }
+            }
+            for (name, (symbol, _)) in &self.intermediate_columns {
+                value["intermediate_columns"][name][0]
+                    .as_object_mut()
+                    .unwrap()
+                    .insert("span".to_string(), span_to_json(&symbol.source));
+            }
+        }
+        let top_level = value.as_object_mut().unwrap();
+        if options.publics {
+            let publics = self
+                .public_declarations_in_source_order()
+                .into_iter()
+                .map(|(_, decl)| serde_json::to_value(decl))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| format!("Failed to serialize analyzed to JSON: {e}"))?;
+            top_level.insert("publics".to_string(), publics.into());
+        }
+        if options.stages {
+            let stages = self
+                .column_stages()
+                .into_iter()
+                .map(|(name, stage)| (name, stage.into()))
+                .collect::<serde_json::Map<_, _>>();
+            top_level.insert("stages".to_string(), stages.into());
+        }
+        if options.witness_definitions {
+            let definitions = self
+                .committed_polys_in_source_order()
+                .into_iter()
+                .filter_map(|(symbol, definition)| match definition {
+                    Some(FunctionValueDefinition::Expression(TypedExpression { e, .. })) => {
+                        Some((symbol.absolute_name.clone(), e.to_string().into()))
+                    }
+                    _ => None,
+                })
+                .collect::<serde_json::Map<_, _>>();
+            top_level.insert("witness_definitions".to_string(), definitions.into());
+        }
+        Ok(value)
+    }
+
+    /// Returns the degree of each identity by identity ID, after inlining
+    /// intermediate columns. Multiplication adds the degrees of its operands
+    /// and references to the next row count like references to the current row.
+    pub fn identity_degrees(&self) -> BTreeMap<u64, usize> {
+        self.identities_with_inlined_intermediate_polynomials()
+            .iter()
+            .map(|identity| (identity.id, identity.degree()))
+            .collect()
+    }
+
+    /// Returns the maximum degree of all identities (see [`Analyzed::identity_degrees`]),
+    /// or zero if there are no identities.
+    pub fn max_degree(&self) -> usize {
+        self.identity_degrees()
+            .into_values()
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Returns the stage of each column by name, where array elements are
+    /// named `name[i]`: The declared stage for witness columns (stage 0 if none is
+    /// declared), stage 0 for fixed columns and, for intermediate columns,
+    /// the latest stage of any column they reference.
+    pub fn column_stages(&self) -> BTreeMap<String, u32> {
+        let mut stages = self
+            .committed_polys_in_source_order()
+            .into_iter()
+            .chain(self.constant_polys_in_source_order())
+            .flat_map(|(symbol, _)| {
+                let stage = symbol.stage.unwrap_or_default();
+                symbol.array_elements().map(move |(_, id)| (id, stage))
+            })
+            .collect::<HashMap<_, _>>();
+        let intermediates = self
+            .intermediate_polys_in_source_order()
+            .into_iter()
+            .flat_map(|(symbol, values)| symbol.array_elements().zip(values))
+            .collect::<Vec<_>>();
+        let values = intermediates
+            .iter()
+            .map(|((_, id), value)| (*id, *value))
+            .collect::<HashMap<_, _>>();
+        for id in values.keys() {
+            intermediate_stage(*id, &values, &mut stages);
+        }
+
+        let names = self
+            .committed_polys_in_source_order()
+            .into_iter()
+            .chain(self.constant_polys_in_source_order())
+            .flat_map(|(symbol, _)| symbol.array_elements())
+            .chain(intermediates.into_iter().map(|(element, _)| element));
+        names
+            .map(|(name, id)| {
+                let stage = stages.get(&id).copied().unwrap_or_default();
+                (name, stage)
+            })
+            .collect()
+    }
+
+    /// Returns a summary of the size of the circuit.
+    pub fn stats(&self) -> CircuitStats {
+        let identity_count = |kind| {
+            self.identities
+                .iter()
+                .filter(|identity| identity.kind == kind)
+                .count()
+        };
+        CircuitStats {
+            namespaces: self
+                .definitions
+                .values()
+                .filter(|(symbol, _)| matches!(symbol.kind, SymbolKind::Poly(_)))
+                .map(|(symbol, _)| &symbol.absolute_name)
+                .chain(self.intermediate_columns.keys())
+                .map(|name| {
+                    AbsoluteSymbolPath::default()
+                        .join(SymbolPath::from_str(name).unwrap())
+                        .parent()
+                })
+                .collect::<BTreeSet<_>>()
+                .len(),
+            witness_columns: self.commitment_count(),
+            fixed_columns: self.constant_count(),
+            intermediate_columns: self.intermediate_count(),
+            polynomial_identities: identity_count(IdentityKind::Polynomial),
+            lookups: identity_count(IdentityKind::Plookup),
+            permutations: identity_count(IdentityKind::Permutation),
+            connections: identity_count(IdentityKind::Connect),
+            degrees: self.degrees().into_iter().collect(),
+        }
+    }
+}
+
+/// A summary of the size of a circuit, returned by [`Analyzed::stats`].
+/// Column counts include every element of column arrays.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CircuitStats {
+    /// The number of namespaces that contain columns.
+    pub namespaces: usize,
+    pub witness_columns: usize,
+    pub fixed_columns: usize,
+    pub intermediate_columns: usize,
+    pub polynomial_identities: usize,
+    pub lookups: usize,
+    pub permutations: usize,
+    pub connections: usize,
+    /// All explicit degrees (or degree ranges), usually just one.
+    pub degrees: BTreeSet<DegreeRange>,
+}
+
+/// A summary of an identity, returned by [`Analyzed::identities`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdentityInfo {
+    /// The position of the identity in [`Analyzed::identities`].
+    pub index: usize,
+    /// The globally unique ID of the identity.
+    pub id: u64,
+    pub kind: IdentityKind,
+    /// The names of the referenced columns, where array elements are named `name[i]`.
+    pub columns: BTreeSet<String>,
 }
 
 /// Takes identities as values and inlines intermediate polynomials everywhere, returning a vector of the updated identities
@@ -386,6 +956,37 @@ fn substitute_intermediate<T: Copy + Display>(
         .collect()
 }
 
+/// Returns the stage of the intermediate column with the given ID (see
+/// [`Analyzed::column_stages`]), storing it and those of the intermediate
+/// columns it references in `stages`.
+fn intermediate_stage<T>(
+    id: PolyID,
+    values: &HashMap<PolyID, &AlgebraicExpression<T>>,
+    stages: &mut HashMap<PolyID, u32>,
+) -> u32 {
+    if let Some(stage) = stages.get(&id) {
+        return *stage;
+    }
+    let Some(value) = values.get(&id) else {
+        return 0;
+    };
+    // Guard against cyclic definitions.
+    stages.insert(id, 0);
+    let stage = value
+        .all_children()
+        .filter_map(|e| match e {
+            AlgebraicExpression::Reference(r) => Some(match r.poly_id.ptype {
+                PolynomialType::Intermediate => intermediate_stage(r.poly_id, values, stages),
+                _ => stages.get(&r.poly_id).copied().unwrap_or_default(),
+            }),
+            _ => None,
+        })
+        .max()
+        .unwrap_or_default();
+    stages.insert(id, stage);
+    stage
+}
+
 /// Recursively inlines intermediate polynomials inside an expression and returns the new expression
 /// This uses a cache to avoid resolving an intermediate polynomial twice
 ///
@@ -482,7 +1083,7 @@ pub fn type_from_definition(
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct Symbol {
     pub id: u64,
     pub source: SourceRef,
@@ -490,7 +1091,47 @@ pub struct Symbol {
     pub stage: Option<u32>,
     pub kind: SymbolKind,
     pub length: Option<DegreeType>,
-    pub degree: Option<DegreeType>,
+    pub degree: Option<DegreeRange>,
+}
+
+/// The range of degrees (numbers of rows) a namespace supports. The actual degree
+/// is picked by the backend. A namespace with a single degree has `min == max`.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+pub struct DegreeRange {
+    pub min: DegreeType,
+    pub max: DegreeType,
+}
+
+impl From<DegreeType> for DegreeRange {
+    fn from(degree: DegreeType) -> Self {
+        DegreeRange {
+            min: degree,
+            max: degree,
+        }
+    }
+}
+
+impl DegreeRange {
+    pub fn is_unique(&self) -> bool {
+        self.min == self.max
+    }
+
+    /// Returns the degree if the range consists of a single degree.
+    pub fn try_into_unique(self) -> Option<DegreeType> {
+        self.is_unique().then_some(self.min)
+    }
+}
+
+impl Display for DegreeRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_unique() {
+            write!(f, "{}", self.min)
+        } else {
+            write!(f, "{}..{}", self.min, self.max)
+        }
+    }
 }
 
 impl Symbol {
@@ -552,7 +1193,7 @@ pub enum SymbolKind {
     Other(),
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub enum FunctionValueDefinition {
     Array(Vec<RepeatedArray>),
     Expression(TypedExpression),
@@ -617,7 +1258,7 @@ impl Children<Expression> for TraitFunction {
 }
 
 /// An array of elements that might be repeated.
-#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct RepeatedArray {
     /// The pattern to be repeated
     pattern: Vec<Expression>,
@@ -672,7 +1313,7 @@ impl Children<Expression> for RepeatedArray {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct PublicDeclaration {
     pub id: u64,
     pub source: SourceRef,
@@ -724,6 +1365,9 @@ pub struct Identity<SelectedExpressions> {
     pub id: u64,
     pub kind: IdentityKind,
     pub source: SourceRef,
+    /// The label given in the source using `#[label("...")]`, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
     /// For a simple polynomial identity, the selector contains
     /// the actual expression (see expression_for_poly_id).
     pub left: SelectedExpressions,
@@ -741,6 +1385,7 @@ impl<T> Identity<SelectedExpressions<AlgebraicExpression<T>>> {
             id,
             kind: IdentityKind::Polynomial,
             source,
+            label: None,
             left: SelectedExpressions {
                 selector: Some(identity),
                 expressions: vec![],
@@ -789,6 +1434,15 @@ impl<T> Identity<SelectedExpressions<AlgebraicExpression<T>>> {
     }
 }
 
+impl<S> Identity<S> {
+    /// Returns the label of the identity or `identity_<id>` if it has none.
+    pub fn label_or_default(&self) -> String {
+        self.label
+            .clone()
+            .unwrap_or_else(|| format!("identity_{}", self.id))
+    }
+}
+
 impl<R> Identity<parsed::SelectedExpressions<parsed::Expression<R>>> {
     /// Constructs an Identity from a polynomial identity (expression assumed to be identical zero).
     pub fn from_polynomial_identity(
@@ -800,6 +1454,7 @@ impl<R> Identity<parsed::SelectedExpressions<parsed::Expression<R>>> {
             id,
             kind: IdentityKind::Polynomial,
             source,
+            label: None,
             left: parsed::SelectedExpressions {
                 selector: Some(identity),
                 expressions: Box::new(ArrayLiteral { items: vec![] }.into()),
@@ -885,7 +1540,7 @@ impl<T> SelectedExpressions<AlgebraicExpression<T>> {
 pub type Expression = parsed::Expression<Reference>;
 pub type TypedExpression = crate::ast::parsed::TypedExpression<Reference, u64>;
 
-#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub enum Reference {
     LocalVar(u64, String),
     Poly(PolynomialReference),
@@ -1289,7 +1944,7 @@ impl<T> From<T> for AlgebraicExpression<T> {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct PolynomialReference {
     /// Name of the polynomial - just for informational purposes.
     /// Comparisons are based on polynomial ID.
@@ -1303,6 +1958,18 @@ pub struct PolynomialReference {
     pub type_args: Option<Vec<Type>>,
 }
 
+/// The ID of a column.
+///
+/// IDs are assigned separately for witness, fixed and intermediate columns, so only
+/// the combination of ID and type is unique. For each type, the IDs are contiguous,
+/// start at zero and follow the order in which the columns are declared, where included
+/// files come before the including file and the elements of a column array have
+/// consecutive IDs. Columns created during evaluation (e.g. by `std::prover::new_witness_col`)
+/// come after all declared columns of the same type, in the order of their creation.
+///
+/// As a consequence, the ID of a column only changes if a column of the same type
+/// is added, removed or resized before it, or if the columns are reordered.
+/// [`Analyzed::poly_ids`] returns the IDs of all columns by name.
 #[derive(
     Debug, Copy, Clone, PartialOrd, Ord, PartialEq, Eq, Serialize, Deserialize, JsonSchema,
 )]
@@ -1355,11 +2022,15 @@ impl Display for PolynomialType {
 
 #[cfg(test)]
 mod tests {
+    use itertools::Itertools;
+
+    use crate::number::{Bn254Field, GoldilocksField};
     use crate::parser_util::SourceRef;
+    use crate::pil_analyzer::{analyze_file, analyze_string};
 
     use crate::ast::analyzed::{AlgebraicReference, PolyID, PolynomialType};
 
-    use super::{AlgebraicExpression, Analyzed};
+    use super::{AlgebraicExpression, Analyzed, JsonExportOptions};
 
     #[test]
     fn insert_remove_identities() {
@@ -1429,4 +2100,560 @@ mod tests {
         let expr = column.clone() * column.clone() * column.clone();
         assert_eq!(expr.degree(), 3);
     }
+
+    #[test]
+    fn json_roundtrip_bn254() {
+        // This literal fits into BN254 but not into Goldilocks.
+        let input = r#"namespace N(4);
+    col witness x;
+    x = 9999999999999999999999999999999;
+"#;
+        let pil = analyze_string::<Bn254Field>(input).unwrap();
+        let json = serde_json::to_string(&pil).unwrap();
+        let deserialized: Analyzed<Bn254Field> = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized.to_string(), pil.to_string());
+        assert!(pil
+            .to_string()
+            .contains("N.x = 9999999999999999999999999999999;"));
+    }
+
+    #[test]
+    fn json_is_deterministic() {
+        let input = r#"namespace N(4);
+    col witness z, a, m;
+    col fixed f = [1]*;
+    pol b = z + a;
+    public p = a(2);
+    z = a * m + f + b;
+"#;
+        let first =
+            serde_json::to_string(&analyze_string::<GoldilocksField>(input).unwrap()).unwrap();
+        for _ in 0..5 {
+            let json =
+                serde_json::to_string(&analyze_string::<GoldilocksField>(input).unwrap()).unwrap();
+            assert_eq!(json, first);
+        }
+        let positions =
+            ["\"N.a\":", "\"N.f\":", "\"N.m\":", "\"N.z\":"].map(|key| first.find(key).unwrap());
+        assert!(positions.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn write_json() {
+        let input = r#"namespace N(4);
+    col witness x, y;
+    col fixed F = [1, 0]*;
+    x' = y * F;
+"#;
+        let analyzed = analyze_string::<GoldilocksField>(input).unwrap();
+        for pretty in [false, true] {
+            let mut buffer = vec![];
+            let options = JsonExportOptions {
+                pretty,
+                ..Default::default()
+            };
+            analyzed.write_json(&mut buffer, &options).unwrap();
+            assert_eq!(
+                String::from_utf8(buffer).unwrap(),
+                analyzed.to_json(&options).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn combined_json_options() {
+        let input = r#"namespace N(4);
+    col witness x, y;
+    col fixed F = [1, 0]*;
+    public out = x(3);
+    x' = y * F;
+"#;
+        let analyzed = analyze_string::<GoldilocksField>(input).unwrap();
+        let options = JsonExportOptions {
+            pretty: true,
+            degrees: true,
+            labels: true,
+            spans: true,
+            publics: true,
+            stages: true,
+            witness_definitions: true,
+        };
+        let json = analyzed.to_json(&options).unwrap();
+        assert!(json.contains('\n'));
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value, analyzed.to_json_value(&options).unwrap());
+        let identity = &value["identities"][0];
+        assert_eq!(identity["degree"], 2);
+        assert_eq!(identity["label"], "identity_0");
+        assert_eq!(identity["span"]["line"], 5);
+        assert_eq!(value["definitions"]["N.x"][0]["span"]["line"], 2);
+        assert_eq!(value["publics"][0]["name"], "N.out");
+        assert_eq!(value["stages"]["N.y"], 0);
+        assert_eq!(value["witness_definitions"].to_string(), "{}");
+
+        let mut buffer = vec![];
+        analyzed.write_json(&mut buffer, &options).unwrap();
+        assert_eq!(String::from_utf8(buffer).unwrap(), json);
+        // Only the generated label is kept when deserializing.
+        let deserialized: Analyzed<GoldilocksField> = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            deserialized.to_string(),
+            analyzed
+                .to_string()
+                .replace("    N.x' =", "    #[label(\"identity_0\")]\n    N.x' =")
+        );
+    }
+
+    #[test]
+    fn identity_degrees() {
+        let input = r#"namespace N(4);
+    col witness x, y;
+    col fixed F = [1, 0]*;
+    col sq = x * x;
+    x' = y;
+    F * (sq * y - x) = 0;
+    [x * y] in [F];
+"#;
+        let analyzed = analyze_string::<GoldilocksField>(input).unwrap();
+        assert_eq!(
+            analyzed.identity_degrees().into_iter().collect::<Vec<_>>(),
+            [(0, 1), (1, 4), (2, 2)]
+        );
+        assert_eq!(analyzed.max_degree(), 4);
+        assert_eq!(Analyzed::<GoldilocksField>::default().max_degree(), 0);
+
+        let json = analyzed
+            .to_json(&JsonExportOptions {
+                degrees: true,
+                ..Default::default()
+            })
+            .unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let degrees = value["identities"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|identity| identity["degree"].as_u64().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(degrees, [1, 4, 2]);
+        let deserialized: Analyzed<GoldilocksField> = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized.to_string(), analyzed.to_string());
+    }
+
+    #[test]
+    fn json_with_labels() {
+        let input = r#"namespace N(8);
+    col witness x, y;
+    #[label("first")]
+    x' = y;
+    y' = x;
+    #[label("booleans")]
+    [x * (1 - x) = 0, y * (1 - y) = 0];
+    #[label("lookup")]
+    [x] in [y];
+"#;
+        let analyzed = analyze_string::<GoldilocksField>(input).unwrap();
+        assert_eq!(
+            analyzed.to_string(),
+            r#"namespace N(8);
+    col witness x;
+    col witness y;
+    #[label("first")]
+    N.x' = N.y;
+    N.y' = N.x;
+    #[label("booleans")]
+    N.x * (1 - N.x) = 0;
+    #[label("booleans")]
+    N.y * (1 - N.y) = 0;
+    #[label("lookup")]
+    [N.x] in [N.y];
+"#
+        );
+        let json = analyzed
+            .to_json(&JsonExportOptions {
+                labels: true,
+                ..Default::default()
+            })
+            .unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let labels = value["identities"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|identity| identity["label"].as_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            labels,
+            ["first", "identity_1", "booleans", "booleans", "lookup"]
+        );
+        let value = analyzed.to_json_value(&Default::default()).unwrap();
+        assert!(value["identities"][1].get("label").is_none());
+    }
+
+    #[test]
+    fn json_with_spans() {
+        let dir = std::env::temp_dir().join(format!("pil_json_spans_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let main = dir.join("main.pil");
+        std::fs::write(
+            &main,
+            "include \"lib.pil\";\nnamespace A(4);\n    col witness x;\n    x = B.y;\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("lib.pil"),
+            "namespace B(4);\n    col witness y;\n    col sq = y * y;\n",
+        )
+        .unwrap();
+        let analyzed = analyze_file::<GoldilocksField>(&main).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let json = analyzed
+            .to_json(&JsonExportOptions {
+                spans: true,
+                ..Default::default()
+            })
+            .unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let span = |value: &serde_json::Value| {
+            let file = value["span"]["file"].as_str().unwrap();
+            (
+                file.rsplit('/').next().unwrap().to_string(),
+                value["span"]["line"].as_u64().unwrap(),
+                value["span"]["col"].as_u64().unwrap(),
+            )
+        };
+        assert_eq!(
+            span(&value["identities"][0]),
+            ("main.pil".to_string(), 4, 5)
+        );
+        assert_eq!(
+            span(&value["definitions"]["A.x"][0]),
+            ("main.pil".to_string(), 3, 5)
+        );
+        assert_eq!(
+            span(&value["definitions"]["B.y"][0]),
+            ("lib.pil".to_string(), 2, 5)
+        );
+        assert_eq!(
+            span(&value["intermediate_columns"]["B.sq"][0]),
+            ("lib.pil".to_string(), 3, 5)
+        );
+        let deserialized: Analyzed<GoldilocksField> = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized.to_string(), analyzed.to_string());
+    }
+
+    #[test]
+    fn json_with_publics() {
+        let input = r#"namespace N(8);
+    col witness x, y[2];
+    public out = x(7);
+    public first = y[1](0);
+    public mid = x(3);
+namespace M(8);
+    col witness z;
+    public out = z(1);
+"#;
+        let analyzed = analyze_string::<GoldilocksField>(input).unwrap();
+        let json = analyzed
+            .to_json(&JsonExportOptions {
+                publics: true,
+                ..Default::default()
+            })
+            .unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let publics = value["publics"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|public| {
+                (
+                    public["name"].as_str().unwrap(),
+                    public["polynomial"]["name"].as_str().unwrap(),
+                    public["array_index"].as_u64(),
+                    public["index"].as_u64().unwrap(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            publics,
+            [
+                ("N.out", "N.x", None, 7),
+                ("N.first", "N.y", Some(1), 0),
+                ("N.mid", "N.x", None, 3),
+                ("M.out", "M.z", None, 1)
+            ]
+        );
+        let deserialized: Analyzed<GoldilocksField> = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized.to_string(), analyzed.to_string());
+    }
+
+    #[test]
+    fn json_with_witness_definitions() {
+        let input = r#"namespace std::prover;
+    let eval: expr -> fe = [];
+    enum Query { Hint(fe), None }
+namespace N(8);
+    col witness x;
+    col witness y(i) query std::prover::Query::Hint(std::prover::eval(x) * 2);
+    col witness z;
+    col witness w(i) query std::prover::Query::None;
+"#;
+        let analyzed = analyze_string::<GoldilocksField>(input).unwrap();
+        let json = analyzed
+            .to_json(&JsonExportOptions {
+                witness_definitions: true,
+                ..Default::default()
+            })
+            .unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            value["witness_definitions"].to_string(),
+            r#"{"N.w":"(query |i| std::prover::Query::None)","N.y":"(query |i| std::prover::Query::Hint(std::prover::eval(N.x) * 2))"}"#
+        );
+        let deserialized: Analyzed<GoldilocksField> = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized.to_string(), analyzed.to_string());
+    }
+
+    #[test]
+    fn symbol_types() {
+        let input = r#"namespace N(8);
+    col witness x, y[2];
+    col sq = x * x;
+    let limit: int = 3;
+    let double: int -> int = |i| i * 2;
+    let quadruple = |i| double(double(i));
+    let<T> id: T -> T = |v| v;
+    enum Op { Add(int), Nop }
+"#;
+        let analyzed = analyze_string::<GoldilocksField>(input).unwrap();
+        let type_of = |name: &str| analyzed.symbol_type(name).map(|ty| ty.to_string());
+        assert_eq!(type_of("N.x").as_deref(), Some("col"));
+        assert_eq!(type_of("N.y").as_deref(), Some("col[]"));
+        assert_eq!(type_of("N.sq").as_deref(), Some("expr"));
+        assert_eq!(type_of("N.limit").as_deref(), Some("int"));
+        assert_eq!(type_of("N.quadruple").as_deref(), Some("int -> int"));
+        assert_eq!(type_of("N::Op::Add").as_deref(), Some("int -> N::Op"));
+        assert_eq!(type_of("N.Op"), None);
+        assert_eq!(type_of("N.id"), None);
+        assert_eq!(type_of("N.unknown"), None);
+        let scheme = analyzed.symbol_type_scheme("N.id").unwrap();
+        assert_eq!(format!("{} {}", scheme.vars, scheme.ty), "T T -> T");
+    }
+
+    #[test]
+    fn stats() {
+        let input = r#"namespace A(8);
+    col witness x, y[2];
+    col fixed F = [1, 0]*;
+    col sq = x * x;
+    x' = y[0];
+    [x] in [F];
+    [y[0], y[1]] is [x, sq];
+namespace B(8);
+    col witness z;
+    z = A.x;
+    let helper: int = 3;
+"#;
+        let stats = analyze_string::<GoldilocksField>(input).unwrap().stats();
+        assert_eq!(
+            stats.to_string(),
+            "namespaces: 2
+witness columns: 4
+fixed columns: 1
+intermediate columns: 1
+polynomial identities: 2
+lookups: 1
+permutations: 1
+connections: 0
+degree: 8"
+        );
+    }
+
+    #[test]
+    fn identities() {
+        let input = r#"namespace A(8);
+    col witness x, y[2];
+    col fixed F = [1, 0]*;
+    col sq = x * x;
+    x' = y[0] + sq;
+    [x] in [F];
+    [y[0], y[1]] is [x, sq];
+    [x] connect [y[1]];
+"#;
+        let mut analyzed = analyze_string::<GoldilocksField>(input).unwrap();
+        analyzed.remove_identities(&[0].into_iter().collect());
+        let infos = analyzed
+            .identities()
+            .map(|info| {
+                format!(
+                    "{} {} {:?}: {}",
+                    info.index,
+                    info.id,
+                    info.kind,
+                    info.columns.iter().join(", ")
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            infos,
+            [
+                "0 1 Plookup: A.F, A.x",
+                "1 2 Permutation: A.sq, A.x, A.y[0], A.y[1]",
+                "2 3 Connect: A.x, A.y[1]"
+            ]
+        );
+    }
+
+    #[test]
+    fn poly_ids() {
+        let input = r#"namespace N(4);
+    col witness a;
+    col fixed F = [1]*;
+    col witness b[2];
+    col sq = a * a;
+    col witness c;
+"#;
+        let ids = |input| {
+            analyze_string::<GoldilocksField>(input)
+                .unwrap()
+                .poly_ids()
+                .into_iter()
+                .map(|(name, id)| format!("{name}: {:?} {}", id.ptype, id.id))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            ids(input),
+            [
+                "N.F: Constant 0",
+                "N.a: Committed 0",
+                "N.b[0]: Committed 1",
+                "N.b[1]: Committed 2",
+                "N.c: Committed 3",
+                "N.sq: Intermediate 0"
+            ]
+        );
+        // Adding columns of a different type and other definitions does not change the IDs.
+        let edited = input.replace(
+            "col witness c;",
+            "col fixed G = [0]*;\n    let x: int = 2;\n    col cube = sq * a;\n    col witness c;",
+        );
+        assert_eq!(
+            ids(&edited),
+            [
+                "N.F: Constant 0",
+                "N.G: Constant 1",
+                "N.a: Committed 0",
+                "N.b[0]: Committed 1",
+                "N.b[1]: Committed 2",
+                "N.c: Committed 3",
+                "N.cube: Intermediate 1",
+                "N.sq: Intermediate 0"
+            ]
+        );
+    }
+
+    #[test]
+    fn common_degree() {
+        let analyzed = analyze_string::<GoldilocksField>(
+            "namespace A(8);\ncol witness x;\nnamespace B(8);\ncol witness y;",
+        )
+        .unwrap();
+        assert_eq!(analyzed.common_degree(), Some(8));
+        assert_eq!(analyzed.degree(), 8);
+
+        let analyzed = analyze_string::<GoldilocksField>(
+            "namespace A(8);\ncol witness x;\nnamespace B(16);\ncol witness y;",
+        )
+        .unwrap();
+        assert_eq!(analyzed.common_degree(), None);
+
+        let analyzed = analyze_string::<GoldilocksField>("let x: int = 1;").unwrap();
+        assert_eq!(analyzed.common_degree(), None);
+    }
+
+    #[test]
+    fn compact_and_pretty_json() {
+        let input = r#"namespace N(4);
+    col witness x;
+    col fixed f = [1]*;
+    x = f;
+"#;
+        let analyzed = analyze_string::<GoldilocksField>(input).unwrap();
+        let compact = analyzed.to_json(&Default::default()).unwrap();
+        let pretty = analyzed
+            .to_json(&JsonExportOptions {
+                pretty: true,
+                ..Default::default()
+            })
+            .unwrap();
+        assert!(!compact.contains('\n'));
+        assert!(pretty.contains('\n'));
+        assert!(compact.len() < pretty.len());
+        let from_compact: serde_json::Value = serde_json::from_str(&compact).unwrap();
+        let from_pretty: serde_json::Value = serde_json::from_str(&pretty).unwrap();
+        assert_eq!(from_compact, from_pretty);
+        assert_eq!(
+            analyzed.to_json_value(&Default::default()).unwrap(),
+            from_compact
+        );
+    }
+
+    #[test]
+    fn validate() {
+        let input = r#"namespace N(8);
+    col witness x, y[2];
+    col fixed f = [1, 0]*;
+    pol i = x + y[1];
+    public p = y[1](7);
+    x * f = i;
+    [x] in [f];
+"#;
+        let pil = analyze_string::<GoldilocksField>(input).unwrap();
+        assert_eq!(pil.validate(), Ok(()));
+
+        let mut broken = pil.clone();
+        broken.definitions.get_mut("N.f").unwrap().0.id = 5;
+        broken.public_declarations.get_mut("N.p").unwrap().index = 8;
+        broken.definitions.get_mut("N.x").unwrap().0.degree = Some(6.into());
+        assert_eq!(
+            broken.validate(),
+            Err(vec![
+                "Degree of N.x is not a power of two: 6".to_string(),
+                "Reference to unknown polynomial N.f (Constant with ID 0) in identity 0"
+                    .to_string(),
+                "Reference to unknown polynomial N.f (Constant with ID 0) in identity 1"
+                    .to_string(),
+                "Public declaration N.p refers to row 8 but the degree of N.y is 8".to_string(),
+            ])
+        );
+    }
+
+    #[test]
+    fn write_and_read_file() {
+        let input = r#"namespace N(4);
+    col fixed F = [1, 0]*;
+    col witness x;
+    col sq = x * x;
+    let f: int -> int = |i| i + 1;
+    x' = sq * F;
+    public out = x(3);
+"#;
+        let analyzed = analyze_string::<GoldilocksField>(input).unwrap();
+        let path = std::env::temp_dir().join(format!("pil_analyzed_{}.bin", std::process::id()));
+        analyzed.write_to_file(&path).unwrap();
+        let loaded = Analyzed::<GoldilocksField>::read_from_file(&path).unwrap();
+        let wrong_field = Analyzed::<Bn254Field>::read_from_file(&path).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+        assert!(loaded == analyzed);
+        assert_eq!(loaded.to_string(), analyzed.to_string());
+        assert_eq!(
+            loaded.to_json(&Default::default()),
+            analyzed.to_json(&Default::default())
+        );
+        assert!(wrong_field.contains("was written for a field with modulus 18446744069414584321"));
+        assert!(Analyzed::<GoldilocksField>::read_from_file(&path)
+            .unwrap_err()
+            .starts_with("Failed to read"));
+    }
 }
@@ -5,7 +5,7 @@ use std::{
         btree_map::{IntoIter, Iter, IterMut},
         BTreeMap, BTreeSet,
     },
-    iter::{once, repeat},
+    iter::{once, repeat_n},
     ops::ControlFlow,
 };
 
@@ -162,16 +162,16 @@ impl FunctionStatements {
     }
 
     /// iterate over the batches by reference
-    pub fn iter_batches(&self) -> impl Iterator<Item = BatchRef> {
+    pub fn iter_batches(&self) -> impl Iterator<Item = BatchRef<'_>> {
         match &self.batches {
             Some(batches) => Either::Left(batches.iter()),
-            None => Either::Right(
-                repeat(&BatchMetadata {
+            None => Either::Right(repeat_n(
+                &BatchMetadata {
                     size: 1,
                     reason: None,
-                })
-                .take(self.inner.len()),
-            ),
+                },
+                self.inner.len(),
+            )),
         }
         .scan(0, move |start, batch| {
             let res = BatchRef {
@@ -192,13 +192,13 @@ impl FunctionStatements {
 
         match self.batches {
             Some(batches) => Either::Left(batches.into_iter()),
-            None => Either::Right(
-                repeat(BatchMetadata {
+            None => Either::Right(repeat_n(
+                BatchMetadata {
                     size: 1,
                     reason: None,
-                })
-                .take(len),
-            ),
+                },
+                len,
+            )),
         }
         .map(move |batch| Batch {
             reason: batch.reason,
@@ -324,17 +324,17 @@ impl CallableSymbolDefinitions {
     }
 
     /// Returns an iterator over references to definitions
-    pub fn iter(&self) -> impl Iterator<Item = CallableSymbolDefinitionRef> {
+    pub fn iter(&self) -> impl Iterator<Item = CallableSymbolDefinitionRef<'_>> {
         self.into_iter()
     }
 
     /// Returns an iterator over mutable references to definitions
-    pub fn iter_mut(&mut self) -> impl Iterator<Item = CallableSymbolDefinitionMut> {
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = CallableSymbolDefinitionMut<'_>> {
         self.into_iter()
     }
 
     /// Returns an iterator over references to operation definitions
-    pub fn operation_definitions(&self) -> impl Iterator<Item = OperationDefinitionRef> {
+    pub fn operation_definitions(&self) -> impl Iterator<Item = OperationDefinitionRef<'_>> {
         self.0.iter().filter_map(|(name, symbol)| {
             <&OperationSymbol>::try_from(symbol)
                 .map(|operation| OperationDefinitionRef { name, operation })
@@ -343,7 +343,7 @@ impl CallableSymbolDefinitions {
     }
 
     /// Returns an iterator over references to function definitions
-    pub fn function_definitions(&self) -> impl Iterator<Item = FunctionDefinitionRef> {
+    pub fn function_definitions(&self) -> impl Iterator<Item = FunctionDefinitionRef<'_>> {
         self.0.iter().filter_map(|(name, symbol)| {
             <&FunctionSymbol>::try_from(symbol)
                 .map(|function| FunctionDefinitionRef { name, function })
@@ -352,7 +352,9 @@ impl CallableSymbolDefinitions {
     }
 
     /// Returns an iterator over mutable references to operation definitions
-    pub fn operation_definitions_mut(&mut self) -> impl Iterator<Item = OperationDefinitionMut> {
+    pub fn operation_definitions_mut(
+        &mut self,
+    ) -> impl Iterator<Item = OperationDefinitionMut<'_>> {
         self.0.iter_mut().filter_map(|(name, symbol)| {
             <&mut OperationSymbol>::try_from(symbol)
                 .map(|operation| OperationDefinitionMut { name, operation })
@@ -361,7 +363,7 @@ impl CallableSymbolDefinitions {
     }
 
     /// Returns an iterator over mutable references to function definitions
-    pub fn function_definitions_mut(&mut self) -> impl Iterator<Item = FunctionDefinitionMut> {
+    pub fn function_definitions_mut(&mut self) -> impl Iterator<Item = FunctionDefinitionMut<'_>> {
         self.0.iter_mut().filter_map(|(name, symbol)| {
             <&mut FunctionSymbol>::try_from(symbol)
                 .map(|function| FunctionDefinitionMut { name, function })
@@ -371,16 +373,12 @@ impl CallableSymbolDefinitions {
 
     /// Returns an iterator over references to operations
     pub fn operations(&self) -> impl Iterator<Item = &OperationSymbol> {
-        self.0
-            .iter()
-            .filter_map(|(_, symbol)| symbol.try_into().ok())
+        self.0.values().filter_map(|symbol| symbol.try_into().ok())
     }
 
     /// Returns an iterator over references to functions
     pub fn functions(&self) -> impl Iterator<Item = &FunctionSymbol> {
-        self.0
-            .iter()
-            .filter_map(|(_, symbol)| symbol.try_into().ok())
+        self.0.values().filter_map(|symbol| symbol.try_into().ok())
     }
 
     /// Returns an iterator over references to operations
@@ -757,22 +755,24 @@ impl Machine {
     }
 
     /// Returns an iterator over references to the operation definitions
-    pub fn operation_definitions(&self) -> impl Iterator<Item = OperationDefinitionRef> {
+    pub fn operation_definitions(&self) -> impl Iterator<Item = OperationDefinitionRef<'_>> {
         self.callable.operation_definitions()
     }
 
     /// Returns an iterator over references to the function definitions
-    pub fn function_definitions(&self) -> impl Iterator<Item = FunctionDefinitionRef> {
+    pub fn function_definitions(&self) -> impl Iterator<Item = FunctionDefinitionRef<'_>> {
         self.callable.function_definitions()
     }
 
     /// Returns an iterator over mutable references to the operation definitions
-    pub fn operation_definitions_mut(&mut self) -> impl Iterator<Item = OperationDefinitionMut> {
+    pub fn operation_definitions_mut(
+        &mut self,
+    ) -> impl Iterator<Item = OperationDefinitionMut<'_>> {
         self.callable.operation_definitions_mut()
     }
 
     /// Returns an iterator over mutable references to the function definitions
-    pub fn function_definitions_mut(&mut self) -> impl Iterator<Item = FunctionDefinitionMut> {
+    pub fn function_definitions_mut(&mut self) -> impl Iterator<Item = FunctionDefinitionMut<'_>> {
         self.callable.function_definitions_mut()
     }
 
@@ -1,6 +1,6 @@
 use std::{
     fmt::{Display, Formatter},
-    iter::{empty, once, repeat},
+    iter::{empty, once, repeat_n},
     str::FromStr,
 };
 
@@ -65,7 +65,7 @@ pub enum SymbolValue {
 }
 
 impl SymbolValue {
-    pub fn as_ref(&self) -> SymbolValueRef {
+    pub fn as_ref(&self) -> SymbolValueRef<'_> {
         match self {
             SymbolValue::Machine(machine) => SymbolValueRef::Machine(machine),
             SymbolValue::Import(i) => SymbolValueRef::Import(i),
@@ -102,7 +102,7 @@ pub enum Module {
 }
 
 impl Module {
-    fn as_ref(&self) -> ModuleRef {
+    fn as_ref(&self) -> ModuleRef<'_> {
         match self {
             Module::External(n) => ModuleRef::External(n),
             Module::Local(m) => ModuleRef::Local(m),
@@ -298,17 +298,19 @@ impl AbsoluteSymbolPath {
         let common_prefix_len = self.common_prefix(base).parts.len();
         // Start with max(0, base.parts.len() - common_root.parts.len())
         // repetitions of "super".
-        let parts = repeat(Part::Super)
-            .take(base.parts.len().saturating_sub(common_prefix_len))
-            // append the parts of self after the common root.
-            .chain(
-                self.parts
-                    .iter()
-                    .skip(common_prefix_len)
-                    .cloned()
-                    .map(Part::Named),
-            )
-            .collect();
+        let parts = repeat_n(
+            Part::Super,
+            base.parts.len().saturating_sub(common_prefix_len),
+        )
+        // append the parts of self after the common root.
+        .chain(
+            self.parts
+                .iter()
+                .skip(common_prefix_len)
+                .cloned()
+                .map(Part::Named),
+        )
+        .collect();
         SymbolPath { parts }
     }
 
@@ -325,11 +327,19 @@ impl AbsoluteSymbolPath {
     }
 
     /// Resolves a relative path in the context of this absolute path.
-    pub fn join<P: Into<SymbolPath>>(mut self, other: P) -> Self {
+    ///
+    /// Panics if the relative path goes beyond the root.
+    pub fn join<P: Into<SymbolPath>>(self, other: P) -> Self {
+        self.try_join(other).unwrap()
+    }
+
+    /// Resolves a relative path in the context of this absolute path.
+    /// Returns `None` if the relative path goes beyond the root.
+    pub fn try_join<P: Into<SymbolPath>>(mut self, other: P) -> Option<Self> {
         for part in other.into().parts {
             match part {
                 Part::Super => {
-                    self.pop().unwrap();
+                    self.pop()?;
                 }
                 Part::Named(name) => {
                     if name.is_empty() {
@@ -340,7 +350,7 @@ impl AbsoluteSymbolPath {
                 }
             }
         }
-        self
+        Some(self)
     }
 
     /// Appends a part to the end of the path and returns a new copy.
@@ -830,4 +840,15 @@ mod test {
         let rel = v.relative_to(&base);
         assert_eq!(base.join(rel), v);
     }
+
+    #[test]
+    fn try_join_beyond_root() {
+        let base = parse_absolute_path("::x");
+        let path = SymbolPath::from_str("super::y").unwrap();
+        assert_eq!(
+            base.clone().try_join(path.clone()),
+            Some(parse_absolute_path("::y"))
+        );
+        assert_eq!(base.parent().try_join(path), None);
+    }
 }
@@ -1,11 +1,13 @@
 use crate::number::BigUint;
+use crate::parser_util::SourceRef;
 
 use crate::ast::parsed::{Expression, SourceReference};
 
 use super::{
     asm::{parse_absolute_path, Part, SymbolPath},
-    BinaryOperation, BinaryOperator, IndexAccess, NamespacedPolynomialReference, UnaryOperation,
-    UnaryOperator,
+    ArrayLiteral, BinaryOperation, BinaryOperator, FunctionDefinition, IndexAccess,
+    NamespacedPolynomialReference, PilStatement, PolynomialName, SelectedExpressions,
+    UnaryOperation, UnaryOperator,
 };
 
 pub fn absolute_reference(name: &str) -> Expression {
@@ -55,3 +57,68 @@ pub fn identity(lhs: Expression, rhs: Expression) -> Expression {
     }
     .into()
 }
+
+/// Returns a namespace statement. All names declared after this statement
+/// are relative to the namespace `name` (which can contain `::`).
+pub fn namespace(name: &str, degree: Option<u64>) -> PilStatement {
+    PilStatement::Namespace(
+        SourceRef::unknown(),
+        name.parse().unwrap(),
+        degree.map(|d| Expression::from(BigUint::from(d)).into()),
+        Default::default(),
+    )
+}
+
+/// Returns a declaration of the given (non-array) witness columns.
+pub fn witness_columns<S: Into<String>>(names: impl IntoIterator<Item = S>) -> PilStatement {
+    PilStatement::PolynomialCommitDeclaration(
+        SourceRef::unknown(),
+        None,
+        names
+            .into_iter()
+            .map(|name| PolynomialName {
+                name: name.into(),
+                array_size: None,
+            })
+            .collect(),
+        None,
+    )
+}
+
+/// Returns the definition of a fixed column, e.g. `col fixed name = value;`.
+pub fn fixed_column<S: Into<String>>(name: S, value: FunctionDefinition) -> PilStatement {
+    PilStatement::PolynomialConstantDefinition(SourceRef::unknown(), name.into(), value)
+}
+
+/// Returns a polynomial identity statement `lhs = rhs;`.
+pub fn polynomial_identity(lhs: Expression, rhs: Expression) -> PilStatement {
+    PilStatement::Expression(SourceRef::unknown(), identity(lhs, rhs))
+}
+
+/// Returns the selected expressions `selector $ [items...]`, for use in
+/// lookups and permutations.
+pub fn selected_expressions(
+    selector: Option<Expression>,
+    items: Vec<Expression>,
+) -> SelectedExpressions<Expression> {
+    SelectedExpressions {
+        selector,
+        expressions: Box::new(ArrayLiteral { items }.into()),
+    }
+}
+
+/// Returns a plookup identity statement `left in right;`.
+pub fn plookup_identity(
+    left: SelectedExpressions<Expression>,
+    right: SelectedExpressions<Expression>,
+) -> PilStatement {
+    PilStatement::PlookupIdentity(SourceRef::unknown(), left, right)
+}
+
+/// Returns a permutation identity statement `left is right;`.
+pub fn permutation_identity(
+    left: SelectedExpressions<Expression>,
+    right: SelectedExpressions<Expression>,
+) -> PilStatement {
+    PilStatement::PermutationIdentity(SourceRef::unknown(), left, right)
+}
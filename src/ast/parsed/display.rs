@@ -5,7 +5,7 @@ use itertools::Itertools;
 use crate::ast::{
     indent,
     parsed::{BinaryOperator, UnaryOperator},
-    write_indented_by, write_items, write_items_indented,
+    write_indented_by, write_items, write_items_indented, writeln_indented_by,
 };
 
 //use crate::ast::types::{ArrayType, FunctionType, TupleType, TypeBounds};
@@ -131,6 +131,31 @@ impl Display for MachineProperties {
     }
 }
 
+impl Display for NamespaceDegree {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        if self.min == self.max {
+            write!(f, "{}", self.min)
+        } else {
+            write!(f, "{}..{}", self.min, self.max)
+        }
+    }
+}
+
+impl Display for NamespaceBudget {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        let props = self
+            .max_identities
+            .map(|n| format!("max_identities: {n}"))
+            .into_iter()
+            .chain(self.max_degree.map(|d| format!("max_degree: {d}")))
+            .join(", ");
+        if !props.is_empty() {
+            write!(f, " with {props}")?;
+        }
+        Ok(())
+    }
+}
+
 impl Display for InstructionBody {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         write!(
@@ -360,6 +385,16 @@ impl<E: Display> Display for IndexAccess<Expression<E>> {
     }
 }
 
+impl<E: Display> Display for SliceAccess<Expression<E>> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        if self.array.precedence().is_none() {
+            write!(f, "{}[{}..{}]", self.array, self.start, self.end)
+        } else {
+            write!(f, "({})[{}..{}]", self.array, self.start, self.end)
+        }
+    }
+}
+
 impl<E: Display> Display for FunctionCall<Expression<E>> {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         if self.function.precedence().is_none() {
@@ -398,6 +433,34 @@ impl Display for Pattern {
     }
 }
 
+impl<E: Display> Display for ArrayComprehension<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "[{} for {} in ", self.value, self.pattern)?;
+        match &self.iterable {
+            ComprehensionIterable::Array(array) => write!(f, "{array}")?,
+            ComprehensionIterable::Range(start, end) => write!(f, "{start}..{end}")?,
+        }
+        if let Some(condition) = &self.condition {
+            write!(f, " if {condition}")?;
+        }
+        write!(f, "]")
+    }
+}
+
+impl<E> Display for Cast<E>
+where
+    E: Display + Precedence,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self.expr.precedence() {
+            Some(precedence) if precedence > CAST_PRECEDENCE => {
+                write!(f, "({}) as {}", self.expr, self.ty)
+            }
+            _ => write!(f, "{} as {}", self.expr, self.ty),
+        }
+    }
+}
+
 impl<E: Display> Display for IfExpression<E> {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         write!(
@@ -457,21 +520,43 @@ impl Display for PilStatement {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         match self {
             PilStatement::Include(_, path) => write!(f, "include {};", quote(path)),
-            PilStatement::Namespace(_, name, poly_length) => {
+            PilStatement::Namespace(_, name, poly_length, budget) => {
                 write!(f, "namespace")?;
                 let name = name.to_string();
                 match poly_length {
-                    None if name.is_empty() => {
-                        write!(f, ";")
-                    }
+                    None if name.is_empty() => {}
                     None => {
-                        write!(f, " {name};")
+                        write!(f, " {name}")?;
                     }
                     Some(poly_length) => {
-                        write!(f, " {name}({poly_length});")
+                        write!(f, " {name}({poly_length})")?;
                     }
                 }
+                write!(f, "{budget};")
             }
+            PilStatement::NamespaceBlock(_, name, params, poly_length, budget, statements) => {
+                write!(f, "namespace {name}")?;
+                if !params.is_empty() {
+                    write!(f, "<{}>", params.iter().format(", "))?;
+                }
+                if let Some(poly_length) = poly_length {
+                    write!(f, "({poly_length})")?;
+                }
+                writeln!(f, "{budget} {{")?;
+                for statement in statements {
+                    match statement {
+                        // Other statements are already indented by one level.
+                        PilStatement::NamespaceBlock(..) => writeln_indented_by(f, statement, 1)?,
+                        _ => writeln!(f, "{statement}")?,
+                    }
+                }
+                write!(f, "}}")
+            }
+            PilStatement::NamespaceInstance(_, name, template, arguments) => write_indented_by(
+                f,
+                format!("namespace {name} = {template}({});", format_list(arguments)),
+                1,
+            ),
             PilStatement::LetStatement(_, pattern, type_scheme, value) => write_indented_by(
                 f,
                 format!(
@@ -532,8 +617,17 @@ impl Display for PilStatement {
                 1,
             ),
             PilStatement::Expression(_, e) => write_indented_by(f, format!("{e};"), 1),
+            PilStatement::LabeledIdentity(_, label, identity) => {
+                writeln_indented_by(f, format!("#[label({})]", quote(label)), 1)?;
+                write!(f, "{identity}")
+            }
             PilStatement::EnumDeclaration(_, enum_decl) => write_indented_by(f, enum_decl, 1),
+            PilStatement::StructDeclaration(_, struct_decl) => write_indented_by(f, struct_decl, 1),
             PilStatement::TraitDeclaration(_, trait_decl) => write_indented_by(f, trait_decl, 1),
+            PilStatement::TraitImplementation(_, trait_impl) => write_indented_by(f, trait_impl, 1),
+            PilStatement::TypeAlias(_, name, ty) => {
+                write_indented_by(f, format!("type {name} = {ty};"), 1)
+            }
         }
     }
 }
@@ -582,10 +676,15 @@ impl Display for FunctionDefinition {
 
 impl<E: Display> Display for TraitDeclaration<E> {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
-        write!(
-            f,
+        write!(f, "{}", self.to_string_with_name(&self.name))
+    }
+}
+
+impl<E: Display> TraitDeclaration<E> {
+    /// Formats the trait declaration, exchanging its name by the provided one.
+    pub fn to_string_with_name(&self, name: &str) -> String {
+        format!(
             "trait {name}<{type_vars}> {{\n{functions}}}",
-            name = self.name,
             type_vars = self.type_vars.iter().format(", "),
             functions = indent(
                 self.functions.iter().map(|m| format!("{m},\n")).format(""),
@@ -595,6 +694,24 @@ impl<E: Display> Display for TraitDeclaration<E> {
     }
 }
 
+impl<E: Display> Display for TraitImplementation<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(
+            f,
+            "impl {}<{}> {{\n{}}}",
+            self.name,
+            self.type_name,
+            indent(
+                self.functions
+                    .iter()
+                    .map(|(name, value)| format!("{name}: {value},\n"))
+                    .format(""),
+                1
+            )
+        )
+    }
+}
+
 impl<E: Display> Display for TraitFunction<E> {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         write!(f, "{}: {}", self.name, self.ty)
@@ -625,6 +742,39 @@ impl<E: Display> EnumDeclaration<E> {
     }
 }
 
+impl<E: Display> Display for StructDeclaration<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        let type_vars = if self.type_vars.is_empty() {
+            Default::default()
+        } else {
+            format!("<{}>", self.type_vars)
+        };
+        write!(
+            f,
+            "struct {}{type_vars} {{\n{}}}",
+            self.name,
+            indent(
+                self.fields
+                    .iter()
+                    .map(|field| format!("{field},\n"))
+                    .format(""),
+                1
+            )
+        )
+    }
+}
+
+impl<E: Display> Display for StructField<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(
+            f,
+            "{}: {}",
+            self.name,
+            format_type_with_parentheses(&self.ty)
+        )
+    }
+}
+
 impl<Expr: Display> Display for SelectedExpressions<Expr> {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         write!(
@@ -674,12 +824,15 @@ impl<Ref: Display> Display for Expression<Ref> {
                 write!(f, "{unaryop}")
             }
             Expression::IndexAccess(_, index_access) => write!(f, "{index_access}"),
+            Expression::SliceAccess(_, slice_access) => write!(f, "{slice_access}"),
             Expression::FunctionCall(_, fun_call) => write!(f, "{fun_call}"),
             Expression::FreeInput(_, input) => write!(f, "${{ {input} }}"),
             Expression::MatchExpression(_, match_expr) => {
                 write!(f, "{match_expr}")
             }
             Expression::IfExpression(_, e) => write!(f, "{e}"),
+            Expression::ArrayComprehension(_, e) => write!(f, "{e}"),
+            Expression::Cast(_, e) => write!(f, "{e}"),
             Expression::BlockExpression(_, block_expr) => {
                 write!(f, "{block_expr}")
             }
@@ -854,6 +1007,7 @@ impl Display for UnaryOperator {
             match self {
                 UnaryOperator::Minus => "-",
                 UnaryOperator::LogicalNot => "!",
+                UnaryOperator::BinaryNot => "~",
                 UnaryOperator::Next => "'",
             }
         )
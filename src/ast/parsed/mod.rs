@@ -12,13 +12,13 @@ use std::{
     str::FromStr,
 };
 
+use crate::number::{BigInt, BigUint, DegreeType};
 use auto_enums::auto_enum;
 use derive_more::Display;
-use crate::number::{BigInt, BigUint, DegreeType};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::parser_util::SourceRef;
+use crate::parser_util::{Error, SourceRef};
 
 use self::{
     asm::{Part, SymbolPath},
@@ -60,8 +60,29 @@ pub struct PILFile(pub Vec<PilStatement>);
 pub enum PilStatement {
     /// File name
     Include(SourceRef, String),
-    /// Name of namespace and optional polynomial degree (constant)
-    Namespace(SourceRef, SymbolPath, Option<Expression>),
+    /// Name of namespace, optional polynomial degree (constant or range) and budget
+    Namespace(
+        SourceRef,
+        SymbolPath,
+        Option<NamespaceDegree>,
+        NamespaceBudget,
+    ),
+    /// Name of a namespace relative to the enclosing namespace, parameters, optional
+    /// polynomial degree (defaults to the degree of the enclosing namespace), budget and
+    /// the statements inside the namespace, i.e. `namespace N(16) { ... }`.
+    /// A block with parameters (`namespace N<P>(P) { ... }`) is a template that only
+    /// defines symbols when it is instantiated using [`PilStatement::NamespaceInstance`].
+    NamespaceBlock(
+        SourceRef,
+        SymbolPath,
+        Vec<String>,
+        Option<NamespaceDegree>,
+        NamespaceBudget,
+        Vec<PilStatement>,
+    ),
+    /// Name of the new namespace relative to the enclosing namespace, the template
+    /// and the values of its (integer) parameters, i.e. `namespace M = N(8);`.
+    NamespaceInstance(SourceRef, String, SymbolPath, Vec<Expression>),
     LetStatement(
         SourceRef,
         String,
@@ -102,11 +123,83 @@ pub enum PilStatement {
         SelectedExpressions<Expression>,
     ),
     ConnectIdentity(SourceRef, Vec<Expression>, Vec<Expression>),
+    /// An identity statement preceded by `#[label("...")]`.
+    LabeledIdentity(SourceRef, String, Box<PilStatement>),
     EnumDeclaration(SourceRef, EnumDeclaration<Expression>),
+    StructDeclaration(SourceRef, StructDeclaration<Expression>),
     TraitDeclaration(SourceRef, TraitDeclaration<Expression>),
+    TraitImplementation(SourceRef, TraitImplementation<Expression>),
+    /// A type alias `type Name = Type;`. The name stands for the type wherever
+    /// a type is expected, it does not introduce a new named type.
+    TypeAlias(SourceRef, String, Type<Expression>),
     Expression(SourceRef, Expression),
 }
 
+/// The degree of a namespace, either a single value as in `namespace N(16);`
+/// or a range of degrees as in `namespace N(16..1024);`, from which the backend
+/// picks the actual degree. For a single value, `min` and `max` are the same.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
+pub struct NamespaceDegree {
+    pub min: Expression,
+    pub max: Expression,
+}
+
+impl From<Expression> for NamespaceDegree {
+    fn from(degree: Expression) -> Self {
+        NamespaceDegree {
+            min: degree.clone(),
+            max: degree,
+        }
+    }
+}
+
+impl NamespaceDegree {
+    pub fn children(&self) -> impl Iterator<Item = &Expression> {
+        [&self.min, &self.max].into_iter()
+    }
+
+    pub fn children_mut(&mut self) -> impl Iterator<Item = &mut Expression> {
+        [&mut self.min, &mut self.max].into_iter()
+    }
+}
+
+/// Limits on the identities of a namespace, declared using
+/// `namespace N(16) with max_identities: 10, max_degree: 3;`.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Default, Clone)]
+pub struct NamespaceBudget {
+    /// The maximum number of identities in the namespace.
+    pub max_identities: Option<u64>,
+    /// The maximum degree of each identity in the namespace.
+    pub max_degree: Option<u64>,
+}
+
+impl NamespaceBudget {
+    pub fn try_from_prop_list(
+        source_ref: SourceRef,
+        prop_list: Vec<(String, BigUint)>,
+    ) -> Result<Self, Error> {
+        let mut budget: Self = Default::default();
+        for (name, value) in prop_list {
+            let limit = match name.as_str() {
+                "max_identities" => &mut budget.max_identities,
+                "max_degree" => &mut budget.max_degree,
+                _ => {
+                    return Err(
+                        source_ref.with_error(format!("unknown namespace property `{name}`"))
+                    );
+                }
+            };
+            let value = u64::try_from(value).map_err(|_| {
+                source_ref.with_error(format!("`{name}` namespace property is too large"))
+            })?;
+            if limit.replace(value).is_some() {
+                return Err(source_ref.with_error(format!("`{name}` already defined")));
+            }
+        }
+        Ok(budget)
+    }
+}
+
 impl PilStatement {
     /// If the statement is a symbol definition, returns all (local) names of defined symbols
     /// and their category.
@@ -123,6 +216,9 @@ impl PilStatement {
     /// and their category.
     /// For an enum, returns the name of the enum and all the variants, where the first
     /// component is the name of the enum and the second the name of the variant.
+    /// A struct is treated like an enum with the single variant `new` (its constructor)
+    /// and additionally yields one accessor function per field.
+    /// A trait implementation yields its functions as contained in the implementing type.
     pub fn symbol_definition_names_and_contained(
         &self,
     ) -> Box<dyn Iterator<Item = (&String, Option<&String>, SymbolCategory)> + '_> {
@@ -140,6 +236,22 @@ impl PilStatement {
                         .map(move |v| (name, Some(&v.name), SymbolCategory::TypeConstructor)),
                 ),
             ),
+            PilStatement::StructDeclaration(_, StructDeclaration { name, fields, .. }) => Box::new(
+                [
+                    (name, None, SymbolCategory::Type),
+                    (
+                        name,
+                        Some(&*STRUCT_CONSTRUCTOR_NAME),
+                        SymbolCategory::TypeConstructor,
+                    ),
+                ]
+                .into_iter()
+                .chain(
+                    fields
+                        .iter()
+                        .map(move |f| (name, Some(&f.name), SymbolCategory::Value)),
+                ),
+            ),
             PilStatement::TraitDeclaration(
                 _,
                 TraitDeclaration {
@@ -152,6 +264,21 @@ impl PilStatement {
                         .map(move |f| (name, Some(&f.name), SymbolCategory::Value)),
                 ),
             ),
+            PilStatement::TraitImplementation(
+                _,
+                TraitImplementation {
+                    type_name,
+                    functions,
+                    ..
+                },
+            ) => Box::new(
+                functions
+                    .iter()
+                    .map(move |(name, _)| (type_name, Some(name), SymbolCategory::Value)),
+            ),
+            PilStatement::TypeAlias(_, name, _) => {
+                Box::new(once((name, None, SymbolCategory::Type)))
+            }
             PilStatement::PolynomialConstantDeclaration(_, polynomials)
             | PilStatement::PolynomialCommitDeclaration(_, _, polynomials, _) => Box::new(
                 polynomials
@@ -160,15 +287,69 @@ impl PilStatement {
             ),
 
             PilStatement::Include(_, _)
-            | PilStatement::Namespace(_, _, _)
+            | PilStatement::Namespace(_, _, _, _)
+            | PilStatement::NamespaceBlock(_, _, _, _, _, _)
+            | PilStatement::NamespaceInstance(_, _, _, _)
             | PilStatement::PlookupIdentity(_, _, _)
             | PilStatement::PermutationIdentity(_, _, _)
             | PilStatement::ConnectIdentity(_, _, _)
+            | PilStatement::LabeledIdentity(_, _, _)
             | PilStatement::Expression(_, _) => Box::new(empty()),
         }
     }
 }
 
+impl SourceReference for PilStatement {
+    fn source_reference(&self) -> &SourceRef {
+        match self {
+            PilStatement::Include(s, _)
+            | PilStatement::Namespace(s, _, _, _)
+            | PilStatement::NamespaceBlock(s, _, _, _, _, _)
+            | PilStatement::NamespaceInstance(s, _, _, _)
+            | PilStatement::LetStatement(s, _, _, _)
+            | PilStatement::PolynomialDefinition(s, _, _)
+            | PilStatement::PublicDeclaration(s, _, _, _, _)
+            | PilStatement::PolynomialConstantDeclaration(s, _)
+            | PilStatement::PolynomialConstantDefinition(s, _, _)
+            | PilStatement::PolynomialCommitDeclaration(s, _, _, _)
+            | PilStatement::PlookupIdentity(s, _, _)
+            | PilStatement::PermutationIdentity(s, _, _)
+            | PilStatement::ConnectIdentity(s, _, _)
+            | PilStatement::LabeledIdentity(s, _, _)
+            | PilStatement::EnumDeclaration(s, _)
+            | PilStatement::StructDeclaration(s, _)
+            | PilStatement::TraitDeclaration(s, _)
+            | PilStatement::TraitImplementation(s, _)
+            | PilStatement::TypeAlias(s, _, _)
+            | PilStatement::Expression(s, _) => s,
+        }
+    }
+    fn source_reference_mut(&mut self) -> &mut SourceRef {
+        match self {
+            PilStatement::Include(s, _)
+            | PilStatement::Namespace(s, _, _, _)
+            | PilStatement::NamespaceBlock(s, _, _, _, _, _)
+            | PilStatement::NamespaceInstance(s, _, _, _)
+            | PilStatement::LetStatement(s, _, _, _)
+            | PilStatement::PolynomialDefinition(s, _, _)
+            | PilStatement::PublicDeclaration(s, _, _, _, _)
+            | PilStatement::PolynomialConstantDeclaration(s, _)
+            | PilStatement::PolynomialConstantDefinition(s, _, _)
+            | PilStatement::PolynomialCommitDeclaration(s, _, _, _)
+            | PilStatement::PlookupIdentity(s, _, _)
+            | PilStatement::PermutationIdentity(s, _, _)
+            | PilStatement::ConnectIdentity(s, _, _)
+            | PilStatement::LabeledIdentity(s, _, _)
+            | PilStatement::EnumDeclaration(s, _)
+            | PilStatement::StructDeclaration(s, _)
+            | PilStatement::TraitDeclaration(s, _)
+            | PilStatement::TraitImplementation(s, _)
+            | PilStatement::TypeAlias(s, _, _)
+            | PilStatement::Expression(s, _) => s,
+        }
+    }
+}
+
 impl Children<Expression> for PilStatement {
     /// Returns an iterator over all (top-level) expressions in this statement.
     fn children(&self) -> Box<dyn Iterator<Item = &Expression> + '_> {
@@ -180,12 +361,24 @@ impl Children<Expression> for PilStatement {
             PilStatement::ConnectIdentity(_start, left, right) => {
                 Box::new(left.iter().chain(right.iter()))
             }
-            PilStatement::Expression(_, e)
-            | PilStatement::Namespace(_, _, Some(e))
-            | PilStatement::PolynomialDefinition(_, _, e) => Box::new(once(e)),
+            PilStatement::LabeledIdentity(_, _, identity) => identity.children(),
+            PilStatement::NamespaceBlock(_, _, _, degree, _, statements) => Box::new(
+                degree
+                    .iter()
+                    .flat_map(|d| d.children())
+                    .chain(statements.iter().flat_map(|s| s.children())),
+            ),
+            PilStatement::NamespaceInstance(_, _, _, arguments) => Box::new(arguments.iter()),
+            PilStatement::Namespace(_, _, Some(degree), _) => Box::new(degree.children()),
+            PilStatement::Expression(_, e) | PilStatement::PolynomialDefinition(_, _, e) => {
+                Box::new(once(e))
+            }
 
             PilStatement::EnumDeclaration(_, enum_decl) => enum_decl.children(),
+            PilStatement::StructDeclaration(_, struct_decl) => struct_decl.children(),
             PilStatement::TraitDeclaration(_, trait_decl) => trait_decl.children(),
+            PilStatement::TraitImplementation(_, trait_impl) => trait_impl.children(),
+            PilStatement::TypeAlias(_, _, ty) => ty.children(),
 
             PilStatement::LetStatement(_, _, type_scheme, value) => Box::new(
                 type_scheme
@@ -200,7 +393,7 @@ impl Children<Expression> for PilStatement {
             | PilStatement::PolynomialCommitDeclaration(_, _, _, Some(def)) => def.children(),
             PilStatement::PolynomialCommitDeclaration(_, _, _, None)
             | PilStatement::Include(_, _)
-            | PilStatement::Namespace(_, _, None)
+            | PilStatement::Namespace(_, _, None, _)
             | PilStatement::PolynomialConstantDeclaration(_, _) => Box::new(empty()),
         }
     }
@@ -215,12 +408,24 @@ impl Children<Expression> for PilStatement {
             PilStatement::ConnectIdentity(_start, left, right) => {
                 Box::new(left.iter_mut().chain(right.iter_mut()))
             }
-            PilStatement::Expression(_, e)
-            | PilStatement::Namespace(_, _, Some(e))
-            | PilStatement::PolynomialDefinition(_, _, e) => Box::new(once(e)),
+            PilStatement::LabeledIdentity(_, _, identity) => identity.children_mut(),
+            PilStatement::NamespaceBlock(_, _, _, degree, _, statements) => Box::new(
+                degree
+                    .iter_mut()
+                    .flat_map(|d| d.children_mut())
+                    .chain(statements.iter_mut().flat_map(|s| s.children_mut())),
+            ),
+            PilStatement::NamespaceInstance(_, _, _, arguments) => Box::new(arguments.iter_mut()),
+            PilStatement::Namespace(_, _, Some(degree), _) => Box::new(degree.children_mut()),
+            PilStatement::Expression(_, e) | PilStatement::PolynomialDefinition(_, _, e) => {
+                Box::new(once(e))
+            }
 
             PilStatement::EnumDeclaration(_, enum_decl) => enum_decl.children_mut(),
+            PilStatement::StructDeclaration(_, struct_decl) => struct_decl.children_mut(),
             PilStatement::TraitDeclaration(_, trait_decl) => trait_decl.children_mut(),
+            PilStatement::TraitImplementation(_, trait_impl) => trait_impl.children_mut(),
+            PilStatement::TypeAlias(_, _, ty) => ty.children_mut(),
 
             PilStatement::LetStatement(_, _, ty, value) => {
                 Box::new(ty.iter_mut().flat_map(|t| t.ty.children_mut()).chain(value))
@@ -232,7 +437,7 @@ impl Children<Expression> for PilStatement {
             | PilStatement::PolynomialCommitDeclaration(_, _, _, Some(def)) => def.children_mut(),
             PilStatement::PolynomialCommitDeclaration(_, _, _, None)
             | PilStatement::Include(_, _)
-            | PilStatement::Namespace(_, _, None)
+            | PilStatement::Namespace(_, _, None, _)
             | PilStatement::PolynomialConstantDeclaration(_, _) => Box::new(empty()),
         }
     }
@@ -320,6 +525,49 @@ impl<R> Children<Expression<R>> for EnumVariant<Expression<R>> {
     }
 }
 
+lazy_static::lazy_static! {
+    /// The name of the constructor function of a struct, i.e. `Point::new` for `struct Point`.
+    /// It is also the name of the (single) variant of the enum the struct is analyzed into.
+    pub static ref STRUCT_CONSTRUCTOR_NAME: String = "new".to_string();
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct StructDeclaration<E = u64> {
+    pub name: String,
+    pub type_vars: TypeBounds,
+    pub fields: Vec<StructField<E>>,
+}
+
+impl<E: Clone> StructDeclaration<E> {
+    /// Returns the enum this struct is analyzed into: It has a single variant
+    /// called `new` whose fields are the types of the struct fields, in order.
+    pub fn to_enum_declaration(&self) -> EnumDeclaration<E> {
+        EnumDeclaration {
+            name: self.name.clone(),
+            type_vars: self.type_vars.clone(),
+            variants: vec![EnumVariant {
+                name: STRUCT_CONSTRUCTOR_NAME.clone(),
+                fields: Some(self.fields.iter().map(|f| f.ty.clone()).collect()),
+            }],
+        }
+    }
+}
+
+impl<R> Children<Expression<R>> for StructDeclaration<Expression<R>> {
+    fn children(&self) -> Box<dyn Iterator<Item = &Expression<R>> + '_> {
+        Box::new(self.fields.iter().flat_map(|f| f.ty.children()))
+    }
+    fn children_mut(&mut self) -> Box<dyn Iterator<Item = &mut Expression<R>> + '_> {
+        Box::new(self.fields.iter_mut().flat_map(|f| f.ty.children_mut()))
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct StructField<E = u64> {
+    pub name: String,
+    pub ty: Type<E>,
+}
+
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TraitDeclaration<E = u64> {
     pub name: String,
@@ -357,6 +605,28 @@ impl<R> Children<Expression<R>> for TraitFunction<Expression<R>> {
     }
 }
 
+/// An implementation of a trait for a named type, for example
+/// `impl Add<Point> { add: |a, b| ... }`.
+/// Only the traits of overloadable operators can be implemented.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct TraitImplementation<E> {
+    /// The name of the implemented trait.
+    pub name: String,
+    /// The name of the implementing type, which has to be declared in the same namespace.
+    pub type_name: String,
+    /// The implemented functions by name.
+    pub functions: Vec<(String, E)>,
+}
+
+impl<R> Children<Expression<R>> for TraitImplementation<Expression<R>> {
+    fn children(&self) -> Box<dyn Iterator<Item = &Expression<R>> + '_> {
+        Box::new(self.functions.iter().map(|(_, f)| f))
+    }
+    fn children_mut(&mut self) -> Box<dyn Iterator<Item = &mut Expression<R>> + '_> {
+        Box::new(self.functions.iter_mut().map(|(_, f)| f))
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct SelectedExpressions<E = Expression<NamespacedPolynomialReference>> {
     pub selector: Option<E>,
@@ -401,11 +671,16 @@ pub enum Expression<Ref = NamespacedPolynomialReference> {
     UnaryOperation(SourceRef, UnaryOperation<Self>),
     BinaryOperation(SourceRef, BinaryOperation<Self>),
     IndexAccess(SourceRef, IndexAccess<Self>),
+    SliceAccess(SourceRef, SliceAccess<Self>),
     FunctionCall(SourceRef, FunctionCall<Self>),
     FreeInput(SourceRef, Box<Self>),
     MatchExpression(SourceRef, MatchExpression<Self>),
     IfExpression(SourceRef, IfExpression<Self>),
     BlockExpression(SourceRef, BlockExpression<Self>),
+    /// Only present in parsed code, desugared during analysis.
+    ArrayComprehension(SourceRef, ArrayComprehension<Self>),
+    /// Only present in parsed code, desugared during analysis.
+    Cast(SourceRef, Cast<Self>),
 }
 
 /// Comparison function for expressions that ignore source information.
@@ -438,11 +713,14 @@ impl_partial_eq_for_expression!(
     BinaryOperation,
     UnaryOperation,
     IndexAccess,
+    SliceAccess,
     FunctionCall,
     FreeInput,
     MatchExpression,
     IfExpression,
-    BlockExpression
+    BlockExpression,
+    ArrayComprehension,
+    Cast
 );
 
 pub trait SourceReference {
@@ -480,13 +758,40 @@ impl_source_reference!(
     BinaryOperation,
     UnaryOperation,
     IndexAccess,
+    SliceAccess,
     FunctionCall,
     FreeInput,
     MatchExpression,
     IfExpression,
-    BlockExpression
+    BlockExpression,
+    ArrayComprehension,
+    Cast
 );
 
+/// `expr as ty`, the conversion of `expr` to the type `ty`, which has to be one of
+/// `int`, `fe` or `expr`. The type of `expr` has to satisfy `FromLiteral`.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Cast<E = Expression<NamespacedPolynomialReference>> {
+    pub expr: Box<E>,
+    pub ty: Type,
+}
+
+impl<Ref> From<Cast<Expression<Ref>>> for Expression<Ref> {
+    fn from(cast: Cast<Expression<Ref>>) -> Self {
+        Expression::Cast(SourceRef::unknown(), cast)
+    }
+}
+
+impl<E> Children<E> for Cast<E> {
+    fn children(&self) -> Box<dyn Iterator<Item = &E> + '_> {
+        Box::new(once(self.expr.as_ref()))
+    }
+
+    fn children_mut(&mut self) -> Box<dyn Iterator<Item = &mut E> + '_> {
+        Box::new(once(self.expr.as_mut()))
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct UnaryOperation<E = Expression<NamespacedPolynomialReference>> {
     pub op: UnaryOperator,
@@ -707,11 +1012,14 @@ impl<R> Expression<R> {
             Expression::BinaryOperation(_, binary_op) => binary_op.children(),
             Expression::UnaryOperation(_, unary_op) => unary_op.children(),
             Expression::IndexAccess(_, index_access) => index_access.children(),
+            Expression::SliceAccess(_, slice_access) => slice_access.children(),
             Expression::FunctionCall(_, function_call) => function_call.children(),
             Expression::FreeInput(_, e) => once(e.as_ref()),
             Expression::MatchExpression(_, match_expr) => match_expr.children(),
             Expression::IfExpression(_, if_expr) => if_expr.children(),
             Expression::BlockExpression(_, block_expr) => block_expr.children(),
+            Expression::ArrayComprehension(_, comprehension) => comprehension.children(),
+            Expression::Cast(_, cast) => cast.children(),
         }
     }
 
@@ -732,11 +1040,14 @@ impl<R> Expression<R> {
             Expression::BinaryOperation(_, binary_op) => binary_op.children_mut(),
             Expression::UnaryOperation(_, unary_op) => unary_op.children_mut(),
             Expression::IndexAccess(_, index_access) => index_access.children_mut(),
+            Expression::SliceAccess(_, slice_access) => slice_access.children_mut(),
             Expression::FunctionCall(_, function_call) => function_call.children_mut(),
             Expression::FreeInput(_, e) => once(e.as_mut()),
             Expression::MatchExpression(_, match_expr) => match_expr.children_mut(),
             Expression::IfExpression(_, if_expr) => if_expr.children_mut(),
             Expression::BlockExpression(_, block_expr) => block_expr.children_mut(),
+            Expression::ArrayComprehension(_, comprehension) => comprehension.children_mut(),
+            Expression::Cast(_, cast) => cast.children_mut(),
         }
     }
 
@@ -858,6 +1169,7 @@ impl<E> Children<E> for ArrayLiteral<E> {
 pub enum UnaryOperator {
     Minus,
     LogicalNot,
+    BinaryNot,
     Next,
 }
 
@@ -865,7 +1177,7 @@ impl UnaryOperator {
     /// Returns true if the operator is a prefix-operator and false if it is a postfix operator.
     pub fn is_prefix(&self) -> bool {
         match self {
-            UnaryOperator::Minus | UnaryOperator::LogicalNot => true,
+            UnaryOperator::Minus | UnaryOperator::LogicalNot | UnaryOperator::BinaryNot => true,
             UnaryOperator::Next => false,
         }
     }
@@ -914,7 +1226,7 @@ impl Precedence for UnaryOperator {
         let precedence = match self {
             // NOTE: Any modification must be done with care to not overlap with BinaryOperator's precedence
             Next => 1,
-            Minus | LogicalNot => 2,
+            Minus | LogicalNot | BinaryNot => 2,
         };
 
         Some(precedence)
@@ -929,24 +1241,25 @@ impl Precedence for BinaryOperator {
             // Unary Oprators
             // **
             Pow => 3,
+            // as (see CAST_PRECEDENCE)
             // * / %
-            Mul | Div | Mod => 4,
+            Mul | Div | Mod => 5,
             // + -
-            Add | Sub => 5,
+            Add | Sub => 6,
             // << >>
-            ShiftLeft | ShiftRight => 6,
+            ShiftLeft | ShiftRight => 7,
             // &
-            BinaryAnd => 7,
+            BinaryAnd => 8,
             // ^
-            BinaryXor => 8,
+            BinaryXor => 9,
             // |
-            BinaryOr => 9,
+            BinaryOr => 10,
             // = == != < > <= >=
-            Identity | Equal | NotEqual | Less | Greater | LessEqual | GreaterEqual => 10,
+            Identity | Equal | NotEqual | Less | Greater | LessEqual | GreaterEqual => 11,
             // &&
-            LogicalAnd => 11,
+            LogicalAnd => 12,
             // ||
-            LogicalOr => 12,
+            LogicalOr => 13,
             // .. ..=
             // ??
         };
@@ -955,11 +1268,15 @@ impl Precedence for BinaryOperator {
     }
 }
 
+/// The precedence of `as`, which binds weaker than `**` but stronger than `*`.
+const CAST_PRECEDENCE: ExpressionPrecedence = 4;
+
 impl<E> Precedence for Expression<E> {
     fn precedence(&self) -> Option<ExpressionPrecedence> {
         match self {
             Expression::UnaryOperation(_, operation) => operation.op.precedence(),
             Expression::BinaryOperation(_, operation) => operation.op.precedence(),
+            Expression::Cast(_, _) => Some(CAST_PRECEDENCE),
             _ => None,
         }
     }
@@ -1003,6 +1320,30 @@ impl<E> Children<E> for IndexAccess<E> {
     }
 }
 
+/// The elements of `array` from `start` (inclusive) to `end` (exclusive).
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SliceAccess<E = Expression<NamespacedPolynomialReference>> {
+    pub array: Box<E>,
+    pub start: Box<E>,
+    pub end: Box<E>,
+}
+
+impl<Ref> From<SliceAccess<Expression<Ref>>> for Expression<Ref> {
+    fn from(sa: SliceAccess<Expression<Ref>>) -> Self {
+        Expression::SliceAccess(SourceRef::unknown(), sa)
+    }
+}
+
+impl<E> Children<E> for SliceAccess<E> {
+    fn children(&self) -> Box<dyn Iterator<Item = &E> + '_> {
+        Box::new([self.array.as_ref(), self.start.as_ref(), self.end.as_ref()].into_iter())
+    }
+
+    fn children_mut(&mut self) -> Box<dyn Iterator<Item = &mut E> + '_> {
+        Box::new([self.array.as_mut(), self.start.as_mut(), self.end.as_mut()].into_iter())
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct FunctionCall<E = Expression<NamespacedPolynomialReference>> {
     pub function: Box<E>,
@@ -1041,6 +1382,57 @@ impl<E> Children<E> for MatchArm<E> {
     }
 }
 
+/// `[value for pattern in iterable]` or `[value for pattern in iterable if condition]`,
+/// the array of `value` for each element of `iterable` (that satisfies `condition`).
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ArrayComprehension<E = Expression<NamespacedPolynomialReference>> {
+    pub value: Box<E>,
+    pub pattern: Box<Pattern>,
+    pub iterable: ComprehensionIterable<E>,
+    pub condition: Option<Box<E>>,
+}
+
+/// The values an array comprehension iterates over.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Serialize, Deserialize, JsonSchema)]
+pub enum ComprehensionIterable<E = Expression<NamespacedPolynomialReference>> {
+    /// The elements of an array.
+    Array(Box<E>),
+    /// The integers from `start` (inclusive) to `end` (exclusive), i.e. `start..end`.
+    Range(Box<E>, Box<E>),
+}
+
+impl<Ref> From<ArrayComprehension<Expression<Ref>>> for Expression<Ref> {
+    fn from(comprehension: ArrayComprehension<Expression<Ref>>) -> Self {
+        Expression::ArrayComprehension(SourceRef::unknown(), comprehension)
+    }
+}
+
+impl<E> Children<E> for ArrayComprehension<E> {
+    fn children(&self) -> Box<dyn Iterator<Item = &E> + '_> {
+        let iterable = match &self.iterable {
+            ComprehensionIterable::Array(array) => vec![array.as_ref()],
+            ComprehensionIterable::Range(start, end) => vec![start.as_ref(), end.as_ref()],
+        };
+        Box::new(
+            once(self.value.as_ref())
+                .chain(iterable)
+                .chain(self.condition.as_deref()),
+        )
+    }
+
+    fn children_mut(&mut self) -> Box<dyn Iterator<Item = &mut E> + '_> {
+        let iterable = match &mut self.iterable {
+            ComprehensionIterable::Array(array) => vec![array.as_mut()],
+            ComprehensionIterable::Range(start, end) => vec![start.as_mut(), end.as_mut()],
+        };
+        Box::new(
+            once(self.value.as_mut())
+                .chain(iterable)
+                .chain(self.condition.as_deref_mut()),
+        )
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct IfExpression<E = Expression<NamespacedPolynomialReference>> {
     pub condition: Box<E>,
@@ -47,7 +47,7 @@ pub trait ExpressionVisitable<Expr> {
     where
         F: FnMut(&mut Expr),
     {
-        self.pre_visit_expressions_return_mut(&mut move |e| {
+        let _ = self.pre_visit_expressions_return_mut(&mut move |e| {
             f(e);
             ControlFlow::Continue::<()>(())
         });
@@ -67,7 +67,7 @@ pub trait ExpressionVisitable<Expr> {
     where
         F: FnMut(&Expr),
     {
-        self.pre_visit_expressions_return(&mut move |e| {
+        let _ = self.pre_visit_expressions_return(&mut move |e| {
             f(e);
             ControlFlow::Continue::<()>(())
         });
@@ -87,7 +87,7 @@ pub trait ExpressionVisitable<Expr> {
     where
         F: FnMut(&mut Expr),
     {
-        self.post_visit_expressions_return_mut(&mut move |e| {
+        let _ = self.post_visit_expressions_return_mut(&mut move |e| {
             f(e);
             ControlFlow::Continue::<()>(())
         });
@@ -107,7 +107,7 @@ pub trait ExpressionVisitable<Expr> {
     where
         F: FnMut(&Expr),
     {
-        self.post_visit_expressions_return(&mut move |e| {
+        let _ = self.post_visit_expressions_return(&mut move |e| {
             f(e);
             ControlFlow::Continue::<()>(())
         });
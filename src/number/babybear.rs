@@ -0,0 +1,76 @@
+// The `MontConfig` derive emits its impls inside a const block.
+#![allow(non_local_definitions)]
+
+use ark_ff::{Fp64, MontBackend, MontConfig};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(MontConfig)]
+#[modulus = "2013265921"]
+#[generator = "31"]
+pub struct BabyBearConfig;
+pub type Fr = Fp64<MontBackend<BabyBearConfig, 1>>;
+
+powdr_field!(BabyBearField, Fr);
+
+#[cfg(test)]
+mod tests {
+    use super::BabyBearField;
+    use crate::number::{traits::int_from_hex_str, FieldElement, LargeInt};
+    use std::str::FromStr;
+    use test_log::test;
+
+    #[test]
+    fn modulus() {
+        assert_eq!(
+            BabyBearField::modulus(),
+            int_from_hex_str::<BabyBearField>("78000001")
+        );
+        assert_eq!(BabyBearField::BITS, 31);
+    }
+
+    #[test]
+    fn minus_one() {
+        let minus_one = BabyBearField::from(0) - BabyBearField::from(1);
+        assert_eq!(minus_one.to_integer().try_into_u64(), Some(0x78000000));
+        assert_eq!(minus_one.to_string(), "2013265920");
+    }
+
+    #[test]
+    fn arithmetic() {
+        let a = BabyBearField::from(0x70000000u32);
+        let b = BabyBearField::from(0x10000000u32);
+        assert_eq!(a + b, BabyBearField::from(0x08000000u32 - 1));
+        assert_eq!(b - a, BabyBearField::from(0x18000001u32));
+        assert_eq!((a * b) / b, a);
+        let inv = BabyBearField::from(1) / a;
+        assert_eq!(inv * a, BabyBearField::from(1));
+        assert_eq!(
+            BabyBearField::from(2).pow(31u32.into()),
+            BabyBearField::from(1u64 << 31)
+        );
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            BabyBearField::from_str("2013265920").unwrap(),
+            -BabyBearField::from(1)
+        );
+        assert!(BabyBearField::from_str("2013265921").is_err());
+        assert!(BabyBearField::from_str_radix("78000001", 16).is_err());
+        assert_eq!(BabyBearField::checked_from(2013265921u64.into()), None);
+    }
+
+    #[test]
+    fn format() {
+        let minus_one = BabyBearField::from(0) - BabyBearField::from(1);
+        assert_eq!(format!("{minus_one:x}"), "78000000");
+    }
+
+    #[test]
+    #[should_panic]
+    fn div_by_zero() {
+        let _ = BabyBearField::from(1) / BabyBearField::from(0);
+    }
+}
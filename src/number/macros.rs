@@ -1,5 +1,8 @@
 macro_rules! powdr_field {
     ($name:ident, $ark_type:ty) => {
+        powdr_field!($name, $ark_type, Some(KnownField::$name));
+    };
+    ($name:ident, $ark_type:ty, $known_field:expr) => {
         use crate::number::{
             traits::{FieldElement, KnownField, LargeInt},
             BigUint, DegreeType,
@@ -321,7 +324,7 @@ macro_rules! powdr_field {
             const BITS: u32 = <$ark_type>::MODULUS_BIT_SIZE;
 
             fn known_field() -> Option<KnownField> {
-                Some(KnownField::$name)
+                $known_field
             }
 
             fn from_str_radix(s: &str, radix: u32) -> Result<Self, String> {
@@ -4,21 +4,25 @@
 
 #[macro_use]
 mod macros;
+mod babybear;
 mod bn254;
 mod goldilocks;
 mod serialize;
+mod toy;
 mod traits;
 pub use serialize::{
     buffered_write_file, read_polys_csv_file, write_polys_csv_file, CsvRenderMode, ReadWrite,
 };
 
+pub use babybear::BabyBearField;
 pub use bn254::Bn254Field;
 pub use goldilocks::GoldilocksField;
+pub use toy::ToyField;
 pub use traits::KnownField;
 
+/// An arbitrary precision big integer, to be used as a last recourse
 pub use ibig::{IBig as BigInt, UBig as BigUint};
 pub use traits::{FieldElement, LargeInt};
-/// An arbitrary precision big integer, to be used as a last recourse
 
 /// The type of polynomial degrees and indices into columns.
 pub type DegreeType = u64;
@@ -43,4 +47,21 @@ mod test {
         assert_eq!(log2_exact(BigUint::from(1u32) << 300), Some(300));
         assert_eq!(log2_exact(17u32.into()), None);
     }
+
+    #[test]
+    fn known_field_from_str() {
+        for field in KnownField::ALL {
+            assert_eq!(field.to_string().parse::<KnownField>(), Ok(field));
+        }
+        assert_eq!("BN254".parse(), Ok(KnownField::Bn254Field));
+        assert_eq!(GoldilocksField::known_field(), "goldilocks".parse().ok());
+        assert_eq!(BabyBearField::known_field(), "babybear".parse().ok());
+        assert_eq!(
+            "mersenne31".parse::<KnownField>(),
+            Err(
+                "Unknown field \"mersenne31\", expected one of: babybear, goldilocks, bn254"
+                    .to_string()
+            )
+        );
+    }
 }
@@ -0,0 +1,49 @@
+// The `MontConfig` derive emits its impls inside a const block.
+#![allow(non_local_definitions)]
+
+use ark_ff::{Fp64, MontBackend, MontConfig};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(MontConfig)]
+#[modulus = "97"]
+#[generator = "5"]
+pub struct ToyConfig;
+pub type Fr = Fp64<MontBackend<ToyConfig, 1>>;
+
+// A tiny prime field for testing, so that values wrap around quickly and
+// all field elements can be enumerated. It is not a known field.
+powdr_field!(ToyField, Fr, None);
+
+#[cfg(test)]
+mod tests {
+    use super::ToyField;
+    use crate::number::{FieldElement, LargeInt};
+    use std::str::FromStr;
+    use test_log::test;
+
+    #[test]
+    fn modulus() {
+        assert_eq!(ToyField::modulus().try_into_u64(), Some(97));
+        assert_eq!(ToyField::BITS, 7);
+        assert_eq!(ToyField::known_field(), None);
+    }
+
+    #[test]
+    fn arithmetic() {
+        let a = ToyField::from(90);
+        let b = ToyField::from(10);
+        assert_eq!(a + b, ToyField::from(3));
+        assert_eq!(b - a, ToyField::from(17));
+        assert_eq!(-ToyField::from(1), ToyField::from(96));
+        assert_eq!((ToyField::from(1) / a) * a, ToyField::from(1));
+        assert_eq!(ToyField::from(100), ToyField::from(3));
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(ToyField::from_str("96").unwrap(), -ToyField::from(1));
+        assert!(ToyField::from_str("97").is_err());
+        assert_eq!(ToyField::checked_from(97u64.into()), None);
+    }
+}
@@ -62,12 +62,49 @@ pub trait LargeInt:
     fn from_hex(s: &str) -> Self;
 }
 
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum KnownField {
+    BabyBearField,
     GoldilocksField,
     Bn254Field,
 }
 
+impl KnownField {
+    /// All known fields, in the order they are listed in the enum.
+    pub const ALL: [KnownField; 3] = [
+        KnownField::BabyBearField,
+        KnownField::GoldilocksField,
+        KnownField::Bn254Field,
+    ];
+}
+
+impl fmt::Display for KnownField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KnownField::BabyBearField => write!(f, "babybear"),
+            KnownField::GoldilocksField => write!(f, "goldilocks"),
+            KnownField::Bn254Field => write!(f, "bn254"),
+        }
+    }
+}
+
+impl FromStr for KnownField {
+    type Err = String;
+
+    /// Parses the lower-case name of a field, as used on command lines.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        KnownField::ALL
+            .into_iter()
+            .find(|field| field.to_string() == s.to_lowercase())
+            .ok_or_else(|| {
+                format!(
+                    "Unknown field \"{s}\", expected one of: {}",
+                    KnownField::ALL.map(|f| f.to_string()).join(", ")
+                )
+            })
+    }
+}
+
 /// A field element
 pub trait FieldElement:
     'static
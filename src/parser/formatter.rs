@@ -0,0 +1,330 @@
+//! Canonical formatting of PIL source files.
+
+use crate::ast::indent;
+use crate::ast::parsed::{Expression, PilStatement, SourceReference};
+use crate::parser::parse;
+use crate::parser_util::{find_comments, Error};
+
+/// Parses `input` and prints it again in canonical form (whitespace, indentation and
+/// operator spacing as produced by the `Display` implementation of the AST),
+/// keeping all comments. The keywords of column declarations (`col witness` or
+/// `pol commit`, ...) and lambda expressions that are the value of a definition stay
+/// as they are written in the source.
+///
+/// Comments before a statement are printed on their own lines before it and comments
+/// after the end of a statement on the same line stay at the end of that statement.
+/// Comments inside of a statement (or the header of a namespace block) are moved
+/// before the statement, since the statement itself might be re-arranged.
+/// Any number of empty lines between two statements or comments is turned into
+/// a single empty line. Only the first line of a multi-line comment is re-indented.
+/// Formatting the output again does not change it.
+pub fn format_source(file_name: Option<&str>, input: &str) -> Result<String, Error> {
+    let pil_file = parse(file_name, input)?;
+    let comments = find_comments(file_name, input)?;
+    // The input with all comments replaced by spaces, so that the end of statements
+    // can be found by looking for the last character that is not whitespace.
+    let mut code = input.as_bytes().to_vec();
+    for (start, end) in &comments {
+        code[*start..*end].fill(b' ');
+    }
+    // We only replaced complete characters, since the delimiters are ASCII.
+    let code = String::from_utf8(code).unwrap();
+    let mut formatter = Formatter {
+        input,
+        code: &code,
+        comments,
+        next_comment: 0,
+        lines: vec![],
+    };
+    formatter.format_statements(&pil_file.0, input.len(), 0, false);
+    Ok(formatter
+        .lines
+        .into_iter()
+        .map(|line| line + "\n")
+        .collect())
+}
+
+struct Formatter<'a> {
+    input: &'a str,
+    code: &'a str,
+    /// Start and end of all comments, in source order.
+    comments: Vec<(usize, usize)>,
+    /// The first comment that has not been printed yet.
+    next_comment: usize,
+    lines: Vec<String>,
+}
+
+impl Formatter<'_> {
+    /// Formats the statements of a file (`in_block` is false) or namespace block together
+    /// with the comments before `end`. The statements are indented by
+    /// `level` in addition to the indentation of their `Display` implementation.
+    fn format_statements(
+        &mut self,
+        statements: &[PilStatement],
+        end: usize,
+        level: usize,
+        in_block: bool,
+    ) {
+        // The end of the last item printed on the current level and its indentation.
+        let mut previous: Option<(usize, usize)> = None;
+        // Statements after a `namespace` statement belong to that namespace and are
+        // indented, while comments before the first one are at the level of the file.
+        let mut in_namespace = in_block;
+        for (i, statement) in statements.iter().enumerate() {
+            let statement_start = statement.source_reference().start;
+            let next_start = statements
+                .get(i + 1)
+                .map(|s| s.source_reference().start)
+                .unwrap_or(end);
+            let statement_end = self.code_end(statement_start, next_start);
+            let indentation = match statement {
+                PilStatement::Namespace(..) | PilStatement::NamespaceBlock(..) => {
+                    level + usize::from(in_block)
+                }
+                _ => level + usize::from(in_namespace),
+            };
+            in_namespace |= matches!(statement, PilStatement::Namespace(..));
+
+            self.format_comments_until(statement_start, indentation, &mut previous);
+            self.add_blank_line_if_needed(previous, statement_start);
+            if let PilStatement::NamespaceBlock(source_ref, name, params, degree, budget, inner) =
+                statement
+            {
+                let body_start = self.code.as_bytes()[statement_start..statement_end]
+                    .iter()
+                    .position(|c| *c == b'{')
+                    .unwrap()
+                    + statement_start;
+                self.format_inner_comments(body_start, indentation);
+                let header = PilStatement::NamespaceBlock(
+                    source_ref.clone(),
+                    name.clone(),
+                    params.clone(),
+                    degree.clone(),
+                    budget.clone(),
+                    vec![],
+                )
+                .to_string();
+                self.lines
+                    .push(indent(header.lines().next().unwrap(), indentation));
+                self.format_statements(inner, statement_end - 1, indentation, true);
+                self.lines.push(indent("}", indentation));
+            } else {
+                self.format_inner_comments(statement_end, indentation);
+                self.lines.extend(
+                    indent(self.statement_to_string(statement, statement_start), level)
+                        .split('\n')
+                        .map(|line| line.to_string()),
+                );
+            }
+            previous = Some((statement_end, indentation));
+        }
+        self.format_comments_until(end, level + usize::from(in_namespace), &mut previous);
+    }
+
+    /// Returns the `Display` output of `statement`, but with the keywords of column
+    /// declarations and the parentheses around a lambda expression as the value of
+    /// a definition as in the source.
+    fn statement_to_string(&self, statement: &PilStatement, start: usize) -> String {
+        let mut printed = statement.to_string();
+        let printed_keywords = match statement {
+            PilStatement::PolynomialDefinition(..) => "pol",
+            PilStatement::PolynomialConstantDeclaration(..)
+            | PilStatement::PolynomialConstantDefinition(..) => "pol constant",
+            PilStatement::PolynomialCommitDeclaration(..) => "pol commit",
+            _ => "",
+        };
+        if !printed_keywords.is_empty() {
+            let source_keywords = self.code[start..]
+                .split_ascii_whitespace()
+                .take(printed_keywords.split(' ').count())
+                .collect::<Vec<_>>()
+                .join(" ");
+            printed = printed.replacen(printed_keywords, &source_keywords, 1);
+        }
+        let value = match statement {
+            PilStatement::LetStatement(_, _, _, Some(value))
+            | PilStatement::PolynomialDefinition(_, _, value) => Some(value),
+            _ => None,
+        };
+        if let Some(Expression::LambdaExpression(source_ref, _)) = value {
+            // The `Display` implementation always puts lambda expressions in parentheses.
+            if !self.code[..source_ref.start].trim_end().ends_with('(') {
+                let open = printed.find(" = (").unwrap() + 3;
+                let close = printed.rfind(");").unwrap();
+                printed = format!(
+                    "{}{}{}",
+                    &printed[..open],
+                    &printed[open + 1..close],
+                    &printed[close + 1..]
+                );
+            }
+        }
+        printed
+    }
+
+    /// Prints the comments that start before `end` on their own lines. Used for the
+    /// comments inside of a statement, which are moved before the statement.
+    fn format_inner_comments(&mut self, end: usize, indentation: usize) {
+        while let Some(&(start, comment_end)) = self.comments.get(self.next_comment) {
+            if start >= end {
+                break;
+            }
+            self.next_comment += 1;
+            let comment = self.input[start..comment_end].trim_end();
+            self.lines.push(indent_first_line(comment, indentation));
+        }
+    }
+
+    /// Prints all comments that start before `position`. Comments on the same line
+    /// as the end of the previous item are appended to it.
+    fn format_comments_until(
+        &mut self,
+        position: usize,
+        indentation: usize,
+        previous: &mut Option<(usize, usize)>,
+    ) {
+        while let Some(&(start, end)) = self.comments.get(self.next_comment) {
+            if start >= position {
+                break;
+            }
+            self.next_comment += 1;
+            let comment = self.input[start..end].trim_end();
+            match previous {
+                Some((previous_end, _)) if !self.input[*previous_end..start].contains('\n') => {
+                    let line = self.lines.last_mut().unwrap();
+                    line.push(' ');
+                    line.push_str(comment);
+                }
+                _ => {
+                    self.add_blank_line_if_needed(*previous, start);
+                    self.lines.push(indent_first_line(comment, indentation));
+                }
+            }
+            *previous = Some((end, indentation));
+        }
+    }
+
+    /// Adds an empty line if the input has an empty line between the end of the previous
+    /// item and `position`.
+    fn add_blank_line_if_needed(&mut self, previous: Option<(usize, usize)>, position: usize) {
+        if let Some((previous_end, _)) = previous {
+            if previous_end < position
+                && self.input[previous_end..position].matches('\n').count() > 1
+            {
+                self.lines.push(String::new());
+            }
+        }
+    }
+
+    /// Returns the position after the last character in `start..end` that is neither
+    /// whitespace nor part of a comment.
+    fn code_end(&self, start: usize, end: usize) -> usize {
+        self.code.as_bytes()[start..end]
+            .iter()
+            .rposition(|c| !c.is_ascii_whitespace())
+            .map(|i| start + i + 1)
+            .unwrap_or(start)
+    }
+}
+
+fn indent_first_line(s: &str, indentation: usize) -> String {
+    format!("{}{s}", "    ".repeat(indentation))
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::format_source;
+
+    fn assert_formatted(input: &str, expected: &str) {
+        let formatted = format_source(None, input).unwrap();
+        assert_eq!(formatted, expected);
+        assert_eq!(format_source(None, &formatted).unwrap(), formatted);
+    }
+
+    #[test]
+    fn whitespace_and_operators() {
+        assert_formatted(
+            "namespace   N(4);\ncol witness x,y;\n  x'=x*(y+1);\n\n\n\nlet f=|i|i+1;\nlet g=(|i|i);",
+            "namespace N(4);\n    col witness x, y;\n    x' = x * (y + 1);\n\n    let f = |i| i + 1;\n    let g = (|i| i);\n",
+        );
+    }
+
+    #[test]
+    fn comments() {
+        let input = r#"// The main machine.
+namespace Main(8); // degree 8
+
+    /// The input.
+    col witness x;
+    col fixed F = [1, /* two */ 2]*;   // fixed
+    /* multi-line
+       comment */
+    x*F=x;
+// At the end.
+"#;
+        let expected = r#"// The main machine.
+namespace Main(8); // degree 8
+
+    /// The input.
+    col witness x;
+    /* two */
+    col fixed F = [1, 2]*; // fixed
+    /* multi-line
+       comment */
+    x * F = x;
+    // At the end.
+"#;
+        assert_formatted(input, expected);
+    }
+
+    #[test]
+    fn namespace_blocks() {
+        let input = r#"namespace A(4) {
+  // in A
+  col witness a; // trailing
+  namespace B {
+     let x = "// not a comment";
+  }
+  // end of A
+}
+"#;
+        let expected = r#"namespace A(4) {
+    // in A
+    col witness a; // trailing
+    namespace B {
+        let x = "// not a comment";
+    }
+    // end of A
+}
+"#;
+        assert_formatted(input, expected);
+    }
+
+    #[test]
+    fn keywords() {
+        assert_formatted(
+            "namespace N(4);\npol commit a;\ncol fixed B(i) { i };\npol constant C;\ncol d = a;",
+            "namespace N(4);\n    pol commit a;\n    col fixed B(i) { i };\n    pol constant C;\n    col d = a;\n",
+        );
+    }
+
+    #[test]
+    fn file_level_comments() {
+        assert_formatted(
+            "// header\ninclude \"a.pil\"; // a\n\n/* b */\nnamespace N(4);\n// c\n",
+            "// header\ninclude \"a.pil\"; // a\n\n/* b */\nnamespace N(4);\n    // c\n",
+        );
+        assert_formatted(
+            "// only\n\n  /* comments */\n",
+            "// only\n\n/* comments */\n",
+        );
+    }
+
+    #[test]
+    fn parse_error() {
+        assert!(format_source(None, "namespace N(4); col witness").is_err());
+    }
+}
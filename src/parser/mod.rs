@@ -8,10 +8,11 @@ use crate::ast::parsed::{
     types::{Type, TypeBounds, TypeScheme},
     Expression, SourceReference,
 };
-use crate::parser_util::{handle_parse_error, Error, SourceRef};
+use crate::parser_util::{blank_out_block_comments, handle_parse_error, Error, SourceRef};
 
 use std::sync::Arc;
 
+pub mod formatter;
 pub mod test_utils;
 
 lalrpop_mod!(
@@ -57,15 +58,64 @@ impl ParserContext {
 
 lazy_static::lazy_static! {
     static ref PIL_FILE_PARSER: powdr::PILFileParser = powdr::PILFileParser::new();
+    static ref PIL_STATEMENT_PARSER: powdr::PilStatementParser = powdr::PilStatementParser::new();
+    static ref EXPRESSION_PARSER: powdr::ExpressionParser = powdr::ExpressionParser::new();
     static ref ASM_MODULE_PARSER: powdr::ASMModuleParser = powdr::ASMModuleParser::new();
     static ref TYPE_PARSER: powdr::TypeExprParser = powdr::TypeExprParser::new();
     static ref TYPE_VAR_BOUNDS_PARSER: powdr::TypeVarBoundsParser = powdr::TypeVarBoundsParser::new();
 }
 
+/// Parses a PIL file into its AST without analyzing it, so the result can be used by
+/// tools like formatters. All nodes carry the location in `input` they were parsed from.
+/// The returned error (the first one, see [`parse_collecting_errors`])
+/// contains the location of the problem in its [`Error::source_ref`].
 pub fn parse(file_name: Option<&str>, input: &str) -> Result<crate::ast::parsed::PILFile, Error> {
+    parse_collecting_errors(file_name, input).map_err(|mut errors| errors.remove(0))
+}
+
+/// Same as [`parse`], but if a statement cannot be parsed, parsing continues after
+/// the next semicolon and all errors are returned, in source order.
+pub fn parse_collecting_errors(
+    file_name: Option<&str>,
+    input: &str,
+) -> Result<crate::ast::parsed::PILFile, Vec<Error>> {
+    let ctx = ParserContext::new(file_name, input);
+    let lexer_input = blank_out_block_comments(file_name, input).map_err(|e| vec![e])?;
+    let mut recovered = vec![];
+    let result = PIL_FILE_PARSER.parse(&ctx, &mut recovered, &lexer_input);
+    let mut errors = recovered
+        .into_iter()
+        .map(|recovery| handle_parse_error(recovery.error, file_name, input))
+        .collect::<Vec<_>>();
+    match result {
+        Ok(pil_file) if errors.is_empty() => Ok(pil_file),
+        Ok(_) => Err(errors),
+        Err(err) => {
+            errors.push(handle_parse_error(err, file_name, input));
+            Err(errors)
+        }
+    }
+}
+
+/// Parses a single PIL statement, for example a declaration, a definition or an identity,
+/// including its terminating semicolon.
+pub fn parse_statement(
+    file_name: Option<&str>,
+    input: &str,
+) -> Result<crate::ast::parsed::PilStatement, Error> {
     let ctx = ParserContext::new(file_name, input);
-    PIL_FILE_PARSER
-        .parse(&ctx, input)
+    let lexer_input = blank_out_block_comments(file_name, input)?;
+    PIL_STATEMENT_PARSER
+        .parse(&ctx, &mut vec![], &lexer_input)
+        .map_err(|err| handle_parse_error(err, file_name, input))
+}
+
+/// Parses a single expression.
+pub fn parse_expression(file_name: Option<&str>, input: &str) -> Result<Expression, Error> {
+    let ctx = ParserContext::new(file_name, input);
+    let lexer_input = blank_out_block_comments(file_name, input)?;
+    EXPRESSION_PARSER
+        .parse(&ctx, &mut vec![], &lexer_input)
         .map_err(|err| handle_parse_error(err, file_name, input))
 }
 
@@ -81,15 +131,16 @@ pub fn parse_module(
     input: &str,
 ) -> Result<crate::ast::parsed::asm::ASMModule, Error> {
     let ctx = ParserContext::new(file_name, input);
+    let lexer_input = blank_out_block_comments(file_name, input)?;
     ASM_MODULE_PARSER
-        .parse(&ctx, input)
+        .parse(&ctx, &mut vec![], &lexer_input)
         .map_err(|err| handle_parse_error(err, file_name, input))
 }
 
 pub fn parse_type(input: &str) -> Result<Type<crate::ast::parsed::Expression>, Error> {
     let ctx = ParserContext::new(None, input);
     TYPE_PARSER
-        .parse(&ctx, input)
+        .parse(&ctx, &mut vec![], input)
         .map_err(|err| handle_parse_error(err, None, input))
 }
 
@@ -98,7 +149,7 @@ pub fn parse_type_var_bounds(input: &str) -> Result<TypeBounds, Error> {
     // We use GoldilocksField here, because we need to specify a concrete type,
     // even though the grammar for TypeBounds does not depend on the field.
     TYPE_VAR_BOUNDS_PARSER
-        .parse(&ctx, input)
+        .parse(&ctx, &mut vec![], input)
         .map_err(|err| handle_parse_error(err, None, input))
 }
 
@@ -153,14 +204,18 @@ mod test {
     fn empty() {
         let input = "";
         let ctx = ParserContext::new(None, input);
-        assert!(powdr::PILFileParser::new().parse(&ctx, input).is_ok());
+        assert!(powdr::PILFileParser::new()
+            .parse(&ctx, &mut vec![], input)
+            .is_ok());
     }
 
     #[test]
     fn simple_include() {
         let input = "include \"x\";";
         let ctx = ParserContext::new(None, input);
-        let parsed = powdr::PILFileParser::new().parse(&ctx, input).unwrap();
+        let parsed = powdr::PILFileParser::new()
+            .parse(&ctx, &mut vec![], input)
+            .unwrap();
         assert_eq!(
             parsed,
             PILFile(vec![PilStatement::Include(
@@ -179,7 +234,9 @@ mod test {
     fn start_offsets() {
         let input = "include \"x\"; pol commit t;";
         let ctx = ParserContext::new(None, input);
-        let parsed = powdr::PILFileParser::new().parse(&ctx, input).unwrap();
+        let parsed = powdr::PILFileParser::new()
+            .parse(&ctx, &mut vec![], input)
+            .unwrap();
         assert_eq!(
             parsed,
             PILFile(vec![
@@ -215,7 +272,7 @@ mod test {
         let input = "[f] in [g];";
         let ctx = ParserContext::new(None, input);
         let parsed = powdr::PILFileParser::new()
-            .parse(&ctx, "[f] in [g];")
+            .parse(&ctx, &mut vec![], "[f] in [g];")
             .unwrap();
         assert_eq!(
             parsed,
@@ -339,6 +396,32 @@ mod test {
         }
     }
 
+    #[test]
+    /// Test that formatting pil files is idempotent and does not change the AST
+    fn format_pil() {
+        use test_utils::ClearSourceRefs;
+        let crate_dir = env!("CARGO_MANIFEST_DIR");
+        let basedir = std::path::PathBuf::from(format!("{crate_dir}/src/test_data/"));
+        let pil_files = find_files_with_ext(basedir, "pil".into());
+        for (file, orig_string) in pil_files {
+            let formatted =
+                formatter::format_source(Some(&file), &orig_string).unwrap_err_to_stderr();
+            assert_eq!(
+                formatter::format_source(Some(&file), &formatted).unwrap_err_to_stderr(),
+                formatted,
+                "formatting is not idempotent for file: {file}"
+            );
+            let mut orig_pil = parse(Some(&file), &orig_string).unwrap_err_to_stderr();
+            let mut formatted_pil = parse(Some(&file), &formatted).unwrap_err_to_stderr();
+            orig_pil.clear_source_refs();
+            formatted_pil.clear_source_refs();
+            assert_eq!(
+                orig_pil, formatted_pil,
+                "formatting changed the AST of file: {file}"
+            );
+        }
+    }
+
     use self::parse;
 
     #[test]
@@ -385,6 +468,36 @@ namespace Fibonacci(N);
         assert_eq!(input.trim(), printed.trim());
     }
 
+    #[test]
+    fn reparse_binary_not() {
+        let input = r#"let x = ~(~7 & 3) + (!true || false);"#;
+        let printed = format!("{}", parse(Some("input"), input).unwrap());
+        assert_eq!(input.trim(), printed.trim());
+    }
+
+    #[test]
+    fn reparse_namespace_budget() {
+        let input = "namespace N(8) with max_identities: 10, max_degree: 3;\nnamespace M with max_degree: 2;";
+        let printed = format!("{}", parse(Some("input"), input).unwrap());
+        assert_eq!(input.trim(), printed.trim());
+        let err = parse(
+            Some("input"),
+            "namespace N with max_degree: 2, max_degree: 3;",
+        )
+        .unwrap_err();
+        assert_eq!(err.message(), "`max_degree` already defined");
+    }
+
+    #[test]
+    fn hex_and_binary_literals() {
+        let input = r#"let x = [0xffffffff, 0xff_ff, 0b1010, 0b1111_0000, 1_000];"#;
+        let printed = format!("{}", parse(Some("input"), input).unwrap());
+        assert_eq!(
+            printed.trim(),
+            "let x = [4294967295, 65535, 10, 240, 1000];"
+        );
+    }
+
     #[test]
     fn array_literals() {
         let input = r#"let x = [[1], [2], [3 + 7]];"#;
@@ -437,6 +550,192 @@ namespace N(2);
         assert_eq!(input.trim(), printed.trim());
     }
 
+    #[test]
+    fn struct_decls() {
+        let input = r#"
+namespace N(2);
+    struct Empty {
+    }
+    struct Point {
+        x: int,
+        y: fe,
+    }
+    struct Pair<T1, T2: Ord> {
+        first: T1,
+        second: T2[],
+        f: (T1 -> T2),
+    }
+"#;
+        let printed = format!("{}", parse(Some("input"), input).unwrap());
+        assert_eq!(input.trim(), printed.trim());
+    }
+
+    #[test]
+    fn trait_impls() {
+        let input = r#"
+namespace N(2);
+    impl Add<Point> {
+        add: (|a, b| Point.new(Point.x(a) + Point.x(b))),
+    }
+    impl Neg<Point> {
+    }
+"#;
+        let printed = format!("{}", parse(Some("input"), input).unwrap());
+        assert_eq!(input.trim(), printed.trim());
+    }
+
+    #[test]
+    fn slices() {
+        let input = r#"
+namespace N(2);
+    let x = [1, 2, 3][0..2];
+    let y = (|a| a)(x)[1..N.len(x)];
+    let z = x[0..1][0];
+"#;
+        let printed = format!("{}", parse(Some("input"), input).unwrap());
+        assert_eq!(input.trim(), printed.trim());
+    }
+
+    #[test]
+    fn trailing_commas() {
+        let input = r#"
+namespace N(2);
+    let x = [1, 2, 3,];
+    let y = (1, [4,], 5,);
+    let z = N.f(x, y,);
+"#;
+        let printed = format!("{}", parse(Some("input"), input).unwrap());
+        let expected = r#"
+namespace N(2);
+    let x = [1, 2, 3];
+    let y = (1, [4], 5);
+    let z = N.f(x, y);
+"#;
+        assert_eq!(expected.trim(), printed.trim());
+    }
+
+    #[test]
+    fn type_scheme_with_multiple_bounds() {
+        let scheme = parse_type_scheme("T: Ord + Add, U: Eq, V", "T[], (U -> V) -> (T, V)");
+        assert_eq!(
+            scheme
+                .vars
+                .bounds()
+                .map(|(var, bounds)| (var.as_str(), bounds.iter().map(|b| b.as_str()).collect()))
+                .collect::<Vec<(_, Vec<_>)>>(),
+            [("T", vec!["Add", "Ord"]), ("U", vec!["Eq"]), ("V", vec![])]
+        );
+        assert_eq!(scheme.vars.to_string(), "T: Add + Ord, U: Eq, V");
+        assert_eq!(scheme.ty.to_string(), "T[], (U -> V) -> (T, V)");
+    }
+
+    #[test]
+    fn namespace_blocks() {
+        let input = r#"
+namespace A(8) {
+    pol commit x;
+    namespace B::C with max_degree: 2 {
+        let y = A.x;
+        namespace D(4) {
+        }
+    }
+    x = 0;
+}
+namespace E;
+namespace Adder<N, M>(N) {
+    pol commit a;
+    namespace Inner = Adder(N / 2, M);
+}
+    namespace Instance = Adder(4, 2);
+"#;
+        let printed = format!("{}", parse(Some("input"), input).unwrap());
+        assert_eq!(input.trim(), printed.trim());
+        assert!(parse(Some("input"), "namespace A { namespace B; }").is_err());
+        assert!(parse(Some("input"), "namespace A { include \"x.pil\"; }").is_err());
+    }
+
+    #[test]
+    fn identity_labels() {
+        let input = r#"
+namespace N(2);
+    #[label("bool")]
+    x * (1 - x) = 0;
+    #[label("table \"a\"")]
+    [x] in [y];
+"#;
+        let printed = format!("{}", parse(Some("input"), input).unwrap());
+        assert_eq!(input.trim(), printed.trim());
+        let err = parse(Some("input"), "#[name(\"a\")] x = 0;").unwrap_err();
+        assert_eq!(err.message(), "unknown attribute `name`");
+    }
+
+    #[test]
+    fn namespace_degree_ranges() {
+        let input = r#"
+namespace A(8..2 ** 10);
+    pol commit x;
+namespace B(N) {
+}
+namespace C(1 + 1..N) with max_identities: 1;
+"#;
+        let printed = format!("{}", parse(Some("input"), input).unwrap());
+        assert_eq!(input.trim(), printed.trim());
+    }
+
+    #[test]
+    fn recover_from_errors() {
+        let input = r#"
+namespace N(2);
+    let x = 1 +;
+    let y = 2;
+    col witness w w;
+    let z = 3;
+"#;
+        let errors = parse_collecting_errors(Some("input"), input).unwrap_err();
+        let positions = errors
+            .iter()
+            .map(|e| &input[e.source_ref().start..e.source_ref().end])
+            .collect::<Vec<_>>();
+        assert_eq!(positions, [";", "w"]);
+        assert_eq!(
+            parse(Some("input"), input).unwrap_err().message(),
+            errors[0].message()
+        );
+
+        // An error at the end of the input is reported together with earlier errors.
+        let errors = parse_collecting_errors(Some("input"), "let x = 1 +;\nlet y =").unwrap_err();
+        assert_eq!(errors.len(), 2);
+    }
+
+    #[test]
+    fn nested_block_comments() {
+        let input = r#"
+namespace N(2);
+    /* a /* nested */
+       comment */
+    let x = /* inline */ 1 +;
+"#;
+        let err = parse(Some("input"), input).unwrap_err();
+        assert_eq!(err.source_ref().to_string(), "input:5:29");
+        let printed = format!(
+            "{}",
+            parse(Some("input"), &input.replace(" +;", ";")).unwrap()
+        );
+        assert_eq!(printed.trim(), "namespace N(2);\n    let x = 1;");
+    }
+
+    #[test]
+    fn type_aliases() {
+        let input = r#"
+namespace N(2);
+    type Word = int[32];
+    type Pair = (Word, std::prelude::Constr);
+    type F = (fe -> int)[];
+"#;
+        let printed = format!("{}", parse(Some("input"), input).unwrap());
+        assert_eq!(input.trim(), printed.trim());
+    }
+
     #[test]
     fn patterns() {
         let input = r#"
@@ -561,4 +860,67 @@ namespace N(8);
         let printed = format!("{}", parse(Some("input"), input).unwrap_err_to_stderr());
         assert_eq!(expected.trim(), printed.trim());
     }
+
+    #[test]
+    fn array_comprehension() {
+        let input = r#"
+namespace N(8);
+    let a = [x*2 for x in 0..n];
+    let b = [f(i,j) for (i, j) in pairs if i>0 && j < 3];
+    let c = [[y for y in 0..x] for x in [1,2]];
+"#;
+        let expected = r#"
+namespace N(8);
+    let a = [x * 2 for x in 0..n];
+    let b = [f(i, j) for (i, j) in pairs if i > 0 && j < 3];
+    let c = [[y for y in 0..x] for x in [1, 2]];
+"#;
+        let printed = format!("{}", parse(Some("input"), input).unwrap_err_to_stderr());
+        assert_eq!(expected.trim(), printed.trim());
+    }
+
+    #[test]
+    fn casts() {
+        let input = r#"
+namespace N(8);
+    let a = -x as fe + y as fe * z as int;
+    let b = x ** 2 as expr;
+    let c = 2 ** (x as int);
+    let d = (x + 1) as fe as expr;
+    let e = (x as expr)';
+    let f = [i as fe for i in 0..3];
+"#;
+        let expected = r#"
+namespace N(8);
+    let a = -x as fe + y as fe * z as int;
+    let b = x ** 2 as expr;
+    let c = 2 ** (x as int);
+    let d = (x + 1) as fe as expr;
+    let e = (x as expr)';
+    let f = [i as fe for i in 0..3];
+"#;
+        let printed = format!("{}", parse(Some("input"), input).unwrap_err_to_stderr());
+        assert_eq!(expected.trim(), printed.trim());
+        // Parsing the printed code again has to result in the same structure.
+        let reparsed = format!("{}", parse(Some("input"), &printed).unwrap_err_to_stderr());
+        assert_eq!(printed, reparsed);
+    }
+
+    #[test]
+    fn single_statement_and_expression() {
+        let statement = parse_statement(None, "col witness x, y;").unwrap();
+        assert_eq!(statement.to_string().trim(), "pol commit x, y;");
+        let statement = parse_statement(None, "x' = x /* next */ * 2;").unwrap();
+        assert_eq!(statement.to_string().trim(), "x' = x * 2;");
+        assert!(parse_statement(None, "col witness x; col witness y;").is_err());
+
+        let expr = parse_expression(Some("input"), "a + b * (c - 1)").unwrap();
+        assert_eq!(expr.to_string(), "a + b * (c - 1)");
+        let source_ref = expr.source_reference();
+        assert_eq!((source_ref.start, source_ref.end), (0, 15));
+
+        let error = parse_expression(Some("input"), "a + * b").unwrap_err();
+        assert_eq!(error.source_ref().start, 4);
+        assert_eq!(error.source_ref().end, 5);
+    }
 }
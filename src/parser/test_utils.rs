@@ -97,7 +97,7 @@ impl ClearSourceRefs for PilStatement {
 
         match self {
             PilStatement::Include(s, _)
-            | PilStatement::Namespace(s, _, _)
+            | PilStatement::Namespace(s, _, _, _)
             | PilStatement::LetStatement(s, _, _, _)
             | PilStatement::PolynomialDefinition(s, _, _)
             | PilStatement::PublicDeclaration(s, _, _, _, _)
@@ -108,8 +108,22 @@ impl ClearSourceRefs for PilStatement {
             | PilStatement::PermutationIdentity(s, _, _)
             | PilStatement::ConnectIdentity(s, _, _)
             | PilStatement::Expression(s, _)
+            | PilStatement::NamespaceInstance(s, _, _, _)
             | PilStatement::EnumDeclaration(s, _)
-            | PilStatement::TraitDeclaration(s, _) => *s = SourceRef::unknown(),
+            | PilStatement::StructDeclaration(s, _)
+            | PilStatement::TraitDeclaration(s, _)
+            | PilStatement::TraitImplementation(s, _)
+            | PilStatement::TypeAlias(s, _, _) => *s = SourceRef::unknown(),
+            PilStatement::LabeledIdentity(s, _, identity) => {
+                *s = SourceRef::unknown();
+                identity.clear_source_refs();
+            }
+            PilStatement::NamespaceBlock(s, _, _, _, _, statements) => {
+                *s = SourceRef::unknown();
+                statements
+                    .iter_mut()
+                    .for_each(ClearSourceRefs::clear_source_refs);
+            }
         }
     }
 }
@@ -128,9 +142,12 @@ impl<R> ClearSourceRefs for Expression<R> {
             | Expression::UnaryOperation(_, _)
             | Expression::BinaryOperation(_, _)
             | Expression::IndexAccess(_, _)
+            | Expression::SliceAccess(_, _)
             | Expression::FunctionCall(_, _)
             | Expression::FreeInput(_, _)
-            | Expression::IfExpression(_, _) => {}
+            | Expression::IfExpression(_, _)
+            | Expression::Cast(_, _) => {}
+            Expression::ArrayComprehension(_, c) => c.pattern.clear_source_refs(),
             Expression::BlockExpression(_, b) => b.clear_source_refs(),
             Expression::MatchExpression(_, m) => m.clear_source_refs(),
             Expression::LambdaExpression(_, l) => l.clear_source_refs(),
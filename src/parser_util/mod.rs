@@ -30,6 +30,35 @@ impl SourceRef {
             message,
         }
     }
+
+    /// Returns the 1-based line and column of the start of this reference,
+    /// if the file contents are known.
+    pub fn line_col(&self) -> Option<(usize, usize)> {
+        let before = self.file_contents.as_deref()?.get(..self.start)?;
+        let line = before.matches('\n').count() + 1;
+        let col = before.chars().rev().take_while(|c| *c != '\n').count() + 1;
+        Some((line, col))
+    }
+
+    /// Returns the source code this reference points to, if the file contents are known.
+    pub fn source_text(&self) -> Option<&str> {
+        self.file_contents.as_deref()?.get(self.start..self.end)
+    }
+}
+
+/// Formats the source reference as `file:line:col`, falling back to
+/// `file:start-end` (byte offsets) if the file contents are not known.
+impl fmt::Display for SourceRef {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.line_col() {
+            Some((line, col)) => write!(
+                f,
+                "{}:{line}:{col}",
+                self.file_name.as_deref().unwrap_or("input")
+            ),
+            None => write!(f, "{self:?}"),
+        }
+    }
 }
 
 impl Debug for SourceRef {
@@ -44,7 +73,7 @@ impl Debug for SourceRef {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Error {
     source_ref: SourceRef,
     message: String,
@@ -52,7 +81,7 @@ pub struct Error {
 
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(f, "{} at {:?}", self.message, self.source_ref)
+        write!(f, "{}: {}", self.source_ref, self.message)
     }
 }
 
@@ -81,6 +110,10 @@ impl Error {
     pub fn message(&self) -> &str {
         &self.message
     }
+
+    pub fn source_ref(&self) -> &SourceRef {
+        &self.source_ref
+    }
 }
 
 pub fn handle_parse_error(
@@ -116,6 +149,86 @@ pub fn handle_parse_error(
     }
 }
 
+/// Returns the start and end of all line comments `// ...` and block comments `/* ... */`
+/// in `input`, in source order.
+/// Block comments can be nested. Comment delimiters inside string literals are ignored.
+/// Returns an error if a block comment is not terminated.
+pub fn find_comments(file_name: Option<&str>, input: &str) -> Result<Vec<(usize, usize)>, Error> {
+    let bytes = input.as_bytes();
+    let mut comments = vec![];
+    let mut i = 0;
+    while i < bytes.len() {
+        match &bytes[i..] {
+            [b'/', b'/', ..] => {
+                let start = i;
+                while i < bytes.len() && !matches!(bytes[i], b'\n' | b'\r') {
+                    i += 1;
+                }
+                comments.push((start, i));
+            }
+            [b'"', ..] => {
+                i += 1;
+                while i < bytes.len() && !matches!(bytes[i], b'"' | b'\n' | b'\r') {
+                    i += if bytes[i] == b'\\' { 2 } else { 1 };
+                }
+                i += 1;
+            }
+            [b'/', b'*', ..] => {
+                let start = i;
+                let mut depth = 0;
+                loop {
+                    match &bytes[i..] {
+                        [b'/', b'*', ..] => {
+                            depth += 1;
+                            i += 2;
+                        }
+                        [b'*', b'/', ..] => {
+                            depth -= 1;
+                            i += 2;
+                            if depth == 0 {
+                                break;
+                            }
+                        }
+                        [_, ..] => i += 1,
+                        [] => {
+                            let source_ref = SourceRef {
+                                file_name: file_name.map(Into::into),
+                                file_contents: Some(input.into()),
+                                start,
+                                end: start + 2,
+                            };
+                            return Err(
+                                source_ref.with_error("Unterminated block comment.".to_string())
+                            );
+                        }
+                    }
+                }
+                comments.push((start, i));
+            }
+            _ => i += 1,
+        }
+    }
+    Ok(comments)
+}
+
+/// Replaces all block comments `/* ... */` in `input` by spaces, keeping line breaks,
+/// so that byte offsets and thus source references stay the same.
+/// Returns an error if a block comment is not terminated, see [`find_comments`].
+pub fn blank_out_block_comments(file_name: Option<&str>, input: &str) -> Result<String, Error> {
+    let mut output = input.as_bytes().to_vec();
+    for (start, end) in find_comments(file_name, input)? {
+        if input[start..].starts_with("/*") {
+            for b in &mut output[start..end] {
+                if !matches!(b, b'\n' | b'\r') {
+                    *b = b' ';
+                }
+            }
+        }
+    }
+    // We only replaced complete characters, since the delimiters are ASCII.
+    Ok(String::from_utf8(output).unwrap())
+}
+
 /// Convenience trait that outputs parser errors to stderr and panics.
 /// Should be used mostly in tests.
 pub trait UnwrapErrToStderr {
@@ -136,3 +249,59 @@ impl<T> UnwrapErrToStderr for Result<T, Error> {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{blank_out_block_comments, find_comments, SourceRef};
+
+    fn source_ref(contents: &str, start: usize) -> SourceRef {
+        SourceRef {
+            file_name: Some("file.pil".into()),
+            file_contents: Some(contents.into()),
+            start,
+            end: start + 1,
+        }
+    }
+
+    #[test]
+    fn line_col() {
+        let contents = "namespace N(4);\n    col witness x;\n";
+        assert_eq!(source_ref(contents, 0).line_col(), Some((1, 1)));
+        assert_eq!(source_ref(contents, 10).line_col(), Some((1, 11)));
+        assert_eq!(source_ref(contents, 20).line_col(), Some((2, 5)));
+        assert_eq!(source_ref(contents, 200).line_col(), None);
+        assert_eq!(source_ref(contents, 20).to_string(), "file.pil:2:5");
+        assert_eq!(
+            source_ref(contents, 20)
+                .with_error("oops".to_string())
+                .to_string(),
+            "file.pil:2:5: oops"
+        );
+        assert_eq!(SourceRef::unknown().to_string(), ":0-0");
+    }
+
+    #[test]
+    fn block_comments() {
+        let input = "a /* b /* c */\n d */ e // f /* g\n\"/* h\" /*/ i */";
+        assert_eq!(
+            blank_out_block_comments(None, input).unwrap(),
+            "a             \n      e // f /* g\n\"/* h\"         "
+        );
+        let input = "a /* b /* c */ d";
+        assert_eq!(
+            blank_out_block_comments(Some("file.pil"), input)
+                .unwrap_err()
+                .to_string(),
+            "file.pil:1:3: Unterminated block comment."
+        );
+    }
+
+    #[test]
+    fn comments() {
+        let input = "a /* b /* c */\n d */ e // f /* g\n\"// h\" /*/ i */";
+        assert_eq!(
+            find_comments(None, input).unwrap(),
+            vec![(2, 20), (23, 32), (40, 48)]
+        );
+    }
+}
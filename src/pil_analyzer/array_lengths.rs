@@ -0,0 +1,279 @@
+use std::collections::HashMap;
+
+use crate::ast::analyzed::{
+    Expression, FunctionValueDefinition, Identity, IdentityKind, PolynomialReference, Reference,
+    Symbol, TypedExpression,
+};
+use crate::ast::parsed::types::{ArrayType, Type};
+use crate::ast::parsed::visitor::AllChildren;
+use crate::ast::parsed::{
+    ArrayLiteral, BinaryOperation, BinaryOperator, FunctionCall, Number, SelectedExpressions,
+    SliceAccess, SourceReference,
+};
+use crate::pil_analyzer::type_unifier::TypeError;
+
+/// How many definitions are followed when computing the length of an array.
+const MAX_DEPTH: usize = 32;
+
+/// Computes the lengths of arrays without evaluating any code, as far as they
+/// are determined by the shape of the expressions.
+pub struct ArrayLengths<'a> {
+    definitions: &'a HashMap<String, (Symbol, Option<FunctionValueDefinition>)>,
+}
+
+impl<'a> ArrayLengths<'a> {
+    pub fn new(
+        definitions: &'a HashMap<String, (Symbol, Option<FunctionValueDefinition>)>,
+    ) -> Self {
+        Self { definitions }
+    }
+
+    /// Returns the length of the array `e` evaluates to, if it is known statically.
+    /// Lengths are known for column arrays, array literals, definitions with a
+    /// declared length and concatenations of arrays of known length, and they are
+    /// tracked through `std::array::map`, `std::array::new` (with a literal length),
+    /// `std::array::reverse`, `std::array::flatten` (of an array literal), `std::array::zip`,
+    /// `std::utils::cartesian_product`, `std::convert::to_bits` (with a literal number of bits)
+    /// and slices with literal bounds. The conversions `std::array::from_tuple<n>` return
+    /// arrays of length n.
+    pub fn length(&self, e: &Expression) -> Option<u64> {
+        self.length_with_depth(e, 0)
+    }
+
+    fn length_with_depth(&self, e: &Expression, depth: usize) -> Option<u64> {
+        let length = |e: &Expression| self.length_with_depth(e, depth);
+        match e {
+            Expression::Reference(_, Reference::Poly(PolynomialReference { name, .. })) => {
+                let (symbol, value) = self.definitions.get(name)?;
+                match value {
+                    _ if symbol.length.is_some() => symbol.length,
+                    Some(FunctionValueDefinition::Expression(TypedExpression {
+                        e,
+                        type_scheme,
+                    })) => match type_scheme {
+                        Some(scheme) if !scheme.vars.is_empty() => None,
+                        Some(scheme) if declared_length(&scheme.ty).is_some() => {
+                            declared_length(&scheme.ty)
+                        }
+                        _ if depth < MAX_DEPTH => self.length_with_depth(e, depth + 1),
+                        _ => None,
+                    },
+                    _ => None,
+                }
+            }
+            Expression::ArrayLiteral(_, ArrayLiteral { items }) => Some(items.len() as u64),
+            Expression::BinaryOperation(
+                _,
+                BinaryOperation {
+                    left,
+                    op: BinaryOperator::Add,
+                    right,
+                },
+            ) => Some(length(left)? + length(right)?),
+            Expression::SliceAccess(_, SliceAccess { array, start, end }) => {
+                let (start, end) = (literal(start)?, literal(end)?);
+                (start <= end && end <= length(array)?).then_some(end - start)
+            }
+            Expression::FunctionCall(
+                _,
+                FunctionCall {
+                    function,
+                    arguments,
+                },
+            ) => {
+                let Expression::Reference(_, Reference::Poly(PolynomialReference { name, .. })) =
+                    function.as_ref()
+                else {
+                    return None;
+                };
+                match (name.as_str(), &arguments[..]) {
+                    ("std::array::map" | "std::array::reverse", [array, ..]) => length(array),
+                    ("std::array::new", [len, _]) => literal(len),
+                    (
+                        "std::array::flatten",
+                        [Expression::ArrayLiteral(_, ArrayLiteral { items })],
+                    ) => items.iter().map(length).sum(),
+                    ("std::array::zip", [left, right, _]) => {
+                        let length = length(left)?;
+                        (Some(length) == self.length_with_depth(right, depth)).then_some(length)
+                    }
+                    ("std::utils::cartesian_product", [left, right]) => {
+                        Some(length(left)? * length(right)?)
+                    }
+                    ("std::convert::to_bits", [_, bits]) => literal(bits),
+                    ("std::array::from_tuple2", [_]) => Some(2),
+                    ("std::array::from_tuple3", [_]) => Some(3),
+                    ("std::array::from_tuple4", [_]) => Some(4),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Checks the statically known array lengths in the definitions and identities:
+    /// Values of definitions need to have the declared length, both arrays passed to
+    /// `std::array::zip` need to have the same length, arrays passed to `std::array::sum`
+    /// must not be empty and both sides of lookups, permutations and connections need
+    /// to have the same number of expressions.
+    /// The errors are sorted by their position in the source.
+    pub fn check(
+        &self,
+        identities: &[Identity<SelectedExpressions<Expression>>],
+    ) -> Vec<TypeError> {
+        let mut definitions = self.definitions.values().collect::<Vec<_>>();
+        definitions.sort_by(|(a, _), (b, _)| a.absolute_name.cmp(&b.absolute_name));
+        let declared_length_errors = definitions.iter().filter_map(|(symbol, value)| {
+            let Some(FunctionValueDefinition::Expression(TypedExpression { e, type_scheme })) =
+                value
+            else {
+                return None;
+            };
+            let declared = symbol
+                .length
+                .or_else(|| declared_length(&type_scheme.as_ref()?.ty))?;
+            let length = self.length(e)?;
+            (length != declared).then(|| {
+                TypeError::other(
+                    e.source_reference(),
+                    format!(
+                        "Array {e} has length {length}, but {} is declared to have length {declared}.",
+                        symbol.absolute_name
+                    ),
+                )
+            })
+        });
+        let call_errors = definitions
+            .iter()
+            .filter_map(|(_, value)| value.as_ref())
+            .flat_map(|value| value.all_children())
+            .chain(identities.iter().flat_map(|id| id.all_children()))
+            .filter_map(|e| {
+                let Expression::FunctionCall(_, FunctionCall { function, arguments }) = e else {
+                    return None;
+                };
+                let Expression::Reference(_, Reference::Poly(PolynomialReference { name, .. })) =
+                    function.as_ref()
+                else {
+                    return None;
+                };
+                let message = match (name.as_str(), &arguments[..]) {
+                    ("std::array::zip", [left, right, _]) => {
+                        let (left, right) = (self.length(left)?, self.length(right)?);
+                        (left != right).then(|| format!("std::array::zip is applied to arrays of different lengths {left} and {right} in {e}."))?
+                    }
+                    ("std::array::sum", [array]) => (self.length(array)? == 0).then(|| {
+                        format!("std::array::sum is applied to an empty array in {e}, use std::array::sum_with_zero to supply a zero.")
+                    })?,
+                    _ => return None,
+                };
+                Some(TypeError::other(e.source_reference(), message))
+            });
+        let identity_errors = identities.iter().filter_map(|identity| {
+            let kind = match identity.kind {
+                IdentityKind::Polynomial => return None,
+                IdentityKind::Plookup => "Lookup",
+                IdentityKind::Permutation => "Permutation",
+                IdentityKind::Connect => "Connection",
+            };
+            let left = self.length(&identity.left.expressions)?;
+            let right = self.length(&identity.right.expressions)?;
+            (left != right).then(|| {
+                TypeError::other(
+                    &identity.source,
+                    format!("{kind} {identity} has {left} expressions on the left side, but {right} on the right side."),
+                )
+            })
+        });
+        let mut errors = declared_length_errors
+            .chain(call_errors)
+            .chain(identity_errors)
+            .collect::<Vec<_>>();
+        errors.sort_by(|a, b| {
+            let (a, b) = (a.span(), b.span());
+            (&a.file_name, a.start).cmp(&(&b.file_name, b.start))
+        });
+        errors
+    }
+}
+
+/// Returns the declared length if `ty` is an array type with a length.
+fn declared_length(ty: &Type) -> Option<u64> {
+    match ty {
+        Type::Array(ArrayType { length, .. }) => *length,
+        _ => None,
+    }
+}
+
+/// Returns the value of `e` if it is a number literal.
+fn literal(e: &Expression) -> Option<u64> {
+    match e {
+        Expression::Number(_, Number { value, .. }) => u64::try_from(value).ok(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::number::GoldilocksField;
+    use crate::pil_analyzer::{analyze_string, AnalysisError};
+
+    const DECLARATIONS: &str = r#"
+namespace std::array;
+    let<T1, T2> map: T1[], (T1 -> T2) -> T2[] = [];
+    let<T> new: int, (int -> T) -> T[] = [];
+    let<T> flatten: T[][] -> T[] = [];
+    let<T1, T2, T3> zip: T1[], T2[], (T1, T2 -> T3) -> T3[] = [];
+    let<T: Add> sum: T[] -> T = [];
+"#;
+
+    fn errors(input: &str) -> Vec<String> {
+        let input = format!("{DECLARATIONS}{input}");
+        match analyze_string::<GoldilocksField>(&input) {
+            Err(AnalysisError::Type(errors)) => errors.iter().map(|e| e.to_string()).collect(),
+            Ok(_) => vec![],
+            Err(e) => panic!("Unexpected error: {e}"),
+        }
+    }
+
+    #[test]
+    fn consistent_lengths() {
+        let input = r#"
+namespace N(8);
+    col witness x[3], y[2];
+    col fixed T1(i) { i };
+    col fixed T2(i) { i * 2 };
+    let both = std::array::map(x + y, |e| e * 2);
+    let table: expr[5] = std::array::flatten([[T1, T2], std::array::new(3, |_| T1)]);
+    both in table;
+    std::array::zip(x, [1, 2, 3], |a, b| a * b) is x;
+    y in std::array::map(x, |e| e + 1)[1..3];
+"#;
+        assert_eq!(errors(input), Vec::<String>::new());
+    }
+
+    #[test]
+    fn inconsistent_lengths() {
+        let input = r#"
+namespace N(8);
+    col witness x[3], y[2];
+    col fixed T(i) { i };
+    let doubled = std::array::map(x, |e| e * 2);
+    let ones: expr[2] = std::array::new(3, |_| 1);
+    let products = std::array::zip(x, y, |a, b| a * b);
+    doubled in [T, T];
+    doubled + y is x + [T];
+    std::array::sum(x[1..1]) = 0;
+"#;
+        assert_eq!(
+            errors(input),
+            [
+                "input:13:25: Array std::array::new::<expr>(3, (|_| 1)) has length 3, but N.ones is declared to have length 2.",
+                "input:14:20: std::array::zip is applied to arrays of different lengths 3 and 2 in std::array::zip::<expr, expr, expr>(N.x, N.y, (|a, b| a * b)).",
+                "input:15:5: Lookup N.doubled in [N.T, N.T]; has 3 expressions on the left side, but 2 on the right side.",
+                "input:16:5: Permutation N.doubled + N.y is N.x + [N.T]; has 5 expressions on the left side, but 4 on the right side.",
+                "input:17:5: std::array::sum is applied to an empty array in std::array::sum::<expr>(N.x[1..1]), use std::array::sum_with_zero to supply a zero."
+            ]
+        );
+    }
+}
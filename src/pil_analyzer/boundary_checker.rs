@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+
+use itertools::Itertools;
+
+use crate::ast::analyzed::{
+    AlgebraicBinaryOperation, AlgebraicBinaryOperator, AlgebraicExpression,
+    AlgebraicUnaryOperation, Analyzed, IdentityKind, PolyID, PolynomialType,
+};
+use crate::ast::parsed::visitor::AllChildren;
+use crate::number::FieldElement;
+use crate::parser_util::Error;
+
+/// Returns a warning for every polynomial identity that uses the next row of a
+/// witness or intermediate column outside of a product with a selector.
+/// Such an identity also has to hold for the last row, where the next row is the
+/// first row, which is usually not intended.
+/// A selector is an expression that only references fixed columns (directly or
+/// through intermediate columns), like `(1 - LAST)`.
+/// Next references inside the definitions of intermediate columns are not considered.
+pub fn unrestricted_next_warnings<T: FieldElement>(pil: &Analyzed<T>) -> Vec<Error> {
+    let intermediates = pil
+        .intermediate_columns
+        .values()
+        .flat_map(|(symbol, values)| {
+            symbol
+                .array_elements()
+                .zip(values)
+                .map(|((_, poly_id), value)| (poly_id, value))
+        })
+        .collect::<HashMap<_, _>>();
+    pil.identities
+        .iter()
+        .filter(|identity| identity.kind == IdentityKind::Polynomial)
+        .filter_map(|identity| {
+            let mut columns = vec![];
+            unrestricted_next_references(
+                identity.expression_for_poly_id(),
+                &intermediates,
+                &mut columns,
+            );
+            let columns = columns.into_iter().unique().collect::<Vec<_>>();
+            (!columns.is_empty()).then(|| {
+                identity.source.with_error(format!(
+                    "Identity {identity} uses the next row of column{} {} without a selector, so it also applies to the wrap-around from the last to the first row.",
+                    if columns.len() == 1 { "" } else { "s" },
+                    columns.iter().format(", ")
+                ))
+            })
+        })
+        .collect()
+}
+
+/// Collects the names of all witness and intermediate columns whose next row is
+/// referenced in `e` outside of a product with a selector.
+fn unrestricted_next_references<'a, T>(
+    e: &'a AlgebraicExpression<T>,
+    intermediates: &HashMap<PolyID, &AlgebraicExpression<T>>,
+    columns: &mut Vec<&'a str>,
+) {
+    match e {
+        AlgebraicExpression::BinaryOperation(AlgebraicBinaryOperation {
+            left,
+            op: AlgebraicBinaryOperator::Mul,
+            right,
+        }) if is_selector(left, intermediates) || is_selector(right, intermediates) => {}
+        AlgebraicExpression::Reference(reference)
+            if reference.next && reference.poly_id.ptype != PolynomialType::Constant =>
+        {
+            columns.push(&reference.name);
+        }
+        AlgebraicExpression::Reference(_)
+        | AlgebraicExpression::PublicReference(_)
+        | AlgebraicExpression::Challenge(_)
+        | AlgebraicExpression::Number(_) => {}
+        AlgebraicExpression::BinaryOperation(AlgebraicBinaryOperation { left, right, .. }) => {
+            unrestricted_next_references(left, intermediates, columns);
+            unrestricted_next_references(right, intermediates, columns);
+        }
+        AlgebraicExpression::UnaryOperation(AlgebraicUnaryOperation { expr, .. }) => {
+            unrestricted_next_references(expr, intermediates, columns);
+        }
+    }
+}
+
+/// Returns true if `e` references at least one column and all columns it references
+/// are fixed columns or intermediate columns that are selectors.
+fn is_selector<T>(
+    e: &AlgebraicExpression<T>,
+    intermediates: &HashMap<PolyID, &AlgebraicExpression<T>>,
+) -> bool {
+    let mut references = e
+        .all_children()
+        .filter_map(|e| match e {
+            AlgebraicExpression::Reference(reference) => Some(reference),
+            _ => None,
+        })
+        .peekable();
+    references.peek().is_some()
+        && e.all_children().all(|e| {
+            !matches!(
+                e,
+                AlgebraicExpression::PublicReference(_) | AlgebraicExpression::Challenge(_)
+            )
+        })
+        && references.all(|reference| match reference.poly_id.ptype {
+            PolynomialType::Constant => true,
+            PolynomialType::Committed => false,
+            PolynomialType::Intermediate => intermediates
+                .get(&reference.poly_id)
+                .is_some_and(|value| is_selector(value, intermediates)),
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::number::GoldilocksField;
+    use crate::pil_analyzer::{analyze_string_with_options, AnalysisOptions};
+
+    fn warnings(input: &str) -> Vec<String> {
+        let options = AnalysisOptions {
+            report_lints: true,
+            ..Default::default()
+        };
+        let (_, warnings) =
+            analyze_string_with_options::<GoldilocksField>("input", input, &options).unwrap();
+        warnings.iter().map(|w| w.to_string()).collect()
+    }
+
+    #[test]
+    fn next_without_selector() {
+        let input = r#"namespace N(4);
+    col fixed LAST = [0, 0, 0, 1];
+    col witness x, y;
+    col not_last = 1 - LAST;
+    col sq = x * x;
+    x' = x + 1;
+    y' * x' = y + sq' + x';
+    (1 - LAST) * (x' - x) = 0;
+    not_last * y' = not_last * y;
+    LAST' * x = 0;
+    x * (y' - y) = 0;
+"#;
+        assert_eq!(
+            warnings(input),
+            [
+                "input:6:5: Identity N.x' = N.x + 1; uses the next row of column N.x without a selector, so it also applies to the wrap-around from the last to the first row.",
+                "input:7:5: Identity N.y' * N.x' = N.y + N.sq' + N.x'; uses the next row of columns N.y, N.x, N.sq without a selector, so it also applies to the wrap-around from the last to the first row.",
+                "input:11:5: Identity N.x * (N.y' - N.y) = 0; uses the next row of column N.y without a selector, so it also applies to the wrap-around from the last to the first row."
+            ]
+        );
+    }
+}
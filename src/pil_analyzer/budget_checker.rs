@@ -0,0 +1,140 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use crate::ast::analyzed::Analyzed;
+use crate::ast::parsed::NamespaceBudget;
+use crate::number::FieldElement;
+use crate::parser_util::SourceRef;
+use crate::pil_analyzer::AnalysisError;
+
+/// The budgets declared by namespaces, together with the namespace of every identity,
+/// collected while processing the statements.
+#[derive(Default)]
+pub struct NamespaceBudgets {
+    /// The namespace, the source of the namespace statement and the declared budget.
+    budgets: Vec<(String, SourceRef, NamespaceBudget)>,
+    /// The namespace of each identity, keyed by the location of the identity in the source.
+    /// Identities keep their source when they are condensed.
+    identity_namespaces: HashMap<(Option<Arc<str>>, usize, usize), String>,
+}
+
+impl NamespaceBudgets {
+    pub fn add_budget(&mut self, namespace: String, source: SourceRef, budget: NamespaceBudget) {
+        if budget != NamespaceBudget::default() {
+            self.budgets.push((namespace, source, budget));
+        }
+    }
+
+    pub fn add_identity(&mut self, namespace: String, source: &SourceRef) {
+        self.identity_namespaces.insert(location(source), namespace);
+    }
+}
+
+fn location(source: &SourceRef) -> (Option<Arc<str>>, usize, usize) {
+    (source.file_name.clone(), source.start, source.end)
+}
+
+/// Checks that every namespace with a budget has at most the declared number of identities
+/// and that none of its identities has a degree larger than the declared maximum degree,
+/// where the degree is computed as in [`Analyzed::identity_degrees`].
+/// If a namespace statement is repeated with a budget, each of the budgets is checked.
+pub fn check_budgets<T: FieldElement>(
+    pil: &Analyzed<T>,
+    budgets: &NamespaceBudgets,
+) -> Result<(), AnalysisError> {
+    if budgets.budgets.is_empty() {
+        return Ok(());
+    }
+    let degrees = pil.identity_degrees();
+    let mut identities_by_namespace = BTreeMap::<&str, Vec<_>>::new();
+    for identity in &pil.identities {
+        if let Some(namespace) = budgets.identity_namespaces.get(&location(&identity.source)) {
+            identities_by_namespace
+                .entry(namespace)
+                .or_default()
+                .push(identity);
+        }
+    }
+    let mut errors = vec![];
+    for (namespace, source, budget) in &budgets.budgets {
+        let identities = identities_by_namespace
+            .get(namespace.as_str())
+            .map(|identities| identities.as_slice())
+            .unwrap_or_default();
+        if let Some(max_identities) = budget.max_identities {
+            if identities.len() as u64 > max_identities {
+                errors.push(source.with_error(format!(
+                    "Namespace {namespace} has {} identities, which exceeds its budget of {max_identities}.",
+                    identities.len()
+                )));
+            }
+        }
+        if let Some(max_degree) = budget.max_degree {
+            errors.extend(
+                identities
+                    .iter()
+                    .filter(|identity| degrees[&identity.id] as u64 > max_degree)
+                    .map(|identity| {
+                        identity.source.with_error(format!(
+                            "Identity {identity} has degree {}, which exceeds the maximum degree of {max_degree} of namespace {namespace}.",
+                            degrees[&identity.id]
+                        ))
+                    }),
+            );
+        }
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(AnalysisError::BudgetExceeded(errors))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::number::GoldilocksField;
+    use crate::pil_analyzer::{analyze_string, AnalysisError};
+
+    #[test]
+    fn budgets_are_respected() {
+        let input = r#"namespace N(4) with max_identities: 2, max_degree: 2;
+    col witness x, y;
+    x * y = 0;
+    x' = y;
+namespace M(4);
+    col witness z;
+    z * z * z = 0;
+namespace N(4);
+    y' = x;
+"#;
+        let Err(AnalysisError::BudgetExceeded(errors)) = analyze_string::<GoldilocksField>(input)
+        else {
+            panic!()
+        };
+        assert_eq!(
+            errors.iter().map(|e| e.to_string()).collect::<Vec<_>>(),
+            ["input:1:1: Namespace N has 3 identities, which exceeds its budget of 2."]
+        );
+
+        let input = r#"namespace N(4) with max_degree: 2;
+    col witness x, y;
+    col sq = x * x;
+    sq * y = 0;
+    x' = sq;
+"#;
+        let Err(AnalysisError::BudgetExceeded(errors)) = analyze_string::<GoldilocksField>(input)
+        else {
+            panic!()
+        };
+        assert_eq!(
+            errors.iter().map(|e| e.to_string()).collect::<Vec<_>>(),
+            ["input:4:5: Identity N.sq * N.y = 0; has degree 3, which exceeds the maximum degree of 2 of namespace N."]
+        );
+
+        let input = r#"namespace N(4) with max_identities: 1, max_degree: 3;
+    col witness x;
+    x * x * x = 1;
+"#;
+        assert!(analyze_string::<GoldilocksField>(input).is_ok());
+    }
+}
@@ -10,7 +10,7 @@ use std::{
 
 use crate::ast::{
     analyzed::{
-        self, AlgebraicExpression, AlgebraicReference, Analyzed, Expression,
+        self, AlgebraicExpression, AlgebraicReference, Analyzed, DegreeRange, Expression,
         FunctionValueDefinition, Identity, IdentityKind, PolynomialType, PublicDeclaration,
         SelectedExpressions, StatementIdentifier, Symbol, SymbolKind,
     },
@@ -19,15 +19,16 @@ use crate::ast::{
         asm::{AbsoluteSymbolPath, SymbolPath},
         display::format_type_scheme_around_name,
         types::{ArrayType, Type},
-        TypedExpression,
+        SourceReference, TypedExpression,
     },
 };
-use crate::number::{DegreeType, FieldElement};
-use crate::parser_util::SourceRef;
+use crate::number::FieldElement;
+use crate::parser_util::{Error, SourceRef};
 
 use crate::pil_analyzer::{
     evaluator::{self, Definitions, EvalError, SymbolLookup, Value},
     statement_processor::Counters,
+    FeOverflow,
 };
 
 type ParsedIdentity = Identity<parsed::SelectedExpressions<Expression>>;
@@ -39,12 +40,14 @@ pub fn condense<T: FieldElement>(
     identities: &[ParsedIdentity],
     source_order: Vec<StatementIdentifier>,
     auto_added_symbols: HashSet<String>,
-) -> Analyzed<T> {
-    let mut condenser = Condenser::new(&definitions);
+    fe_overflow: FeOverflow,
+) -> Result<Analyzed<T>, Vec<Error>> {
+    let mut condenser = Condenser::new(&definitions, fe_overflow);
 
     let mut condensed_identities = vec![];
     let mut intermediate_columns = HashMap::new();
     let mut new_columns = vec![];
+    let mut errors = vec![];
     // Condense identities and intermediate columns and update the source order.
     let source_order = source_order
         .into_iter()
@@ -57,7 +60,9 @@ pub fn condense<T: FieldElement>(
             }
             let statement = match s {
                 StatementIdentifier::Identity(index) => {
-                    condenser.condense_identity(&identities[index]);
+                    if let Err(e) = condenser.condense_identity(&identities[index]) {
+                        errors.push(e);
+                    }
                     None
                 }
                 StatementIdentifier::Definition(name)
@@ -70,7 +75,7 @@ pub fn condense<T: FieldElement>(
                     let Some(FunctionValueDefinition::Expression(e)) = definition else {
                         panic!("Expected expression")
                     };
-                    let value = if let Some(length) = symbol.length {
+                    let value: Result<_, _> = if let Some(length) = symbol.length {
                         let scheme = e.type_scheme.as_ref();
                         assert!(
                             scheme.unwrap().vars.is_empty()
@@ -81,9 +86,9 @@ pub fn condense<T: FieldElement>(
                             "Intermediate column type has to be expr[], but got: {}",
                             format_type_scheme_around_name(&name, &e.type_scheme)
                         );
-                        let result = condenser.condense_to_array_of_algebraic_expressions(&e.e);
-                        assert_eq!(result.len() as u64, length);
-                        result
+                        condenser
+                            .condense_to_array_of_algebraic_expressions(&e.e)
+                            .inspect(|result| assert_eq!(result.len() as u64, length))
                     } else {
                         assert_eq!(
                             e.type_scheme,
@@ -91,9 +96,16 @@ pub fn condense<T: FieldElement>(
                             "Intermediate column type has to be expr, but got: {}",
                             format_type_scheme_around_name(&name, &e.type_scheme)
                         );
-                        vec![condenser.condense_to_algebraic_expression(&e.e)]
+                        condenser
+                            .condense_to_algebraic_expression(&e.e)
+                            .map(|e| vec![e])
                     };
-                    intermediate_columns.insert(name.clone(), (symbol.clone(), value));
+                    match value {
+                        Ok(value) => {
+                            intermediate_columns.insert(name.clone(), (symbol.clone(), value));
+                        }
+                        Err(e) => errors.push(e),
+                    }
                     Some(StatementIdentifier::Definition(name))
                 }
                 s => Some(s),
@@ -126,6 +138,9 @@ pub fn condense<T: FieldElement>(
                 .chain(statement)
         })
         .collect();
+    if !errors.is_empty() {
+        return Err(errors);
+    }
 
     definitions.retain(|name, _| !intermediate_columns.contains_key(name));
     for (symbol, value) in new_columns {
@@ -142,20 +157,20 @@ pub fn condense<T: FieldElement>(
         // maybe move it into PublicDeclaration.
         reference.poly_id = Some(symbol.into());
     }
-    Analyzed {
+    Ok(Analyzed {
         definitions,
         public_declarations,
         intermediate_columns,
         identities: condensed_identities,
         source_order,
         auto_added_symbols,
-    }
+    })
 }
 
 type SymbolCacheKey = (String, Option<Vec<Type>>);
 
 pub struct Condenser<'a, T> {
-    degree: Option<DegreeType>,
+    degree: Option<DegreeRange>,
     /// All the definitions from the PIL file.
     symbols: &'a HashMap<String, (Symbol, Option<FunctionValueDefinition>)>,
     /// Evaluation cache.
@@ -169,10 +184,15 @@ pub struct Condenser<'a, T> {
     /// The names of all new olumns ever generated, to avoid duplicates.
     all_new_names: HashSet<String>,
     new_constraints: Vec<AnalyzedIdentity<T>>,
+    /// What to do with field element literals that are too large for the field.
+    fe_overflow: FeOverflow,
 }
 
 impl<'a, T: FieldElement> Condenser<'a, T> {
-    pub fn new(symbols: &'a HashMap<String, (Symbol, Option<FunctionValueDefinition>)>) -> Self {
+    pub fn new(
+        symbols: &'a HashMap<String, (Symbol, Option<FunctionValueDefinition>)>,
+        fe_overflow: FeOverflow,
+    ) -> Self {
         let counters = Counters::with_existing(symbols.values().map(|(sym, _)| sym), None, None);
         Self {
             symbols,
@@ -183,10 +203,11 @@ impl<'a, T: FieldElement> Condenser<'a, T> {
             new_columns: vec![],
             all_new_names: HashSet::new(),
             new_constraints: vec![],
+            fe_overflow,
         }
     }
 
-    pub fn condense_identity(&mut self, identity: &'a ParsedIdentity) {
+    pub fn condense_identity(&mut self, identity: &'a ParsedIdentity) -> Result<(), Error> {
         if identity.kind == IdentityKind::Polynomial {
             let expr = identity.expression_for_poly_id();
             evaluator::evaluate(expr, self)
@@ -195,24 +216,27 @@ impl<'a, T: FieldElement> Condenser<'a, T> {
                         assert!(items.is_empty());
                         Ok(())
                     } else {
-                        self.add_constraints(expr, identity.source.clone())
+                        let start = self.new_constraints.len();
+                        self.add_constraints(expr, identity.source.clone())?;
+                        for constraint in &mut self.new_constraints[start..] {
+                            constraint.label.clone_from(&identity.label);
+                        }
+                        Ok(())
                     }
                 })
-                .unwrap_or_else(|err| {
-                    panic!(
-                        "Error reducing expression to constraint:\nExpression: {expr}\nError: {err:?}"
-                    )
-                });
+                .map_err(|err| reduction_error(&identity.source, expr, err))
         } else {
-            let left = self.condense_selected_expressions(&identity.left);
-            let right = self.condense_selected_expressions(&identity.right);
+            let left = self.condense_selected_expressions(&identity.left)?;
+            let right = self.condense_selected_expressions(&identity.right)?;
             self.new_constraints.push(Identity {
                 id: self.counters.dispense_identity_id(),
                 kind: identity.kind,
                 source: identity.source.clone(),
+                label: identity.label.clone(),
                 left,
                 right,
-            })
+            });
+            Ok(())
         }
     }
 
@@ -220,7 +244,7 @@ impl<'a, T: FieldElement> Condenser<'a, T> {
     pub fn set_namespace_and_degree(
         &mut self,
         namespace: AbsoluteSymbolPath,
-        degree: Option<DegreeType>,
+        degree: Option<DegreeRange>,
     ) {
         self.namespace = namespace;
         self.degree = degree;
@@ -239,24 +263,29 @@ impl<'a, T: FieldElement> Condenser<'a, T> {
     fn condense_selected_expressions(
         &mut self,
         sel_expr: &'a parsed::SelectedExpressions<Expression>,
-    ) -> SelectedExpressions<AlgebraicExpression<T>> {
-        SelectedExpressions {
+    ) -> Result<SelectedExpressions<AlgebraicExpression<T>>, Error> {
+        Ok(SelectedExpressions {
             selector: sel_expr
                 .selector
                 .as_ref()
-                .map(|expr| self.condense_to_algebraic_expression(expr)),
-            expressions: self.condense_to_array_of_algebraic_expressions(&sel_expr.expressions),
-        }
+                .map(|expr| self.condense_to_algebraic_expression(expr))
+                .transpose()?,
+            expressions: self.condense_to_array_of_algebraic_expressions(&sel_expr.expressions)?,
+        })
     }
 
     /// Evaluates the expression and expects it to result in an algebraic expression.
-    fn condense_to_algebraic_expression(&mut self, e: &'a Expression) -> AlgebraicExpression<T> {
-        let result = evaluator::evaluate(e, self).unwrap_or_else(|err| {
-            panic!("Error reducing expression to constraint:\nExpression: {e}\nError: {err:?}")
-        });
+    fn condense_to_algebraic_expression(
+        &mut self,
+        e: &'a Expression,
+    ) -> Result<AlgebraicExpression<T>, Error> {
+        let result = evaluator::evaluate(e, self)
+            .map_err(|err| reduction_error(e.source_reference(), e, err))?;
         match result.as_ref() {
-            Value::Expression(expr) => expr.clone(),
-            _ => panic!("Expected expression but got {result}"),
+            Value::Expression(expr) => Ok(expr.clone()),
+            _ => Err(e
+                .source_reference()
+                .with_error(format!("Expected expression but got {result}"))),
         }
     }
 
@@ -264,19 +293,22 @@ impl<'a, T: FieldElement> Condenser<'a, T> {
     fn condense_to_array_of_algebraic_expressions(
         &mut self,
         e: &'a Expression,
-    ) -> Vec<AlgebraicExpression<T>> {
-        let result = evaluator::evaluate(e, self).unwrap_or_else(|err| {
-            panic!("Error reducing expression to constraint:\nExpression: {e}\nError: {err:?}")
-        });
+    ) -> Result<Vec<AlgebraicExpression<T>>, Error> {
+        let result = evaluator::evaluate(e, self)
+            .map_err(|err| reduction_error(e.source_reference(), e, err))?;
         match result.as_ref() {
             Value::Array(items) => items
                 .iter()
                 .map(|item| match item.as_ref() {
-                    Value::Expression(expr) => expr.clone(),
-                    _ => panic!("Expected expression but got {item}"),
+                    Value::Expression(expr) => Ok(expr.clone()),
+                    _ => Err(e
+                        .source_reference()
+                        .with_error(format!("Expected expression but got {item}"))),
                 })
                 .collect(),
-            _ => panic!("Expected array of algebraic expressions but got {result}"),
+            _ => Err(e.source_reference().with_error(format!(
+                "Expected array of algebraic expressions but got {result}"
+            ))),
         }
     }
 }
@@ -284,7 +316,7 @@ impl<'a, T: FieldElement> Condenser<'a, T> {
 impl<'a, T: FieldElement> SymbolLookup<'a, T> for Condenser<'a, T> {
     fn lookup(
         &mut self,
-        name: &'a str,
+        name: &str,
         type_args: Option<Vec<Type>>,
     ) -> Result<Arc<Value<'a, T>>, EvalError> {
         // Cache already computed values.
@@ -305,11 +337,31 @@ impl<'a, T: FieldElement> SymbolLookup<'a, T> for Condenser<'a, T> {
         Definitions(self.symbols).lookup_public_reference(name)
     }
 
+    fn fe_overflow(&self) -> FeOverflow {
+        self.fe_overflow
+    }
+
     fn degree(&self) -> Result<Arc<Value<'a, T>>, EvalError> {
         let degree = self.degree.ok_or(EvalError::DataNotAvailable)?;
+        let degree = degree.try_into_unique().ok_or_else(|| {
+            EvalError::Unsupported(format!(
+                "Cannot evaluate degree, the degree of namespace {} is the range {degree}. Use std::prover::min_degree or std::prover::max_degree instead.",
+                self.namespace.relative_to(&Default::default())
+            ))
+        })?;
         Ok(Value::Integer(degree.into()).into())
     }
 
+    fn min_degree(&self) -> Result<Arc<Value<'a, T>>, EvalError> {
+        let degree = self.degree.ok_or(EvalError::DataNotAvailable)?;
+        Ok(Value::Integer(degree.min.into()).into())
+    }
+
+    fn max_degree(&self) -> Result<Arc<Value<'a, T>>, EvalError> {
+        let degree = self.degree.ok_or(EvalError::DataNotAvailable)?;
+        Ok(Value::Integer(degree.max.into()).into())
+    }
+
     fn new_column(
         &mut self,
         name: &str,
@@ -397,13 +449,19 @@ impl<'a, T: FieldElement> Condenser<'a, T> {
     }
 }
 
+fn reduction_error(source: &SourceRef, e: &Expression, err: EvalError) -> Error {
+    source.with_error(format!(
+        "Error reducing expression to constraint:\nExpression: {e}\nError: {err:?}"
+    ))
+}
+
 fn to_constraint<T: FieldElement>(
     constraint: &Value<'_, T>,
     source: SourceRef,
     counters: &mut Counters,
 ) -> AnalyzedIdentity<T> {
     match constraint {
-        Value::Enum("Identity", Some(fields)) => {
+        Value::Enum(_, "Identity", Some(fields)) => {
             assert_eq!(fields.len(), 2);
             AnalyzedIdentity::from_polynomial_identity(
                 counters.dispense_identity_id(),
@@ -411,7 +469,7 @@ fn to_constraint<T: FieldElement>(
                 to_expr(&fields[0]) - to_expr(&fields[1]),
             )
         }
-        Value::Enum(kind @ "Lookup" | kind @ "Permutation", Some(fields)) => {
+        Value::Enum(_, kind @ "Lookup" | kind @ "Permutation", Some(fields)) => {
             assert_eq!(fields.len(), 2);
             let kind = if *kind == "Lookup" {
                 IdentityKind::Plookup
@@ -445,11 +503,12 @@ fn to_constraint<T: FieldElement>(
                 id: counters.dispense_identity_id(),
                 kind,
                 source,
+                label: None,
                 left: to_selected_exprs(sel_from, from),
                 right: to_selected_exprs(sel_to, to),
             }
         }
-        Value::Enum("Connection", Some(fields)) => {
+        Value::Enum(_, "Connection", Some(fields)) => {
             assert_eq!(fields.len(), 1);
 
             let (from, to): (Vec<_>, Vec<_>) = if let Value::Array(a) = fields[0].as_ref() {
@@ -471,6 +530,7 @@ fn to_constraint<T: FieldElement>(
                 id: counters.dispense_identity_id(),
                 kind: IdentityKind::Connect,
                 source,
+                label: None,
                 left: analyzed::SelectedExpressions {
                     selector: None,
                     expressions: from.into_iter().map(to_expr).collect(),
@@ -497,8 +557,8 @@ fn to_selected_exprs<'a, T: Clone>(
 
 fn to_option_expr<T: Clone>(value: &Value<'_, T>) -> Option<AlgebraicExpression<T>> {
     match value {
-        Value::Enum("None", None) => None,
-        Value::Enum("Some", Some(fields)) => {
+        Value::Enum(_, "None", None) => None,
+        Value::Enum(_, "Some", Some(fields)) => {
             assert_eq!(fields.len(), 1);
             Some(to_expr(&fields[0]))
         }
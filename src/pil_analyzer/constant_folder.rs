@@ -0,0 +1,176 @@
+use crate::ast::analyzed::{
+    AlgebraicBinaryOperation, AlgebraicBinaryOperator, AlgebraicExpression,
+    AlgebraicUnaryOperation, AlgebraicUnaryOperator, Analyzed, IdentityKind,
+};
+use crate::number::FieldElement;
+use crate::parser_util::Error;
+use crate::pil_analyzer::AnalysisError;
+
+/// Replaces all sub-expressions of identities and intermediate columns that
+/// only consist of numbers by their value in the field.
+/// References to columns, publics and challenges are left untouched.
+pub fn fold_constants<T: FieldElement>(analyzed: &mut Analyzed<T>) {
+    analyzed.post_visit_expressions_in_identities_mut(&mut fold_constants_in_expression);
+}
+
+/// Checks the polynomial identities that are constant after constant folding.
+/// Identities that reduce to a nonzero constant can never be satisfied and result in
+/// an [`AnalysisError::UnsatisfiableIdentity`]. Identities that reduce to zero
+/// are always satisfied and returned as warnings, they can be removed using
+/// [`remove_trivial_identities`](crate::pil_analyzer::optimizer::remove_trivial_identities).
+pub fn check_constant_identities<T: FieldElement>(
+    analyzed: &Analyzed<T>,
+) -> Result<Vec<Error>, AnalysisError> {
+    let mut errors = vec![];
+    let mut warnings = vec![];
+    for identity in &analyzed.identities {
+        if identity.kind != IdentityKind::Polynomial {
+            continue;
+        }
+        if let AlgebraicExpression::Number(value) = identity.expression_for_poly_id() {
+            // Refer to the identity as written in the source, its folded form is not helpful.
+            let source = match identity.source.source_text() {
+                Some(text) => text.to_string(),
+                None => identity.to_string(),
+            };
+            if value.is_zero() {
+                warnings.push(identity.source.with_error(format!(
+                    "Identity {source} is always satisfied and has no effect."
+                )));
+            } else {
+                errors.push(identity.source.with_error(format!(
+                    "Identity {source} can never be satisfied, it reduces to the nonzero constant {value}."
+                )));
+            }
+        }
+    }
+    if errors.is_empty() {
+        Ok(warnings)
+    } else {
+        Err(AnalysisError::UnsatisfiableIdentity(errors))
+    }
+}
+
+/// Folds the given expression assuming that all its sub-expressions are already folded.
+fn fold_constants_in_expression<T: FieldElement>(e: &mut AlgebraicExpression<T>) {
+    let value = match e {
+        AlgebraicExpression::BinaryOperation(AlgebraicBinaryOperation { left, op, right }) => {
+            match (left.as_ref(), right.as_ref()) {
+                (AlgebraicExpression::Number(l), AlgebraicExpression::Number(r)) => {
+                    Some(apply_binary_operator(*l, *op, *r))
+                }
+                _ => None,
+            }
+        }
+        AlgebraicExpression::UnaryOperation(AlgebraicUnaryOperation {
+            op: AlgebraicUnaryOperator::Minus,
+            expr,
+        }) => match expr.as_ref() {
+            AlgebraicExpression::Number(n) => Some(-*n),
+            _ => None,
+        },
+        _ => None,
+    };
+    if let Some(value) = value {
+        *e = AlgebraicExpression::Number(value);
+    }
+}
+
+/// Evaluates an expression that only consists of numbers to its value in the field.
+/// Returns None if the expression references columns, publics or challenges.
+pub fn try_evaluate_constant<T: FieldElement>(e: &AlgebraicExpression<T>) -> Option<T> {
+    match e {
+        AlgebraicExpression::Number(n) => Some(*n),
+        AlgebraicExpression::BinaryOperation(AlgebraicBinaryOperation { left, op, right }) => {
+            Some(apply_binary_operator(
+                try_evaluate_constant(left)?,
+                *op,
+                try_evaluate_constant(right)?,
+            ))
+        }
+        AlgebraicExpression::UnaryOperation(AlgebraicUnaryOperation {
+            op: AlgebraicUnaryOperator::Minus,
+            expr,
+        }) => Some(-try_evaluate_constant(expr)?),
+        _ => None,
+    }
+}
+
+fn apply_binary_operator<T: FieldElement>(l: T, op: AlgebraicBinaryOperator, r: T) -> T {
+    match op {
+        AlgebraicBinaryOperator::Add => l + r,
+        AlgebraicBinaryOperator::Sub => l - r,
+        AlgebraicBinaryOperator::Mul => l * r,
+        AlgebraicBinaryOperator::Pow => l.pow(r.to_integer()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::number::GoldilocksField;
+    use crate::pil_analyzer::{
+        analyze_string, analyze_string_with_options, AnalysisError, AnalysisOptions,
+    };
+
+    #[test]
+    fn fold_constants() {
+        let input = r#"namespace N(4);
+    let<T: FromLiteral> K: T = 5;
+    col witness x;
+    pol i = x * (2 + 3 * K) - -(7 - 8);
+    x' = (2 + 3 * K) * x + K ** 2 + i;
+    [x * (1 + 1)] in [-x];
+"#;
+        let analyzed = analyze_string::<GoldilocksField>(input).unwrap();
+        let formatted = analyzed.to_string();
+        assert!(formatted.contains("col i = N.x * 17 - 1;"), "{formatted}");
+        assert!(
+            formatted.contains("N.x' = 17 * N.x + 25 + N.i;"),
+            "{formatted}"
+        );
+        assert!(formatted.contains("[N.x * 2] in [-N.x];"), "{formatted}");
+    }
+
+    #[test]
+    fn constant_identities() {
+        let input = r#"namespace N(4);
+    let<T: FromLiteral> K: T = 3;
+    col witness x;
+    x = 1;
+    K - 3 = 0;
+    (2 + K) * 2 = 10;
+"#;
+        let options = AnalysisOptions {
+            report_lints: true,
+            ..Default::default()
+        };
+        let (_, warnings) =
+            analyze_string_with_options::<GoldilocksField>("input", input, &options).unwrap();
+        assert_eq!(
+            warnings.iter().map(|w| w.to_string()).collect::<Vec<_>>(),
+            [
+                "input:5:5: Identity K - 3 = 0 is always satisfied and has no effect.",
+                "input:6:5: Identity (2 + K) * 2 = 10 is always satisfied and has no effect."
+            ]
+        );
+
+        let input = r#"namespace N(4);
+    col witness x;
+    x = 1;
+    1 = 0;
+    2 * 2 = 5;
+"#;
+        let Err(AnalysisError::UnsatisfiableIdentity(errors)) =
+            analyze_string::<GoldilocksField>(input)
+        else {
+            panic!()
+        };
+        assert_eq!(
+            errors.iter().map(|e| e.to_string()).collect::<Vec<_>>(),
+            [
+                "input:4:5: Identity 1 = 0 can never be satisfied, it reduces to the nonzero constant 1.",
+                "input:5:5: Identity 2 * 2 = 5 can never be satisfied, it reduces to the nonzero constant 18446744069414584320."
+            ]
+        );
+    }
+}
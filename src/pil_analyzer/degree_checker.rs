@@ -0,0 +1,63 @@
+use crate::ast::analyzed::Analyzed;
+use crate::number::FieldElement;
+use crate::pil_analyzer::AnalysisError;
+
+/// Checks that no identity has a degree larger than `max_degree`,
+/// where the degree is computed as in [`Analyzed::identity_degrees`].
+/// Returns an error for every offending identity, naming it and its degree.
+/// Callers that only want to warn can print the error and continue.
+pub fn check_max_degree<T: FieldElement>(
+    pil: &Analyzed<T>,
+    max_degree: usize,
+) -> Result<(), AnalysisError> {
+    let degrees = pil.identity_degrees();
+    let errors = pil
+        .identities
+        .iter()
+        .filter(|identity| degrees[&identity.id] > max_degree)
+        .map(|identity| {
+            identity.source.with_error(format!(
+                "Identity {identity} has degree {}, which exceeds the maximum degree of {max_degree}.",
+                degrees[&identity.id]
+            ))
+        })
+        .collect::<Vec<_>>();
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(AnalysisError::DegreeTooHigh(errors))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::number::GoldilocksField;
+    use crate::pil_analyzer::{analyze_string, AnalysisError};
+
+    use super::check_max_degree;
+
+    #[test]
+    fn reject_identities_above_max_degree() {
+        let input = r#"namespace N(4);
+    col witness x, y;
+    col sq = x * x;
+    x' = y;
+    sq * y = x;
+    [x * y * y * y] in [y];
+"#;
+        let analyzed = analyze_string::<GoldilocksField>(input).unwrap();
+        assert!(check_max_degree(&analyzed, 4).is_ok());
+        let Err(AnalysisError::DegreeTooHigh(errors)) = check_max_degree(&analyzed, 3) else {
+            panic!()
+        };
+        let messages = errors.iter().map(|e| e.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            ["input:6:5: Identity [N.x * N.y * N.y * N.y] in [N.y]; has degree 4, which exceeds the maximum degree of 3."]
+        );
+        let Err(AnalysisError::DegreeTooHigh(errors)) = check_max_degree(&analyzed, 2) else {
+            panic!()
+        };
+        assert_eq!(errors.len(), 2);
+    }
+}
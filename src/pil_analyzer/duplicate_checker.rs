@@ -0,0 +1,97 @@
+use std::collections::BTreeMap;
+
+use crate::ast::analyzed::{AlgebraicExpression, Analyzed, IdentityKind};
+use crate::number::FieldElement;
+use crate::parser_util::Error;
+
+/// Returns pairs `(duplicate, original)` of indices into [`Analyzed::identities`] where
+/// the identity at `duplicate` is structurally identical to the earlier identity at
+/// `original`: Both have the same kind and the same expressions on both sides, while
+/// IDs, labels and source locations are ignored. Constants should already be folded,
+/// as done during analysis, so that e.g. `x = 2 * 3` and `x = 6` are duplicates.
+/// Since column references are absolute, identities in different namespaces can only
+/// be duplicates if they constrain the same columns.
+pub fn duplicate_identities<T: Ord>(pil: &Analyzed<T>) -> Vec<(usize, usize)> {
+    let mut first_occurrence = BTreeMap::new();
+    pil.identities
+        .iter()
+        .enumerate()
+        .filter_map(|(index, identity)| {
+            let key = (identity.kind, &identity.left, &identity.right);
+            match first_occurrence.get(&key) {
+                Some(original) => Some((index, *original)),
+                None => {
+                    first_occurrence.insert(key, index);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// Returns a warning for every identity that is a duplicate of an earlier identity
+/// (see [`duplicate_identities`]), reporting the locations of both.
+/// Constant polynomial identities are skipped, since they are already reported
+/// as having no effect.
+pub fn duplicate_identity_warnings<T: FieldElement>(pil: &Analyzed<T>) -> Vec<Error> {
+    duplicate_identities(pil)
+        .into_iter()
+        .filter(|(duplicate, _)| {
+            let identity = &pil.identities[*duplicate];
+            identity.kind != IdentityKind::Polynomial
+                || !matches!(
+                    identity.expression_for_poly_id(),
+                    AlgebraicExpression::Number(_)
+                )
+        })
+        .map(|(duplicate, original)| {
+            let duplicate = &pil.identities[duplicate];
+            let original = &pil.identities[original];
+            duplicate.source.with_error(format!(
+                "Identity {duplicate} is a duplicate of the identity at {}.",
+                original.source
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::number::GoldilocksField;
+    use crate::pil_analyzer::{analyze_string_with_options, AnalysisOptions};
+
+    fn warnings(input: &str) -> Vec<String> {
+        let options = AnalysisOptions {
+            report_lints: true,
+            ..Default::default()
+        };
+        let (_, warnings) =
+            analyze_string_with_options::<GoldilocksField>("input", input, &options).unwrap();
+        warnings.iter().map(|w| w.to_string()).collect()
+    }
+
+    #[test]
+    fn duplicate_identities() {
+        let input = r#"namespace N(4);
+    col fixed F = [1, 2, 3, 4];
+    col witness x, y;
+    x = y * F;
+    #[label("again")]
+    x = y * F;
+    y = 2 * 3;
+    [x] in [F];
+    y = 6;
+    x = F * y;
+    [x] in [F];
+    [x] is [F];
+"#;
+        assert_eq!(
+            warnings(input),
+            [
+                "input:6:5: Identity N.x = N.y * N.F; is a duplicate of the identity at input:4:5.",
+                "input:9:5: Identity N.y = 6; is a duplicate of the identity at input:7:5.",
+                "input:11:5: Identity [N.x] in [N.F]; is a duplicate of the identity at input:8:5."
+            ]
+        );
+    }
+}
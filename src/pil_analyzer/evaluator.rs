@@ -1,28 +1,34 @@
 use std::{
     collections::HashMap,
     fmt::{self, Display},
+    str::FromStr,
     sync::Arc,
 };
 
 use itertools::Itertools;
-use num_traits::Signed;
+use num_traits::{Signed, Zero};
 
 use crate::ast::{
     analyzed::{
         AlgebraicBinaryOperation, AlgebraicBinaryOperator, AlgebraicExpression, AlgebraicReference,
-        AlgebraicUnaryOperation, AlgebraicUnaryOperator, Challenge, Expression,
-        FunctionValueDefinition, Reference, Symbol, SymbolKind, TypedExpression,
+        AlgebraicUnaryOperation, AlgebraicUnaryOperator, Analyzed, Challenge, Expression,
+        FunctionValueDefinition, PolynomialType, Reference, Symbol, SymbolKind, TypedExpression,
     },
     parsed::{
+        asm::SymbolPath,
         display::quote,
         types::{Type, TypeScheme},
         ArrayLiteral, BinaryOperation, BinaryOperator, BlockExpression, FunctionCall, IfExpression,
         IndexAccess, LambdaExpression, LetStatementInsideBlock, MatchArm, MatchExpression, Number,
-        Pattern, StatementInsideBlock, UnaryOperation, UnaryOperator,
+        Pattern, SliceAccess, StatementInsideBlock, UnaryOperation, UnaryOperator,
     },
 };
-use crate::number::{BigInt, BigUint, FieldElement, LargeInt};
+use crate::number::{BigInt, BigUint, DegreeType, FieldElement, LargeInt};
 use crate::parser_util::SourceRef;
+use crate::pil_analyzer::type_builtins::{
+    binary_operator_trait, operator_trait_function, unary_operator_trait,
+};
+use crate::pil_analyzer::{constant_folder, FeOverflow};
 
 /// Evaluates an expression given a hash map of definitions.
 pub fn evaluate_expression<'a, T: FieldElement>(
@@ -109,6 +115,8 @@ pub enum EvalError {
     DataNotAvailable,
     /// Failed assertion, with reason.
     FailedAssertion(String),
+    /// Division by zero, e.g. trying to invert the zero field element.
+    DivisionByZero(String),
 }
 
 impl Display for EvalError {
@@ -121,6 +129,7 @@ impl Display for EvalError {
             EvalError::SymbolNotFound(msg) => write!(f, "Symbol not found: {msg}"),
             EvalError::DataNotAvailable => write!(f, "Data not (yet) available."),
             EvalError::FailedAssertion(msg) => write!(f, "Assertion failed: {msg}"),
+            EvalError::DivisionByZero(msg) => write!(f, "Division by zero: {msg}"),
         }
     }
 }
@@ -134,8 +143,12 @@ pub enum Value<'a, T> {
     Tuple(Vec<Arc<Self>>),
     Array(Vec<Arc<Self>>),
     Closure(Closure<'a, T>),
-    TypeConstructor(&'a str),
-    Enum(&'a str, Option<Vec<Arc<Self>>>),
+    /// A constructor of an enum variant that has fields:
+    /// The absolute name of the enum and the name of the variant.
+    TypeConstructor(&'a str, &'a str),
+    /// An enum value: The absolute name of the enum, the name of the variant
+    /// and the values of its fields.
+    Enum(&'a str, &'a str, Option<Vec<Arc<Self>>>),
     BuiltinFunction(BuiltinFunction),
     Expression(AlgebraicExpression<T>),
 }
@@ -211,8 +224,8 @@ impl<'a, T: FieldElement> Value<'a, T> {
                 )
             }
             Value::Closure(c) => c.type_formatted(),
-            Value::TypeConstructor(name) => format!("{name}_constructor"),
-            Value::Enum(name, _) => name.to_string(),
+            Value::TypeConstructor(_, name) => format!("{name}_constructor"),
+            Value::Enum(_, name, _) => name.to_string(),
             Value::BuiltinFunction(b) => format!("builtin_{b:?}"),
             Value::Expression(_) => "expr".to_string(),
         }
@@ -276,7 +289,7 @@ impl<'a, T: FieldElement> Value<'a, T> {
             }
             Pattern::Variable(_, _) => Some(vec![v.clone()]),
             Pattern::Enum(_, name, fields_pattern) => {
-                let Value::Enum(n, data) = v.as_ref() else {
+                let Value::Enum(_, n, data) = v.as_ref() else {
                     panic!()
                 };
                 if name.name() != n {
@@ -308,43 +321,152 @@ impl<'a, T: FieldElement> Value<'a, T> {
     }
 }
 
-const BUILTINS: [(&str, BuiltinFunction); 10] = [
+const BUILTINS: [(&str, BuiltinFunction); 39] = [
+    ("std::array::contains", BuiltinFunction::ArrayContains),
+    ("std::array::find", BuiltinFunction::ArrayFind),
+    ("std::array::flatten", BuiltinFunction::ArrayFlatten),
+    ("std::array::fold", BuiltinFunction::ArrayFold),
+    ("std::array::from_tuple2", BuiltinFunction::TupleToArray(2)),
+    ("std::array::from_tuple3", BuiltinFunction::TupleToArray(3)),
+    ("std::array::from_tuple4", BuiltinFunction::TupleToArray(4)),
     ("std::array::len", BuiltinFunction::ArrayLen),
+    ("std::array::map", BuiltinFunction::ArrayMap),
+    ("std::array::new", BuiltinFunction::ArrayNew),
+    ("std::array::reverse", BuiltinFunction::ArrayReverse),
+    ("std::array::sum", BuiltinFunction::ArraySum),
+    ("std::array::to_tuple2", BuiltinFunction::ArrayToTuple(2)),
+    ("std::array::to_tuple3", BuiltinFunction::ArrayToTuple(3)),
+    ("std::array::to_tuple4", BuiltinFunction::ArrayToTuple(4)),
+    ("std::array::zip", BuiltinFunction::ArrayZip),
     ("std::check::panic", BuiltinFunction::Panic),
+    ("std::check::require", BuiltinFunction::Require),
+    ("std::convert::bool", BuiltinFunction::ToBool),
     ("std::convert::expr", BuiltinFunction::ToExpr),
     ("std::convert::fe", BuiltinFunction::ToFe),
     ("std::convert::int", BuiltinFunction::ToInt),
+    ("std::convert::to_bits", BuiltinFunction::ToBits),
+    ("std::convert::to_string", BuiltinFunction::ToString),
     ("std::debug::print", BuiltinFunction::Print),
+    ("std::field::bits", BuiltinFunction::FieldBits),
     ("std::field::modulus", BuiltinFunction::Modulus),
+    ("std::fmt::format", BuiltinFunction::Format),
+    ("std::math::ff::inv_field", BuiltinFunction::InvField),
     ("std::prelude::challenge", BuiltinFunction::Challenge),
+    (
+        "std::prover::capture_constraints",
+        BuiltinFunction::CaptureConstraints,
+    ),
     ("std::prover::degree", BuiltinFunction::Degree),
     ("std::prover::eval", BuiltinFunction::Eval),
+    ("std::prover::max_degree", BuiltinFunction::MaxDegree),
+    ("std::prover::min_degree", BuiltinFunction::MinDegree),
+    (
+        "std::prover::new_witness_col",
+        BuiltinFunction::NewWitnessCol,
+    ),
+    (
+        "std::utils::cartesian_product",
+        BuiltinFunction::CartesianProduct,
+    ),
+    ("std::utils::is_power_of_two", BuiltinFunction::IsPowerOfTwo),
+    (
+        "std::utils::next_power_of_two",
+        BuiltinFunction::NextPowerOfTwo,
+    ),
 ];
 
 #[derive(Clone, Copy, Debug)]
 pub enum BuiltinFunction {
     /// std::array::len: _[] -> int, returns the length of an array
     ArrayLen,
+    /// std::array::map: T[], (T -> U) -> U[], applies a function to each element of an array
+    ArrayMap,
+    /// std::array::new: int, (int -> T) -> T[], creates the array [f(0), f(1), ..., f(n - 1)]
+    ArrayNew,
+    /// std::array::fold: T[], U, (U, T -> U) -> U, combines the elements of an array
+    /// from left to right, starting with the given initial value
+    ArrayFold,
+    /// std::array::sum: T[] -> T, returns the sum of the elements of a non-empty array
+    ArraySum,
+    /// std::array::zip: T1[], T2[], (T1, T2 -> T3) -> T3[], combines two arrays of the same
+    /// length elementwise
+    ArrayZip,
+    /// std::array::contains: T[], T -> bool, returns true if an element of the array is
+    /// equal to the given value
+    ArrayContains,
+    /// std::array::find: T[], (T -> bool) -> int, returns the index of the first element
+    /// for which the predicate returns true, or -1 if there is none
+    ArrayFind,
+    /// std::array::flatten: T[][] -> T[], concatenates the inner arrays from left to right
+    ArrayFlatten,
+    /// std::array::reverse: T[] -> T[], returns the elements of an array in reverse order
+    ArrayReverse,
+    /// std::array::to_tuple<n>: T[] -> (T, ..., T), converts an array of length n into an
+    /// n-tuple with the same elements. Fails if the array has a different length.
+    ArrayToTuple(usize),
+    /// std::array::from_tuple<n>: (T, ..., T) -> T[], converts an n-tuple into an array
+    /// of length n with the same elements
+    TupleToArray(usize),
     /// std::field::modulus: -> int, returns the field modulus as int
     Modulus,
+    /// std::field::bits: -> int, returns the number of bits of the field modulus
+    FieldBits,
+    /// std::math::ff::inv_field: fe -> fe, returns the multiplicative inverse of a
+    /// non-zero field element
+    InvField,
     /// std::check::panic: string -> !, fails evaluation and uses its parameter for error reporting.
     /// Does not return.
     Panic,
-    /// std::debug::print: string -> [], prints its argument on stdout.
-    /// Returns an empty array.
+    /// std::check::require: bool, string -> (), fails evaluation with the given message
+    /// if the condition is false. Returns an empty tuple otherwise.
+    Require,
+    /// std::debug::print: T -> (), outputs its argument using [`SymbolLookup::print`],
+    /// which prints it on stderr by default. Returns an empty array.
     Print,
+    /// std::convert::bool: int -> bool, converts zero to false and all other integers to true
+    ToBool,
     /// std::convert::expr: fe/int -> expr, converts fe to expr
     ToExpr,
     /// std::convert::int: fe/int -> int, converts fe to int
     ToInt,
-    /// std::convert::fe: int/fe -> fe, converts int to fe
+    /// std::convert::fe: int/fe/expr -> fe, converts int or a constant expr to fe
     ToFe,
+    /// std::convert::to_bits: int, int -> int[], returns the given number of bits of a
+    /// non-negative integer, least significant bit first. Fails if the value does not fit.
+    ToBits,
+    /// std::convert::to_string: T -> string, converts a value to a string the same way
+    /// std::debug::print does, i.e. strings are not quoted
+    ToString,
+    /// std::fmt::format: string, T[] -> string, replaces each "{}" in the template by
+    /// the next argument converted to a string
+    Format,
     /// std::prover::challenge: int, int -> expr, constructs a challenge with a given stage and ID.
     Challenge,
     /// std::prover::degree: -> int, returns the current column length / degree.
+    /// Fails if the degree of the current namespace is a range.
     Degree,
+    /// std::prover::min_degree: -> int, returns the minimum degree of the current namespace.
+    MinDegree,
+    /// std::prover::max_degree: -> int, returns the maximum degree of the current namespace.
+    MaxDegree,
     /// std::prover::eval: expr -> fe, evaluates an expression on the current row
     Eval,
+    /// std::prover::new_witness_col: string -> expr, creates a new witness column in the
+    /// current namespace and returns a reference to it. The name is made unique by
+    /// appending a suffix if needed. Only available in a constr context.
+    NewWitnessCol,
+    /// std::prover::capture_constraints: (-> ()) -> Constr[], calls the function and returns
+    /// the constraints it emits instead of adding them. Columns it creates are still added.
+    /// Only available in a constr context.
+    CaptureConstraints,
+    /// std::utils::cartesian_product: T[], U[] -> (T, U)[], returns all pairs of an element of
+    /// the first and an element of the second array, in row-major order
+    CartesianProduct,
+    /// std::utils::is_power_of_two: int -> bool, returns true if the argument is 2**k for some k >= 0
+    IsPowerOfTwo,
+    /// std::utils::next_power_of_two: int -> int, returns the smallest 2**k with k >= 0 that is
+    /// at least the argument
+    NextPowerOfTwo,
 }
 
 impl<'a, T: Display> Display for Value<'a, T> {
@@ -357,8 +479,8 @@ impl<'a, T: Display> Display for Value<'a, T> {
             Value::Tuple(items) => write!(f, "({})", items.iter().format(", ")),
             Value::Array(elements) => write!(f, "[{}]", elements.iter().format(", ")),
             Value::Closure(closure) => write!(f, "{closure}"),
-            Value::TypeConstructor(name) => write!(f, "{name}_constructor"),
-            Value::Enum(name, data) => {
+            Value::TypeConstructor(_, name) => write!(f, "{name}_constructor"),
+            Value::Enum(_, name, data) => {
                 write!(f, "{name}")?;
                 if let Some(data) = data {
                     write!(f, "({})", data.iter().format(", "))?;
@@ -445,11 +567,11 @@ impl<'a> Definitions<'a> {
                     let type_args = type_arg_mapping(type_scheme, type_args);
                     evaluate_generic(value, &type_args, symbols)?
                 }
-                Some(FunctionValueDefinition::TypeConstructor(_type_name, variant)) => {
+                Some(FunctionValueDefinition::TypeConstructor(enum_decl, variant)) => {
                     if variant.fields.is_none() {
-                        Value::Enum(&variant.name, None).into()
+                        Value::Enum(&enum_decl.name, &variant.name, None).into()
                     } else {
-                        Value::TypeConstructor(&variant.name).into()
+                        Value::TypeConstructor(&enum_decl.name, &variant.name).into()
                     }
                 }
                 _ => Err(EvalError::Unsupported(
@@ -474,6 +596,146 @@ impl<'a, T: FieldElement> SymbolLookup<'a, T> for Definitions<'a> {
     }
 }
 
+/// Symbol lookup that evaluates fixed columns at a given row, so that
+/// `std::prover::eval` can be used at compile time on fixed columns
+/// that are defined by a function or an array.
+#[derive(Clone, Copy)]
+pub struct DefinitionsAtRow<'a> {
+    pub definitions: &'a HashMap<String, (Symbol, Option<FunctionValueDefinition>)>,
+    pub row: DegreeType,
+}
+
+impl<'a, T: FieldElement> SymbolLookup<'a, T> for DefinitionsAtRow<'a> {
+    fn lookup(
+        &mut self,
+        name: &str,
+        type_args: Option<Vec<Type>>,
+    ) -> Result<Arc<Value<'a, T>>, EvalError> {
+        Definitions::lookup_with_symbols(self.definitions, name, type_args, self)
+    }
+
+    fn lookup_public_reference(&self, name: &str) -> Result<Arc<Value<'a, T>>, EvalError> {
+        Ok(Value::from(AlgebraicExpression::PublicReference(name.to_string())).into())
+    }
+
+    fn eval_reference(
+        &self,
+        reference: &AlgebraicReference,
+    ) -> Result<Arc<Value<'a, T>>, EvalError> {
+        let not_known = || {
+            EvalError::Unsupported(format!(
+                "Value of column {} is not known at compile time.",
+                reference.name
+            ))
+        };
+        if reference.poly_id.ptype != PolynomialType::Constant {
+            return Err(not_known());
+        }
+        let (symbol, value) = self
+            .definitions
+            .values()
+            .find(|(symbol, _)| {
+                matches!(symbol.kind, SymbolKind::Poly(PolynomialType::Constant))
+                    && symbol
+                        .array_elements()
+                        .any(|(_, poly_id)| poly_id == reference.poly_id)
+            })
+            .ok_or_else(|| EvalError::SymbolNotFound(reference.name.clone()))?;
+        let row = if reference.next {
+            let degree = symbol.degree.ok_or_else(|| {
+                EvalError::Unsupported(format!(
+                    "Cannot evaluate next reference to {} without a degree.",
+                    reference.name
+                ))
+            })?;
+            let degree = degree.try_into_unique().ok_or_else(|| {
+                EvalError::Unsupported(format!(
+                    "Cannot evaluate next reference to {}, its degree is the range {degree}.",
+                    reference.name
+                ))
+            })?;
+            (self.row + 1) % degree
+        } else {
+            self.row
+        };
+        let mut symbols = DefinitionsAtRow { row, ..*self };
+        let result = match value {
+            Some(FunctionValueDefinition::Expression(TypedExpression { e, .. })) => {
+                let function = evaluate(e, &mut symbols)?;
+                evaluate_function_call(
+                    function,
+                    vec![Value::Integer(row.into()).into()],
+                    &mut symbols,
+                )?
+            }
+            Some(FunctionValueDefinition::Array(arrays)) => {
+                let mut offset = row;
+                let array = arrays
+                    .iter()
+                    .find(|array| {
+                        if offset < array.size() {
+                            true
+                        } else {
+                            offset -= array.size();
+                            false
+                        }
+                    })
+                    .ok_or_else(|| {
+                        EvalError::OutOfBounds(format!(
+                            "Row {row} is out of bounds for fixed column {}.",
+                            reference.name
+                        ))
+                    })?;
+                let pattern = array.pattern();
+                evaluate(&pattern[offset as usize % pattern.len()], &mut symbols)?
+            }
+            _ => return Err(not_known()),
+        };
+        Ok(Value::FieldElement(result.try_to_field_element()?).into())
+    }
+}
+
+/// Evaluates all fixed columns on all rows of their namespace degree and returns
+/// their values by name, in source order. Array columns are returned element by
+/// element with names of the form `name[i]`.
+/// Fails if a fixed column has no single degree or its definition depends on values
+/// that are not known at compile time.
+pub fn evaluate_fixed_columns<T: FieldElement>(
+    pil: &Analyzed<T>,
+) -> Result<Vec<(String, Vec<T>)>, EvalError> {
+    pil.constant_polys_in_source_order()
+        .into_iter()
+        .flat_map(|(symbol, _)| symbol.array_elements().map(move |e| (symbol, e)))
+        .map(|(symbol, (name, poly_id))| {
+            let degree = symbol.degree.ok_or_else(|| {
+                EvalError::Unsupported(format!(
+                    "Cannot evaluate fixed column {name} without a degree."
+                ))
+            })?;
+            let degree = degree.try_into_unique().ok_or_else(|| {
+                EvalError::Unsupported(format!(
+                    "Cannot evaluate fixed column {name}, its degree is the range {degree}."
+                ))
+            })?;
+            let reference = AlgebraicReference {
+                name,
+                poly_id,
+                next: false,
+            };
+            let values = (0..degree)
+                .map(|row| {
+                    let symbols = DefinitionsAtRow {
+                        definitions: &pil.definitions,
+                        row,
+                    };
+                    symbols.eval_reference(&reference)?.try_to_field_element()
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok((reference.name, values))
+        })
+        .collect()
+}
+
 impl<'a> From<&'a HashMap<String, (Symbol, Option<FunctionValueDefinition>)>> for Definitions<'a> {
     fn from(value: &'a HashMap<String, (Symbol, Option<FunctionValueDefinition>)>) -> Self {
         Definitions(value)
@@ -483,7 +745,7 @@ impl<'a> From<&'a HashMap<String, (Symbol, Option<FunctionValueDefinition>)>> fo
 pub trait SymbolLookup<'a, T: FieldElement> {
     fn lookup(
         &mut self,
-        name: &'a str,
+        name: &str,
         type_args: Option<Vec<Type>>,
     ) -> Result<Arc<Value<'a, T>>, EvalError>;
 
@@ -523,6 +785,12 @@ pub trait SymbolLookup<'a, T: FieldElement> {
         })
     }
 
+    /// What to do with literals of generic type that turn out to be field elements
+    /// too large for the field.
+    fn fe_overflow(&self) -> FeOverflow {
+        FeOverflow::Error
+    }
+
     fn eval_challenge(&self, _challenge: &Challenge) -> Result<Arc<Value<'a, T>>, EvalError> {
         Err(EvalError::DataNotAvailable)
     }
@@ -540,6 +808,24 @@ pub trait SymbolLookup<'a, T: FieldElement> {
         ))
     }
 
+    fn min_degree(&self) -> Result<Arc<Value<'a, T>>, EvalError> {
+        Err(EvalError::Unsupported(
+            "Cannot evaluate minimum degree.".to_string(),
+        ))
+    }
+
+    fn max_degree(&self) -> Result<Arc<Value<'a, T>>, EvalError> {
+        Err(EvalError::Unsupported(
+            "Cannot evaluate maximum degree.".to_string(),
+        ))
+    }
+
+    /// Outputs a message printed by `std::debug::print`.
+    #[allow(clippy::print_stderr)]
+    fn print(&mut self, message: &str) {
+        eprint!("{message}");
+    }
+
     fn new_column(
         &mut self,
         name: &str,
@@ -577,6 +863,20 @@ enum Operation<'a, T> {
     LetStatement(&'a LetStatementInsideBlock<Expression>),
     /// Add a constraint to the constraint set.
     AddConstraint,
+    /// Call a function on the given arguments.
+    Call(Arc<Value<'a, T>>, Vec<Arc<Value<'a, T>>>),
+    /// Call a function on the topmost value of the value stack and the given argument.
+    /// Used to evaluate one step of `std::array::fold`.
+    FoldStep(Arc<Value<'a, T>>, Arc<Value<'a, T>>),
+    /// Collect the given number of topmost values of the value stack into an array.
+    CollectArray(usize),
+    /// Check the result of calling a predicate on the element at the given index of
+    /// an array, which is the topmost value of the value stack, and call it on the
+    /// next element if the result was false. Used to evaluate `std::array::find`.
+    FindStep(Arc<Value<'a, T>>, Arc<Value<'a, T>>, usize),
+    /// Replace the return value of a function called by `std::prover::capture_constraints`
+    /// by the array of constraints it emitted.
+    EndCapture,
 }
 
 /// We use a non-recursive algorithm to evaluate potentially recursive expressions.
@@ -589,6 +889,9 @@ struct Evaluator<'a, 'b, T: FieldElement, S: SymbolLookup<'a, T>> {
     type_args: HashMap<String, Type>,
     op_stack: Vec<Operation<'a, T>>,
     value_stack: Vec<Arc<Value<'a, T>>>,
+    /// Constraints emitted inside (possibly nested) calls of `std::prover::capture_constraints`,
+    /// innermost call last.
+    captured_constraints: Vec<Vec<Arc<Value<'a, T>>>>,
 }
 
 impl<'a, 'b, T: FieldElement, S: SymbolLookup<'a, T>> Evaluator<'a, 'b, T, S> {
@@ -599,6 +902,7 @@ impl<'a, 'b, T: FieldElement, S: SymbolLookup<'a, T>> Evaluator<'a, 'b, T, S> {
             type_args,
             op_stack: vec![],
             value_stack: vec![],
+            captured_constraints: vec![],
         }
     }
 
@@ -658,11 +962,44 @@ impl<'a, 'b, T: FieldElement, S: SymbolLookup<'a, T>> Evaluator<'a, 'b, T, S> {
                 }
                 Operation::AddConstraint => {
                     let result = self.value_stack.pop().unwrap();
-                    match result.as_ref() {
-                        Value::Tuple(t) if t.is_empty() => {}
-                        _ => self.symbols.add_constraints(result, SourceRef::unknown())?,
+                    match (result.as_ref(), self.captured_constraints.last_mut()) {
+                        (Value::Tuple(t), _) if t.is_empty() => {}
+                        (Value::Array(items), Some(captured)) => captured.extend(items.clone()),
+                        (_, Some(captured)) => captured.push(result),
+                        (_, None) => self.symbols.add_constraints(result, SourceRef::unknown())?,
+                    }
+                }
+                Operation::Call(function, arguments) => {
+                    self.combine_function_call(function, arguments)?
+                }
+                Operation::FoldStep(function, item) => {
+                    let acc = self.value_stack.pop().unwrap();
+                    self.combine_function_call(function, vec![acc, item])?
+                }
+                Operation::CollectArray(len) => {
+                    let items = self.value_stack.split_off(self.value_stack.len() - len);
+                    self.value_stack.push(Value::Array(items).into());
+                }
+                Operation::FindStep(function, array, index) => {
+                    let found = self.value_stack.pop().unwrap();
+                    let Value::Bool(found) = found.as_ref() else {
+                        Err(EvalError::TypeError(format!(
+                            "Expected bool from predicate of std::array::find, but got {found}: {}",
+                            found.type_formatted()
+                        )))?
+                    };
+                    if *found {
+                        self.value_stack
+                            .push(Value::Integer((index as u64).into()).into());
+                    } else {
+                        self.find_from(function, array, index + 1);
                     }
                 }
+                Operation::EndCapture => {
+                    self.value_stack.pop().unwrap();
+                    let captured = self.captured_constraints.pop().unwrap();
+                    self.value_stack.push(Value::Array(captured).into());
+                }
             };
         }
         assert_eq!(self.value_stack.len(), 1);
@@ -686,9 +1023,12 @@ impl<'a, 'b, T: FieldElement, S: SymbolLookup<'a, T>> Evaluator<'a, 'b, T, S> {
                     value: n,
                     type_: ty,
                 },
-            ) => self
-                .value_stack
-                .push(evaluate_literal(n.clone(), ty, &self.type_args)?),
+            ) => self.value_stack.push(evaluate_literal(
+                n.clone(),
+                ty,
+                &self.type_args,
+                self.symbols.fe_overflow(),
+            )?),
             Expression::String(_, s) => self.value_stack.push(Value::String(s.clone()).into()),
             Expression::Tuple(_, items) => {
                 self.op_stack.push(Operation::Combine(expr));
@@ -706,6 +1046,18 @@ impl<'a, 'b, T: FieldElement, S: SymbolLookup<'a, T>> Evaluator<'a, 'b, T, S> {
                     self.expand(&items[0])?;
                 }
             }
+            Expression::BinaryOperation(
+                _,
+                BinaryOperation {
+                    left,
+                    op: BinaryOperator::LogicalAnd | BinaryOperator::LogicalOr,
+                    ..
+                },
+            ) => {
+                // The right operand is only evaluated if needed, see `combine`.
+                self.op_stack.push(Operation::Combine(expr));
+                self.expand(left)?;
+            }
             Expression::BinaryOperation(_, BinaryOperation { left, right, .. }) => {
                 self.op_stack.push(Operation::Combine(expr));
                 self.op_stack.push(Operation::Expand(right));
@@ -731,6 +1083,12 @@ impl<'a, 'b, T: FieldElement, S: SymbolLookup<'a, T>> Evaluator<'a, 'b, T, S> {
                 self.op_stack.push(Operation::Expand(index));
                 self.expand(array)?;
             }
+            Expression::SliceAccess(_, SliceAccess { array, start, end }) => {
+                self.op_stack.push(Operation::Combine(expr));
+                self.op_stack.push(Operation::Expand(end));
+                self.op_stack.push(Operation::Expand(start));
+                self.expand(array)?;
+            }
             Expression::FunctionCall(
                 _,
                 FunctionCall {
@@ -780,6 +1138,10 @@ impl<'a, 'b, T: FieldElement, S: SymbolLookup<'a, T>> Evaluator<'a, 'b, T, S> {
             Expression::FreeInput(_, _) => Err(EvalError::Unsupported(
                 "Cannot evaluate free input.".to_string(),
             ))?,
+            Expression::ArrayComprehension(_, _) => {
+                unreachable!("Array comprehensions are desugared during analysis.")
+            }
+            Expression::Cast(_, _) => unreachable!("Casts are desugared during analysis."),
         };
         Ok(())
     }
@@ -791,7 +1153,7 @@ impl<'a, 'b, T: FieldElement, S: SymbolLookup<'a, T>> Evaluator<'a, 'b, T, S> {
         Ok(match reference {
             Reference::LocalVar(i, _name) => self.local_vars[*i as usize].clone(),
             Reference::Poly(poly) => {
-                if let Some((_, b)) = BUILTINS.iter().find(|(n, _)| (n == &poly.name)) {
+                if let Some((_, b)) = BUILTINS.iter().find(|(n, _)| n == &poly.name) {
                     Value::BuiltinFunction(*b).into()
                 } else {
                     let type_args = poly.type_args.clone().map(|mut ta| {
@@ -821,18 +1183,51 @@ impl<'a, 'b, T: FieldElement, S: SymbolLookup<'a, T>> Evaluator<'a, 'b, T, S> {
                     .split_off(self.value_stack.len() - items.len());
                 Value::Array(inner_values).into()
             }
+            Expression::BinaryOperation(
+                _,
+                BinaryOperation {
+                    op: op @ (BinaryOperator::LogicalAnd | BinaryOperator::LogicalOr),
+                    right,
+                    ..
+                },
+            ) => {
+                let left = self.value_stack.pop().unwrap();
+                let Value::Bool(left) = left.as_ref() else {
+                    Err(EvalError::TypeError(format!(
+                        "Expected boolean value but got {left}"
+                    )))?
+                };
+                // The result is the value of the right operand unless
+                // the left operand already determines it.
+                if *left == (*op == BinaryOperator::LogicalOr) {
+                    Value::Bool(*left).into()
+                } else {
+                    return self.expand(right);
+                }
+            }
             Expression::BinaryOperation(_, BinaryOperation { op, .. }) => {
                 let right = self.value_stack.pop().unwrap();
                 let left = self.value_stack.pop().unwrap();
+                if let Some(function) =
+                    self.operator_implementation(&left, binary_operator_trait(*op))?
+                {
+                    return self.combine_function_call(function, vec![left, right]);
+                }
                 evaluate_binary_operation(&left, *op, &right)?
             }
             Expression::UnaryOperation(_, UnaryOperation { op, .. }) => {
                 let inner = self.value_stack.pop().unwrap();
+                if let Some(function) =
+                    self.operator_implementation(&inner, unary_operator_trait(*op))?
+                {
+                    return self.combine_function_call(function, vec![inner]);
+                }
                 match (op, inner.as_ref()) {
                     (UnaryOperator::Minus, Value::FieldElement(e)) => {
                         Value::FieldElement(-*e).into()
                     }
                     (UnaryOperator::LogicalNot, Value::Bool(b)) => Value::Bool(!b).into(),
+                    (UnaryOperator::BinaryNot, Value::Integer(n)) => Value::Integer(!n).into(),
                     (UnaryOperator::Minus, Value::Integer(n)) => Value::Integer(-n).into(),
                     (UnaryOperator::Next, Value::Expression(e)) => e
                         .clone()
@@ -846,7 +1241,9 @@ impl<'a, 'b, T: FieldElement, S: SymbolLookup<'a, T>> Evaluator<'a, 'b, T, S> {
                             ))
                         })?,
                     (op, Value::Expression(e)) => Value::from(AlgebraicExpression::new_unary(
-                        (*op).try_into().unwrap(),
+                        (*op).try_into().map_err(|msg| {
+                            EvalError::TypeError(format!("{msg} Expression: {op}{e}"))
+                        })?,
                         e.clone(),
                     ))
                     .into(),
@@ -863,10 +1260,12 @@ impl<'a, 'b, T: FieldElement, S: SymbolLookup<'a, T>> Evaluator<'a, 'b, T, S> {
                     panic!()
                 };
                 match index.as_ref() {
-                    Value::Integer(index)
-                        if index.is_negative()
-                            || *index >= (elements.len() as u64).into() =>
-                    {
+                    Value::Integer(index) if index.is_negative() => {
+                        Err(EvalError::OutOfBounds(format!(
+                            "Index {index} in {expr} is negative, but array indices have to be non-negative."
+                        )))?
+                    }
+                    Value::Integer(index) if *index >= (elements.len() as u64).into() => {
                         Err(EvalError::OutOfBounds(format!(
                             "Index access out of bounds: Tried to access element {index} of array of size {} in: {expr}.",
                             elements.len()
@@ -881,6 +1280,30 @@ impl<'a, 'b, T: FieldElement, S: SymbolLookup<'a, T>> Evaluator<'a, 'b, T, S> {
                     )))?,
                 }
             }
+            Expression::SliceAccess(_, _) => {
+                let end = self.value_stack.pop().unwrap();
+                let start = self.value_stack.pop().unwrap();
+                let array = self.value_stack.pop().unwrap();
+                let Value::Array(elements) = array.as_ref() else {
+                    panic!()
+                };
+                let (Value::Integer(start), Value::Integer(end)) = (start.as_ref(), end.as_ref())
+                else {
+                    Err(EvalError::TypeError(format!(
+                        "Expected integers for array slice bounds but got {start}: {} and {end}: {}",
+                        start.type_formatted(),
+                        end.type_formatted()
+                    )))?
+                };
+                if start.is_negative() || start > end || *end > (elements.len() as u64).into() {
+                    Err(EvalError::OutOfBounds(format!(
+                        "Tried to access elements {start}..{end} of array of size {} in: {expr}.",
+                        elements.len()
+                    )))?
+                }
+                let range = usize::try_from(start).unwrap()..usize::try_from(end).unwrap();
+                Value::Array(elements[range].to_vec()).into()
+            }
             Expression::FunctionCall(_, FunctionCall { arguments, .. }) => {
                 let arguments = self
                     .value_stack
@@ -926,19 +1349,55 @@ impl<'a, 'b, T: FieldElement, S: SymbolLookup<'a, T>> Evaluator<'a, 'b, T, S> {
         Ok(())
     }
 
+    /// If `value` is a value of a user-defined named type, returns the function
+    /// implementing the operator trait `trait_name` for that type.
+    fn operator_implementation(
+        &mut self,
+        value: &Value<'a, T>,
+        trait_name: Option<&str>,
+    ) -> Result<Option<Arc<Value<'a, T>>>, EvalError> {
+        let (Some(trait_name), Value::Enum(enum_name, _, _)) = (trait_name, value) else {
+            return Ok(None);
+        };
+        let enum_path = SymbolPath::from_str(enum_name).unwrap();
+        let (function, _) =
+            operator_trait_function(trait_name, &Type::NamedType(enum_path.clone(), None)).unwrap();
+        let name = enum_path
+            .join(SymbolPath::from_identifier(function.to_string()))
+            .to_dotted_string();
+        self.symbols.lookup(&name, None).map(Some)
+    }
+
     fn combine_function_call(
         &mut self,
         function: Arc<Value<'a, T>>,
         arguments: Vec<Arc<Value<'a, T>>>,
     ) -> Result<(), EvalError> {
         match function.as_ref() {
+            Value::BuiltinFunction(
+                b @ (BuiltinFunction::ArrayMap
+                | BuiltinFunction::ArrayNew
+                | BuiltinFunction::ArrayFold
+                | BuiltinFunction::ArrayZip
+                | BuiltinFunction::ArrayFind),
+            ) => self.combine_higher_order_builtin(*b, arguments)?,
+            Value::BuiltinFunction(BuiltinFunction::ArraySum) => {
+                self.combine_array_sum(arguments)?
+            }
+            Value::BuiltinFunction(BuiltinFunction::CaptureConstraints) => {
+                check_builtin_argument_count(BuiltinFunction::CaptureConstraints, &arguments)?;
+                let function = arguments.into_iter().next().unwrap();
+                self.captured_constraints.push(vec![]);
+                self.op_stack.push(Operation::EndCapture);
+                self.op_stack.push(Operation::Call(function, vec![]));
+            }
             Value::BuiltinFunction(b) => {
                 self.value_stack
                     .push(evaluate_builtin_function(*b, arguments, self.symbols)?)
             }
-            Value::TypeConstructor(name) => self
+            Value::TypeConstructor(enum_name, name) => self
                 .value_stack
-                .push(Value::Enum(name, Some(arguments)).into()),
+                .push(Value::Enum(enum_name, name, Some(arguments)).into()),
             Value::Closure(Closure {
                 lambda,
                 environment,
@@ -973,12 +1432,144 @@ impl<'a, 'b, T: FieldElement, S: SymbolLookup<'a, T>> Evaluator<'a, 'b, T, S> {
         };
         Ok(())
     }
+
+    /// Evaluates `std::array::sum`. For arrays of user-defined named types,
+    /// the items are added using the type's implementation of `Add`.
+    fn combine_array_sum(&mut self, arguments: Vec<Arc<Value<'a, T>>>) -> Result<(), EvalError> {
+        check_builtin_argument_count(BuiltinFunction::ArraySum, &arguments)?;
+        let items = expect_array("std::array::sum", &arguments[0])?.to_vec();
+        let add = match items.first() {
+            Some(first) => {
+                self.operator_implementation(first, binary_operator_trait(BinaryOperator::Add))?
+            }
+            None => None,
+        };
+        match add {
+            Some(add) => {
+                self.value_stack.push(items[0].clone());
+                self.op_stack.extend(
+                    items[1..]
+                        .iter()
+                        .rev()
+                        .map(|item| Operation::FoldStep(add.clone(), item.clone())),
+                );
+            }
+            None => self.value_stack.push(evaluate_builtin_function(
+                BuiltinFunction::ArraySum,
+                arguments,
+                self.symbols,
+            )?),
+        }
+        Ok(())
+    }
+
+    /// Evaluates builtins that call back into PIL functions.
+    /// The function calls are scheduled on the operation stack so that
+    /// evaluation stays non-recursive.
+    fn combine_higher_order_builtin(
+        &mut self,
+        b: BuiltinFunction,
+        mut arguments: Vec<Arc<Value<'a, T>>>,
+    ) -> Result<(), EvalError> {
+        check_builtin_argument_count(b, &arguments)?;
+        let function = arguments.pop().unwrap();
+        match b {
+            BuiltinFunction::ArrayMap => {
+                let items = expect_array("std::array::map", &arguments[0])?;
+                self.op_stack.push(Operation::CollectArray(items.len()));
+                self.op_stack.extend(
+                    items
+                        .iter()
+                        .rev()
+                        .map(|item| Operation::Call(function.clone(), vec![item.clone()])),
+                );
+            }
+            BuiltinFunction::ArrayNew => {
+                let length = arguments[0].try_to_integer()?;
+                let length = usize::try_from(&length).map_err(|_| {
+                    EvalError::TypeError(format!(
+                        "std::array::new called with invalid length {length}"
+                    ))
+                })?;
+                self.op_stack.push(Operation::CollectArray(length));
+                self.op_stack.extend((0..length).rev().map(|i| {
+                    Operation::Call(function.clone(), vec![Value::Integer(i.into()).into()])
+                }));
+            }
+            BuiltinFunction::ArrayFold => {
+                let initial = arguments.pop().unwrap();
+                let items = expect_array("std::array::fold", &arguments[0])?;
+                self.value_stack.push(initial);
+                self.op_stack.extend(
+                    items
+                        .iter()
+                        .rev()
+                        .map(|item| Operation::FoldStep(function.clone(), item.clone())),
+                );
+            }
+            BuiltinFunction::ArrayZip => {
+                let left = expect_array("std::array::zip", &arguments[0])?;
+                let right = expect_array("std::array::zip", &arguments[1])?;
+                if left.len() != right.len() {
+                    Err(EvalError::TypeError(format!(
+                        "std::array::zip called on arrays of different lengths: {} and {}",
+                        left.len(),
+                        right.len()
+                    )))?
+                }
+                self.op_stack.push(Operation::CollectArray(left.len()));
+                self.op_stack.extend(
+                    left.iter().zip(right).rev().map(|(l, r)| {
+                        Operation::Call(function.clone(), vec![l.clone(), r.clone()])
+                    }),
+                );
+            }
+            BuiltinFunction::ArrayFind => {
+                let array = arguments.pop().unwrap();
+                expect_array("std::array::find", &array)?;
+                self.find_from(function, array, 0);
+            }
+            _ => unreachable!(),
+        }
+        Ok(())
+    }
+
+    /// Schedules calling `function` on the element at `index` of `array`,
+    /// followed by a [`Operation::FindStep`], or pushes -1 if `index` is past the end.
+    fn find_from(&mut self, function: Arc<Value<'a, T>>, array: Arc<Value<'a, T>>, index: usize) {
+        let Value::Array(items) = array.as_ref() else {
+            unreachable!()
+        };
+        match items.get(index) {
+            Some(item) => {
+                let item = item.clone();
+                self.op_stack
+                    .push(Operation::FindStep(function.clone(), array, index));
+                self.op_stack.push(Operation::Call(function, vec![item]));
+            }
+            None => self.value_stack.push(Value::Integer((-1).into()).into()),
+        }
+    }
+}
+
+fn expect_array<'x, 'a, T: FieldElement>(
+    name: &str,
+    value: &'x Value<'a, T>,
+) -> Result<&'x [Arc<Value<'a, T>>], EvalError> {
+    match value {
+        Value::Array(items) => Ok(items),
+        v => Err(EvalError::TypeError(format!(
+            "Expected array for {name}, but got {v}: {}",
+            v.type_formatted()
+        ))),
+    }
 }
 
 fn evaluate_literal<'a, T: FieldElement>(
     n: BigUint,
     ty: &Option<Type<u64>>,
     type_args: &HashMap<String, Type>,
+    fe_overflow: FeOverflow,
 ) -> Result<Arc<Value<'a, T>>, EvalError> {
     let ty = if let Some(Type::TypeVar(tv)) = ty {
         match &type_args[tv] {
@@ -990,16 +1581,18 @@ fn evaluate_literal<'a, T: FieldElement>(
             )))?,
         }
     } else {
-        ty.as_ref().cloned().unwrap_or_else(|| Type::Int)
+        ty.as_ref().cloned().unwrap_or(Type::Int)
     };
     if ty == Type::Int {
         return Ok(Value::Integer(n.into()).into());
     }
-    let fe = T::checked_from(n.clone()).ok_or_else(|| {
-        EvalError::TypeError(format!(
+    let fe = match T::checked_from(n.clone()) {
+        Some(fe) => fe,
+        None if fe_overflow == FeOverflow::Wrap => T::from(n % T::modulus().to_arbitrary_integer()),
+        None => Err(EvalError::TypeError(format!(
             "Number literal {n} is too large for field element."
-        ))
-    })?;
+        )))?,
+    };
     Ok((if ty == Type::Fe {
         Value::FieldElement(fe)
     } else if ty == Type::Expr {
@@ -1035,37 +1628,37 @@ fn evaluate_binary_operation<'a, T: FieldElement>(
             evaluate_binary_operation_field(*l, op, *r)?
         }
         (Value::FieldElement(l), BinaryOperator::Pow, Value::Integer(r)) => {
-            let exp: u64 = r
-                .clone()
-                .try_into()
-                .map_err(|_| EvalError::TypeError(format!("Exponent in {l}**{r} is too large.")))?;
-            Value::FieldElement(l.pow(exp.into())).into()
+            Value::FieldElement(field_pow(*l, r)?).into()
         }
+        (
+            Value::Expression(AlgebraicExpression::Number(l)),
+            BinaryOperator::Pow,
+            Value::Integer(r),
+        ) => Value::Expression(AlgebraicExpression::Number(field_pow(*l, r)?)).into(),
         (Value::Expression(l), BinaryOperator::Pow, Value::Integer(r)) => {
             let exp: u64 = r
                 .clone()
                 .try_into()
                 .map_err(|_| EvalError::TypeError(format!("Exponent in {l}**{r} is too large.")))?;
-            match l {
-                AlgebraicExpression::Number(l) => {
-                    Value::Expression(AlgebraicExpression::Number(l.pow(exp.into()))).into()
-                }
-                l => {
-                    assert!(
-                        BigUint::from(exp) < T::modulus().to_arbitrary_integer(),
-                        "Exponent too large: {exp}"
-                    );
-                    Value::from(AlgebraicExpression::new_binary(
-                        l.clone(),
-                        AlgebraicBinaryOperator::Pow,
-                        T::from(exp).into(),
-                    ))
-                    .into()
-                }
+            if BigUint::from(exp) >= T::modulus().to_arbitrary_integer() {
+                Err(EvalError::TypeError(format!(
+                    "Exponent in {l}**{r} is too large for the field."
+                )))?
             }
+            Value::from(AlgebraicExpression::new_binary(
+                l.clone(),
+                AlgebraicBinaryOperator::Pow,
+                T::from(exp).into(),
+            ))
+            .into()
         }
         (l @ Value::Expression(_), BinaryOperator::Identity, r @ Value::Expression(_)) => {
-            Value::Enum("Identity", Some(vec![l.clone().into(), r.clone().into()])).into()
+            Value::Enum(
+                "std::prelude::Constr",
+                "Identity",
+                Some(vec![l.clone().into(), r.clone().into()]),
+            )
+            .into()
         }
         (Value::Expression(l), op, Value::Expression(r)) => match (l, r) {
             (AlgebraicExpression::Number(l), AlgebraicExpression::Number(r)) => {
@@ -1077,7 +1670,9 @@ fn evaluate_binary_operation<'a, T: FieldElement>(
             }
             (l, r) => Value::from(AlgebraicExpression::new_binary(
                 l.clone(),
-                op.try_into().unwrap(),
+                op.try_into().map_err(|msg| {
+                    EvalError::TypeError(format!("{msg} Expression: {l} {op} {r}"))
+                })?,
                 r.clone(),
             ))
             .into(),
@@ -1090,23 +1685,42 @@ fn evaluate_binary_operation<'a, T: FieldElement>(
     })
 }
 
-#[allow(clippy::print_stdout)]
-fn evaluate_builtin_function<'a, T: FieldElement>(
+fn check_builtin_argument_count<T>(
     b: BuiltinFunction,
-    mut arguments: Vec<Arc<Value<'a, T>>>,
-    symbols: &mut impl SymbolLookup<'a, T>,
-) -> Result<Arc<Value<'a, T>>, EvalError> {
+    arguments: &[Arc<Value<'_, T>>],
+) -> Result<(), EvalError> {
     let params = match b {
         BuiltinFunction::ArrayLen => 1,
+        BuiltinFunction::ArrayMap => 2,
+        BuiltinFunction::ArrayNew => 2,
+        BuiltinFunction::ArrayFold => 3,
+        BuiltinFunction::ArraySum => 1,
+        BuiltinFunction::ArrayZip => 3,
+        BuiltinFunction::ArrayContains => 2,
+        BuiltinFunction::ArrayFind => 2,
+        BuiltinFunction::ArrayFlatten => 1,
+        BuiltinFunction::ArrayReverse => 1,
+        BuiltinFunction::ArrayToTuple(_) | BuiltinFunction::TupleToArray(_) => 1,
         BuiltinFunction::Modulus => 0,
+        BuiltinFunction::FieldBits => 0,
+        BuiltinFunction::InvField => 1,
         BuiltinFunction::Panic => 1,
+        BuiltinFunction::Require => 2,
         BuiltinFunction::Print => 1,
+        BuiltinFunction::ToBool => 1,
         BuiltinFunction::ToExpr => 1,
         BuiltinFunction::ToFe => 1,
         BuiltinFunction::ToInt => 1,
+        BuiltinFunction::ToBits => 2,
+        BuiltinFunction::ToString => 1,
+        BuiltinFunction::Format => 2,
         BuiltinFunction::Challenge => 2,
-        BuiltinFunction::Degree => 0,
+        BuiltinFunction::Degree | BuiltinFunction::MinDegree | BuiltinFunction::MaxDegree => 0,
         BuiltinFunction::Eval => 1,
+        BuiltinFunction::NewWitnessCol => 1,
+        BuiltinFunction::CaptureConstraints => 1,
+        BuiltinFunction::CartesianProduct => 2,
+        BuiltinFunction::IsPowerOfTwo | BuiltinFunction::NextPowerOfTwo => 1,
     };
 
     if arguments.len() != params {
@@ -1115,6 +1729,23 @@ fn evaluate_builtin_function<'a, T: FieldElement>(
                 arguments.len(),
             )))?
     }
+    Ok(())
+}
+
+/// Converts a value to a string, without quoting strings.
+fn to_unquoted_string<T: Display>(value: &Value<'_, T>) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        v => v.to_string(),
+    }
+}
+
+fn evaluate_builtin_function<'a, T: FieldElement>(
+    b: BuiltinFunction,
+    mut arguments: Vec<Arc<Value<'a, T>>>,
+    symbols: &mut impl SymbolLookup<'a, T>,
+) -> Result<Arc<Value<'a, T>>, EvalError> {
+    check_builtin_argument_count(b, &arguments)?;
     Ok(match b {
         BuiltinFunction::ArrayLen => match arguments.pop().unwrap().as_ref() {
             Value::Array(arr) => Value::Integer((arr.len() as u64).into()).into(),
@@ -1123,6 +1754,23 @@ fn evaluate_builtin_function<'a, T: FieldElement>(
                 v.type_formatted()
             ),
         },
+        BuiltinFunction::Require => {
+            let msg = match arguments.pop().unwrap().as_ref() {
+                Value::String(msg) => msg.clone(),
+                v => panic!(
+                    "Expected string for std::check::require, but got {v}: {}",
+                    v.type_formatted()
+                ),
+            };
+            match arguments.pop().unwrap().as_ref() {
+                Value::Bool(true) => Value::Tuple(vec![]).into(),
+                Value::Bool(false) => Err(EvalError::FailedAssertion(msg))?,
+                v => panic!(
+                    "Expected bool for std::check::require, but got {v}: {}",
+                    v.type_formatted()
+                ),
+            }
+        }
         BuiltinFunction::Panic => {
             let msg = match arguments.pop().unwrap().as_ref() {
                 Value::String(msg) => msg.clone(),
@@ -1134,14 +1782,42 @@ fn evaluate_builtin_function<'a, T: FieldElement>(
             Err(EvalError::FailedAssertion(msg))?
         }
         BuiltinFunction::Print => {
-            let msg = arguments.pop().unwrap();
-            if let Value::String(s) = msg.as_ref() {
-                print!("{s}");
-            } else {
-                print!("{msg}");
-            }
+            symbols.print(&to_unquoted_string(&arguments.pop().unwrap()));
             Value::Array(Default::default()).into()
         }
+        BuiltinFunction::ToString => {
+            Value::String(to_unquoted_string(&arguments.pop().unwrap())).into()
+        }
+        BuiltinFunction::Format => {
+            let args = arguments.pop().unwrap();
+            let template = arguments.pop().unwrap();
+            let (Value::String(template), Value::Array(args)) = (template.as_ref(), args.as_ref())
+            else {
+                panic!("Expected string and array for std::fmt::format, but got {template} and {args}.")
+            };
+            let parts = template.split("{}").collect::<Vec<_>>();
+            if parts.len() != args.len() + 1 {
+                Err(EvalError::TypeError(format!(
+                    "Template {} for std::fmt::format has {} placeholders, but {} arguments were given.",
+                    quote(template),
+                    parts.len() - 1,
+                    args.len()
+                )))?
+            }
+            let args = args.iter().map(|arg| to_unquoted_string(arg));
+            Value::String(
+                parts
+                    .into_iter()
+                    .map(|part| part.to_string())
+                    .interleave(args)
+                    .collect(),
+            )
+            .into()
+        }
+        BuiltinFunction::ToBool => {
+            let arg = arguments.pop().unwrap();
+            Value::Bool(!arg.try_to_integer()?.is_zero()).into()
+        }
         BuiltinFunction::ToExpr => {
             let arg = arguments.pop().unwrap();
             Value::from(AlgebraicExpression::Number(arg.try_to_field_element()?)).into()
@@ -1152,11 +1828,61 @@ fn evaluate_builtin_function<'a, T: FieldElement>(
         }
         BuiltinFunction::ToFe => {
             let arg = arguments.pop().unwrap();
-            Value::FieldElement(arg.try_to_field_element()?).into()
+            let value = match arg.as_ref() {
+                Value::Expression(e) => {
+                    constant_folder::try_evaluate_constant(e).ok_or_else(|| {
+                        EvalError::TypeError(format!(
+                        "Expression {e} is not constant and cannot be converted to a field element."
+                    ))
+                    })?
+                }
+                _ => arg.try_to_field_element()?,
+            };
+            Value::FieldElement(value).into()
+        }
+        BuiltinFunction::ToBits => {
+            let num_bits = arguments.pop().unwrap().try_to_integer()?;
+            let value = arguments.pop().unwrap().try_to_integer()?;
+            let num_bits = usize::try_from(num_bits.clone()).map_err(|_| {
+                EvalError::TypeError(format!(
+                    "Invalid number of bits for std::convert::to_bits: {num_bits}"
+                ))
+            })?;
+            let bits = BigUint::try_from(value.clone())
+                .ok()
+                .filter(|v| v.bit_len() <= num_bits)
+                .ok_or_else(|| {
+                    EvalError::TypeError(format!(
+                        "Value {value} does not fit into {num_bits} bits in std::convert::to_bits."
+                    ))
+                })?;
+            Value::Array(
+                (0..num_bits)
+                    .map(|i| Value::Integer(u32::from(bits.bit(i)).into()).into())
+                    .collect(),
+            )
+            .into()
         }
         BuiltinFunction::Modulus => {
             Value::Integer(T::modulus().to_arbitrary_integer().into()).into()
         }
+        BuiltinFunction::FieldBits => Value::Integer(T::BITS.into()).into(),
+        BuiltinFunction::InvField => {
+            let arg = arguments.pop().unwrap();
+            let x = match arg.as_ref() {
+                Value::FieldElement(x) => *x,
+                v => panic!(
+                    "Expected field element for std::math::ff::inv_field, but got {v}: {}",
+                    v.type_formatted()
+                ),
+            };
+            if x.is_zero() {
+                Err(EvalError::DivisionByZero(
+                    "Tried to compute the inverse of the zero field element.".to_string(),
+                ))?
+            }
+            Value::FieldElement(T::one() / x).into()
+        }
         BuiltinFunction::Challenge => {
             let [stage, index] = &arguments[..] else {
                 panic!()
@@ -1167,13 +1893,111 @@ fn evaluate_builtin_function<'a, T: FieldElement>(
             let Value::Integer(id) = (**index).clone() else {
                 panic!()
             };
-            Value::Expression(AlgebraicExpression::Challenge(Challenge {
-                id: u64::try_from(id).unwrap(),
-                stage: u32::try_from(stage).unwrap(),
-            }))
+            let (Ok(id), Ok(stage)) = (u64::try_from(&id), u32::try_from(&stage)) else {
+                Err(EvalError::OutOfBounds(format!(
+                    "Invalid challenge stage {stage} or id {id}, both have to be non-negative and fit into 32 and 64 bits, respectively."
+                )))?
+            };
+            Value::Expression(AlgebraicExpression::Challenge(Challenge { id, stage })).into()
+        }
+        BuiltinFunction::ArraySum => {
+            let arg = arguments.pop().unwrap();
+            let items = expect_array("std::array::sum", &arg)?;
+            let Some((first, rest)) = items.split_first() else {
+                Err(EvalError::TypeError(
+                    "std::array::sum called on an empty array, use std::array::sum_with_zero instead."
+                        .to_string(),
+                ))?
+            };
+            rest.iter().try_fold(first.clone(), |acc, item| {
+                evaluate_binary_operation(&acc, BinaryOperator::Add, item)
+            })?
+        }
+        BuiltinFunction::ArrayFlatten => {
+            let arrays = arguments.pop().unwrap();
+            let mut items = vec![];
+            for inner in expect_array("std::array::flatten", &arrays)? {
+                items.extend(expect_array("std::array::flatten", inner)?.iter().cloned());
+            }
+            Value::Array(items).into()
+        }
+        BuiltinFunction::ArrayReverse => {
+            let array = arguments.pop().unwrap();
+            let items = expect_array("std::array::reverse", &array)?;
+            Value::Array(items.iter().rev().cloned().collect()).into()
+        }
+        BuiltinFunction::ArrayToTuple(n) => {
+            let array = arguments.pop().unwrap();
+            let items = expect_array(&format!("std::array::to_tuple{n}"), &array)?;
+            if items.len() != n {
+                Err(EvalError::TypeError(format!(
+                    "std::array::to_tuple{n} called on an array of length {}",
+                    items.len()
+                )))?
+            }
+            Value::Tuple(items.to_vec()).into()
+        }
+        BuiltinFunction::TupleToArray(n) => match arguments.pop().unwrap().as_ref() {
+            Value::Tuple(items) if items.len() == n => Value::Array(items.clone()).into(),
+            v => Err(EvalError::TypeError(format!(
+                "Expected {n}-tuple for std::array::from_tuple{n}, but got {v}: {}",
+                v.type_formatted()
+            )))?,
+        },
+        BuiltinFunction::CartesianProduct => {
+            let right = arguments.pop().unwrap();
+            let left = arguments.pop().unwrap();
+            let right = expect_array("std::utils::cartesian_product", &right)?;
+            Value::Array(
+                expect_array("std::utils::cartesian_product", &left)?
+                    .iter()
+                    .cartesian_product(right)
+                    .map(|(l, r)| Value::Tuple(vec![l.clone(), r.clone()]).into())
+                    .collect(),
+            )
             .into()
         }
+        BuiltinFunction::IsPowerOfTwo => {
+            let n = arguments.pop().unwrap().try_to_integer()?;
+            let is_power_of_two = BigUint::try_from(n).is_ok_and(|n| n.is_power_of_two());
+            Value::Bool(is_power_of_two).into()
+        }
+        BuiltinFunction::NextPowerOfTwo => {
+            let n = arguments.pop().unwrap().try_to_integer()?;
+            let power = match BigUint::try_from(n - 1) {
+                Ok(n) if n > BigUint::from(0u32) => BigUint::from(1u32) << n.bit_len(),
+                _ => BigUint::from(1u32),
+            };
+            Value::Integer(power.into()).into()
+        }
+        BuiltinFunction::ArrayContains => {
+            let value = arguments.pop().unwrap();
+            let array = arguments.pop().unwrap();
+            let items = expect_array("std::array::contains", &array)?;
+            let mut contained = false;
+            for item in items {
+                match evaluate_binary_operation(item, BinaryOperator::Equal, &value)?.as_ref() {
+                    Value::Bool(true) => {
+                        contained = true;
+                        break;
+                    }
+                    Value::Bool(false) => {}
+                    _ => unreachable!(),
+                }
+            }
+            Value::Bool(contained).into()
+        }
+        BuiltinFunction::ArrayMap
+        | BuiltinFunction::ArrayNew
+        | BuiltinFunction::ArrayFold
+        | BuiltinFunction::ArrayZip
+        | BuiltinFunction::ArrayFind
+        | BuiltinFunction::CaptureConstraints => {
+            unreachable!("Evaluated by the evaluator because it calls functions.")
+        }
         BuiltinFunction::Degree => symbols.degree()?,
+        BuiltinFunction::MinDegree => symbols.min_degree()?,
+        BuiltinFunction::MaxDegree => symbols.max_degree()?,
         BuiltinFunction::Eval => {
             let arg = arguments.pop().unwrap();
             match arg.as_ref() {
@@ -1184,9 +2008,42 @@ fn evaluate_builtin_function<'a, T: FieldElement>(
                 ),
             }
         }
+        BuiltinFunction::NewWitnessCol => {
+            let arg = arguments.pop().unwrap();
+            let Value::String(name) = arg.as_ref() else {
+                panic!(
+                    "Expected string for std::prover::new_witness_col, but got {arg}: {}",
+                    arg.type_formatted()
+                )
+            };
+            if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                Err(EvalError::TypeError(format!(
+                    "Invalid name for new witness column: {}",
+                    quote(name)
+                )))?
+            }
+            symbols.new_column(name, None, SourceRef::unknown())?
+        }
     })
 }
 
+/// Computes `base**exponent` for a non-negative exponent of arbitrary size
+/// using exponentiation by squaring.
+fn field_pow<T: FieldElement>(base: T, exponent: &BigInt) -> Result<T, EvalError> {
+    let exp = BigUint::try_from(exponent.clone())
+        .map_err(|_| EvalError::TypeError(format!("Negative exponent in {base}**{exponent}.")))?;
+    if exp.is_zero() {
+        return Ok(T::one());
+    }
+    if base.is_zero() {
+        return Ok(base);
+    }
+    // The exponent can be reduced modulo the order of the multiplicative group,
+    // because base**(modulus - 1) = 1 for non-zero base.
+    let exp = exp % (T::modulus().to_arbitrary_integer() - 1u32);
+    Ok(base.pow(T::from(exp).to_integer()))
+}
+
 pub fn evaluate_binary_operation_field<'a, T: FieldElement>(
     left: T,
     op: BinaryOperator,
@@ -1214,14 +2071,17 @@ pub fn evaluate_binary_operation_integer<'a, T>(
         BinaryOperator::Add => Value::Integer(left + right),
         BinaryOperator::Sub => Value::Integer(left - right),
         BinaryOperator::Mul => Value::Integer(left * right),
+        BinaryOperator::Div | BinaryOperator::Mod if right.is_zero() => {
+            Err(EvalError::DivisionByZero(format!("{left} {op} {right}")))?
+        }
         BinaryOperator::Div => Value::Integer(left / right),
-        BinaryOperator::Pow => Value::Integer(left.pow(usize::try_from(right).unwrap())),
+        BinaryOperator::Pow => Value::Integer(left.pow(integer_operand(left, op, right)?)),
         BinaryOperator::Mod => Value::Integer(left % right),
         BinaryOperator::BinaryAnd => Value::Integer(left & right),
         BinaryOperator::BinaryXor => Value::Integer(left ^ right),
         BinaryOperator::BinaryOr => Value::Integer(left | right),
-        BinaryOperator::ShiftLeft => Value::Integer(left << usize::try_from(right).unwrap()),
-        BinaryOperator::ShiftRight => Value::Integer(left >> usize::try_from(right).unwrap()),
+        BinaryOperator::ShiftLeft => Value::Integer(left << integer_operand(left, op, right)?),
+        BinaryOperator::ShiftRight => Value::Integer(left >> integer_operand(left, op, right)?),
         BinaryOperator::Less => Value::Bool(left < right),
         BinaryOperator::LessEqual => Value::Bool(left <= right),
         BinaryOperator::Equal => Value::Bool(left == right),
@@ -1235,10 +2095,19 @@ pub fn evaluate_binary_operation_integer<'a, T>(
     .into())
 }
 
+/// Converts the right operand of an exponentiation or shift to `usize`.
+fn integer_operand(left: &BigInt, op: BinaryOperator, right: &BigInt) -> Result<usize, EvalError> {
+    usize::try_from(right).map_err(|_| {
+        EvalError::OutOfBounds(format!(
+            "The right operand in {left} {op} {right} has to be non-negative and small enough."
+        ))
+    })
+}
+
 #[cfg(test)]
 mod test {
+    use crate::number::{BabyBearField, Bn254Field, GoldilocksField, ToyField};
     use crate::pil_analyzer::evaluator;
-    use crate::number::GoldilocksField;
     use pretty_assertions::assert_eq;
 
     use crate::pil_analyzer::pil_analyzer::analyze_string;
@@ -1246,7 +2115,11 @@ mod test {
     use super::*;
 
     fn parse_and_evaluate_symbol(input: &str, symbol: &str) -> String {
-        let analyzed = analyze_string::<GoldilocksField>(input);
+        parse_and_evaluate_symbol_in_field::<GoldilocksField>(input, symbol)
+    }
+
+    fn parse_and_evaluate_symbol_in_field<T: FieldElement>(input: &str, symbol: &str) -> String {
+        let analyzed = analyze_string::<T>(input).unwrap();
         let Some(FunctionValueDefinition::Expression(TypedExpression {
             e: symbol,
             type_scheme: _,
@@ -1254,13 +2127,13 @@ mod test {
         else {
             panic!()
         };
-        evaluate::<GoldilocksField>(symbol, &mut Definitions(&analyzed.definitions))
+        evaluate::<T>(symbol, &mut Definitions(&analyzed.definitions))
             .unwrap()
             .to_string()
     }
 
     pub fn evaluate_function<T: FieldElement>(input: &str, function: &str) -> T {
-        let analyzed = analyze_string::<GoldilocksField>(input);
+        let analyzed = analyze_string::<T>(input).unwrap();
         let mut symbols = evaluator::Definitions(&analyzed.definitions);
         let function = symbols.lookup(function, None).unwrap();
         let result = evaluator::evaluate_function_call(function, vec![], &mut symbols)
@@ -1384,32 +2257,210 @@ mod test {
     }
 
     #[test]
-    fn hex_number_outside_field() {
-        // This tests that the parser does not lose precision when parsing large integers.
+    fn require_success() {
         let src = r#"
-            let N: int = 0x9999999999999999999999999999999;
+            namespace std::check(2);
+            let require: bool, string -> () = [];
+            namespace F(2);
+            let x: int = 7;
+            let y: () = std::check::require(x > 5, "x is too small");
         "#;
-        parse_and_evaluate_symbol(src, "N");
+        assert_eq!(parse_and_evaluate_symbol(src, "F.y"), "()");
     }
 
     #[test]
-    fn decimal_number_outside_field() {
-        // This tests that the parser does not lose precision when parsing large integers.
+    #[should_panic = r#"FailedAssertion("x is too small")"#]
+    fn require_failure() {
         let src = r#"
-            let N: int = 9999999999999999999999999999999;
+            namespace std::check(2);
+            let require: bool, string -> () = [];
+            namespace F(2);
+            let x: int = 3;
+            let y: () = std::check::require(x > 5, "x is too small");
         "#;
-        parse_and_evaluate_symbol(src, "N");
+        parse_and_evaluate_symbol(src, "F.y");
     }
 
     #[test]
-    #[should_panic = "Number literal 9999999999999999999999999999999 is too large for field element."]
-    fn decimal_number_outside_field_for_fe() {
+    fn to_bool() {
         let src = r#"
-            let N: fe = 9999999999999999999999999999999;
+            namespace std::convert(2);
+            let bool: int -> bool = [];
+            namespace F(2);
+            let x: int = 7;
+            let r: bool[] = [std::convert::bool(0), std::convert::bool(1), std::convert::bool(-3)];
+            let c: bool = std::convert::bool(x - 7) || std::convert::bool(x) && !std::convert::bool(0);
+        "#;
+        assert_eq!(parse_and_evaluate_symbol(src, "F.r"), "[false, true, true]");
+        assert_eq!(parse_and_evaluate_symbol(src, "F.c"), "true");
+    }
+
+    #[test]
+    fn constant_expr_to_fe() {
+        let src = r#"
+            namespace std::convert(2);
+            let<T: FromLiteral> fe: T -> fe = [];
+            namespace F(2);
+            let e: -> expr = || (2 + 3) * 4 - 1;
+            let x: fe = std::convert::fe(e());
+            let y: fe = std::convert::fe(-(e() * e()));
+        "#;
+        assert_eq!(parse_and_evaluate_symbol(src, "F.x"), "19");
+        assert_eq!(
+            parse_and_evaluate_symbol(src, "F.y"),
+            "18446744069414583960"
+        );
+    }
+
+    #[test]
+    #[should_panic = "Expression F.w + 1 is not constant and cannot be converted to a field element."]
+    fn non_constant_expr_to_fe() {
+        let src = r#"
+            namespace std::convert(2);
+            let<T: FromLiteral> fe: T -> fe = [];
+            namespace F(2);
+            col witness w;
+            let x: fe = std::convert::fe(w + 1);
+        "#;
+        parse_and_evaluate_symbol(src, "F.x");
+    }
+
+    #[test]
+    fn hex_number_outside_field() {
+        // This tests that the parser does not lose precision when parsing large integers.
+        let src = r#"
+            let N: int = 0x9999999999999999999999999999999;
         "#;
         parse_and_evaluate_symbol(src, "N");
     }
 
+    #[test]
+    fn decimal_number_outside_field() {
+        // This tests that the parser does not lose precision when parsing large integers.
+        let src = r#"
+            let N: int = 9999999999999999999999999999999;
+        "#;
+        parse_and_evaluate_symbol(src, "N");
+    }
+
+    #[test]
+    #[should_panic = "Number literal 9999999999999999999999999999999 is too large for a field element of the goldilocks field"]
+    fn decimal_number_outside_field_for_fe() {
+        let src = r#"
+            let N: fe = 9999999999999999999999999999999;
+        "#;
+        parse_and_evaluate_symbol(src, "N");
+    }
+
+    #[test]
+    fn decimal_number_in_bn254() {
+        let src = r#"
+            let N: fe = 9999999999999999999999999999999;
+        "#;
+        assert_eq!(
+            parse_and_evaluate_symbol_in_field::<Bn254Field>(src, "N"),
+            "9999999999999999999999999999999"
+        );
+    }
+
+    #[test]
+    fn field_bits() {
+        let src = r#"
+            namespace std::field;
+                let bits: -> int = [];
+                let modulus: -> int = [];
+                let characteristic: -> int = modulus;
+            namespace main;
+                let b: int = std::field::bits();
+                let fits: bool = std::field::characteristic() < 2**std::field::bits()
+                    && 2**(std::field::bits() - 1) < std::field::characteristic();
+        "#;
+        assert_eq!(
+            parse_and_evaluate_symbol_in_field::<Bn254Field>(src, "main.b"),
+            "254"
+        );
+        assert_eq!(
+            parse_and_evaluate_symbol_in_field::<GoldilocksField>(src, "main.b"),
+            "64"
+        );
+        assert_eq!(
+            parse_and_evaluate_symbol_in_field::<BabyBearField>(src, "main.b"),
+            "31"
+        );
+        assert_eq!(parse_and_evaluate_symbol(src, "main.fits"), "true");
+    }
+
+    #[test]
+    fn modulus() {
+        let src = r#"
+            namespace std::field;
+                let modulus = [];
+            namespace main;
+                let m: int = std::field::modulus();
+        "#;
+        assert_eq!(
+            parse_and_evaluate_symbol_in_field::<Bn254Field>(src, "main.m"),
+            "21888242871839275222246405745257275088548364400416034343698204186575808495617"
+        );
+        assert_eq!(
+            parse_and_evaluate_symbol_in_field::<GoldilocksField>(src, "main.m"),
+            "18446744069414584321"
+        );
+        assert_eq!(
+            parse_and_evaluate_symbol_in_field::<BabyBearField>(src, "main.m"),
+            "2013265921"
+        );
+        assert_eq!(
+            parse_and_evaluate_symbol_in_field::<ToyField>(src, "main.m"),
+            "97"
+        );
+    }
+
+    #[test]
+    fn toy_field() {
+        let src = r#"
+            namespace std::convert;
+                let<T: FromLiteral> fe: T -> fe = [];
+            namespace main;
+                let x: fe = 90 + 10;
+                let y: fe = -1;
+                let i: int = 96;
+                let z: fe = std::convert::fe(i) * 2;
+        "#;
+        assert_eq!(
+            parse_and_evaluate_symbol_in_field::<ToyField>(src, "main.x"),
+            "3"
+        );
+        assert_eq!(
+            parse_and_evaluate_symbol_in_field::<ToyField>(src, "main.y"),
+            "96"
+        );
+        assert_eq!(
+            parse_and_evaluate_symbol_in_field::<ToyField>(src, "main.z"),
+            "95"
+        );
+        let err = analyze_string::<ToyField>("let w: fe = 97;").unwrap_err();
+        assert!(
+            err.to_string().contains("Number literal 97 is too large"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn big_integer_arithmetic() {
+        let src = r#"
+            let x: int = 2 ** 200;
+            let y: int = (x + 1) * (x - 1) - x * x;
+            let z: int = (x >> 190) % 1000 + (x / 2 ** 199) + (-x) / x;
+        "#;
+        assert_eq!(
+            parse_and_evaluate_symbol(src, "x"),
+            "1606938044258990275541962092341162602522202993782792835301376"
+        );
+        assert_eq!(parse_and_evaluate_symbol(src, "y"), "-1");
+        assert_eq!(parse_and_evaluate_symbol(src, "z"), "25");
+    }
+
     #[test]
     fn zero_power_zero() {
         let src = r#"
@@ -1420,6 +2471,57 @@ mod test {
         assert_eq!(parse_and_evaluate_symbol(src, "zpz_fe"), "1".to_string());
     }
 
+    #[test]
+    fn field_power_with_large_exponent() {
+        let src = r#"
+        let p: int = 2**64 - 2**32 + 1;
+        let three: fe = 3;
+        let two: fe = 2;
+        let zero: fe = 0;
+        let a = three ** (p - 1);
+        let b = three ** p;
+        let c = three ** (p + 1);
+        let d = three ** (5 * (p - 1) + 2);
+        let e = two ** 64;
+        let f = zero ** p;
+        "#;
+        for (symbol, value) in [
+            ("a", "1"),
+            ("b", "3"),
+            ("c", "9"),
+            ("d", "9"),
+            ("e", "4294967295"),
+            ("f", "0"),
+        ] {
+            assert_eq!(parse_and_evaluate_symbol(src, symbol), value, "{symbol}");
+        }
+
+        let src = r#"
+        let r: int = 21888242871839275222246405745257275088548364400416034343698204186575808495617;
+        let five: fe = 5;
+        let a = five ** (r - 1);
+        let b = five ** (r + 2);
+        "#;
+        assert_eq!(
+            parse_and_evaluate_symbol_in_field::<Bn254Field>(src, "a"),
+            "1"
+        );
+        assert_eq!(
+            parse_and_evaluate_symbol_in_field::<Bn254Field>(src, "b"),
+            "125"
+        );
+    }
+
+    #[test]
+    #[should_panic = "Negative exponent in 3**-1."]
+    fn field_power_with_negative_exponent() {
+        let src = r#"
+        let three: fe = 3;
+        let a = three ** -1;
+        "#;
+        parse_and_evaluate_symbol(src, "a");
+    }
+
     #[test]
     fn debug_print() {
         let src = r#"
@@ -1445,6 +2547,48 @@ mod test {
         parse_and_evaluate_symbol(src, "std::debug::N");
     }
 
+    #[test]
+    fn debug_print_output() {
+        struct Output<'a> {
+            definitions: &'a HashMap<String, (Symbol, Option<FunctionValueDefinition>)>,
+            output: String,
+        }
+        impl<'a> SymbolLookup<'a, GoldilocksField> for Output<'a> {
+            fn lookup(
+                &mut self,
+                name: &str,
+                type_args: Option<Vec<Type>>,
+            ) -> Result<Arc<Value<'a, GoldilocksField>>, EvalError> {
+                Definitions::lookup_with_symbols(self.definitions, name, type_args, self)
+            }
+            fn print(&mut self, message: &str) {
+                self.output.push_str(message);
+            }
+        }
+
+        let src = r#"
+            namespace std::debug;
+                let<T: ToString> print: T -> () = [];
+            namespace N(8);
+                let x: int = 2;
+                let f: int -> () = |i| { let _ = std::debug::print((i, [x, 3], "a")); std::debug::print("\n") };
+                let N = { let _ = f(1); f(7) };
+                col witness w[{ let _ = std::debug::print("not printed"); 2 }];
+        "#;
+        let analyzed = analyze_string::<GoldilocksField>(src).unwrap();
+        let Some(FunctionValueDefinition::Expression(TypedExpression { e, .. })) =
+            &analyzed.definitions["N.N"].1
+        else {
+            panic!()
+        };
+        let mut symbols = Output {
+            definitions: &analyzed.definitions,
+            output: String::new(),
+        };
+        evaluate(e, &mut symbols).unwrap();
+        assert_eq!(symbols.output, "(1, [2, 3], \"a\")\n(7, [2, 3], \"a\")\n");
+    }
+
     #[test]
     fn local_vars() {
         let src = r#"
@@ -1683,4 +2827,776 @@ mod test {
 
         assert_eq!(parse_and_evaluate_symbol(input, "g"), "7".to_string());
     }
+
+    #[test]
+    fn array_map() {
+        let src = r#"
+            namespace std::array;
+                let<T1, T2> map: T1[], (T1 -> T2) -> T2[] = [];
+            namespace main;
+                let x: int[] = std::array::map([1, 2, 3], |i| i * 2);
+                let e: int[] = std::array::map([], |i| i + 1);
+        "#;
+        assert_eq!(parse_and_evaluate_symbol(src, "main.x"), "[2, 4, 6]");
+        assert_eq!(parse_and_evaluate_symbol(src, "main.e"), "[]");
+    }
+
+    #[test]
+    fn array_fold() {
+        let src = r#"
+            namespace std::array;
+                let<T1, T2> fold: T1[], T2, (T2, T1 -> T2) -> T2 = [];
+            namespace main;
+                let x: int = std::array::fold([1, 2, 3], 10, |acc, i| acc * 2 + i);
+                let e: int = std::array::fold([], 7, |acc, i| acc + i);
+        "#;
+        assert_eq!(parse_and_evaluate_symbol(src, "main.x"), "91");
+        assert_eq!(parse_and_evaluate_symbol(src, "main.e"), "7");
+    }
+
+    #[test]
+    fn inv_field() {
+        let src = r#"
+            namespace std::math::ff;
+                let inv_field: fe -> fe = [];
+            namespace main;
+                let x: fe = std::math::ff::inv_field(2);
+                let y: fe = std::math::ff::inv_field(7) * 7;
+        "#;
+        assert_eq!(
+            parse_and_evaluate_symbol(src, "main.x"),
+            "9223372034707292161"
+        );
+        assert_eq!(parse_and_evaluate_symbol(src, "main.y"), "1");
+    }
+
+    #[test]
+    fn inv_field_of_zero() {
+        let src = r#"
+            namespace std::math::ff;
+                let inv_field: fe -> fe = [];
+            namespace main;
+                let x: fe = std::math::ff::inv_field(0);
+        "#;
+        let analyzed = analyze_string::<GoldilocksField>(src).unwrap();
+        let Some(FunctionValueDefinition::Expression(TypedExpression { e, .. })) =
+            &analyzed.definitions["main.x"].1
+        else {
+            panic!()
+        };
+        let err =
+            evaluate::<GoldilocksField>(e, &mut Definitions(&analyzed.definitions)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Division by zero: Tried to compute the inverse of the zero field element."
+        );
+    }
+
+    #[test]
+    fn binary_not() {
+        let src = r#"
+            let x: int = ~5;
+            let y: int = ~(-1);
+            let z: int = ~0x0f & 0xff;
+            let b: bool = !(~0 == -1);
+        "#;
+        assert_eq!(parse_and_evaluate_symbol(src, "x"), "-6");
+        assert_eq!(parse_and_evaluate_symbol(src, "y"), "0");
+        assert_eq!(parse_and_evaluate_symbol(src, "z"), "240");
+        assert_eq!(parse_and_evaluate_symbol(src, "b"), "false");
+    }
+
+    #[test]
+    fn binary_not_on_field_element() {
+        let src = "let x: fe = ~5;";
+        let err = analyze_string::<GoldilocksField>(src).unwrap_err();
+        assert!(matches!(err, crate::pil_analyzer::AnalysisError::Type(_)));
+    }
+
+    #[test]
+    fn array_sum() {
+        let src = r#"
+            namespace std::array;
+                let<T: Add> sum: T[] -> T = [];
+                let<T1, T2> fold: T1[], T2, (T2, T1 -> T2) -> T2 = [];
+                let<T: Add> sum_with_zero: T[], T -> T = |arr, zero| fold(arr, zero, |a, b| a + b);
+            namespace main;
+                let x: int = std::array::sum([1, 2, 3, 4]);
+                let y: fe = std::array::sum([7]);
+                let z: int = std::array::sum_with_zero([1, 2], 0);
+                let e: int = std::array::sum_with_zero([], 5);
+        "#;
+        assert_eq!(parse_and_evaluate_symbol(src, "main.x"), "10");
+        assert_eq!(parse_and_evaluate_symbol(src, "main.y"), "7");
+        assert_eq!(parse_and_evaluate_symbol(src, "main.z"), "3");
+        assert_eq!(parse_and_evaluate_symbol(src, "main.e"), "5");
+    }
+
+    #[test]
+    fn array_sum_empty() {
+        let src = r#"
+            namespace std::array;
+                let<T: Add> sum: T[] -> T = [];
+                let<T> new: int, (int -> T) -> T[] = [];
+            namespace main;
+                let x: int = std::array::sum([]);
+        "#;
+        let err = analyze_string::<GoldilocksField>(src).unwrap_err();
+        assert!(
+            matches!(err, crate::pil_analyzer::AnalysisError::Type(_)),
+            "{err}"
+        );
+
+        // Arrays whose length is not known statically are checked during evaluation.
+        let src = src.replace(
+            "std::array::sum([])",
+            "std::array::sum(std::array::new(0 * 1, |i| i))",
+        );
+        let analyzed = analyze_string::<GoldilocksField>(&src).unwrap();
+        let Some(FunctionValueDefinition::Expression(TypedExpression { e, .. })) =
+            &analyzed.definitions["main.x"].1
+        else {
+            panic!()
+        };
+        let err =
+            evaluate::<GoldilocksField>(e, &mut Definitions(&analyzed.definitions)).unwrap_err();
+        assert!(matches!(err, EvalError::TypeError(_)), "{err}");
+    }
+
+    #[test]
+    fn array_new() {
+        let src = r#"
+            namespace std::array;
+                let<T> new: int, (int -> T) -> T[] = [];
+            namespace main;
+                let x: int[] = std::array::new(4, |i| i * i);
+                let e: int[] = std::array::new(0, |i| i);
+                let n: int[][] = std::array::new(2, |i| std::array::new(i + 1, |j| i + j));
+        "#;
+        assert_eq!(parse_and_evaluate_symbol(src, "main.x"), "[0, 1, 4, 9]");
+        assert_eq!(parse_and_evaluate_symbol(src, "main.e"), "[]");
+        assert_eq!(parse_and_evaluate_symbol(src, "main.n"), "[[0], [1, 2]]");
+    }
+
+    #[test]
+    #[should_panic = "std::array::new called with invalid length -1"]
+    fn array_new_negative_length() {
+        let src = r#"
+            namespace std::array;
+                let<T> new: int, (int -> T) -> T[] = [];
+            namespace main;
+                let x: int[] = std::array::new(-1, |i| i);
+        "#;
+        parse_and_evaluate_symbol(src, "main.x");
+    }
+
+    #[test]
+    fn array_zip() {
+        let src = r#"
+            namespace std::array;
+                let<T1, T2, T3> zip: T1[], T2[], (T1, T2 -> T3) -> T3[] = [];
+            namespace main;
+                let x: int[] = std::array::zip([1, 2, 3], [10, 20, 30], |a, b| a * b);
+                let e: int[] = std::array::zip([], [], |a, b| a + b);
+        "#;
+        assert_eq!(parse_and_evaluate_symbol(src, "main.x"), "[10, 40, 90]");
+        assert_eq!(parse_and_evaluate_symbol(src, "main.e"), "[]");
+    }
+
+    #[test]
+    fn array_zip_length_mismatch() {
+        let src = r#"
+            namespace std::array;
+                let<T1, T2, T3> zip: T1[], T2[], (T1, T2 -> T3) -> T3[] = [];
+            namespace main;
+                // The length of the second array is only known after evaluation.
+                let pair: int -> int[] = |i| [i, 20];
+                let x: int[] = std::array::zip([1, 2, 3], pair(10), |a, b| a * b);
+        "#;
+        let analyzed = analyze_string::<GoldilocksField>(src).unwrap();
+        let Some(FunctionValueDefinition::Expression(TypedExpression { e, .. })) =
+            &analyzed.definitions["main.x"].1
+        else {
+            panic!()
+        };
+        let err =
+            evaluate::<GoldilocksField>(e, &mut Definitions(&analyzed.definitions)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Type error: std::array::zip called on arrays of different lengths: 3 and 2"
+        );
+    }
+
+    #[test]
+    fn array_elementwise_operations() {
+        let src = r#"
+            namespace std::array;
+                let<T1, T2, T3> zip: T1[], T2[], (T1, T2 -> T3) -> T3[] = [];
+                let<T: Add> add: T[], T[] -> T[] = |a, b| zip(a, b, |x, y| x + y);
+                let<T: Sub> sub: T[], T[] -> T[] = |a, b| zip(a, b, |x, y| x - y);
+                let<T: Mul> mul: T[], T[] -> T[] = |a, b| zip(a, b, |x, y| x * y);
+            namespace main(8);
+                let a: int[] = [1, 2, 3];
+                let b: int[] = [10, 20, 30];
+                let sum = std::array::add(a, b);
+                let difference = std::array::sub(a, b);
+                let product = std::array::mul(a, b);
+                let concatenated = a + b;
+                let nested: int[][] = std::array::add([[1], [2]], [[3], [4, 5]]);
+                col witness x[2], y[2];
+                let s: expr[2] = std::array::add(x, std::array::mul(y, [2, 3]));
+                s[1] = 0;
+        "#;
+        assert_eq!(parse_and_evaluate_symbol(src, "main.sum"), "[11, 22, 33]");
+        assert_eq!(
+            parse_and_evaluate_symbol(src, "main.difference"),
+            "[-9, -18, -27]"
+        );
+        assert_eq!(
+            parse_and_evaluate_symbol(src, "main.product"),
+            "[10, 40, 90]"
+        );
+        assert_eq!(
+            parse_and_evaluate_symbol(src, "main.concatenated"),
+            "[1, 2, 3, 10, 20, 30]"
+        );
+        assert_eq!(
+            parse_and_evaluate_symbol(src, "main.nested"),
+            "[[1, 3], [2, 4, 5]]"
+        );
+        let analyzed = analyze_string::<GoldilocksField>(src).unwrap();
+        assert_eq!(analyzed.identities[0].to_string(), "main.s[1] = 0;");
+        assert_eq!(
+            analyzed.intermediate_columns["main.s"]
+                .1
+                .iter()
+                .map(|e| e.to_string())
+                .collect::<Vec<_>>(),
+            ["main.x[0] + main.y[0] * 2", "main.x[1] + main.y[1] * 3"]
+        );
+
+        let src = r#"
+            namespace std::array;
+                let<T1, T2, T3> zip: T1[], T2[], (T1, T2 -> T3) -> T3[] = [];
+                let<T: Add> add: T[], T[] -> T[] = |a, b| zip(a, b, |x, y| x + y);
+            namespace main;
+                let x: int[] = std::array::add([1, 2, 3], [10, 20]);
+        "#;
+        let analyzed = analyze_string::<GoldilocksField>(src).unwrap();
+        let Some(FunctionValueDefinition::Expression(TypedExpression { e, .. })) =
+            &analyzed.definitions["main.x"].1
+        else {
+            panic!()
+        };
+        let err =
+            evaluate::<GoldilocksField>(e, &mut Definitions(&analyzed.definitions)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Type error: std::array::zip called on arrays of different lengths: 3 and 2"
+        );
+    }
+
+    #[test]
+    fn binary_literal_outside_field() {
+        let src = r#"
+            let x: fe = 0b1_0000000000000000000000000000000000000000000000000000000000000000;
+        "#;
+        let err = analyze_string::<BabyBearField>(src).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("Number literal 18446744073709551616 is too large"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn eval_fixed_columns_at_row() {
+        let src = r#"
+            namespace std::prover;
+                let eval: expr -> fe = [];
+            namespace main(8);
+                col fixed SQUARE(i) { i * i };
+                col fixed TABLE = [10, 20, 30] + [7]*;
+                col witness w;
+                let at_row = query || std::prover::eval(SQUARE) + std::prover::eval(TABLE');
+                let witness_value = query || std::prover::eval(w);
+        "#;
+        let analyzed = analyze_string::<GoldilocksField>(src).unwrap();
+        let evaluate_at_row = |name: &str, row: DegreeType| {
+            let mut symbols = DefinitionsAtRow {
+                definitions: &analyzed.definitions,
+                row,
+            };
+            let function: Arc<Value<GoldilocksField>> = symbols.lookup(name, None).unwrap();
+            evaluator::evaluate_function_call(function, vec![], &mut symbols).map(|v| v.to_string())
+        };
+        assert_eq!(evaluate_at_row("main.at_row", 0).unwrap(), "20");
+        assert_eq!(evaluate_at_row("main.at_row", 1).unwrap(), "31");
+        assert_eq!(evaluate_at_row("main.at_row", 5).unwrap(), "32");
+        // The next row of the last row is the first row.
+        assert_eq!(evaluate_at_row("main.at_row", 7).unwrap(), "59");
+        assert_eq!(
+            evaluate_at_row("main.witness_value", 0)
+                .unwrap_err()
+                .to_string(),
+            "Operation unsupported: Value of column main.w is not known at compile time."
+        );
+    }
+
+    #[test]
+    fn evaluate_all_fixed_columns() {
+        let src = r#"
+            namespace main(4);
+                let square: int -> int = |i| i * i;
+                col fixed SQUARE(i) { square(i) };
+                col fixed TABLE = [10, 20, 30] + [7]*;
+                col witness w;
+        "#;
+        let analyzed = analyze_string::<GoldilocksField>(src).unwrap();
+        let values = evaluate_fixed_columns(&analyzed)
+            .unwrap()
+            .into_iter()
+            .map(|(name, values)| (name, values.iter().map(|v| v.to_string()).join(", ")))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            values,
+            [
+                ("main.SQUARE".to_string(), "0, 1, 4, 9".to_string()),
+                ("main.TABLE".to_string(), "10, 20, 30, 7".to_string())
+            ]
+        );
+
+        let src = r#"
+            namespace main;
+                col fixed NO_DEGREE(i) { i };
+        "#;
+        let analyzed = analyze_string::<GoldilocksField>(src).unwrap();
+        assert_eq!(
+            evaluate_fixed_columns(&analyzed).unwrap_err().to_string(),
+            "Operation unsupported: Cannot evaluate fixed column main.NO_DEGREE without a degree."
+        );
+    }
+
+    #[test]
+    fn overloaded_operators() {
+        let src = r#"
+            namespace std::array;
+                let<T: Add> sum: T[] -> T = [];
+            namespace N(4);
+            struct Point {
+                x: int,
+                y: int,
+            }
+            impl Add<Point> {
+                add: |a, b| Point::new(Point::x(a) + Point::x(b), Point::y(a) + Point::y(b)),
+            }
+            impl Neg<Point> {
+                neg: |a| Point::new(-Point::x(a), -Point::y(a)),
+            }
+            impl Pow<Point> {
+                pow: |a, e| Point::new(Point::x(a) ** e, Point::y(a) ** e),
+            }
+            let<T: Add> double: T -> T = |v| v + v;
+            let p: Point = -(double(Point::new(1, 2)) ** 2) + Point::new(10, 20);
+            let sum: Point = std::array::sum([Point::new(1, 2), Point::new(3, 4)]);
+        "#;
+        assert_eq!(parse_and_evaluate_symbol(src, "N.p"), "new(6, 4)");
+        assert_eq!(parse_and_evaluate_symbol(src, "N.sum"), "new(4, 6)");
+    }
+
+    #[test]
+    fn array_slices() {
+        let src = r#"namespace N(16);
+            let arr: int[] = [1, 2, 3, 4, 5];
+            let middle = arr[1..4];
+            let empty = arr[2..2];
+            let tail = |a, i| a[i..5];
+            let last_two = tail(arr, 3);
+        "#;
+        assert_eq!(parse_and_evaluate_symbol(src, "N.middle"), "[2, 3, 4]");
+        assert_eq!(parse_and_evaluate_symbol(src, "N.empty"), "[]");
+        assert_eq!(parse_and_evaluate_symbol(src, "N.last_two"), "[4, 5]");
+    }
+
+    #[test]
+    #[should_panic = "Index -1 in x[i - 2] is negative, but array indices have to be non-negative."]
+    fn negative_index_access() {
+        let src = r#"
+            let f: int -> int = |i| {
+                let x: int[] = [1, 2, 3];
+                x[i - 2]
+            };
+            let y: int = f(1);
+        "#;
+        parse_and_evaluate_symbol(src, "y");
+    }
+
+    #[test]
+    #[should_panic = "Tried to access elements 2..6 of array of size 5"]
+    fn array_slice_out_of_bounds() {
+        let src = r#"namespace N(16);
+            let arr: int[] = [1, 2, 3, 4, 5];
+            let upto = |i| arr[2..i];
+            let x = upto(6);
+        "#;
+        parse_and_evaluate_symbol(src, "N.x");
+    }
+
+    #[test]
+    fn string_concatenation_and_format() {
+        let src = r#"
+            namespace std::convert;
+                let<T: ToString> to_string: T -> string = [];
+            namespace std::fmt;
+                let<T: ToString> format: string, T[] -> string = [];
+            namespace N(16);
+                col witness w;
+                let i: int = 7;
+                let row = "row " + std::convert::to_string(i);
+                let s = std::convert::to_string("already a string");
+                let e = std::convert::to_string(w + 1);
+                let f = std::fmt::format("{} at {}: {}", ["w", row, std::convert::to_string(-i)]);
+                let nums: int[] = [1, 2];
+                let g = std::fmt::format("{}-{}", nums);
+        "#;
+        assert_eq!(parse_and_evaluate_symbol(src, "N.row"), r#""row 7""#);
+        assert_eq!(
+            parse_and_evaluate_symbol(src, "N.s"),
+            r#""already a string""#
+        );
+        assert_eq!(parse_and_evaluate_symbol(src, "N.e"), r#""N.w + 1""#);
+        assert_eq!(parse_and_evaluate_symbol(src, "N.f"), r#""w at row 7: -7""#);
+        assert_eq!(parse_and_evaluate_symbol(src, "N.g"), r#""1-2""#);
+    }
+
+    #[test]
+    #[should_panic = "has 2 placeholders, but 1 arguments were given"]
+    fn format_argument_count_mismatch() {
+        let src = r#"
+            namespace std::fmt;
+                let<T: ToString> format: string, T[] -> string = [];
+            namespace N(16);
+                let f = std::fmt::format("{} and {}", ["x"]);
+        "#;
+        parse_and_evaluate_symbol(src, "N.f");
+    }
+
+    #[test]
+    fn array_contains_and_find() {
+        let src = r#"
+            namespace std::array;
+                let<T: Eq> contains: T[], T -> bool = [];
+                let<T> find: T[], (T -> bool) -> int = [];
+            namespace std::check;
+                let panic: string -> ! = [];
+            namespace N(16);
+                let ints: int[] = [3, 5, 7];
+                let fes: fe[] = [1, 2, 4];
+                let strings: string[] = ["a", "b"];
+                let c1 = std::array::contains(ints, 5);
+                let c2 = std::array::contains(fes, 3);
+                let c3 = std::array::contains(strings, "b");
+                let f1 = std::array::find(ints, |x| x > 4);
+                let f2 = std::array::find(strings, |s| s == "c");
+                let f3 = std::array::find(ints, |x| if x <= 5 { x == 5 } else { std::check::panic("evaluated past the first match") });
+        "#;
+        assert_eq!(parse_and_evaluate_symbol(src, "N.c1"), "true");
+        assert_eq!(parse_and_evaluate_symbol(src, "N.c2"), "false");
+        assert_eq!(parse_and_evaluate_symbol(src, "N.c3"), "true");
+        assert_eq!(parse_and_evaluate_symbol(src, "N.f1"), "1");
+        assert_eq!(parse_and_evaluate_symbol(src, "N.f2"), "-1");
+        assert_eq!(parse_and_evaluate_symbol(src, "N.f3"), "1");
+    }
+
+    #[test]
+    fn short_circuit_logical_operators() {
+        let src = r#"
+            let f: int -> bool = |x| x != 0 && 10 / x > 1;
+            let g: int -> bool = |x| x == 0 || 10 / x > 1;
+            let a: bool = f(0);
+            let b: bool = f(5);
+            let c: bool = g(0);
+            let d: bool = g(20);
+            let e: bool = (f(5) && g(0)) || f(10 / 0);
+        "#;
+        assert_eq!(parse_and_evaluate_symbol(src, "a"), "false");
+        assert_eq!(parse_and_evaluate_symbol(src, "b"), "true");
+        assert_eq!(parse_and_evaluate_symbol(src, "c"), "true");
+        assert_eq!(parse_and_evaluate_symbol(src, "d"), "false");
+        assert_eq!(parse_and_evaluate_symbol(src, "e"), "true");
+    }
+
+    #[test]
+    fn array_reverse() {
+        let src = r#"
+            namespace std::array;
+                let<T> reverse: T[] -> T[] = [];
+                let<T> len: T[] -> int = [];
+            namespace main;
+                let x: int[] = std::array::reverse([1, 2, 3]);
+                let e: string[] = std::array::reverse([]);
+                let n = std::array::len(std::array::reverse([["a"], ["b", "c"]]));
+        "#;
+        assert_eq!(parse_and_evaluate_symbol(src, "main.x"), "[3, 2, 1]");
+        assert_eq!(parse_and_evaluate_symbol(src, "main.e"), "[]");
+        assert_eq!(parse_and_evaluate_symbol(src, "main.n"), "2");
+
+        let src = r#"
+            namespace std::array;
+                let<T> reverse: T[] -> T[] = [];
+            namespace main(4);
+                col witness bits[3];
+                let rev: expr[3] = std::array::reverse(bits);
+                rev[0] = bits[2] * rev[2];
+        "#;
+        let analyzed = analyze_string::<GoldilocksField>(src).unwrap();
+        assert_eq!(
+            analyzed.identities[0].to_string(),
+            "main.rev[0] = main.bits[2] * main.rev[2];"
+        );
+        assert_eq!(
+            analyzed.intermediate_columns["main.rev"]
+                .1
+                .iter()
+                .join(", "),
+            "main.bits[2], main.bits[1], main.bits[0]"
+        );
+    }
+
+    #[test]
+    fn tuple_conversions() {
+        let src = r#"
+            namespace std::array;
+                let<T> to_tuple2: T[] -> (T, T) = [];
+                let<T> to_tuple3: T[] -> (T, T, T) = [];
+                let<T> from_tuple2: (T, T) -> T[] = [];
+                let<T> from_tuple4: (T, T, T, T) -> T[] = [];
+                let<T1, T2> map: T1[], (T1 -> T2) -> T2[] = [];
+            namespace main;
+                let t: (int, int, int) = std::array::to_tuple3(std::array::map([1, 2, 3], |i| i * 2));
+                let a: string[] = std::array::from_tuple4(("a", "b", "c", "d"));
+                let sum = |(x, y)| x + y;
+                let s: int = sum(std::array::to_tuple2(std::array::from_tuple2((7, 8))));
+                let wrong_length: (int, int) = std::array::to_tuple2([1, 2, 3]);
+        "#;
+        assert_eq!(parse_and_evaluate_symbol(src, "main.t"), "(2, 4, 6)");
+        assert_eq!(
+            parse_and_evaluate_symbol(src, "main.a"),
+            r#"["a", "b", "c", "d"]"#
+        );
+        assert_eq!(parse_and_evaluate_symbol(src, "main.s"), "15");
+        let analyzed = analyze_string::<GoldilocksField>(src).unwrap();
+        let Some(FunctionValueDefinition::Expression(TypedExpression { e, .. })) =
+            &analyzed.definitions["main.wrong_length"].1
+        else {
+            panic!()
+        };
+        let err =
+            evaluate::<GoldilocksField>(e, &mut Definitions(&analyzed.definitions)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Type error: std::array::to_tuple2 called on an array of length 3"
+        );
+    }
+
+    #[test]
+    fn powers_of_two() {
+        let src = r#"
+            namespace std::utils;
+                let is_power_of_two: int -> bool = [];
+                let next_power_of_two: int -> int = [];
+            namespace main;
+                let inputs: int[] = [-4, 0, 1, 2, 3, 4, 5, 1023, 1024, 1025, 2**100, 2**100 + 1];
+                let is_power = std::array::map(inputs, std::utils::is_power_of_two);
+                let next = std::array::map(inputs, std::utils::next_power_of_two);
+            namespace std::array;
+                let<T1, T2> map: T1[], (T1 -> T2) -> T2[] = [];
+        "#;
+        assert_eq!(
+            parse_and_evaluate_symbol(src, "main.is_power"),
+            "[false, false, true, true, false, true, false, false, true, false, true, false]"
+        );
+        assert_eq!(
+            parse_and_evaluate_symbol(src, "main.next"),
+            format!(
+                "[1, 1, 1, 2, 4, 4, 8, 1024, 1024, 2048, {}, {}]",
+                BigUint::from(1u32) << 100,
+                BigUint::from(1u32) << 101
+            )
+        );
+    }
+
+    #[test]
+    fn to_bits() {
+        let src = r#"
+            namespace std::convert;
+                let to_bits: int, int -> int[] = [];
+            namespace main;
+                let small: int[] = std::convert::to_bits(6, 4);
+                let zero: int[] = std::convert::to_bits(0, 0);
+                let wide: int = std::array::len(std::convert::to_bits(2**100 + 1, 128));
+                let wide_top: int = std::convert::to_bits(2**100 + 1, 101)[100];
+                let too_large: int[] = std::convert::to_bits(16, 4);
+                let negative: int[] = std::convert::to_bits(-1, 4);
+            namespace std::array;
+                let<T> len: T[] -> int = [];
+        "#;
+        assert_eq!(parse_and_evaluate_symbol(src, "main.small"), "[0, 1, 1, 0]");
+        assert_eq!(parse_and_evaluate_symbol(src, "main.zero"), "[]");
+        assert_eq!(parse_and_evaluate_symbol(src, "main.wide"), "128");
+        assert_eq!(parse_and_evaluate_symbol(src, "main.wide_top"), "1");
+        let analyzed = analyze_string::<GoldilocksField>(src).unwrap();
+        for (name, value) in [("too_large", 16), ("negative", -1)] {
+            let Some(FunctionValueDefinition::Expression(TypedExpression { e, .. })) =
+                &analyzed.definitions[&format!("main.{name}")].1
+            else {
+                panic!()
+            };
+            let err = evaluate::<GoldilocksField>(e, &mut Definitions(&analyzed.definitions))
+                .unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                format!(
+                    "Type error: Value {value} does not fit into 4 bits in std::convert::to_bits."
+                )
+            );
+        }
+    }
+
+    #[test]
+    fn cartesian_product() {
+        let src = r#"
+            namespace std::utils;
+                let<T, U> cartesian_product: T[], U[] -> (T, U)[] = [];
+            namespace main;
+                let x: (int, string)[] = std::utils::cartesian_product([1, 2, 3], ["a", "b"]);
+                let e: (int, int)[] = std::utils::cartesian_product([1, 2], []);
+        "#;
+        assert_eq!(
+            parse_and_evaluate_symbol(src, "main.x"),
+            r#"[(1, "a"), (1, "b"), (2, "a"), (2, "b"), (3, "a"), (3, "b")]"#
+        );
+        assert_eq!(parse_and_evaluate_symbol(src, "main.e"), "[]");
+    }
+
+    #[test]
+    fn array_flatten() {
+        let src = r#"
+            namespace std::array;
+                let<T> flatten: T[][] -> T[] = [];
+                let<T> len: T[] -> int = [];
+                let<T1, T2> map: T1[], (T1 -> T2) -> T2[] = [];
+                let<T> new: int, (int -> T) -> T[] = [];
+            namespace N(16);
+                let nested: int[][] = [[1, 2], [], [3], [4, 5, 6]];
+                let flat = std::array::flatten(nested);
+                let empty: int[] = std::array::flatten([]);
+                let l = std::array::len(flat);
+                let mapped = std::array::flatten(std::array::map([1, 2, 3], |i| std::array::new(i, |j| i * 10 + j)));
+        "#;
+        assert_eq!(
+            parse_and_evaluate_symbol(src, "N.flat"),
+            "[1, 2, 3, 4, 5, 6]"
+        );
+        assert_eq!(parse_and_evaluate_symbol(src, "N.empty"), "[]");
+        assert_eq!(parse_and_evaluate_symbol(src, "N.l"), "6");
+        assert_eq!(
+            parse_and_evaluate_symbol(src, "N.mapped"),
+            "[10, 20, 21, 30, 31, 32]"
+        );
+    }
+
+    #[test]
+    fn array_comprehension() {
+        let src = r#"
+            namespace std::array;
+                let<T> flatten: T[][] -> T[] = [];
+                let<T1, T2> map: T1[], (T1 -> T2) -> T2[] = [];
+                let<T> new: int, (int -> T) -> T[] = [];
+            namespace N(16);
+                let n: int = 4;
+                let doubled = [i * 2 for i in 1..n];
+                let even = [i for i in 0..6 if i % 2 == 0];
+                let pairs: (int, string)[] = [(1, "a"), (2, "b")];
+                let names = [s for (_, s) in pairs];
+                let triangle = [[j for j in 0..i] for i in 0..4 if i > 0];
+                let empty: int[] = [i for i in 3..3];
+        "#;
+        assert_eq!(parse_and_evaluate_symbol(src, "N.doubled"), "[2, 4, 6]");
+        assert_eq!(parse_and_evaluate_symbol(src, "N.even"), "[0, 2, 4]");
+        assert_eq!(parse_and_evaluate_symbol(src, "N.names"), r#"["a", "b"]"#);
+        assert_eq!(
+            parse_and_evaluate_symbol(src, "N.triangle"),
+            "[[0], [0, 1], [0, 1, 2]]"
+        );
+        assert_eq!(parse_and_evaluate_symbol(src, "N.empty"), "[]");
+    }
+
+    #[test]
+    fn array_comprehension_range_start() {
+        let src = r#"
+            namespace std::array;
+                let<T1, T2> map: T1[], (T1 -> T2) -> T2[] = [];
+                let<T> new: int, (int -> T) -> T[] = [];
+                let<T: Add> sum: T[] -> T = [];
+            namespace N(16);
+                let __i: int = 2;
+                let sum = std::array::sum([i for i in __i..5]);
+                let i: int = 1;
+                let nested = [[j for j in i..3] for i in i..3];
+        "#;
+        assert_eq!(parse_and_evaluate_symbol(src, "N.sum"), "9");
+        assert_eq!(parse_and_evaluate_symbol(src, "N.nested"), "[[1, 2], [2]]");
+    }
+
+    #[test]
+    fn array_comprehension_type_error() {
+        let src = r#"
+            namespace std::array;
+                let<T1, T2> map: T1[], (T1 -> T2) -> T2[] = [];
+            namespace N(16);
+                let x: int[] = [s for s in ["a", "b"]];
+        "#;
+        let error = analyze_string::<GoldilocksField>(src).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("Expected type int but got type string"));
+    }
+
+    #[test]
+    fn casts() {
+        let src = r#"
+            namespace std::convert;
+                let<T: FromLiteral> int: T -> int = [];
+                let<T: FromLiteral> fe: T -> fe = [];
+                let<T: FromLiteral> expr: T -> expr = [];
+            namespace N(16);
+                let x: int = 7;
+                let y: int = 3;
+                let a: fe = x as fe;
+                let b: int = a as int + 1;
+                let c: fe = 2 * y as fe + y ** 2 as fe;
+                let d: int = (y as fe + 4) as int / 2;
+        "#;
+        assert_eq!(parse_and_evaluate_symbol(src, "N.a"), "7");
+        assert_eq!(parse_and_evaluate_symbol(src, "N.b"), "8");
+        assert_eq!(parse_and_evaluate_symbol(src, "N.c"), "15");
+        assert_eq!(parse_and_evaluate_symbol(src, "N.d"), "3");
+    }
+
+    #[test]
+    fn invalid_casts() {
+        let analyze = |definition: &str| {
+            let src = format!(
+                "namespace std::convert; let<T: FromLiteral> int: T -> int = []; namespace N(16); {definition}"
+            );
+            analyze_string::<GoldilocksField>(&src)
+                .unwrap_err()
+                .to_string()
+        };
+        assert!(analyze("let x: bool = 1 as bool;")
+            .contains("Cannot cast to bool, only casts to int, fe and expr are allowed."));
+        assert!(analyze("let x = \"a\" as int;")
+            .contains("Type string does not satisfy trait FromLiteral."));
+    }
 }
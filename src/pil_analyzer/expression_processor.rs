@@ -2,14 +2,15 @@ use core::panic;
 use crate::ast::{
     analyzed::{Expression, PolynomialReference, Reference, RepeatedArray},
     parsed::{
-        self, asm::SymbolPath, types::Type, ArrayExpression, ArrayLiteral, BinaryOperation,
-        BlockExpression, IfExpression, LambdaExpression, LetStatementInsideBlock, MatchArm,
-        MatchExpression, NamespacedPolynomialReference, Number, Pattern, SelectedExpressions,
-        StatementInsideBlock, SymbolCategory, UnaryOperation,
+        self, asm::SymbolPath, types::Type, ArrayComprehension, ArrayExpression, ArrayLiteral,
+        BinaryOperation, BinaryOperator, BlockExpression, Cast, ComprehensionIterable,
+        FunctionCall, FunctionKind, IfExpression, LambdaExpression, LetStatementInsideBlock,
+        MatchArm, MatchExpression, NamespacedPolynomialReference, Number, Pattern,
+        SelectedExpressions, SourceReference, StatementInsideBlock, SymbolCategory, UnaryOperation,
     },
 };
 use crate::number::DegreeType;
-use crate::parser_util::SourceRef;
+use crate::parser_util::{Error, SourceRef};
 use std::{
     collections::{HashMap, HashSet},
     str::FromStr,
@@ -43,21 +44,24 @@ impl<'a, D: AnalysisDriver> ExpressionProcessor<'a, D> {
     pub fn process_selected_expressions(
         &mut self,
         expr: SelectedExpressions<parsed::Expression>,
-    ) -> SelectedExpressions<Expression> {
-        SelectedExpressions {
-            selector: expr.selector.map(|e| self.process_expression(e)),
-            expressions: Box::new(self.process_expression(*expr.expressions)),
-        }
+    ) -> Result<SelectedExpressions<Expression>, Error> {
+        Ok(SelectedExpressions {
+            selector: expr
+                .selector
+                .map(|e| self.process_expression(e))
+                .transpose()?,
+            expressions: Box::new(self.process_expression(*expr.expressions)?),
+        })
     }
 
     pub fn process_array_expression(
         &mut self,
         array_expression: crate::ast::parsed::ArrayExpression,
         size: DegreeType,
-    ) -> Vec<RepeatedArray> {
-        match array_expression {
+    ) -> Result<Vec<RepeatedArray>, Error> {
+        Ok(match array_expression {
             ArrayExpression::Value(expressions) => {
-                let values = self.process_expressions(expressions);
+                let values = self.process_expressions(expressions)?;
                 let size = values.len() as DegreeType;
                 vec![RepeatedArray::new(values, size)]
             }
@@ -66,20 +70,23 @@ impl<'a, D: AnalysisDriver> ExpressionProcessor<'a, D> {
                     vec![]
                 } else {
                     vec![RepeatedArray::new(
-                        self.process_expressions(expressions),
+                        self.process_expressions(expressions)?,
                         size,
                     )]
                 }
             }
             ArrayExpression::Concat(left, right) => self
-                .process_array_expression(*left, size)
+                .process_array_expression(*left, size)?
                 .into_iter()
-                .chain(self.process_array_expression(*right, size))
+                .chain(self.process_array_expression(*right, size)?)
                 .collect(),
-        }
+        })
     }
 
-    pub fn process_expressions(&mut self, exprs: Vec<parsed::Expression>) -> Vec<Expression> {
+    pub fn process_expressions(
+        &mut self,
+        exprs: Vec<parsed::Expression>,
+    ) -> Result<Vec<Expression>, Error> {
         exprs
             .into_iter()
             .map(|e| self.process_expression(e))
@@ -89,25 +96,28 @@ impl<'a, D: AnalysisDriver> ExpressionProcessor<'a, D> {
     pub fn process_vec_into_selected_expression(
         &mut self,
         exprs: Vec<parsed::Expression>,
-    ) -> SelectedExpressions<Expression> {
+    ) -> Result<SelectedExpressions<Expression>, Error> {
         let exprs = Expression::ArrayLiteral(
             SourceRef::unknown(),
             ArrayLiteral {
-                items: self.process_expressions(exprs),
+                items: self.process_expressions(exprs)?,
             },
         );
 
-        SelectedExpressions {
+        Ok(SelectedExpressions {
             selector: None,
             expressions: Box::new(exprs),
-        }
+        })
     }
 
-    pub fn process_expression(&mut self, expr: parsed::Expression) -> Expression {
+    /// Processes a parsed expression into an analyzed expression, resolving all references.
+    /// Returns an error if a reference cannot be resolved.
+    pub fn process_expression(&mut self, expr: parsed::Expression) -> Result<Expression, Error> {
         use parsed::Expression as PExpression;
-        match expr {
+        Ok(match expr {
             PExpression::Reference(src, poly) => {
-                Expression::Reference(src, self.process_reference(poly))
+                let reference = self.process_reference(poly, &src)?;
+                Expression::Reference(src, reference)
             }
             PExpression::PublicReference(src, name) => Expression::PublicReference(src, name),
             PExpression::Number(src, Number { value: n, type_: t }) => {
@@ -115,17 +125,18 @@ impl<'a, D: AnalysisDriver> ExpressionProcessor<'a, D> {
             }
             PExpression::String(src, value) => Expression::String(src, value),
             PExpression::Tuple(src, items) => {
-                Expression::Tuple(src, self.process_expressions(items))
+                Expression::Tuple(src, self.process_expressions(items)?)
             }
             PExpression::ArrayLiteral(src, ArrayLiteral { items }) => Expression::ArrayLiteral(
                 src,
                 ArrayLiteral {
-                    items: self.process_expressions(items),
+                    items: self.process_expressions(items)?,
                 },
             ),
-            PExpression::LambdaExpression(src, lambda_expression) => {
-                Expression::LambdaExpression(src, self.process_lambda_expression(lambda_expression))
-            }
+            PExpression::LambdaExpression(src, lambda_expression) => Expression::LambdaExpression(
+                src,
+                self.process_lambda_expression(lambda_expression)?,
+            ),
             PExpression::BinaryOperation(
                 src,
                 BinaryOperation {
@@ -136,9 +147,9 @@ impl<'a, D: AnalysisDriver> ExpressionProcessor<'a, D> {
             ) => Expression::BinaryOperation(
                 src,
                 BinaryOperation {
-                    left: Box::new(self.process_expression(*l)),
+                    left: Box::new(self.process_expression(*l)?),
                     op,
-                    right: Box::new(self.process_expression(*r)),
+                    right: Box::new(self.process_expression(*r)?),
                 },
             ),
             PExpression::UnaryOperation(src, UnaryOperation { op, expr: value }) => {
@@ -146,39 +157,47 @@ impl<'a, D: AnalysisDriver> ExpressionProcessor<'a, D> {
                     src,
                     UnaryOperation {
                         op,
-                        expr: Box::new(self.process_expression(*value)),
+                        expr: Box::new(self.process_expression(*value)?),
                     },
                 )
             }
             PExpression::IndexAccess(src, index_access) => Expression::IndexAccess(
                 src,
                 parsed::IndexAccess {
-                    array: Box::new(self.process_expression(*index_access.array)),
-                    index: Box::new(self.process_expression(*index_access.index)),
+                    array: Box::new(self.process_expression(*index_access.array)?),
+                    index: Box::new(self.process_expression(*index_access.index)?),
+                },
+            ),
+            PExpression::SliceAccess(src, slice_access) => Expression::SliceAccess(
+                src,
+                parsed::SliceAccess {
+                    array: Box::new(self.process_expression(*slice_access.array)?),
+                    start: Box::new(self.process_expression(*slice_access.start)?),
+                    end: Box::new(self.process_expression(*slice_access.end)?),
                 },
             ),
             PExpression::FunctionCall(src, c) => Expression::FunctionCall(
                 src,
                 parsed::FunctionCall {
-                    function: Box::new(self.process_expression(*c.function)),
-                    arguments: self.process_expressions(c.arguments),
+                    function: Box::new(self.process_expression(*c.function)?),
+                    arguments: self.process_expressions(c.arguments)?,
                 },
             ),
             PExpression::MatchExpression(src, MatchExpression { scrutinee, arms }) => {
                 Expression::MatchExpression(
                     src,
                     MatchExpression {
-                        scrutinee: Box::new(self.process_expression(*scrutinee)),
+                        scrutinee: Box::new(self.process_expression(*scrutinee)?),
                         arms: arms
                             .into_iter()
                             .map(|MatchArm { pattern, value }| {
                                 let vars = self.save_local_variables();
-                                let pattern = self.process_pattern(pattern);
-                                let value = self.process_expression(value);
+                                let pattern = self.process_pattern(pattern)?;
+                                let value = self.process_expression(value)?;
                                 self.reset_local_variables(vars);
-                                MatchArm { pattern, value }
+                                Ok(MatchArm { pattern, value })
                             })
-                            .collect(),
+                            .collect::<Result<_, _>>()?,
                     },
                 )
             }
@@ -192,23 +211,27 @@ impl<'a, D: AnalysisDriver> ExpressionProcessor<'a, D> {
             ) => Expression::IfExpression(
                 src,
                 IfExpression {
-                    condition: Box::new(self.process_expression(*condition)),
-                    body: Box::new(self.process_expression(*body)),
-                    else_body: Box::new(self.process_expression(*else_body)),
+                    condition: Box::new(self.process_expression(*condition)?),
+                    body: Box::new(self.process_expression(*body)?),
+                    else_body: Box::new(self.process_expression(*else_body)?),
                 },
             ),
             PExpression::BlockExpression(src, BlockExpression { statements, expr }) => {
-                self.process_block_expression(statements, expr, src)
+                self.process_block_expression(statements, expr, src)?
+            }
+            PExpression::ArrayComprehension(src, comprehension) => {
+                self.process_expression(desugar_array_comprehension(src, comprehension))?
             }
+            PExpression::Cast(src, cast) => self.process_expression(desugar_cast(src, cast)?)?,
             PExpression::FreeInput(_, _) => panic!(),
-        }
+        })
     }
 
     /// Processes a pattern, registering all variables bound in there.
     /// It also changes EnumPatterns consisting of a single identifier that does not resolve
     /// to anything into Variable patterns.
-    fn process_pattern(&mut self, pattern: Pattern) -> Pattern {
-        match pattern {
+    fn process_pattern(&mut self, pattern: Pattern) -> Result<Pattern, Error> {
+        Ok(match pattern {
             Pattern::CatchAll(_)
             | Pattern::Ellipsis(_)
             | Pattern::Number(_, _)
@@ -221,14 +244,17 @@ impl<'a, D: AnalysisDriver> ExpressionProcessor<'a, D> {
                     .count()
                     > 1
                 {
-                    panic!("Only one \"..\"-item allowed in array pattern");
+                    return Err(source_ref
+                        .with_error("Only one \"..\"-item allowed in array pattern".to_string()));
                 }
-                Pattern::Array(source_ref, self.process_pattern_vec(items))
+                Pattern::Array(source_ref, self.process_pattern_vec(items)?)
             }
             Pattern::Tuple(source_ref, items) => {
-                Pattern::Tuple(source_ref, self.process_pattern_vec(items))
+                Pattern::Tuple(source_ref, self.process_pattern_vec(items)?)
+            }
+            Pattern::Variable(source_ref, name) => {
+                self.process_variable_pattern(source_ref, name)?
             }
-            Pattern::Variable(source_ref, name) => self.process_variable_pattern(source_ref, name),
             Pattern::Enum(source_ref, name, None) => {
                 // The parser cannot distinguish between Enum and Variable patterns.
                 // So if "name" is a single identifier that does not resolve to an enum variant,
@@ -236,40 +262,50 @@ impl<'a, D: AnalysisDriver> ExpressionProcessor<'a, D> {
 
                 if let Some((resolved_name, category)) = self.driver.try_resolve_ref(&name) {
                     if category.compatible_with_request(SymbolCategory::TypeConstructor) {
-                        self.process_enum_pattern(source_ref, resolved_name, None)
+                        self.process_enum_pattern(source_ref, resolved_name, None)?
                     } else if let Some(identifier) = name.try_to_identifier() {
                         // It's a single identifier that does not resolve to an enum variant.
-                        self.process_variable_pattern(source_ref, identifier.clone())
+                        self.process_variable_pattern(source_ref, identifier.clone())?
                     } else {
-                        panic!("Expected enum variant but got {category}: {resolved_name}");
+                        return Err(source_ref.with_error(format!(
+                            "Expected enum variant but got {category}: {resolved_name}"
+                        )));
                     }
                 } else if let Some(identifier) = name.try_to_identifier() {
                     // It's a single identifier that does not resolve to an enum variant.
-                    self.process_variable_pattern(source_ref, identifier.clone())
+                    self.process_variable_pattern(source_ref, identifier.clone())?
                 } else {
-                    panic!("Symbol not found: {name}");
+                    return Err(source_ref.with_error(format!("Symbol not found: {name}")));
                 }
             }
             Pattern::Enum(source_ref, name, fields) => {
-                self.process_enum_pattern(source_ref, self.driver.resolve_value_ref(&name), fields)
+                let name = self
+                    .driver
+                    .resolve_value_ref(&name)
+                    .map_err(|e| source_ref.with_error(e))?;
+                self.process_enum_pattern(source_ref, name, fields)?
             }
-        }
+        })
     }
 
-    fn process_pattern_vec(&mut self, patterns: Vec<Pattern>) -> Vec<Pattern> {
+    fn process_pattern_vec(&mut self, patterns: Vec<Pattern>) -> Result<Vec<Pattern>, Error> {
         patterns
             .into_iter()
             .map(|p| self.process_pattern(p))
             .collect()
     }
 
-    fn process_variable_pattern(&mut self, source_ref: SourceRef, name: String) -> Pattern {
+    fn process_variable_pattern(
+        &mut self,
+        source_ref: SourceRef,
+        name: String,
+    ) -> Result<Pattern, Error> {
         let id = self.local_variable_counter;
         if self.local_variables.insert(name.clone(), id).is_some() {
-            panic!("Variable already defined: {name}");
+            return Err(source_ref.with_error(format!("Variable already defined: {name}")));
         }
         self.local_variable_counter += 1;
-        Pattern::Variable(source_ref, name)
+        Ok(Pattern::Variable(source_ref, name))
     }
 
     fn process_enum_pattern(
@@ -277,28 +313,33 @@ impl<'a, D: AnalysisDriver> ExpressionProcessor<'a, D> {
         source_ref: SourceRef,
         name: String,
         fields: Option<Vec<Pattern>>,
-    ) -> Pattern {
-        Pattern::Enum(
+    ) -> Result<Pattern, Error> {
+        let fields = fields
+            .map(|fields| self.process_pattern_vec(fields))
+            .transpose()?;
+        Ok(Pattern::Enum(
             source_ref,
             SymbolPath::from_str(&name).unwrap(),
-            fields.map(|fields| {
-                fields
-                    .into_iter()
-                    .map(|p| self.process_pattern(p))
-                    .collect()
-            }),
-        )
+            fields,
+        ))
     }
 
-    fn process_reference(&mut self, reference: NamespacedPolynomialReference) -> Reference {
-        match reference.try_to_identifier() {
+    fn process_reference(
+        &mut self,
+        reference: NamespacedPolynomialReference,
+        source_ref: &SourceRef,
+    ) -> Result<Reference, Error> {
+        Ok(match reference.try_to_identifier() {
             Some(name) if self.local_variables.contains_key(name) => {
                 let id = self.local_variables[name];
                 self.local_var_references.insert(id);
                 Reference::LocalVar(id, name.to_string())
             }
-            _ => Reference::Poly(self.process_namespaced_polynomial_reference(reference)),
-        }
+            _ => Reference::Poly(
+                self.process_namespaced_polynomial_reference(reference)
+                    .map_err(|e| source_ref.with_error(e))?,
+            ),
+        })
     }
 
     pub fn process_lambda_expression(
@@ -309,7 +350,7 @@ impl<'a, D: AnalysisDriver> ExpressionProcessor<'a, D> {
             body,
             outer_var_references: _,
         }: LambdaExpression,
-    ) -> LambdaExpression<Expression> {
+    ) -> Result<LambdaExpression<Expression>, Error> {
         let previous_local_vars = self.save_local_variables();
         let previous_local_var_refs = self.local_var_references.clone();
         let local_variable_height = self.local_variable_counter;
@@ -317,14 +358,16 @@ impl<'a, D: AnalysisDriver> ExpressionProcessor<'a, D> {
         let params = params
             .into_iter()
             .map(|p| self.process_pattern(p))
-            .collect::<Vec<_>>();
+            .collect::<Result<Vec<_>, _>>()?;
 
         for param in &params {
             if !param.is_irrefutable() {
-                panic!("Function parameters must be irrefutable, but {param} is refutable.");
+                return Err(param.source_reference().with_error(format!(
+                    "Function parameters must be irrefutable, but {param} is refutable."
+                )));
             }
         }
-        let body = Box::new(self.process_expression(*body));
+        let body = Box::new(self.process_expression(*body)?);
 
         let outer_var_references =
             std::mem::replace(&mut self.local_var_references, previous_local_var_refs)
@@ -332,12 +375,12 @@ impl<'a, D: AnalysisDriver> ExpressionProcessor<'a, D> {
                 .filter(|id| *id < local_variable_height)
                 .collect();
         self.reset_local_variables(previous_local_vars);
-        LambdaExpression {
+        Ok(LambdaExpression {
             kind,
             params,
             body,
             outer_var_references,
-        }
+        })
     }
 
     fn process_block_expression(
@@ -345,61 +388,69 @@ impl<'a, D: AnalysisDriver> ExpressionProcessor<'a, D> {
         statements: Vec<StatementInsideBlock>,
         expr: Option<Box<crate::ast::parsed::Expression>>,
         src: SourceRef,
-    ) -> Expression {
+    ) -> Result<Expression, Error> {
         let vars = self.save_local_variables();
 
         let processed_statements = statements
             .into_iter()
             .map(|statement| match statement {
                 StatementInsideBlock::LetStatement(LetStatementInsideBlock { pattern, ty, value }) => {
-                    let value = value.map(|v| self.process_expression(v));
-                    let pattern = self.process_pattern(pattern);
-                    let ty = ty.map(|ty| self.process_number_type(ty));
+                    let value = value.map(|v| self.process_expression(v)).transpose()?;
+                    let pattern = self.process_pattern(pattern)?;
+                    let ty = ty
+                        .map(|ty| self.process_number_type(ty))
+                        .transpose()
+                        .map_err(|e| src.with_error(e))?;
                     if value.is_none() && !matches!(pattern, Pattern::Variable(_, _)) {
-                        panic!("Let statement without value requires a single variable, but got {pattern}.");
+                        return Err(src.with_error(format!("Let statement without value requires a single variable, but got {pattern}.")));
                     }
                     if !pattern.is_irrefutable() {
-                        panic!("Let statement requires an irrefutable pattern, but {pattern} is refutable.");
+                        return Err(src.with_error(format!("Let statement requires an irrefutable pattern, but {pattern} is refutable.")));
                     }
-                    StatementInsideBlock::LetStatement(LetStatementInsideBlock { pattern, ty, value })
+                    Ok(StatementInsideBlock::LetStatement(LetStatementInsideBlock { pattern, ty, value }))
                 }
                 StatementInsideBlock::Expression(expr) => {
-                    StatementInsideBlock::Expression(self.process_expression(expr))
+                    Ok(StatementInsideBlock::Expression(self.process_expression(expr)?))
                 }
             })
-            .collect::<Vec<_>>();
+            .collect::<Result<Vec<_>, _>>()?;
 
-        let processed_expr = expr.map(|expr| Box::new(self.process_expression(*expr)));
+        let processed_expr = expr
+            .map(|expr| self.process_expression(*expr).map(Box::new))
+            .transpose()?;
 
         self.reset_local_variables(vars);
-        Expression::BlockExpression(
+        Ok(Expression::BlockExpression(
             src,
             BlockExpression {
                 statements: processed_statements,
                 expr: processed_expr,
             },
-        )
+        ))
     }
 
+    /// Resolves the name and type arguments of a reference to a global symbol.
+    /// Returns an error message (without source reference) if it cannot be resolved.
     pub fn process_namespaced_polynomial_reference(
         &mut self,
         reference: NamespacedPolynomialReference,
-    ) -> PolynomialReference {
+    ) -> Result<PolynomialReference, String> {
         let type_args = reference
             .type_args
-            .map(|args| args.into_iter().map(|t| self.process_type(t)).collect());
-        PolynomialReference {
-            name: self.driver.resolve_value_ref(&reference.path),
+            .map(|args| args.into_iter().map(|t| self.process_type(t)).collect())
+            .transpose()?;
+        Ok(PolynomialReference {
+            name: self.driver.resolve_value_ref(&reference.path)?,
             poly_id: None,
             type_args,
-        }
+        })
     }
 
-    fn process_type(&self, ty: Type<parsed::Expression>) -> Type<u64> {
+    fn process_type(&self, ty: Type<parsed::Expression>) -> Result<Type<u64>, String> {
         TypeProcessor::new(self.driver, self.type_vars).process_type(ty)
     }
 
-    fn process_number_type(&self, ty: Type<u64>) -> Type<u64> {
+    fn process_number_type(&self, ty: Type<u64>) -> Result<Type<u64>, String> {
         TypeProcessor::new(self.driver, self.type_vars).process_number_type(ty)
     }
 
@@ -420,3 +471,151 @@ struct LocalVariableState {
     pub local_variables: HashMap<String, u64>,
     pub local_variable_counter: u64,
 }
+
+/// Turns `expr as ty` into `std::convert::ty(expr)`, so the cast has the same semantics
+/// as the conversion function, which has to be declared.
+/// Only casts to `int`, `fe` and `expr` are allowed.
+fn desugar_cast(src: SourceRef, Cast { expr, ty }: Cast) -> Result<parsed::Expression, Error> {
+    let name = match ty {
+        Type::Int => "int",
+        Type::Fe => "fe",
+        Type::Expr => "expr",
+        _ => {
+            return Err(src.with_error(format!(
+                "Cannot cast to {ty}, only casts to int, fe and expr are allowed. Use a conversion function like std::convert::bool instead."
+            )))
+        }
+    };
+    Ok(parsed::Expression::FunctionCall(
+        src.clone(),
+        FunctionCall {
+            function: Box::new(parsed::Expression::Reference(
+                src,
+                SymbolPath::from_str(&format!("std::convert::{name}"))
+                    .unwrap()
+                    .into(),
+            )),
+            arguments: vec![*expr],
+        },
+    ))
+}
+
+/// Turns `[value for pattern in iterable if condition]` into
+/// `std::array::flatten(std::array::map(iterable, |pattern| if condition { [value] } else { [] }))`
+/// or, without a condition, into `std::array::map(iterable, |pattern| value)`.
+/// A range `start..end` is turned into the array `std::array::new(end - start, |__i| __i + start)`,
+/// so it has to satisfy `start <= end`. The functions from `std::array` have to be declared.
+fn desugar_array_comprehension(
+    src: SourceRef,
+    ArrayComprehension {
+        value,
+        pattern,
+        iterable,
+        condition,
+    }: ArrayComprehension,
+) -> parsed::Expression {
+    let std_function = |name: &str| {
+        parsed::Expression::Reference(
+            src.clone(),
+            SymbolPath::from_str(&format!("std::array::{name}"))
+                .unwrap()
+                .into(),
+        )
+    };
+    let call = |name: &str, arguments: Vec<parsed::Expression>| {
+        parsed::Expression::FunctionCall(
+            src.clone(),
+            FunctionCall {
+                function: Box::new(std_function(name)),
+                arguments,
+            },
+        )
+    };
+    let lambda = |params: Vec<Pattern>, body: parsed::Expression| {
+        parsed::Expression::LambdaExpression(
+            src.clone(),
+            LambdaExpression {
+                kind: FunctionKind::Pure,
+                params,
+                body: Box::new(body),
+                outer_var_references: Default::default(),
+            },
+        )
+    };
+    let binary_operation = |left, op, right| {
+        parsed::Expression::BinaryOperation(src.clone(), BinaryOperation { left, op, right })
+    };
+    let block = |expr: parsed::Expression| {
+        parsed::Expression::BlockExpression(
+            src.clone(),
+            BlockExpression {
+                statements: vec![],
+                expr: Some(Box::new(expr)),
+            },
+        )
+    };
+
+    let array = match iterable {
+        ComprehensionIterable::Array(array) => *array,
+        ComprehensionIterable::Range(start, end) => {
+            // The generated names cannot be written in source code, so they cannot
+            // shadow or be shadowed by user variables. `start` is evaluated only once.
+            let variable = |name: &str| {
+                parsed::Expression::Reference(
+                    src.clone(),
+                    NamespacedPolynomialReference::from_identifier(name.to_string()),
+                )
+            };
+            let length = binary_operation(end, BinaryOperator::Sub, Box::new(variable("#start")));
+            let element = binary_operation(
+                Box::new(variable("#i")),
+                BinaryOperator::Add,
+                Box::new(variable("#start")),
+            );
+            parsed::Expression::BlockExpression(
+                src.clone(),
+                BlockExpression {
+                    statements: vec![StatementInsideBlock::LetStatement(
+                        LetStatementInsideBlock {
+                            pattern: Pattern::Variable(src.clone(), "#start".to_string()),
+                            ty: None,
+                            value: Some(*start),
+                        },
+                    )],
+                    expr: Some(Box::new(call(
+                        "new",
+                        vec![
+                            length,
+                            lambda(
+                                vec![Pattern::Variable(src.clone(), "#i".to_string())],
+                                element,
+                            ),
+                        ],
+                    ))),
+                },
+            )
+        }
+    };
+    match condition {
+        None => call("map", vec![array, lambda(vec![*pattern], *value)]),
+        Some(condition) => {
+            let body = parsed::Expression::IfExpression(
+                src.clone(),
+                IfExpression {
+                    condition,
+                    body: Box::new(block(
+                        ArrayLiteral {
+                            items: vec![*value],
+                        }
+                        .into(),
+                    )),
+                    else_body: Box::new(block(ArrayLiteral { items: vec![] }.into())),
+                },
+            );
+            call(
+                "flatten",
+                vec![call("map", vec![array, lambda(vec![*pattern], body)])],
+            )
+        }
+    }
+}
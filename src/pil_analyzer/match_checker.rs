@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+
+use crate::ast::analyzed::{Expression, FunctionValueDefinition, Symbol};
+use crate::ast::parsed::{
+    visitor::AllChildren, EnumDeclaration, MatchArm, MatchExpression, Pattern,
+};
+use crate::parser_util::SourceRef;
+
+/// Returns the source references of all match expressions among the given expressions
+/// and their sub-expressions whose arms do not cover all possible values.
+/// Only enums and tuples are known to have a finite set of values, matches
+/// on any other type need a catch-all arm to be exhaustive.
+pub fn non_exhaustive_matches<'a>(
+    definitions: &HashMap<String, (Symbol, Option<FunctionValueDefinition>)>,
+    expressions: impl IntoIterator<Item = &'a Expression>,
+) -> Vec<&'a SourceRef> {
+    expressions
+        .into_iter()
+        .flat_map(|e| e.all_children())
+        .filter_map(|e| match e {
+            Expression::MatchExpression(source_ref, MatchExpression { arms, .. }) => {
+                let rows = arms
+                    .iter()
+                    .map(|MatchArm { pattern, .. }| vec![Some(pattern)])
+                    .collect::<Vec<_>>();
+                (!is_exhaustive(definitions, &rows)).then_some(source_ref)
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Returns true if the rows of patterns cover all possible values, i.e. if every
+/// tuple of values matches at least one row. `None` stands for a pattern that
+/// matches everything.
+fn is_exhaustive(
+    definitions: &HashMap<String, (Symbol, Option<FunctionValueDefinition>)>,
+    rows: &[Vec<Option<&Pattern>>],
+) -> bool {
+    let Some(first_row) = rows.first() else {
+        return false;
+    };
+    if first_row.is_empty() {
+        return true;
+    }
+    let first_column = rows.iter().filter_map(|row| row[0]);
+    if let Some(enum_decl) = first_column
+        .clone()
+        .find_map(|p| enum_of_pattern(definitions, p))
+    {
+        return enum_decl.variants.iter().all(|variant| {
+            let arity = variant.fields.as_ref().map_or(0, |fields| fields.len());
+            let specialized = rows
+                .iter()
+                .filter_map(|row| match row[0] {
+                    Some(Pattern::Enum(_, name, fields)) if name.name() == &variant.name => Some(
+                        fields
+                            .iter()
+                            .flatten()
+                            .map(Some)
+                            .chain(row[1..].iter().cloned())
+                            .collect(),
+                    ),
+                    Some(Pattern::Enum(..)) => None,
+                    p => is_catch_all(p).then(|| expand_catch_all(row, arity)),
+                })
+                .collect::<Vec<_>>();
+            is_exhaustive(definitions, &specialized)
+        });
+    }
+    if let Some(arity) = first_column.clone().find_map(|p| match p {
+        Pattern::Tuple(_, items) => Some(items.len()),
+        _ => None,
+    }) {
+        let specialized = rows
+            .iter()
+            .filter_map(|row| match row[0] {
+                Some(Pattern::Tuple(_, items)) => Some(
+                    items
+                        .iter()
+                        .map(Some)
+                        .chain(row[1..].iter().cloned())
+                        .collect(),
+                ),
+                p => is_catch_all(p).then(|| expand_catch_all(row, arity)),
+            })
+            .collect::<Vec<_>>();
+        return is_exhaustive(definitions, &specialized);
+    }
+    // Numbers, strings and arrays range over infinite sets of values,
+    // so only the rows that match everything in the first column remain.
+    let remaining = rows
+        .iter()
+        .filter(|row| is_catch_all(row[0]))
+        .map(|row| row[1..].to_vec())
+        .collect::<Vec<_>>();
+    is_exhaustive(definitions, &remaining)
+}
+
+fn is_catch_all(pattern: Option<&Pattern>) -> bool {
+    pattern.is_none_or(|p| p.is_irrefutable())
+}
+
+/// Replaces the first item of the row by `arity` catch-all patterns.
+fn expand_catch_all<'a>(row: &[Option<&'a Pattern>], arity: usize) -> Vec<Option<&'a Pattern>> {
+    std::iter::repeat_n(None, arity)
+        .chain(row[1..].iter().cloned())
+        .collect()
+}
+
+fn enum_of_pattern<'a>(
+    definitions: &'a HashMap<String, (Symbol, Option<FunctionValueDefinition>)>,
+    pattern: &Pattern,
+) -> Option<&'a EnumDeclaration> {
+    let Pattern::Enum(_, name, _) = pattern else {
+        return None;
+    };
+    match &definitions[&name.to_dotted_string()].1 {
+        Some(FunctionValueDefinition::TypeConstructor(enum_decl, _)) => Some(enum_decl.as_ref()),
+        _ => unreachable!("Expected type constructor for pattern {pattern}"),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ast::parsed::visitor::Children;
+    use crate::number::GoldilocksField;
+    use crate::pil_analyzer::analyze_string;
+
+    use super::non_exhaustive_matches;
+
+    fn non_exhaustive(input: &str) -> Vec<String> {
+        let analyzed = analyze_string::<GoldilocksField>(input).unwrap();
+        let mut names = analyzed
+            .definitions
+            .iter()
+            .filter(|(_, (_, value))| {
+                !non_exhaustive_matches(
+                    &analyzed.definitions,
+                    value.iter().flat_map(|v| v.children()),
+                )
+                .is_empty()
+            })
+            .map(|(name, _)| name.clone())
+            .collect::<Vec<_>>();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn int_matches() {
+        let input = r#"
+            let a: int -> int = |x| match x { 0 => 1, 1 => 2, _ => 3 };
+            let b: int -> int = |x| match x { 0 => 1, 1 => 2 };
+            let c: int -> int = |x| match x { 0 => 1, y => y };
+            let d: (int, int) -> int = |t| match t { (0, y) => y, (x, _) => x };
+            let e: (int, int) -> int = |t| match t { (0, y) => y, (x, 1) => x };
+        "#;
+        assert_eq!(non_exhaustive(input), ["b", "e"]);
+    }
+
+    #[test]
+    fn enum_matches() {
+        let input = r#"
+            enum O<T> { None, Some(T) }
+            enum Op { Add, Mul(int), Sub(O<int>) }
+            let a: Op -> int = |o| match o { Op::Add => 1, Op::Mul(_) => 2, Op::Sub(_) => 3 };
+            let b: Op -> int = |o| match o { Op::Add => 1, Op::Mul(_) => 2 };
+            let c: Op -> int = |o| match o { Op::Mul(x) => x, _ => 2 };
+            let d: Op -> int = |o| match o { Op::Add => 1, Op::Mul(0) => 2, Op::Sub(_) => 3 };
+            let e: Op -> int = |o| match o {
+                Op::Sub(O::Some(x)) => x,
+                Op::Sub(O::None) => 0,
+                Op::Mul(x) => x,
+                Op::Add => 1,
+            };
+            let f: Op -> int = |o| match o {
+                Op::Sub(O::Some(x)) => x,
+                Op::Mul(x) => x,
+                Op::Add => 1,
+            };
+            let g: (Op, O<int>) -> int = |t| match t {
+                (Op::Add, O::None) => 1,
+                (_, O::Some(x)) => x,
+                (Op::Mul(x), _) => x,
+                (Op::Sub(_), O::None) => 2,
+            };
+        "#;
+        assert_eq!(non_exhaustive(input), ["b", "d", "f"]);
+    }
+}
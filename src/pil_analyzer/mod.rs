@@ -1,12 +1,24 @@
 #![deny(clippy::print_stdout)]
 
+mod array_lengths;
+mod boundary_checker;
+mod budget_checker;
 mod call_graph;
 mod condenser;
+mod constant_folder;
+pub mod degree_checker;
+mod duplicate_checker;
 pub mod evaluator;
 pub mod expression_processor;
+mod match_checker;
+pub mod namespace_filter;
+pub mod optimizer;
+#[allow(clippy::module_inception)]
 pub mod pil_analyzer;
 mod side_effect_checker;
+pub mod stage_checker;
 mod statement_processor;
+pub mod test_utils;
 mod type_builtins;
 mod type_inference;
 mod type_processor;
@@ -19,11 +31,17 @@ use crate::ast::{
     analyzed::{FunctionValueDefinition, Symbol},
     parsed::{
         asm::{AbsoluteSymbolPath, SymbolPath},
+        types::Type,
         SymbolCategory,
     },
 };
 
-pub use pil_analyzer::{analyze_ast, analyze_file, analyze_string};
+pub use pil_analyzer::{
+    analyze_ast, analyze_file, analyze_files, analyze_files_with_dependencies, analyze_string,
+    analyze_string_with_name, analyze_string_with_options, AnalysisError, AnalysisOptions,
+    AnalysisResult, FeOverflow,
+};
+pub use type_unifier::TypeError;
 
 pub trait AnalysisDriver: Clone + Copy {
     /// Turns a declaration into an absolute name.
@@ -32,22 +50,33 @@ pub trait AnalysisDriver: Clone + Copy {
     }
     /// Turns a nested declaration into an absolute name.
     fn resolve_namespaced_decl(&self, path: &[&String]) -> AbsoluteSymbolPath;
-    fn resolve_value_ref(&self, path: &SymbolPath) -> String {
+    fn resolve_value_ref(&self, path: &SymbolPath) -> Result<String, String> {
         self.resolve_ref(path, SymbolCategory::Value)
     }
-    fn resolve_type_ref(&self, path: &SymbolPath) -> String {
+    fn resolve_type_ref(&self, path: &SymbolPath) -> Result<String, String> {
         self.resolve_ref(path, SymbolCategory::Type)
     }
-    fn resolve_ref(&self, path: &SymbolPath, symbol_category: SymbolCategory) -> String {
+    /// Turns a reference into an absolute name, returning an error if it cannot be
+    /// resolved or resolves to a symbol of the wrong category.
+    fn resolve_ref(
+        &self,
+        path: &SymbolPath,
+        symbol_category: SymbolCategory,
+    ) -> Result<String, String> {
         let (path, cat) = self
             .try_resolve_ref(path)
-            .unwrap_or_else(|| panic!("{symbol_category} symbol not found: {path}"));
+            .ok_or_else(|| format!("{symbol_category} symbol not found: {path}"))?;
         if !cat.compatible_with_request(symbol_category) {
-            panic!("Expected symbol of kind {symbol_category} but got {cat}: {path}")
+            return Err(format!(
+                "Expected symbol of kind {symbol_category} but got {cat}: {path}"
+            ));
         }
-        path
+        Ok(path)
     }
     /// Turns a reference to a name with an optional namespace into an absolute name.
     fn try_resolve_ref(&self, path: &SymbolPath) -> Option<(String, SymbolCategory)>;
     fn definitions(&self) -> &HashMap<String, (Symbol, Option<FunctionValueDefinition>)>;
+    /// Returns `Some(ty)` if `name` is the absolute name of a type alias, where `ty` is the
+    /// aliased type or `None` if the declaration of the alias has not been processed yet.
+    fn type_alias(&self, name: &str) -> Option<Option<&Type>>;
 }
@@ -0,0 +1,246 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::ast::analyzed::{
+    AlgebraicExpression, Analyzed, Expression, FunctionValueDefinition, PolyID,
+    PolynomialReference, Reference, StatementIdentifier, SymbolKind,
+};
+use crate::ast::parsed::asm::{AbsoluteSymbolPath, SymbolPath};
+use crate::ast::parsed::visitor::AllChildren;
+
+/// Removes everything that does not belong to one of the given namespaces,
+/// so that only their columns and identities are exported.
+///
+/// The kept symbols are those defined in one of the namespaces (nested namespaces
+/// are not included), type and trait declarations, and all symbols that are
+/// (directly or indirectly) referenced by kept symbols and kept public declarations.
+/// An identity is kept if it references a column of one of the namespaces.
+/// Identities that are kept but reference a column or public value that is not
+/// kept are reported as errors, as are namespaces that do not contain any symbols.
+/// In case of an error, `pil` is not modified.
+/// The IDs of the remaining columns are re-assigned to be contiguous.
+pub fn retain_namespaces<T: Display>(
+    pil: &mut Analyzed<T>,
+    namespaces: &[&str],
+) -> Result<(), Vec<String>> {
+    let namespaces = namespaces
+        .iter()
+        .map(|namespace| (*namespace, to_absolute_path(namespace)))
+        .collect::<Vec<_>>();
+    let is_selected = |name: &str| {
+        let namespace = to_absolute_path(name).parent();
+        namespaces
+            .iter()
+            .any(|(_, selected)| *selected == namespace)
+    };
+
+    let mut errors = namespaces
+        .iter()
+        .filter(|(_, selected)| {
+            !pil.definitions
+                .keys()
+                .chain(pil.intermediate_columns.keys())
+                .any(|name| to_absolute_path(name).parent() == *selected)
+        })
+        .map(|(namespace, _)| format!("Namespace {namespace} does not contain any symbols."))
+        .collect::<Vec<_>>();
+
+    let poly_id_to_name = pil
+        .definitions
+        .values()
+        .map(|(symbol, _)| symbol)
+        .chain(pil.intermediate_columns.values().map(|(symbol, _)| symbol))
+        .filter(|symbol| matches!(symbol.kind, SymbolKind::Poly(_)))
+        .flat_map(|symbol| {
+            symbol
+                .array_elements()
+                .map(|(_, poly_id)| (poly_id, symbol.absolute_name.as_str()))
+        })
+        .collect::<HashMap<PolyID, &str>>();
+
+    let publics = pil
+        .public_declarations
+        .keys()
+        .filter(|name| is_selected(name))
+        .cloned()
+        .collect::<BTreeSet<_>>();
+
+    let mut to_process = pil
+        .definitions
+        .iter()
+        .filter(|(name, (_, value))| {
+            is_selected(name)
+                || !matches!(
+                    value,
+                    None | Some(FunctionValueDefinition::Array(_))
+                        | Some(FunctionValueDefinition::Expression(_))
+                )
+        })
+        .map(|(name, _)| name.clone())
+        .chain(
+            pil.intermediate_columns
+                .keys()
+                .filter(|name| is_selected(name))
+                .cloned(),
+        )
+        .chain(
+            publics
+                .iter()
+                .map(|name| pil.public_declarations[name].polynomial.name.clone()),
+        )
+        .collect::<Vec<_>>();
+    let mut required = BTreeSet::new();
+    while let Some(name) = to_process.pop() {
+        if !required.insert(name.clone()) {
+            continue;
+        }
+        if let Some((_, Some(value))) = pil.definitions.get(&name) {
+            to_process.extend(value.all_children().filter_map(|e| match e {
+                Expression::Reference(_, Reference::Poly(PolynomialReference { name, .. })) => {
+                    Some(name.clone())
+                }
+                _ => None,
+            }));
+        } else if let Some((_, values)) = pil.intermediate_columns.get(&name) {
+            to_process.extend(values.iter().flat_map(|v| v.all_children()).filter_map(
+                |e| match e {
+                    AlgebraicExpression::Reference(reference) => {
+                        Some(poly_id_to_name[&reference.poly_id].to_string())
+                    }
+                    _ => None,
+                },
+            ));
+        }
+    }
+
+    let mut identities_to_remove = BTreeSet::new();
+    for (index, identity) in pil.identities.iter().enumerate() {
+        let columns = identity
+            .all_children()
+            .filter_map(|e| match e {
+                AlgebraicExpression::Reference(reference) => {
+                    Some(poly_id_to_name[&reference.poly_id])
+                }
+                _ => None,
+            })
+            .collect::<BTreeSet<_>>();
+        if !columns.iter().any(|name| is_selected(name)) {
+            identities_to_remove.insert(index);
+            continue;
+        }
+        errors.extend(
+            columns
+                .iter()
+                .filter(|name| !required.contains(**name))
+                .map(|name| {
+                    format!("Identity {identity} references column {name}, which is not exported.")
+                }),
+        );
+        errors.extend(
+            identity
+                .all_children()
+                .filter_map(|e| match e {
+                    AlgebraicExpression::PublicReference(name) if !publics.contains(name) => {
+                        Some(name)
+                    }
+                    _ => None,
+                })
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .map(|name| {
+                    format!(
+                        "Identity {identity} references public value {name}, which is not exported."
+                    )
+                }),
+        );
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    pil.remove_identities(&identities_to_remove);
+    pil.public_declarations
+        .retain(|name, _| publics.contains(name));
+    pil.source_order.retain(|s| match s {
+        StatementIdentifier::PublicDeclaration(name) => publics.contains(name),
+        _ => true,
+    });
+    let to_remove = pil
+        .definitions
+        .keys()
+        .chain(pil.intermediate_columns.keys())
+        .filter(|name| !required.contains(*name))
+        .cloned()
+        .collect::<BTreeSet<_>>();
+    pil.remove_definitions(&to_remove);
+    pil.auto_added_symbols
+        .retain(|name| !to_remove.contains(name));
+    Ok(())
+}
+
+fn to_absolute_path(name: &str) -> AbsoluteSymbolPath {
+    AbsoluteSymbolPath::default().join(SymbolPath::from_str(name).unwrap())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::number::GoldilocksField;
+    use crate::pil_analyzer::analyze_string;
+
+    use super::retain_namespaces;
+
+    #[test]
+    fn retain_single_namespace() {
+        let input = r#"namespace A(4);
+    col witness x, y;
+    col fixed F = [1, 0]*;
+    col sq = x * x;
+    x' = y + sq;
+namespace B(4);
+    col witness z;
+    col fixed G(i) { i };
+    col both = z + A.sq;
+    z' = both * G;
+    public out = z(3);
+namespace C(4);
+    col witness w;
+    [w] in [A.F];
+"#;
+        let mut analyzed = analyze_string::<GoldilocksField>(input).unwrap();
+        retain_namespaces(&mut analyzed, &["B"]).unwrap();
+        assert_eq!(
+            analyzed
+                .identities
+                .iter()
+                .map(|i| i.to_string())
+                .collect::<Vec<_>>(),
+            ["B.z' = B.both * B.G;"]
+        );
+        let mut names = analyzed
+            .definitions
+            .keys()
+            .chain(analyzed.intermediate_columns.keys())
+            .filter(|name| !name.starts_with("std::"))
+            .collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names, ["A.sq", "A.x", "B.G", "B.both", "B.z"]);
+        assert!(analyzed.public_declarations.contains_key("B.out"));
+        assert_eq!(analyzed.definitions["A.x"].0.id, 0);
+        assert_eq!(analyzed.definitions["B.z"].0.id, 1);
+        assert_eq!(analyzed.validate(), Ok(()));
+
+        let mut analyzed = analyze_string::<GoldilocksField>(input).unwrap();
+        assert_eq!(
+            retain_namespaces(&mut analyzed, &["C", "D"]),
+            Err(vec![
+                "Namespace D does not contain any symbols.".to_string(),
+                "Identity [C.w] in [A.F]; references column A.F, which is not exported."
+                    .to_string()
+            ])
+        );
+        assert_eq!(analyzed.identities.len(), 3);
+        retain_namespaces(&mut analyzed, &["A", "C"]).unwrap();
+        assert_eq!(analyzed.identities.len(), 2);
+    }
+}
@@ -0,0 +1,212 @@
+use std::collections::{BTreeSet, HashMap};
+
+use crate::ast::analyzed::{
+    AlgebraicExpression, Analyzed, Expression, IdentityKind, PolyID, PolynomialReference,
+    PolynomialType, Reference, SymbolKind,
+};
+use crate::ast::parsed::visitor::AllChildren;
+use crate::number::FieldElement;
+use crate::pil_analyzer::duplicate_checker::duplicate_identities;
+
+/// Removes all fixed columns that are not (directly or indirectly) referenced by
+/// an identity, a public declaration or any symbol that is not a fixed column.
+/// Only fixed columns are removed: Witness columns are always kept, even if
+/// they are not referenced, since the prover might still assign them.
+/// The IDs of the remaining columns are re-assigned to be contiguous.
+pub fn remove_unreferenced_fixed_columns<T>(pil: &mut Analyzed<T>) {
+    let poly_id_to_name = pil
+        .definitions
+        .values()
+        .map(|(symbol, _)| symbol)
+        .chain(pil.intermediate_columns.values().map(|(symbol, _)| symbol))
+        .filter(|symbol| matches!(symbol.kind, SymbolKind::Poly(_)))
+        .flat_map(|symbol| {
+            symbol
+                .array_elements()
+                .map(|(_, poly_id)| (poly_id, symbol.absolute_name.as_str()))
+        })
+        .collect::<HashMap<PolyID, &str>>();
+
+    // Collect the roots: all symbols referenced from identities and publics
+    // and all symbols that are not fixed columns, since those are kept anyway.
+    let mut to_process = pil
+        .identities
+        .iter()
+        .flat_map(|identity| identity.all_children())
+        .filter_map(|e| match e {
+            AlgebraicExpression::Reference(reference) => {
+                Some(poly_id_to_name[&reference.poly_id].to_string())
+            }
+            _ => None,
+        })
+        .chain(
+            pil.public_declarations
+                .values()
+                .map(|public| public.polynomial.name.clone()),
+        )
+        .chain(
+            pil.definitions
+                .iter()
+                .filter(|(_, (symbol, _))| {
+                    symbol.kind != SymbolKind::Poly(PolynomialType::Constant)
+                })
+                .map(|(name, _)| name.clone()),
+        )
+        .chain(pil.intermediate_columns.keys().cloned())
+        .collect::<Vec<_>>();
+
+    let mut required = BTreeSet::new();
+    while let Some(name) = to_process.pop() {
+        if !required.insert(name.clone()) {
+            continue;
+        }
+        if let Some((_, Some(value))) = pil.definitions.get(&name) {
+            to_process.extend(value.all_children().filter_map(|e| match e {
+                Expression::Reference(_, Reference::Poly(PolynomialReference { name, .. })) => {
+                    Some(name.clone())
+                }
+                _ => None,
+            }));
+        } else if let Some((_, values)) = pil.intermediate_columns.get(&name) {
+            to_process.extend(values.iter().flat_map(|v| v.all_children()).filter_map(
+                |e| match e {
+                    AlgebraicExpression::Reference(reference) => {
+                        Some(poly_id_to_name[&reference.poly_id].to_string())
+                    }
+                    _ => None,
+                },
+            ));
+        }
+    }
+
+    let to_remove = pil
+        .definitions
+        .iter()
+        .filter(|(name, (symbol, _))| {
+            symbol.kind == SymbolKind::Poly(PolynomialType::Constant) && !required.contains(*name)
+        })
+        .map(|(name, _)| name.clone())
+        .collect::<BTreeSet<_>>();
+    pil.remove_definitions(&to_remove);
+}
+
+/// Removes all polynomial identities that are the zero constant, i.e. that are always
+/// satisfied. Constants should already be folded, as done during analysis.
+pub fn remove_trivial_identities<T: FieldElement>(pil: &mut Analyzed<T>) {
+    let to_remove = pil
+        .identities
+        .iter()
+        .enumerate()
+        .filter(|(_, identity)| {
+            identity.kind == IdentityKind::Polynomial
+                && match identity.expression_for_poly_id() {
+                    AlgebraicExpression::Number(n) => n.is_zero(),
+                    _ => false,
+                }
+        })
+        .map(|(index, _)| index)
+        .collect::<BTreeSet<_>>();
+    pil.remove_identities(&to_remove);
+}
+
+/// Removes all identities that are structurally identical to an earlier identity,
+/// keeping the first occurrence. These are the identities reported as duplicates
+/// during analysis with warnings enabled.
+pub fn remove_duplicate_identities<T: FieldElement>(pil: &mut Analyzed<T>) {
+    let to_remove = duplicate_identities(pil)
+        .into_iter()
+        .map(|(duplicate, _)| duplicate)
+        .collect::<BTreeSet<_>>();
+    pil.remove_identities(&to_remove);
+}
+
+#[cfg(test)]
+mod test {
+    use crate::number::GoldilocksField;
+    use crate::pil_analyzer::analyze_string;
+
+    use super::{
+        remove_duplicate_identities, remove_trivial_identities, remove_unreferenced_fixed_columns,
+    };
+
+    #[test]
+    fn remove_unreferenced_fixed_columns_only() {
+        let input = r#"namespace N(4);
+    col fixed UNUSED = [1]*;
+    col fixed A = [2]*;
+    col fixed B = [3]*;
+    let f: int -> int = |i| i * 2;
+    col fixed D(i) { f(i) };
+    col fixed ONLY_IN_FUNCTION = [5]*;
+    let g: -> expr = || ONLY_IN_FUNCTION;
+    col witness w;
+    col witness unused_witness;
+    col i = w * B;
+    w = A * i;
+    [w] in [D];
+"#;
+        let mut analyzed = analyze_string::<GoldilocksField>(input).unwrap();
+        remove_unreferenced_fixed_columns(&mut analyzed);
+        assert!(!analyzed.definitions.contains_key("N.UNUSED"));
+        for name in [
+            "N.A",
+            "N.B",
+            "N.D",
+            "N.f",
+            "N.ONLY_IN_FUNCTION",
+            "N.unused_witness",
+        ] {
+            assert!(analyzed.definitions.contains_key(name), "{name}");
+        }
+        let ids =
+            ["N.A", "N.B", "N.D", "N.ONLY_IN_FUNCTION"].map(|name| analyzed.definitions[name].0.id);
+        assert_eq!(ids, [0, 1, 2, 3]);
+        assert_eq!(analyzed.validate(), Ok(()));
+    }
+
+    #[test]
+    fn remove_trivial() {
+        let input = r#"namespace N(4);
+    col witness x;
+    x = 1;
+    3 - 3 = 0;
+    x * 0 = 0;
+    x' = x;
+"#;
+        let mut analyzed = analyze_string::<GoldilocksField>(input).unwrap();
+        remove_trivial_identities(&mut analyzed);
+        assert_eq!(
+            analyzed
+                .identities
+                .iter()
+                .map(|i| i.to_string())
+                .collect::<Vec<_>>(),
+            ["N.x = 1;", "N.x * 0 = 0;", "N.x' = N.x;"]
+        );
+        assert_eq!(analyzed.validate(), Ok(()));
+    }
+
+    #[test]
+    fn remove_duplicates() {
+        let input = r#"namespace N(4);
+    col fixed F = [1, 2, 3, 4];
+    col witness x;
+    x = F;
+    [x] in [F];
+    x = F;
+    x' = x * F;
+    [x] in [F];
+"#;
+        let mut analyzed = analyze_string::<GoldilocksField>(input).unwrap();
+        remove_duplicate_identities(&mut analyzed);
+        assert_eq!(
+            analyzed
+                .identities
+                .iter()
+                .map(|i| i.to_string())
+                .collect::<Vec<_>>(),
+            ["N.x = N.F;", "[N.x] in [N.F];", "N.x' = N.x * N.F;"]
+        );
+        assert_eq!(analyzed.validate(), Ok(()));
+    }
+}
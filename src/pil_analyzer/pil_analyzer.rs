@@ -1,66 +1,539 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
+use std::fmt;
 use std::fs;
+use std::io;
 use std::iter::once;
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
 use itertools::Itertools;
+use num_traits::Signed;
+use sha2::{Digest, Sha256};
 use crate::ast::parsed::asm::{
-    parse_absolute_path, AbsoluteSymbolPath, ModuleStatement, SymbolPath,
+    parse_absolute_path, AbsoluteSymbolPath, ModuleStatement, Part, SymbolPath,
 };
-use crate::ast::parsed::types::{ArrayType, Type};
-use crate::ast::parsed::visitor::Children;
+use crate::ast::parsed::types::{ArrayType, Type, TypeScheme};
+use crate::ast::parsed::visitor::{AllChildren, Children, ExpressionVisitable};
 use crate::ast::parsed::{
-    self, FunctionKind, LambdaExpression, PILFile, PilStatement, SelectedExpressions,
-    SymbolCategory,
+    self, FunctionKind, IndexAccess, LambdaExpression, NamespaceBudget, NamespaceDegree, Number,
+    PILFile, PilStatement, SelectedExpressions, SliceAccess, SourceReference, SymbolCategory,
+    UnaryOperation, UnaryOperator,
 };
-use crate::number::{DegreeType, FieldElement, GoldilocksField};
+use crate::number::{BigInt, DegreeType, FieldElement, GoldilocksField, LargeInt};
 
 use crate::ast::analyzed::{
-    type_from_definition, Analyzed, Expression, FunctionValueDefinition, Identity, IdentityKind,
-    PolynomialType, PublicDeclaration, StatementIdentifier, Symbol, SymbolKind, TypedExpression,
+    type_from_definition, Analyzed, DegreeRange, Expression, FunctionValueDefinition, Identity,
+    IdentityKind, PolynomialReference, PolynomialType, PublicDeclaration, Reference,
+    StatementIdentifier, Symbol, SymbolKind, TypedExpression,
 };
 use crate::parser::{parse, parse_module, parse_type};
+use crate::parser_util::{Error, SourceRef};
 
-use crate::pil_analyzer::type_builtins::constr_function_statement_type;
+use crate::pil_analyzer::array_lengths::ArrayLengths;
+use crate::pil_analyzer::budget_checker::NamespaceBudgets;
+use crate::pil_analyzer::type_builtins::{builtin_schemes, constr_function_statement_type};
 use crate::pil_analyzer::type_inference::infer_types;
+use crate::pil_analyzer::type_unifier::TypeError;
 use crate::pil_analyzer::{side_effect_checker, AnalysisDriver};
 
 use crate::pil_analyzer::statement_processor::{Counters, PILItem, StatementProcessor};
-use crate::pil_analyzer::{condenser, evaluator, expression_processor::ExpressionProcessor};
+use crate::pil_analyzer::{
+    boundary_checker, budget_checker, condenser, constant_folder, duplicate_checker, evaluator,
+    expression_processor::ExpressionProcessor, match_checker, stage_checker,
+};
 
-pub fn analyze_file<T: FieldElement>(path: &Path) -> Analyzed<T> {
-    let files = import_all_dependencies(path);
-    analyze(files)
+/// An error that prevented a PIL file from being analyzed.
+#[derive(Debug)]
+pub enum AnalysisError {
+    /// A source file could not be read.
+    Io(PathBuf, io::Error),
+    /// The source could not be parsed or contains statements that are not allowed.
+    /// Contains all errors that were found.
+    Parse(Vec<Error>),
+    /// References could not be resolved to symbols of the requested kind or
+    /// statements are invalid otherwise, for example because a pattern is refutable
+    /// where it must not be or a namespace degree cannot be evaluated.
+    /// Contains all errors that were found. Statements that depend on a
+    /// statement with an error are not checked.
+    UnresolvedReference(Vec<Error>),
+    /// Type inference failed. Contains all errors that were found.
+    Type(Vec<TypeError>),
+    /// Query or constr functions are used in a context that does not allow them.
+    /// Contains one error per definition or identity.
+    SideEffect(Vec<Error>),
+    /// Evaluating an identity or an intermediate column failed, for example
+    /// because of a failed assertion. Contains one error per identity or column.
+    Evaluation(Vec<Error>),
+    /// Files include each other. Contains the chain of includes, starting
+    /// and ending with the same file.
+    IncludeCycle(Vec<PathBuf>),
+    /// A symbol is defined more than once. Contains an error at the conflicting
+    /// definition and one at the original definition.
+    DuplicateDefinition(Box<Error>, Box<Error>),
+    /// Identities exceed the maximum degree given to
+    /// [`check_max_degree`](crate::pil_analyzer::degree_checker::check_max_degree).
+    /// Contains one error per identity.
+    DegreeTooHigh(Vec<Error>),
+    /// Intermediate columns use challenges that are not available in their stage,
+    /// see [`check_stages`](crate::pil_analyzer::stage_checker::check_stages).
+    /// Contains one error per use of a challenge.
+    StageOrder(Vec<Error>),
+    /// Namespaces have more identities or identities of higher degree than
+    /// declared in their budget. Contains one error per violation.
+    BudgetExceeded(Vec<Error>),
+    /// Polynomial identities reduce to a nonzero constant after constant folding
+    /// and thus can never be satisfied. Contains one error per identity.
+    UnsatisfiableIdentity(Vec<Error>),
+    /// Warnings were found and the analysis was asked to deny them,
+    /// see [`AnalysisOptions::deny_warnings`]. Contains all warnings.
+    Warnings(Vec<Error>),
 }
 
-pub fn analyze_ast<T: FieldElement>(pil_file: PILFile) -> Analyzed<T> {
-    analyze(vec![pil_file])
+impl fmt::Display for AnalysisError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AnalysisError::Io(path, e) => write!(f, "Could not read {}: {e}", path.display()),
+            AnalysisError::Parse(errors) => write!(
+                f,
+                "{}",
+                errors
+                    .iter()
+                    .map(|e| format!("Parse error: {e}"))
+                    .join("\n")
+            ),
+            AnalysisError::DuplicateDefinition(duplicate, original) => {
+                write!(f, "{duplicate}\n{original}")
+            }
+            AnalysisError::Type(errors) => {
+                write!(
+                    f,
+                    "Error during type inference:\n{}",
+                    errors.iter().join("\n")
+                )
+            }
+            AnalysisError::IncludeCycle(paths) => write!(
+                f,
+                "Cyclic include: {}",
+                paths.iter().map(|p| p.display()).join(" -> ")
+            ),
+            AnalysisError::UnresolvedReference(errors)
+            | AnalysisError::SideEffect(errors)
+            | AnalysisError::Evaluation(errors)
+            | AnalysisError::DegreeTooHigh(errors)
+            | AnalysisError::StageOrder(errors)
+            | AnalysisError::BudgetExceeded(errors)
+            | AnalysisError::UnsatisfiableIdentity(errors)
+            | AnalysisError::Warnings(errors) => {
+                write!(f, "{}", errors.iter().join("\n"))
+            }
+        }
+    }
 }
 
-pub fn analyze_string<T: FieldElement>(contents: &str) -> Analyzed<T> {
-    let pil_file = crate::parser::parse(Some("input"), contents).unwrap_or_else(|err| {
-        eprintln!("Error parsing .pil file:");
-        err.output_to_stderr();
-        panic!();
-    });
-    analyze(vec![pil_file])
+impl std::error::Error for AnalysisError {}
+
+impl AnalysisError {
+    /// Prints the error to stderr, including source snippets where available.
+    pub fn output_to_stderr(&self) {
+        match self {
+            AnalysisError::Io(..) | AnalysisError::IncludeCycle(_) => eprintln!("{self}"),
+            AnalysisError::DuplicateDefinition(duplicate, original) => {
+                duplicate.output_to_stderr();
+                original.output_to_stderr();
+            }
+            AnalysisError::Type(errors) => errors.iter().for_each(|e| e.output_to_stderr()),
+            AnalysisError::Parse(errors)
+            | AnalysisError::UnresolvedReference(errors)
+            | AnalysisError::SideEffect(errors)
+            | AnalysisError::Evaluation(errors)
+            | AnalysisError::DegreeTooHigh(errors)
+            | AnalysisError::StageOrder(errors)
+            | AnalysisError::BudgetExceeded(errors)
+            | AnalysisError::UnsatisfiableIdentity(errors)
+            | AnalysisError::Warnings(errors) => errors.iter().for_each(|e| e.output_to_stderr()),
+        }
+    }
+
+    /// Returns the number of individual errors. A duplicate definition counts as one error.
+    pub fn error_count(&self) -> usize {
+        match self {
+            AnalysisError::Io(..)
+            | AnalysisError::IncludeCycle(_)
+            | AnalysisError::DuplicateDefinition(..) => 1,
+            AnalysisError::Type(errors) => errors.len(),
+            AnalysisError::Parse(errors)
+            | AnalysisError::UnresolvedReference(errors)
+            | AnalysisError::SideEffect(errors)
+            | AnalysisError::Evaluation(errors)
+            | AnalysisError::DegreeTooHigh(errors)
+            | AnalysisError::StageOrder(errors)
+            | AnalysisError::BudgetExceeded(errors)
+            | AnalysisError::UnsatisfiableIdentity(errors)
+            | AnalysisError::Warnings(errors) => errors.len(),
+        }
+    }
+
+    /// Keeps only the first `max_errors` individual errors (but at least one), so that
+    /// inputs with many errors do not flood the output.
+    /// Returns the number of errors that were dropped.
+    pub fn truncate(&mut self, max_errors: usize) -> usize {
+        let max_errors = max_errors.max(1);
+        let dropped = self.error_count().saturating_sub(max_errors);
+        match self {
+            AnalysisError::Io(..)
+            | AnalysisError::IncludeCycle(_)
+            | AnalysisError::DuplicateDefinition(..) => {}
+            AnalysisError::Type(errors) => errors.truncate(max_errors),
+            AnalysisError::Parse(errors)
+            | AnalysisError::UnresolvedReference(errors)
+            | AnalysisError::SideEffect(errors)
+            | AnalysisError::Evaluation(errors)
+            | AnalysisError::DegreeTooHigh(errors)
+            | AnalysisError::StageOrder(errors)
+            | AnalysisError::BudgetExceeded(errors)
+            | AnalysisError::UnsatisfiableIdentity(errors)
+            | AnalysisError::Warnings(errors) => errors.truncate(max_errors),
+        }
+        dropped
+    }
+}
+
+/// What to do with literals of type `fe` (or `expr`) that are not smaller than the
+/// field modulus.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum FeOverflow {
+    /// Report an error.
+    #[default]
+    Error,
+    /// Reduce the literal modulo the field modulus.
+    Wrap,
+}
+
+impl FromStr for FeOverflow {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "error" => Ok(FeOverflow::Error),
+            "wrap" => Ok(FeOverflow::Wrap),
+            _ => Err(format!(
+                "Invalid field element overflow policy: {s}, expected error or wrap."
+            )),
+        }
+    }
+}
+
+impl fmt::Display for FeOverflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FeOverflow::Error => write!(f, "error"),
+            FeOverflow::Wrap => write!(f, "wrap"),
+        }
+    }
+}
+
+/// Options for [`analyze_files`] and [`analyze_string_with_options`].
+/// The default options are used by [`analyze_file`] and [`analyze_string`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct AnalysisOptions {
+    /// Directories to search, in order, for include statements whose target does not
+    /// exist relative to the including file. If the target exists in several of these
+    /// directories, the first one is used and a warning is reported.
+    pub include_dirs: Vec<PathBuf>,
+    /// What to do with literals of type `fe` or `expr` that are too large for the field.
+    pub fe_overflow: FeOverflow,
+    /// If set, every namespace uses this degree, both for the symbols declared in it
+    /// and for degree-dependent builtins like `std::prover::degree`. Namespaces that
+    /// do not declare a degree get the target degree, and a namespace whose declared
+    /// degree (range) does not contain the target degree is an error.
+    pub target_degree: Option<DegreeType>,
+    /// In addition to the warnings that are always produced, also report every column
+    /// and definition that is never used, every identity that uses the next row of a
+    /// column without a selector and every identity that is identical to an earlier one.
+    pub report_lints: bool,
+    /// Make the analysis fail with [`AnalysisError::Warnings`] if there are any warnings.
+    pub deny_warnings: bool,
+    /// If set, the result of analyzing files, including the warnings, is cached in
+    /// this directory. The cache key is the SHA-256 hash of the input paths, the
+    /// options, the paths and contents of all files that are read (including
+    /// transitive includes), the field and the version of this crate. If any of these
+    /// change or the cache cannot be read, the program is analyzed again and the cache
+    /// is updated. Warnings about includes are not cached but always reported.
+    /// Failing to write the cache is not an error. Strings are never cached.
+    pub cache_dir: Option<PathBuf>,
+}
+
+impl AnalysisOptions {
+    fn include_dirs(&self) -> Vec<&Path> {
+        self.include_dirs.iter().map(PathBuf::as_path).collect()
+    }
+}
+
+/// The analyzed program together with the warnings that were found, or the
+/// error that made the analysis fail.
+pub type AnalysisResult<T> = Result<(Analyzed<T>, Vec<Error>), AnalysisError>;
+
+pub fn analyze_file<T: FieldElement>(path: &Path) -> Result<Analyzed<T>, AnalysisError> {
+    let (analyzed, warnings) = analyze_files(&[path], &Default::default())?;
+    log_warnings(warnings);
+    Ok(analyzed)
+}
+
+/// Analyzes several files (and their includes) as a single program and returns
+/// it together with the warnings that were found.
+/// Every file starts in the global namespace and can reference symbols
+/// defined in any of the other files. Files that are reached more than
+/// once (directly or via includes) are only processed once.
+pub fn analyze_files<T: FieldElement>(
+    paths: &[&Path],
+    options: &AnalysisOptions,
+) -> AnalysisResult<T> {
+    analyze_files_with_dependencies(paths, options).1
+}
+
+/// Same as [`analyze_files`], but also returns the files that were read, i.e.
+/// `paths` and all their transitive includes, as sorted absolute paths.
+/// This is useful for dependency tracking in build systems, which is why the files
+/// are also returned if the analysis fails. In that case, only the files read up to
+/// the failure are returned.
+pub fn analyze_files_with_dependencies<T: FieldElement>(
+    paths: &[&Path],
+    options: &AnalysisOptions,
+) -> (Vec<PathBuf>, AnalysisResult<T>) {
+    let include_dirs = options.include_dirs();
+    let mut processed = Default::default();
+    let mut include_warnings = vec![];
+    let result = paths
+        .iter()
+        .map(|path| {
+            import_all_dependencies(
+                path,
+                &include_dirs,
+                &mut processed,
+                &mut vec![],
+                &mut include_warnings,
+            )
+        })
+        .flatten_ok()
+        .collect::<Result<_, _>>()
+        .and_then(|files| match &options.cache_dir {
+            Some(cache_dir) => analyze_cached(
+                files,
+                include_warnings,
+                paths,
+                &processed,
+                cache_dir,
+                options,
+            ),
+            None => analyze(files, include_warnings, options),
+        });
+    (processed.into_keys().collect(), result)
+}
+
+/// Returns the cached result of analyzing `files` from `cache_dir` if it exists and
+/// analyzes and caches them otherwise, see [`AnalysisOptions::cache_dir`].
+fn analyze_cached<T: FieldElement>(
+    files: Vec<PILFile>,
+    include_warnings: Vec<Error>,
+    paths: &[&Path],
+    processed: &BTreeMap<PathBuf, String>,
+    cache_dir: &Path,
+    options: &AnalysisOptions,
+) -> AnalysisResult<T> {
+    let cache_file = cache_dir.join(format!(
+        "{}.cbor",
+        cache_key::<T>(paths, processed, options)
+    ));
+    let cached = fs::read(&cache_file)
+        .ok()
+        .and_then(|bytes| serde_cbor::from_slice::<(Analyzed<T>, Vec<Error>)>(&bytes).ok());
+    let (analyzed, warnings) = match cached {
+        Some(cached) => cached,
+        None => {
+            let (analyzed, warnings) = analyze_collecting_warnings(files, options)?;
+            if let Ok(bytes) = serde_cbor::to_vec(&(&analyzed, &warnings)) {
+                let _ = fs::create_dir_all(cache_dir).and_then(|_| fs::write(&cache_file, bytes));
+            }
+            (analyzed, warnings)
+        }
+    };
+    // The include warnings depend on the contents of the include directories, which
+    // are not part of the cache key, so they are not cached.
+    let warnings = include_warnings.into_iter().chain(warnings).collect();
+    deny_warnings(analyzed, warnings, options)
+}
+
+/// Computes the key for [`analyze_cached`] as the hex-encoded SHA-256 hash of
+/// the crate version, the field, the input paths, the options that influence the
+/// result and the paths and contents of all files in `processed`.
+fn cache_key<T: FieldElement>(
+    paths: &[&Path],
+    processed: &BTreeMap<PathBuf, String>,
+    options: &AnalysisOptions,
+) -> String {
+    let AnalysisOptions {
+        include_dirs,
+        fe_overflow,
+        target_degree,
+        report_lints,
+        deny_warnings: _,
+        cache_dir: _,
+    } = options;
+    let mut hasher = Sha256::new();
+    // Every part is prefixed by its length so that the concatenation is unambiguous.
+    let mut update = |bytes: &[u8]| {
+        hasher.update((bytes.len() as u64).to_le_bytes());
+        hasher.update(bytes);
+    };
+    update(env!("CARGO_PKG_VERSION").as_bytes());
+    update(T::modulus().to_arbitrary_integer().to_string().as_bytes());
+    update(format!("{paths:?}").as_bytes());
+    update(format!("{include_dirs:?} {fe_overflow} {target_degree:?} {report_lints}").as_bytes());
+    for (path, contents) in processed {
+        update(path.as_os_str().as_encoded_bytes());
+        update(contents.as_bytes());
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+/// Analyzes a program that was constructed directly instead of being parsed,
+/// for example using the helpers in [`crate::ast::parsed::build`].
+///
+/// The [`PILFile`] is a flat list of statements that are processed in order,
+/// exactly as if they appeared in a single source file:
+/// Analysis starts in the global namespace, a [`PilStatement::Namespace`]
+/// statement changes the namespace (and degree) for all following statements,
+/// and references are resolved relative to the current namespace.
+/// A [`PilStatement::NamespaceBlock`] only changes the namespace for the statements
+/// inside the block.
+/// Source references can be [`SourceRef::unknown`](crate::parser_util::SourceRef::unknown).
+///
+/// Include statements cannot be resolved without a file system context and
+/// are reported as an error; use [`analyze_file`] for those.
+pub fn analyze_ast<T: FieldElement>(pil_file: PILFile) -> Result<Analyzed<T>, AnalysisError> {
+    if let Some(PilStatement::Include(source_ref, _)) = pil_file
+        .0
+        .iter()
+        .find(|s| matches!(s, PilStatement::Include(_, _)))
+    {
+        return Err(AnalysisError::Parse(vec![source_ref.with_error(
+            "Include statements are not supported when analyzing an AST.".to_string(),
+        )]));
+    }
+    let (analyzed, warnings) = analyze(vec![pil_file], vec![], &Default::default())?;
+    log_warnings(warnings);
+    Ok(analyzed)
 }
 
-fn analyze<T: FieldElement>(files: Vec<PILFile>) -> Analyzed<T> {
-    let mut analyzer = PILAnalyzer::new();
-    analyzer.process(files);
-    analyzer.side_effect_check();
-    analyzer.type_check();
-    analyzer.condense()
+pub fn analyze_string<T: FieldElement>(contents: &str) -> Result<Analyzed<T>, AnalysisError> {
+    analyze_string_with_name("input", contents)
+}
+
+/// Analyzes PIL source that does not come from a file (e.g. stdin),
+/// using `source_name` as the file name in diagnostics.
+pub fn analyze_string_with_name<T: FieldElement>(
+    source_name: &str,
+    contents: &str,
+) -> Result<Analyzed<T>, AnalysisError> {
+    let (analyzed, warnings) =
+        analyze_string_with_options(source_name, contents, &Default::default())?;
+    log_warnings(warnings);
+    Ok(analyzed)
+}
+
+/// Same as [`analyze_files`], but for PIL source that does not come from a file
+/// (e.g. stdin), using `source_name` as the file name in diagnostics.
+/// Include statements are resolved relative to the current directory and
+/// [`AnalysisOptions::cache_dir`] is ignored.
+pub fn analyze_string_with_options<T: FieldElement>(
+    source_name: &str,
+    contents: &str,
+    options: &AnalysisOptions,
+) -> AnalysisResult<T> {
+    let pil_file = crate::parser::parse_collecting_errors(Some(source_name), contents)
+        .map_err(AnalysisError::Parse)?;
+    analyze(vec![pil_file], vec![], options)
+}
+
+/// Emits the warnings of the analysis functions that do not return them
+/// through the `log` crate, so that the application decides where they end up.
+fn log_warnings(warnings: Vec<Error>) {
+    for warning in warnings {
+        log::warn!("{}: Warning: {}", warning.source_ref(), warning.message());
+    }
+}
+
+/// Analyzes the files, adding the warnings found during the analysis to the
+/// `warnings` that were found while reading them.
+fn analyze<T: FieldElement>(
+    files: Vec<PILFile>,
+    mut warnings: Vec<Error>,
+    options: &AnalysisOptions,
+) -> AnalysisResult<T> {
+    let (analyzed, analysis_warnings) = analyze_collecting_warnings(files, options)?;
+    warnings.extend(analysis_warnings);
+    deny_warnings(analyzed, warnings, options)
+}
+
+/// Turns the warnings into an [`AnalysisError::Warnings`] if
+/// [`AnalysisOptions::deny_warnings`] is set.
+fn deny_warnings<T>(
+    analyzed: Analyzed<T>,
+    warnings: Vec<Error>,
+    options: &AnalysisOptions,
+) -> AnalysisResult<T> {
+    if options.deny_warnings && !warnings.is_empty() {
+        Err(AnalysisError::Warnings(warnings))
+    } else {
+        Ok((analyzed, warnings))
+    }
+}
+
+/// Analyzes the files and returns the warnings that were found.
+/// Unused columns and definitions, identities that use the next row without a selector
+/// and duplicate identities are only reported if [`AnalysisOptions::report_lints`] is set.
+fn analyze_collecting_warnings<T: FieldElement>(
+    files: Vec<PILFile>,
+    options: &AnalysisOptions,
+) -> AnalysisResult<T> {
+    let report_lints = options.report_lints;
+    let mut analyzer = PILAnalyzer {
+        fe_overflow: options.fe_overflow,
+        target_degree: options.target_degree,
+        ..PILAnalyzer::new()
+    };
+    analyzer.process(files)?;
+    analyzer.side_effect_check()?;
+    analyzer.type_check()?;
+    analyzer.check_field_literals::<T>()?;
+    analyzer.check_array_bounds()?;
+    analyzer.check_array_lengths()?;
+    let mut warnings = analyzer.non_exhaustive_match_warnings();
+    if report_lints {
+        warnings.extend(analyzer.unused_symbol_warnings());
+    }
+    let budgets = std::mem::take(&mut analyzer.budgets);
+    let mut analyzed = analyzer.condense()?;
+    constant_folder::fold_constants(&mut analyzed);
+    warnings.extend(constant_folder::check_constant_identities(&analyzed)?);
+    if report_lints {
+        warnings.extend(boundary_checker::unrestricted_next_warnings(&analyzed));
+        warnings.extend(duplicate_checker::duplicate_identity_warnings(&analyzed));
+    }
+    stage_checker::check_stages(&analyzed)?;
+    budget_checker::check_budgets(&analyzed, &budgets)?;
+    Ok((analyzed, warnings))
 }
 
 #[derive(Default)]
 struct PILAnalyzer {
     /// Known symbols by name and category, determined in the first step.
     known_symbols: HashMap<String, SymbolCategory>,
+    /// Where the known symbols are defined, to report duplicate definitions.
+    symbol_sources: HashMap<String, SourceRef>,
     current_namespace: AbsoluteSymbolPath,
-    polynomial_degree: Option<DegreeType>,
+    polynomial_degree: Option<DegreeRange>,
     /// Map of definitions, gradually being built up here.
     definitions: HashMap<String, (Symbol, Option<FunctionValueDefinition>)>,
     public_declarations: HashMap<String, PublicDeclaration>,
@@ -71,37 +544,66 @@ struct PILAnalyzer {
     symbol_counters: Option<Counters>,
     /// Symbols from the core that were added automatically but will not be printed.
     auto_added_symbols: HashSet<String>,
+    /// Budgets declared by namespaces and the namespaces of the identities.
+    budgets: NamespaceBudgets,
+    /// What to do with field element literals that are too large for the field.
+    fe_overflow: FeOverflow,
+    /// The degree all namespaces are compiled at, if forced by the caller.
+    target_degree: Option<DegreeType>,
+    /// Type aliases by absolute name with the aliased type, which is `None`
+    /// until the declaration of the alias has been processed.
+    type_aliases: HashMap<String, Option<Type>>,
 }
 
-/// Reads and parses the given path and all its imports.
-fn import_all_dependencies(path: &Path) -> Vec<PILFile> {
-    let mut processed = Default::default();
-    import_all_dependencies_internal(path, &mut processed)
-}
-
-fn import_all_dependencies_internal(path: &Path, processed: &mut HashSet<PathBuf>) -> Vec<PILFile> {
+/// Reads and parses the given path and all its imports,
+/// skipping files that are already in `processed`, which maps every file
+/// that was read to its contents.
+/// Include paths are resolved relative to the directory of the including file,
+/// falling back to `include_dirs`.
+/// `include_stack` is the chain of files that (transitively) include `path`
+/// and is used to detect cycles. Warnings about ambiguous includes are added to `warnings`.
+fn import_all_dependencies(
+    path: &Path,
+    include_dirs: &[&Path],
+    processed: &mut BTreeMap<PathBuf, String>,
+    include_stack: &mut Vec<PathBuf>,
+    warnings: &mut Vec<Error>,
+) -> Result<Vec<PILFile>, AnalysisError> {
     let path = path
         .canonicalize()
-        .unwrap_or_else(|e| panic!("File {path:?} not found: {e}"));
-    if !processed.insert(path.clone()) {
-        return vec![];
+        .map_err(|e| AnalysisError::Io(path.to_path_buf(), e))?;
+    if let Some(pos) = include_stack.iter().position(|p| p == &path) {
+        return Err(AnalysisError::IncludeCycle(
+            include_stack[pos..]
+                .iter()
+                .cloned()
+                .chain(once(path))
+                .collect(),
+        ));
+    }
+    if processed.contains_key(&path) {
+        return Ok(vec![]);
     }
 
-    let contents = fs::read_to_string(path.clone()).unwrap();
+    let contents = fs::read_to_string(&path).map_err(|e| AnalysisError::Io(path.clone(), e))?;
+    let contents = processed.entry(path.clone()).or_insert(contents);
 
-    let ast = crate::parser::parse(Some(path.to_str().unwrap()), &contents).unwrap_or_else(|err| {
-        eprintln!("Error parsing .pil file:");
-        err.output_to_stderr();
-        panic!();
-    });
+    let ast = crate::parser::parse_collecting_errors(Some(path.to_str().unwrap()), contents)
+        .map_err(AnalysisError::Parse)?;
 
     // Filter out non-includes and compute the relative paths of includes.
     let (non_includes, includes) = ast.0.into_iter().fold(
         (vec![], vec![]),
         |(mut non_includes, mut included_paths), s| {
             match s {
-                PilStatement::Include(_, include) => {
-                    included_paths.push(path.parent().unwrap().join(include));
+                PilStatement::Include(source_ref, include) => {
+                    included_paths.push(resolve_include(
+                        path.parent().unwrap(),
+                        &source_ref,
+                        &include,
+                        include_dirs,
+                        warnings,
+                    ));
                 }
                 _ => non_includes.push(s),
             }
@@ -109,11 +611,325 @@ fn import_all_dependencies_internal(path: &Path, processed: &mut HashSet<PathBuf
         },
     );
     // Process includes and add the file itself.
-    includes
+    include_stack.push(path);
+    let files = includes
         .into_iter()
-        .flat_map(|path| import_all_dependencies_internal(&path, processed))
-        .chain(once(PILFile(non_includes)))
-        .collect::<Vec<_>>()
+        .map(|path| {
+            import_all_dependencies(&path, include_dirs, processed, include_stack, warnings)
+        })
+        .flatten_ok()
+        .chain(once(Ok(PILFile(non_includes))))
+        .collect();
+    include_stack.pop();
+    files
+}
+
+/// Returns the path of the file included via `include` from a file in `dir`.
+/// If it does not exist relative to `dir`, the first of `include_dirs`
+/// that contains it is used and a warning is added to `warnings` if others contain it as well.
+/// If none does, the path relative to `dir` is returned.
+fn resolve_include(
+    dir: &Path,
+    source_ref: &SourceRef,
+    include: &str,
+    include_dirs: &[&Path],
+    warnings: &mut Vec<Error>,
+) -> PathBuf {
+    let relative = dir.join(include);
+    if relative.exists() {
+        return relative;
+    }
+    let mut candidates = include_dirs
+        .iter()
+        .map(|include_dir| include_dir.join(include))
+        .filter(|candidate| candidate.exists());
+    let Some(first) = candidates.next() else {
+        return relative;
+    };
+    let others = candidates.collect::<Vec<_>>();
+    if !others.is_empty() {
+        warnings.push(source_ref.with_error(format!(
+            "Include \"{include}\" found in several include directories, using {} (also found: {}).",
+            first.display(),
+            others.iter().map(|p| p.display()).join(", ")
+        )));
+    }
+    first
+}
+
+/// Returns the name of the symbol referenced by `e`, if it is a symbol reference.
+fn referenced_name(e: &Expression) -> Option<&String> {
+    match e {
+        Expression::Reference(_, Reference::Poly(PolynomialReference { name, .. })) => Some(name),
+        _ => None,
+    }
+}
+
+/// Replaces every namespace block by a namespace statement with the absolute path
+/// of the block, followed by the statements inside the block and a namespace
+/// statement that switches back to the enclosing namespace.
+/// A block without a degree inherits the degree of the enclosing namespace.
+///
+/// Blocks with parameters are templates (collected from all files in `templates`)
+/// and are removed. Every instantiation of a template is replaced by a namespace
+/// block with the name of the instance, which starts with one `let` statement per
+/// parameter, followed by a copy of the statements of the template. The statements
+/// are resolved relative to the instance namespace, the arguments are resolved in
+/// the namespace of the instantiation.
+fn flatten_namespace_blocks(
+    PILFile(statements): PILFile,
+    templates: &BTreeMap<AbsoluteSymbolPath, NamespaceTemplate>,
+) -> Result<PILFile, Error> {
+    fn flatten(
+        statements: Vec<PilStatement>,
+        namespace: &SymbolPath,
+        degree: &Option<NamespaceDegree>,
+        templates: &BTreeMap<AbsoluteSymbolPath, NamespaceTemplate>,
+        instantiating: &mut Vec<AbsoluteSymbolPath>,
+        result: &mut Vec<PilStatement>,
+    ) -> Result<(), Error> {
+        let (mut namespace, mut degree) = (namespace.clone(), degree.clone());
+        for statement in statements {
+            match statement {
+                PilStatement::Namespace(_, ref name, ref new_degree, _) => {
+                    namespace = name.clone();
+                    degree = new_degree.clone();
+                    result.push(statement);
+                }
+                PilStatement::NamespaceBlock(_, _, params, _, _, _) if !params.is_empty() => {}
+                PilStatement::NamespaceBlock(source, name, _, block_degree, budget, statements) => {
+                    let block_namespace = namespace.clone().join(name);
+                    let block_degree = block_degree.or_else(|| degree.clone());
+                    result.push(PilStatement::Namespace(
+                        source.clone(),
+                        block_namespace.clone(),
+                        block_degree.clone(),
+                        budget,
+                    ));
+                    flatten(
+                        statements,
+                        &block_namespace,
+                        &block_degree,
+                        templates,
+                        instantiating,
+                        result,
+                    )?;
+                    result.push(PilStatement::Namespace(
+                        source,
+                        namespace.clone(),
+                        degree.clone(),
+                        Default::default(),
+                    ));
+                }
+                PilStatement::NamespaceInstance(source, name, template_name, args) => {
+                    let (template_path, template) = AbsoluteSymbolPath::default()
+                        .join(namespace.clone())
+                        .iter_to_root()
+                        .filter_map(|prefix| prefix.try_join(template_name.clone()))
+                        .find_map(|path| templates.get_key_value(&path))
+                        .ok_or_else(|| {
+                            source.with_error(format!(
+                                "Namespace template not found: {template_name}"
+                            ))
+                        })?;
+                    if args.len() != template.params.len() {
+                        return Err(source.with_error(format!(
+                            "Namespace template {template_name} expects {} arguments, got {}.",
+                            template.params.len(),
+                            args.len()
+                        )));
+                    }
+                    if instantiating.contains(template_path) {
+                        return Err(source.with_error(format!(
+                            "Namespace template {template_name} is instantiated recursively."
+                        )));
+                    }
+                    let instance_namespace =
+                        namespace.clone().join(SymbolPath::from_identifier(name));
+                    let shadowed = template
+                        .params
+                        .iter()
+                        .chain(
+                            template
+                                .statements
+                                .iter()
+                                .flat_map(|s| s.symbol_definition_names().map(|(name, _)| name)),
+                        )
+                        .collect::<HashSet<_>>();
+                    let to_parent = |e: &mut parsed::Expression| {
+                        visit_references(e, &mut |e| resolve_in_parent_namespace(e, &shadowed))
+                    };
+                    // The degree of a namespace statement is evaluated in the previous
+                    // namespace, so the degree of the instance cannot refer to the
+                    // parameters and they are replaced by the arguments instead.
+                    // All other degrees are evaluated inside the instance namespace.
+                    let arguments = template.params.iter().zip(&args).collect::<HashMap<_, _>>();
+                    let instance_degree = match &template.degree {
+                        Some(template_degree) => Some(map_degree(template_degree, |e| {
+                            visit_references(e, &mut |e| substitute_parameters(e, &arguments))
+                        })),
+                        None => degree.clone(),
+                    };
+                    result.push(PilStatement::Namespace(
+                        source.clone(),
+                        instance_namespace.clone(),
+                        instance_degree.clone(),
+                        template.budget.clone(),
+                    ));
+                    for (param, mut arg) in template.params.iter().zip(args) {
+                        to_parent(&mut arg);
+                        result.push(PilStatement::LetStatement(
+                            source.clone(),
+                            param.clone(),
+                            Some(TypeScheme {
+                                vars: Default::default(),
+                                ty: Type::Int,
+                            }),
+                            Some(arg),
+                        ));
+                    }
+                    instantiating.push(template_path.clone());
+                    flatten(
+                        template.statements.clone(),
+                        &instance_namespace,
+                        &instance_degree.map(|d| map_degree(&d, to_parent)),
+                        templates,
+                        instantiating,
+                        result,
+                    )?;
+                    instantiating.pop();
+                    result.push(PilStatement::Namespace(
+                        source,
+                        namespace.clone(),
+                        degree.as_ref().map(|d| map_degree(d, to_parent)),
+                        Default::default(),
+                    ));
+                }
+                _ => result.push(statement),
+            }
+        }
+        Ok(())
+    }
+    let mut result = vec![];
+    flatten(
+        statements,
+        &Default::default(),
+        &None,
+        templates,
+        &mut vec![],
+        &mut result,
+    )?;
+    Ok(PILFile(result))
+}
+
+/// A namespace block with parameters, see [`PilStatement::NamespaceBlock`].
+struct NamespaceTemplate {
+    params: Vec<String>,
+    degree: Option<NamespaceDegree>,
+    budget: NamespaceBudget,
+    statements: Vec<PilStatement>,
+}
+
+/// Returns all namespace templates in the files by their absolute name.
+fn collect_namespace_templates(
+    files: &[PILFile],
+) -> BTreeMap<AbsoluteSymbolPath, NamespaceTemplate> {
+    fn collect(
+        statements: &[PilStatement],
+        mut namespace: AbsoluteSymbolPath,
+        templates: &mut BTreeMap<AbsoluteSymbolPath, NamespaceTemplate>,
+    ) {
+        for statement in statements {
+            match statement {
+                PilStatement::Namespace(_, name, _, _) => {
+                    namespace = AbsoluteSymbolPath::default().join(name.clone());
+                }
+                PilStatement::NamespaceBlock(_, name, params, degree, budget, statements) => {
+                    let path = namespace.clone().join(name.clone());
+                    if params.is_empty() {
+                        collect(statements, path, templates);
+                    } else {
+                        templates.insert(
+                            path,
+                            NamespaceTemplate {
+                                params: params.clone(),
+                                degree: degree.clone(),
+                                budget: budget.clone(),
+                                statements: statements.clone(),
+                            },
+                        );
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    let mut templates = BTreeMap::new();
+    for PILFile(statements) in files {
+        collect(statements, Default::default(), &mut templates);
+    }
+    templates
+}
+
+/// Calls `f` on all references in `e`. Does not descend into expressions that
+/// can declare local variables.
+fn visit_references(e: &mut parsed::Expression, f: &mut impl FnMut(&mut parsed::Expression)) {
+    match e {
+        parsed::Expression::Reference(..) => f(e),
+        parsed::Expression::LambdaExpression(..)
+        | parsed::Expression::BlockExpression(..)
+        | parsed::Expression::MatchExpression(..)
+        | parsed::Expression::ArrayComprehension(..) => {}
+        _ => e.children_mut().for_each(|e| visit_references(e, f)),
+    }
+}
+
+/// Prefixes the reference `e` with `super` if it starts with one of the `shadowed`
+/// names, so that it is resolved relative to the parent namespace.
+fn resolve_in_parent_namespace(e: &mut parsed::Expression, shadowed: &HashSet<&String>) {
+    if let parsed::Expression::Reference(_, reference) = e {
+        let is_shadowed = matches!(
+            reference.path.parts().next(),
+            Some(Part::Named(first)) if shadowed.contains(first)
+        );
+        if is_shadowed {
+            reference.path = SymbolPath::from_parts(
+                once(Part::Super).chain(reference.path.clone().into_parts()),
+            );
+        }
+    }
+}
+
+/// Replaces the reference `e` by the argument if it refers to a parameter.
+fn substitute_parameters(
+    e: &mut parsed::Expression,
+    arguments: &HashMap<&String, &parsed::Expression>,
+) {
+    if let parsed::Expression::Reference(_, reference) = e {
+        if let Some(arg) = reference
+            .path
+            .try_to_identifier()
+            .and_then(|name| arguments.get(name))
+        {
+            *e = (*arg).clone();
+        }
+    }
+}
+
+/// Returns a copy of `degree` with `f` applied to both bounds.
+fn map_degree(degree: &NamespaceDegree, f: impl Fn(&mut parsed::Expression)) -> NamespaceDegree {
+    let NamespaceDegree { mut min, mut max } = degree.clone();
+    f(&mut min);
+    f(&mut max);
+    NamespaceDegree { min, max }
+}
+
+/// Returns the absolute name of the item that contains the symbol `name`,
+/// i.e. its namespace or, for constructors and accessors, its type.
+fn parent_name(name: &str) -> String {
+    parse_absolute_path(&format!("::{}", name.replace('.', "::")))
+        .parent()
+        .to_dotted_string()
 }
 
 impl PILAnalyzer {
@@ -124,30 +940,98 @@ impl PILAnalyzer {
         }
     }
 
-    pub fn process(&mut self, mut files: Vec<PILFile>) {
+    pub fn process(&mut self, files: Vec<PILFile>) -> Result<(), AnalysisError> {
+        let templates = collect_namespace_templates(&files);
+        let mut files = files
+            .into_iter()
+            .map(|file| flatten_namespace_blocks(file, &templates))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AnalysisError::UnresolvedReference(vec![e]))?;
         for PILFile(file) in &files {
             self.current_namespace = Default::default();
             for statement in file {
-                self.collect_names(statement);
+                self.collect_names(statement)?;
             }
         }
 
         if let Some(core) = self.core_types_if_not_present() {
             self.current_namespace = Default::default();
             for statement in &core.0 {
-                for (name, _) in self.collect_names(statement) {
+                for (name, _) in self.collect_names(statement)? {
                     self.auto_added_symbols.insert(name);
                 }
             }
             files = once(core).chain(files).collect();
         }
 
-        for PILFile(file) in files {
+        // If a statement contains an unresolved reference, we continue with the next
+        // statement in order to report as many errors as possible. Statements that
+        // reference a symbol whose definition failed are skipped, since they would only
+        // produce follow-up errors. If a namespace statement cannot be processed, we stop,
+        // because all further statements depend on it.
+        let mut errors = vec![];
+        let mut failed_names = HashSet::new();
+        'files: for PILFile(file) in files {
             self.current_namespace = Default::default();
             for statement in file {
-                self.handle_statement(statement);
+                let is_namespace = matches!(statement, PilStatement::Namespace(..));
+                let names = self.statement_names(&statement);
+                if self.references_any(&statement, &failed_names) {
+                    if is_namespace {
+                        break 'files;
+                    }
+                    failed_names.extend(names.into_iter().map(|(name, _)| name));
+                    continue;
+                }
+                if let Err(e) = self.handle_statement(statement) {
+                    errors.push(e);
+                    if is_namespace {
+                        break 'files;
+                    }
+                    failed_names.extend(names.into_iter().map(|(name, _)| name));
+                }
             }
         }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(AnalysisError::UnresolvedReference(errors))
+        }
+    }
+
+    /// Returns true if the statement references one of the given (absolute) names,
+    /// including references inside array lengths of column declarations and
+    /// named types in type aliases.
+    fn references_any(&self, statement: &PilStatement, names: &HashSet<String>) -> bool {
+        if names.is_empty() {
+            return false;
+        }
+        let array_sizes = match statement {
+            PilStatement::PolynomialConstantDeclaration(_, polynomials)
+            | PilStatement::PolynomialCommitDeclaration(_, _, polynomials, _) => polynomials
+                .iter()
+                .filter_map(|p| p.array_size.as_ref())
+                .collect(),
+            _ => vec![],
+        };
+        let resolves_to_any = |path| {
+            self.driver()
+                .try_resolve_ref(path)
+                .is_some_and(|(name, _)| names.contains(&name))
+        };
+        if let PilStatement::TypeAlias(_, _, ty) = statement {
+            if ty.contained_named_types().any(resolves_to_any) {
+                return true;
+            }
+        }
+        statement
+            .children()
+            .chain(array_sizes)
+            .flat_map(|e| e.all_children())
+            .any(|e| match e {
+                parsed::Expression::Reference(_, reference) => resolves_to_any(&reference.path),
+                _ => false,
+            })
     }
 
     /// Adds core types if they are not present in the input.
@@ -179,8 +1063,9 @@ impl PILAnalyzer {
     }
 
     /// Check that query and constr functions are used in the correct contexts.
-    pub fn side_effect_check(&self) {
-        for (name, (symbol, value)) in &self.definitions {
+    pub fn side_effect_check(&self) -> Result<(), AnalysisError> {
+        let mut errors = vec![];
+        for (name, (symbol, value)) in self.definitions.iter().sorted_by_key(|(name, _)| *name) {
             let Some(value) = value else { continue };
             let context = match symbol.kind {
                 // Witness column value is query function
@@ -201,23 +1086,36 @@ impl PILAnalyzer {
                 // Default is constr.
                 _ => FunctionKind::Constr,
             };
-            value
+            if let Err(err) = value
                 .children()
                 .try_for_each(|e| side_effect_checker::check(&self.definitions, context, e))
-                .unwrap_or_else(|err| panic!("Error checking side-effects of {name}: {err}"))
+            {
+                errors.push(
+                    symbol
+                        .source
+                        .with_error(format!("Error checking side-effects of {name}: {err}")),
+                );
+            }
         }
 
         // for all identities, check that they call pure or constr functions
         for id in &self.identities {
-            id.children()
-                .try_for_each(|e| {
-                    side_effect_checker::check(&self.definitions, FunctionKind::Constr, e)
-                })
-                .unwrap_or_else(|err| panic!("Error checking side-effects of identity {id}: {err}"))
+            if let Err(err) = id.children().try_for_each(|e| {
+                side_effect_checker::check(&self.definitions, FunctionKind::Constr, e)
+            }) {
+                errors.push(id.source.with_error(format!(
+                    "Error checking side-effects of identity {id}: {err}"
+                )));
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(AnalysisError::SideEffect(errors))
         }
     }
 
-    pub fn type_check(&mut self) {
+    pub fn type_check(&mut self) -> Result<(), AnalysisError> {
         let query_type: Type = parse_type("int -> std::prover::Query").unwrap().into();
         let mut expressions = vec![];
         // Collect all definitions with their types and expressions.
@@ -299,15 +1197,8 @@ impl PILAnalyzer {
                 }
             }
         }
-        let inferred_types = infer_types(definitions, &mut expressions)
-            .map_err(|mut errors| {
-                eprintln!("\nError during type inference:");
-                for e in &errors {
-                    e.output_to_stderr();
-                }
-                errors.pop().unwrap()
-            })
-            .unwrap();
+        let inferred_types =
+            infer_types(definitions, &mut expressions).map_err(AnalysisError::Type)?;
         // Store the inferred types.
         for (name, ty) in inferred_types {
             let Some(FunctionValueDefinition::Expression(TypedExpression {
@@ -319,60 +1210,321 @@ impl PILAnalyzer {
             };
             *ts = Some(ty.into());
         }
+        Ok(())
+    }
+
+    /// Checks that all literals of type `fe` or `expr` are smaller than the
+    /// field modulus or, with [`FeOverflow::Wrap`], reduces them modulo the field modulus.
+    /// Literals of generic type are handled during evaluation.
+    pub fn check_field_literals<T: FieldElement>(&mut self) -> Result<(), AnalysisError> {
+        let modulus = T::modulus().to_arbitrary_integer();
+        if self.fe_overflow == FeOverflow::Wrap {
+            let mut reduce = |e: &mut Expression| {
+                if let Expression::Number(
+                    _,
+                    Number {
+                        value,
+                        type_: Some(Type::Fe | Type::Expr),
+                    },
+                ) = e
+                {
+                    *value = &*value % &modulus;
+                }
+            };
+            let definitions = self
+                .definitions
+                .values_mut()
+                .filter_map(|(_, value)| value.as_mut());
+            for definition in definitions {
+                definition.post_visit_expressions_mut(&mut reduce);
+            }
+            for identity in &mut self.identities {
+                identity.post_visit_expressions_mut(&mut reduce);
+            }
+            return Ok(());
+        }
+        let field = T::known_field()
+            .map(|f| f.to_string())
+            .unwrap_or_else(|| "unknown".to_string());
+        let errors = self
+            .definitions
+            .values()
+            .filter_map(|(_, value)| value.as_ref())
+            .flat_map(|value| value.all_children())
+            .chain(self.identities.iter().flat_map(|id| id.all_children()))
+            .filter_map(|e| match e {
+                Expression::Number(
+                    source_ref,
+                    Number {
+                        value,
+                        type_: Some(Type::Fe | Type::Expr),
+                    },
+                ) if *value >= modulus => Some(TypeError::other(
+                    source_ref,
+                    format!(
+                        "Number literal {value} is too large for a field element of the {field} field (modulus {modulus})."
+                    ),
+                )),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(AnalysisError::Type(errors))
+        }
+    }
+
+    /// Checks that index accesses and slices of arrays with a known length
+    /// (see [`ArrayLengths::length`]) stay within the array, as far as the
+    /// indices and bounds are number literals.
+    /// Negative indices are not supported and are rejected for any array.
+    pub fn check_array_bounds(&self) -> Result<(), AnalysisError> {
+        let lengths = ArrayLengths::new(&self.definitions);
+        let errors = self
+            .definitions
+            .values()
+            .filter_map(|(_, value)| value.as_ref())
+            .flat_map(|value| value.all_children())
+            .chain(self.identities.iter().flat_map(|id| id.all_children()))
+            .filter_map(|e| {
+                let length = |array: &Expression| lengths.length(array);
+                let literal = |e: &Expression| match e {
+                    Expression::Number(_, Number { value, .. }) => Some(BigInt::from(value.clone())),
+                    Expression::UnaryOperation(
+                        _,
+                        UnaryOperation {
+                            op: UnaryOperator::Minus,
+                            expr,
+                        },
+                    ) => match expr.as_ref() {
+                        Expression::Number(_, Number { value, .. }) => {
+                            Some(-BigInt::from(value.clone()))
+                        }
+                        _ => None,
+                    },
+                    _ => None,
+                };
+                let message = match e {
+                    Expression::IndexAccess(_, IndexAccess { array, index }) => {
+                        let index = literal(index)?;
+                        if index.is_negative() {
+                            format!("Index {index} in {e} is negative, but array indices have to be non-negative.")
+                        } else {
+                            let length = length(array)?;
+                            if index < length.into() {
+                                return None;
+                            }
+                            format!("Index {index} in {e} is out of range for an array of length {length}.")
+                        }
+                    }
+                    Expression::SliceAccess(_, SliceAccess { array, start, end }) => {
+                        let (start, end) = (literal(start), literal(end));
+                        if let Some(bound) = start.iter().chain(&end).find(|b| b.is_negative()) {
+                            format!("Bound {bound} of slice {e} is negative, but array indices have to be non-negative.")
+                        } else {
+                            let length = length(array)?;
+                            let out_of_range = start.iter().chain(&end).any(|b| *b > length.into())
+                                || matches!((start, end), (Some(start), Some(end)) if start > end);
+                            if !out_of_range {
+                                return None;
+                            }
+                            format!("Slice {e} is out of range for an array of length {length}.")
+                        }
+                    }
+                    _ => return None,
+                };
+                Some(TypeError::other(e.source_reference(), message))
+            })
+            .collect::<Vec<_>>();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(AnalysisError::Type(errors))
+        }
+    }
+
+    /// Checks the array lengths that are known without evaluating any code,
+    /// see [`ArrayLengths::check`].
+    pub fn check_array_lengths(&self) -> Result<(), AnalysisError> {
+        let errors = ArrayLengths::new(&self.definitions).check(&self.identities);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(AnalysisError::Type(errors))
+        }
     }
 
-    pub fn condense<T: FieldElement>(self) -> Analyzed<T> {
+    /// Returns a warning for every match expression whose arms do not cover all values.
+    pub fn non_exhaustive_match_warnings(&self) -> Vec<Error> {
+        let expressions = self
+            .definitions
+            .values()
+            .filter_map(|(_, value)| value.as_ref())
+            .flat_map(|value| value.children())
+            .chain(self.identities.iter().flat_map(|id| id.children()));
+        match_checker::non_exhaustive_matches(&self.definitions, expressions)
+            .into_iter()
+            .map(|source_ref| {
+                source_ref.with_error(
+                    "Match expression is not exhaustive, consider adding a catch-all arm (\"_ => ...\")."
+                        .to_string(),
+                )
+            })
+            .collect()
+    }
+
+    /// Returns a warning for every column and definition that is not referenced
+    /// anywhere except in its own definition. Type and trait declarations, symbols
+    /// that belong to a type (constructors, struct fields and trait implementations),
+    /// builtins and automatically added symbols are not reported.
+    pub fn unused_symbol_warnings(&self) -> Vec<Error> {
+        let type_names = self
+            .definitions
+            .iter()
+            .filter(|(_, (_, value))| {
+                matches!(
+                    value,
+                    Some(
+                        FunctionValueDefinition::TypeDeclaration(_)
+                            | FunctionValueDefinition::TraitDeclaration(_)
+                    )
+                )
+            })
+            .map(|(name, _)| name.as_str())
+            .collect::<HashSet<_>>();
+        let referenced = self
+            .definitions
+            .iter()
+            .flat_map(|(name, (_, value))| {
+                value
+                    .iter()
+                    .flat_map(|value| value.all_children())
+                    .filter_map(referenced_name)
+                    .filter(move |referenced| *referenced != name)
+            })
+            .chain(
+                self.identities
+                    .iter()
+                    .flat_map(|id| id.all_children())
+                    .filter_map(referenced_name),
+            )
+            .chain(
+                self.public_declarations
+                    .values()
+                    .map(|public| &public.polynomial.name),
+            )
+            .map(|name| name.as_str())
+            .collect::<HashSet<_>>();
+        self.source_order
+            .iter()
+            .filter_map(|statement| match statement {
+                StatementIdentifier::Definition(name) => Some(name),
+                _ => None,
+            })
+            .filter(|name| {
+                !referenced.contains(name.as_str())
+                    && !self.auto_added_symbols.contains(*name)
+                    && !builtin_schemes().contains_key(*name)
+                    && !type_names.contains(parent_name(name).as_str())
+            })
+            .filter_map(|name| {
+                let (symbol, value) = &self.definitions[name];
+                let what = match value {
+                    Some(
+                        FunctionValueDefinition::TypeDeclaration(_)
+                        | FunctionValueDefinition::TypeConstructor(_, _)
+                        | FunctionValueDefinition::TraitDeclaration(_)
+                        | FunctionValueDefinition::TraitFunction(_, _),
+                    ) => return None,
+                    _ if matches!(symbol.kind, SymbolKind::Poly(_)) => "Column",
+                    _ => "Definition",
+                };
+                Some(
+                    symbol
+                        .source
+                        .with_error(format!("{what} {name} is never used.")),
+                )
+            })
+            .collect()
+    }
+
+    pub fn condense<T: FieldElement>(self) -> Result<Analyzed<T>, AnalysisError> {
         condenser::condense(
             self.definitions,
             self.public_declarations,
             &self.identities,
             self.source_order,
             self.auto_added_symbols,
+            self.fe_overflow,
         )
+        .map_err(AnalysisError::Evaluation)
     }
 
     /// A step to collect all defined names in the statement.
-    fn collect_names(&mut self, statement: &PilStatement) -> Vec<(String, SymbolCategory)> {
+    /// Returns an error if a name is already defined.
+    fn collect_names(
+        &mut self,
+        statement: &PilStatement,
+    ) -> Result<Vec<(String, SymbolCategory)>, AnalysisError> {
         match statement {
-            PilStatement::Namespace(_, name, _) => {
+            PilStatement::Namespace(_, name, _, _) => {
                 self.current_namespace = AbsoluteSymbolPath::default().join(name.clone());
-                vec![]
+                Ok(vec![])
             }
             PilStatement::Include(_, _) => unreachable!(),
             _ => {
-                let names = statement
-                    .symbol_definition_names_and_contained()
-                    .map(|(name, sub_name, symbol_category)| {
-                        (
-                            match sub_name {
-                                None => self.driver().resolve_decl(name),
-                                Some(sub_name) => self
-                                    .driver()
-                                    .resolve_namespaced_decl(&[name, sub_name])
-                                    .to_dotted_string(),
-                            },
-                            symbol_category,
-                        )
-                    })
-                    .collect::<Vec<_>>();
+                let names = self.statement_names(statement);
+                let source = statement.source_reference();
                 for (name, symbol_kind) in &names {
-                    if self
-                        .known_symbols
-                        .insert(name.clone(), *symbol_kind)
-                        .is_some()
-                    {
-                        panic!("Duplicate symbol definition: {name}");
+                    if let Some(original) = self.symbol_sources.get(name) {
+                        return Err(AnalysisError::DuplicateDefinition(
+                            Box::new(
+                                source.with_error(format!("Duplicate symbol definition: {name}")),
+                            ),
+                            Box::new(
+                                original.with_error(format!("{name} was first defined here.")),
+                            ),
+                        ));
+                    }
+                    self.known_symbols.insert(name.clone(), *symbol_kind);
+                    self.symbol_sources.insert(name.clone(), source.clone());
+                    if let PilStatement::TypeAlias(..) = statement {
+                        self.type_aliases.insert(name.clone(), None);
                     }
                 }
-                names
+                Ok(names)
             }
         }
     }
 
-    fn handle_statement(&mut self, statement: PilStatement) {
+    /// Returns the absolute names of the symbols defined by the statement.
+    fn statement_names(&self, statement: &PilStatement) -> Vec<(String, SymbolCategory)> {
+        statement
+            .symbol_definition_names_and_contained()
+            .map(|(name, sub_name, symbol_category)| {
+                (
+                    match sub_name {
+                        None => self.driver().resolve_decl(name),
+                        Some(sub_name) => self
+                            .driver()
+                            .resolve_namespaced_decl(&[name, sub_name])
+                            .to_dotted_string(),
+                    },
+                    symbol_category,
+                )
+            })
+            .collect()
+    }
+
+    fn handle_statement(&mut self, statement: PilStatement) -> Result<(), Error> {
         match statement {
             PilStatement::Include(_, _) => unreachable!(),
-            PilStatement::Namespace(_, name, degree) => self.handle_namespace(name, degree),
+            PilStatement::Namespace(source, name, degree, budget) => {
+                self.handle_namespace(&source, name, degree)?;
+                self.budgets
+                    .add_budget(self.current_namespace.to_dotted_string(), source, budget);
+            }
             _ => {
                 // We need a mutable reference to the counter, but it is short-lived.
                 let mut counters = self.symbol_counters.take().unwrap();
@@ -380,6 +1532,7 @@ impl PILAnalyzer {
                     StatementProcessor::new(self.driver(), &mut counters, self.polynomial_degree)
                         .handle_statement(statement);
                 self.symbol_counters = Some(counters);
+                let items = items?;
                 for item in items {
                     match item {
                         PILItem::Definition(symbol, value) => {
@@ -398,7 +1551,14 @@ impl PILAnalyzer {
                             self.source_order
                                 .push(StatementIdentifier::PublicDeclaration(name));
                         }
+                        PILItem::TypeAlias(name, ty) => {
+                            self.type_aliases.insert(name, Some(ty));
+                        }
                         PILItem::Identity(identity) => {
+                            self.budgets.add_identity(
+                                self.current_namespace.to_dotted_string(),
+                                &identity.source,
+                            );
                             let index = self.identities.len();
                             self.source_order.push(StatementIdentifier::Identity(index));
                             self.identities.push(identity)
@@ -407,29 +1567,62 @@ impl PILAnalyzer {
                 }
             }
         }
+        Ok(())
     }
 
-    fn handle_namespace(&mut self, name: SymbolPath, degree: Option<parsed::Expression>) {
+    fn handle_namespace(
+        &mut self,
+        source: &SourceRef,
+        name: SymbolPath,
+        degree: Option<NamespaceDegree>,
+    ) -> Result<(), Error> {
         self.polynomial_degree = degree
-            .map(|degree| {
-                ExpressionProcessor::new(self.driver(), &Default::default())
-                    .process_expression(degree)
+            .map(|NamespaceDegree { min, max }| {
+                Ok::<_, Error>(DegreeRange {
+                    min: self.evaluate_degree(min)?,
+                    max: self.evaluate_degree(max)?,
+                })
             })
-            // TODO we should maybe implement a separate evaluator that is able to run before type checking
-            // and is field-independent (only uses integers)?
-            .map(|degree| {
-                u64::try_from(
-                    evaluator::evaluate_expression::<GoldilocksField>(&degree, &self.definitions)
-                        .unwrap()
-                        .try_to_integer()
-                        .unwrap(),
-                )
-                .unwrap()
-            });
+            .transpose()?;
+        if let Some(degree) = self.polynomial_degree {
+            if degree.min > degree.max {
+                return Err(source.with_error(format!(
+                    "The minimum degree {} is larger than the maximum degree {}.",
+                    degree.min, degree.max
+                )));
+            }
+        }
+        if let Some(target) = self.target_degree {
+            if let Some(degree) = self.polynomial_degree {
+                if target < degree.min || target > degree.max {
+                    return Err(source.with_error(format!(
+                        "The degree {degree} of namespace {name} conflicts with the target degree {target}."
+                    )));
+                }
+            }
+            self.polynomial_degree = Some(target.into());
+        }
         self.current_namespace = AbsoluteSymbolPath::default().join(name);
+        Ok(())
+    }
+
+    fn evaluate_degree(&self, degree: parsed::Expression) -> Result<DegreeType, Error> {
+        let degree = ExpressionProcessor::new(self.driver(), &Default::default())
+            .process_expression(degree)?;
+        let error = |message| degree.source_reference().with_error(message);
+        // TODO we should maybe implement a separate evaluator that is able to run before type checking
+        // and is field-independent (only uses integers)?
+        let value = evaluator::evaluate_expression::<GoldilocksField>(&degree, &self.definitions)
+            .and_then(|value| value.try_to_integer())
+            .map_err(|e| error(format!("Error evaluating namespace degree {degree}: {e}")))?;
+        u64::try_from(&value).map_err(|_| {
+            error(format!(
+                "Namespace degree {degree} evaluates to {value}, which is not a valid degree."
+            ))
+        })
     }
 
-    fn driver(&self) -> Driver {
+    fn driver(&self) -> Driver<'_> {
         Driver(self)
     }
 }
@@ -455,7 +1648,7 @@ impl<'a> AnalysisDriver for Driver<'a> {
             .iter_to_root()
             .chain(once(parse_absolute_path("::std::prelude")))
             .find_map(|prefix| {
-                let path = prefix.join(path.clone()).to_dotted_string();
+                let path = prefix.try_join(path.clone())?.to_dotted_string();
                 self.0.known_symbols.get(&path).map(|cat| (path, *cat))
             })
     }
@@ -463,4 +1656,1572 @@ impl<'a> AnalysisDriver for Driver<'a> {
     fn definitions(&self) -> &HashMap<String, (Symbol, Option<FunctionValueDefinition>)> {
         &self.0.definitions
     }
+
+    fn type_alias(&self, name: &str) -> Option<Option<&Type>> {
+        self.0.type_aliases.get(name).map(Option::as_ref)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use itertools::Itertools;
+
+    use crate::number::GoldilocksField;
+
+    use super::{
+        analyze_file, analyze_files, analyze_files_with_dependencies, analyze_string,
+        analyze_string_with_name, analyze_string_with_options, AnalysisError, AnalysisOptions,
+        DegreeRange, FeOverflow, Type, TypeError,
+    };
+
+    #[test]
+    fn source_name_in_source_refs() {
+        let input = "namespace N(4);\ncol witness x;\n";
+        let analyzed = analyze_string_with_name::<GoldilocksField>("<stdin>", input).unwrap();
+        let source = &analyzed.definitions["N.x"].0.source;
+        assert_eq!(source.file_name.as_deref(), Some("<stdin>"));
+    }
+
+    #[test]
+    fn parse_error() {
+        let err = analyze_string::<GoldilocksField>("namespace N(4);\ncol witness;").unwrap_err();
+        assert!(matches!(err, AnalysisError::Parse(_)), "{err}");
+
+        let input = "namespace N(4);\ncol witness;\ncol witness x;\nlet y = );";
+        let AnalysisError::Parse(errors) = analyze_string::<GoldilocksField>(input).unwrap_err()
+        else {
+            panic!()
+        };
+        assert_eq!(errors.len(), 2);
+    }
+
+    #[test]
+    fn unresolved_reference() {
+        let input = "namespace N(4);\ncol witness x;\nx = y;";
+        let err = analyze_string::<GoldilocksField>(input).unwrap_err();
+        let AnalysisError::UnresolvedReference(errors) = err else {
+            panic!("Expected unresolved reference, got: {err}");
+        };
+        let [e] = &errors[..] else { panic!() };
+        assert_eq!(e.message(), "Value symbol not found: y");
+        assert_eq!(e.to_string(), "input:3:5: Value symbol not found: y");
+    }
+
+    #[test]
+    fn multiple_errors() {
+        let input = r#"namespace N(4);
+    col witness x;
+    let a = y;
+    let b = a + 1;
+    x = z;
+    let c: Foo = 7;
+    x' = b;
+"#;
+        let mut err = analyze_string::<GoldilocksField>(input).unwrap_err();
+        assert_eq!(err.error_count(), 3);
+        assert_eq!(
+            err.to_string(),
+            "input:3:13: Value symbol not found: y\n\
+             input:5:9: Value symbol not found: z\n\
+             input:6:5: Type symbol not found: Foo"
+        );
+        assert_eq!(err.truncate(2), 1);
+        assert_eq!(err.error_count(), 2);
+        assert_eq!(err.truncate(0), 1);
+        assert_eq!(err.to_string(), "input:3:13: Value symbol not found: y");
+
+        let input = r#"let a: int = "text";
+let b: int -> int = |i| i + "text";
+let c: bool = 1 == 1;
+"#;
+        let err = analyze_string::<GoldilocksField>(input).unwrap_err();
+        assert!(matches!(err, AnalysisError::Type(_)), "{err}");
+        assert_eq!(err.error_count(), 2, "{err}");
+    }
+
+    #[test]
+    fn unresolved_type() {
+        let input = "let x: Foo = 7;";
+        let err = analyze_string::<GoldilocksField>(input).unwrap_err();
+        let AnalysisError::UnresolvedReference(errors) = err else {
+            panic!("Expected unresolved reference, got: {err}");
+        };
+        assert_eq!(errors[0].message(), "Type symbol not found: Foo");
+    }
+
+    #[test]
+    fn type_error() {
+        let input = "let x: int = \"text\";";
+        let err = analyze_string::<GoldilocksField>(input).unwrap_err();
+        assert!(matches!(err, AnalysisError::Type(ref errors) if !errors.is_empty()));
+    }
+
+    #[test]
+    fn missing_file() {
+        let err =
+            analyze_file::<GoldilocksField>("this/file/does/not/exist.pil".as_ref()).unwrap_err();
+        assert!(matches!(err, AnalysisError::Io(_, _)));
+    }
+
+    #[test]
+    fn fold_columns_into_constraint() {
+        let input = r#"
+            namespace std::array;
+                let<T1, T2> fold: T1[], T2, (T2, T1 -> T2) -> T2 = [];
+                let<T1, T2> map: T1[], (T1 -> T2) -> T2[] = [];
+            namespace N(4);
+                col witness x, y, z;
+                std::array::fold(std::array::map([x, y, z], |c| c * c), 0, |acc, e| acc + e) = 1;
+        "#;
+        let analyzed = analyze_string::<GoldilocksField>(input).unwrap();
+        assert_eq!(analyzed.identities.len(), 1);
+        assert_eq!(
+            analyzed.identities[0].to_string(),
+            "0 + N.x * N.x + N.y * N.y + N.z * N.z = 1;"
+        );
+    }
+
+    #[test]
+    fn includes_relative_to_including_file() {
+        let dir = std::env::temp_dir().join(format!("pil_relative_include_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("lib/inner")).unwrap();
+        let main = dir.join("main.pil");
+        std::fs::write(
+            &main,
+            "include \"lib/b.pil\";\nnamespace A(4);\nB.y = C.z;\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("lib/b.pil"),
+            "include \"inner/c.pil\";\nnamespace B(4);\ncol witness y;\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("lib/inner/c.pil"),
+            "namespace C(4);\ncol witness z;\n",
+        )
+        .unwrap();
+
+        let analyzed = analyze_file::<GoldilocksField>(&main).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(analyzed.identities.iter().join("\n"), "B.y = C.z;");
+    }
+
+    #[test]
+    fn include_dirs() {
+        let dir = std::env::temp_dir().join(format!("pil_include_dirs_{}", std::process::id()));
+        for sub in ["src", "inc1", "inc2"] {
+            std::fs::create_dir_all(dir.join(sub)).unwrap();
+        }
+        let main = dir.join("src/main.pil");
+        std::fs::write(
+            &main,
+            "include \"lib.pil\";\ninclude \"local.pil\";\nnamespace N(4);\ncol witness x;\nx = Lib.ONE;\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("src/local.pil"),
+            "namespace Local(4);\ncol witness l;\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("inc2/local.pil"),
+            "namespace Wrong(4);\ncol witness w;\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("inc1/lib.pil"),
+            "namespace Lib(4);\ncol fixed ONE = [1]*;\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("inc2/lib.pil"),
+            "namespace Lib(4);\ncol fixed TWO = [2]*;\n",
+        )
+        .unwrap();
+
+        let options = AnalysisOptions {
+            include_dirs: vec![dir.join("inc1"), dir.join("inc2")],
+            ..Default::default()
+        };
+        let analyzed = analyze_files::<GoldilocksField>(&[main.as_path()], &options);
+        let denied = analyze_files::<GoldilocksField>(
+            &[main.as_path()],
+            &AnalysisOptions {
+                deny_warnings: true,
+                ..options
+            },
+        );
+        let without_dirs = analyze_file::<GoldilocksField>(&main);
+        let main = main.canonicalize().unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let expected_warning = format!(
+            "{}:1:1: Include \"lib.pil\" found in several include directories, using {} (also found: {}).",
+            main.display(),
+            dir.join("inc1/lib.pil").display(),
+            dir.join("inc2/lib.pil").display()
+        );
+        let (analyzed, warnings) = analyzed.unwrap();
+        assert_eq!(
+            warnings.iter().map(|w| w.to_string()).collect::<Vec<_>>(),
+            std::slice::from_ref(&expected_warning)
+        );
+        let Err(AnalysisError::Warnings(denied)) = denied else {
+            panic!()
+        };
+        assert_eq!(
+            denied.iter().map(|w| w.to_string()).collect::<Vec<_>>(),
+            [expected_warning]
+        );
+        assert!(analyzed.definitions.contains_key("Lib.ONE"));
+        assert!(!analyzed.definitions.contains_key("Lib.TWO"));
+        assert!(analyzed.definitions.contains_key("Local.l"));
+        assert!(!analyzed.definitions.contains_key("Wrong.w"));
+        assert!(matches!(without_dirs, Err(AnalysisError::Io(..))));
+    }
+
+    #[test]
+    fn cached_analysis() {
+        let dir = std::env::temp_dir().join(format!("pil_cache_{}", std::process::id()));
+        let cache_dir = dir.join("cache");
+        std::fs::create_dir_all(&dir).unwrap();
+        let main = dir.join("main.pil");
+        let lib = dir.join("lib.pil");
+        std::fs::write(&main, "include \"lib.pil\";\nnamespace N(4);\nx = 1;\n").unwrap();
+        std::fs::write(&lib, "namespace N(4);\ncol witness x, u;\n").unwrap();
+        let cache_entries = || std::fs::read_dir(&cache_dir).unwrap().count();
+        let options = AnalysisOptions {
+            cache_dir: Some(cache_dir.clone()),
+            report_lints: true,
+            ..Default::default()
+        };
+        let analyze = || {
+            analyze_files::<GoldilocksField>(&[main.as_path()], &options)
+                .unwrap()
+                .0
+                .to_string()
+        };
+
+        let first = analyze();
+        assert_eq!(cache_entries(), 1);
+        assert_eq!(analyze(), first);
+        assert_eq!(cache_entries(), 1);
+
+        // The cache entry is named after the SHA-256 hash of the inputs.
+        let entry = std::fs::read_dir(&cache_dir)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+            .path();
+        let name = entry.file_name().unwrap().to_str().unwrap();
+        let key = name.strip_suffix(".cbor").unwrap();
+        assert!(key.len() == 64 && key.chars().all(|c| c.is_ascii_hexdigit()));
+
+        // Warnings are cached along with the result and denied on a cache hit.
+        let (_, warnings) = analyze_files::<GoldilocksField>(&[main.as_path()], &options).unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].message(), "Column N.u is never used.");
+        let denied = analyze_files::<GoldilocksField>(
+            &[main.as_path()],
+            &AnalysisOptions {
+                deny_warnings: true,
+                ..options.clone()
+            },
+        );
+        assert!(matches!(denied, Err(AnalysisError::Warnings(w)) if w.len() == 1));
+        assert_eq!(cache_entries(), 1);
+
+        // The cache entry is used instead of analyzing again.
+        let planted = analyze_string::<GoldilocksField>("namespace P(4);\ncol witness p;").unwrap();
+        let no_warnings: Vec<crate::parser_util::Error> = vec![];
+        std::fs::write(
+            &entry,
+            serde_cbor::to_vec(&(&planted, no_warnings)).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(analyze(), planted.to_string());
+        let (_, warnings) = analyze_files::<GoldilocksField>(&[main.as_path()], &options).unwrap();
+        assert!(warnings.is_empty());
+
+        // Changing an included file invalidates the cache.
+        std::fs::write(&lib, "namespace N(4);\ncol witness x, y;\n").unwrap();
+        let second = analyze();
+        assert_eq!(cache_entries(), 2);
+        assert!(second.contains("col witness y;"));
+
+        // Corrupt cache entries are ignored.
+        for entry in std::fs::read_dir(&cache_dir).unwrap() {
+            std::fs::write(entry.unwrap().path(), "garbage").unwrap();
+        }
+        assert_eq!(analyze(), second);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn cached_analysis_include_warnings() {
+        let dir = std::env::temp_dir().join(format!("pil_cache_includes_{}", std::process::id()));
+        for sub in ["src", "inc1", "inc2"] {
+            std::fs::create_dir_all(dir.join(sub)).unwrap();
+        }
+        let main = dir.join("src/main.pil");
+        std::fs::write(&main, "include \"lib.pil\";\nnamespace N(4);\nx = 1;\n").unwrap();
+        std::fs::write(
+            dir.join("inc1/lib.pil"),
+            "namespace N(4);\ncol witness x;\n",
+        )
+        .unwrap();
+        let options = AnalysisOptions {
+            include_dirs: vec![dir.join("inc1"), dir.join("inc2")],
+            cache_dir: Some(dir.join("cache")),
+            ..Default::default()
+        };
+        let analyze = || analyze_files::<GoldilocksField>(&[main.as_path()], &options);
+
+        let (_, warnings) = analyze().unwrap();
+        assert!(warnings.is_empty());
+
+        // The include warning is reported although the cache entry is used.
+        std::fs::write(
+            dir.join("inc2/lib.pil"),
+            "namespace N(4);\ncol witness x;\n",
+        )
+        .unwrap();
+        let (_, warnings) = analyze().unwrap();
+        assert_eq!(std::fs::read_dir(dir.join("cache")).unwrap().count(), 1);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0]
+            .message()
+            .starts_with("Include \"lib.pil\" found in several include directories"));
+
+        // It is not cached either.
+        std::fs::remove_file(dir.join("inc2/lib.pil")).unwrap();
+        let (_, warnings) = analyze().unwrap();
+        assert!(warnings.is_empty());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn combined_options() {
+        let dir = std::env::temp_dir().join(format!("pil_options_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let main = dir.join("main.pil");
+        std::fs::write(
+            &main,
+            "namespace N;\ncol witness x;\nx = 18446744069414584322;\n",
+        )
+        .unwrap();
+        let mut options = AnalysisOptions {
+            fe_overflow: FeOverflow::Wrap,
+            target_degree: Some(8),
+            report_lints: true,
+            deny_warnings: true,
+            cache_dir: Some(dir.join("cache")),
+            ..Default::default()
+        };
+        let analyze = |options: &AnalysisOptions| {
+            analyze_files::<GoldilocksField>(&[main.as_path()], options)
+        };
+        let (analyzed, warnings) = analyze(&options).unwrap();
+        assert!(warnings.is_empty());
+        assert_eq!(analyzed.identities.iter().join("\n"), "N.x = 1;");
+        assert_eq!(analyzed.degree(), 8);
+
+        // Options that change the result are part of the cache key.
+        options.target_degree = Some(16);
+        assert_eq!(analyze(&options).unwrap().0.degree(), 16);
+
+        std::fs::write(&main, "namespace N;\ncol witness x, y;\nx = 1;\n").unwrap();
+        let result = analyze(&options);
+        std::fs::remove_dir_all(&dir).unwrap();
+        let Err(AnalysisError::Warnings(warnings)) = result else {
+            panic!()
+        };
+        assert_eq!(warnings[0].message(), "Column N.y is never used.");
+    }
+
+    #[test]
+    fn dependencies() {
+        let dir = std::env::temp_dir().join(format!("pil_dependencies_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("lib")).unwrap();
+        let main = dir.join("main.pil");
+        std::fs::write(&main, "include \"lib/a.pil\";\nnamespace M(4);\n").unwrap();
+        std::fs::write(
+            dir.join("lib/a.pil"),
+            "include \"b.pil\";\nnamespace A(4);\ncol witness x;\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("lib/b.pil"),
+            "namespace B(4);\nlet y: int = \"y\";\n",
+        )
+        .unwrap();
+        let canonical_dir = dir.canonicalize().unwrap();
+        let expected = ["lib/a.pil", "lib/b.pil", "main.pil"].map(|p| canonical_dir.join(p));
+
+        let (dependencies, result) =
+            analyze_files_with_dependencies::<GoldilocksField>(&[&main], &Default::default());
+        assert!(matches!(result, Err(AnalysisError::Type(_))));
+        assert_eq!(dependencies, expected);
+
+        // Files read before a failure to read an include are still reported.
+        std::fs::remove_file(dir.join("lib/b.pil")).unwrap();
+        let (dependencies, result) =
+            analyze_files_with_dependencies::<GoldilocksField>(&[&main], &Default::default());
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(matches!(result, Err(AnalysisError::Io(..))));
+        assert_eq!(dependencies, [expected[0].clone(), expected[2].clone()]);
+    }
+
+    #[test]
+    fn include_cycle() {
+        let dir = std::env::temp_dir().join(format!("pil_include_cycle_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let a = dir.join("a.pil");
+        std::fs::write(&a, "include \"b.pil\";\nnamespace A(4);\n").unwrap();
+        std::fs::write(dir.join("b.pil"), "include \"a.pil\";\nnamespace B(4);\n").unwrap();
+
+        let err = analyze_file::<GoldilocksField>(&a).unwrap_err();
+        std::fs::remove_dir_all(&dir).unwrap();
+        let AnalysisError::IncludeCycle(paths) = &err else {
+            panic!("{err}")
+        };
+        let names = paths
+            .iter()
+            .map(|p| p.file_name().unwrap().to_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["a.pil", "b.pil", "a.pil"]);
+        assert!(err.to_string().starts_with("Cyclic include: "));
+    }
+
+    #[test]
+    fn multiple_files() {
+        let dir = std::env::temp_dir().join(format!("pil_multiple_files_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let common = dir.join("common.pil");
+        let a = dir.join("a.pil");
+        let b = dir.join("b.pil");
+        std::fs::write(&common, "namespace Common(4);\ncol fixed ONE = [1]*;\n").unwrap();
+        std::fs::write(
+            &a,
+            "include \"common.pil\";\nnamespace A(4);\ncol witness x;\nx = B.y * Common.ONE;\n",
+        )
+        .unwrap();
+        std::fs::write(
+            &b,
+            "include \"common.pil\";\nnamespace B(4);\ncol witness y;\ny = A.x;\n",
+        )
+        .unwrap();
+
+        let (analyzed, _) =
+            analyze_files::<GoldilocksField>(&[a.as_path(), b.as_path()], &Default::default())
+                .unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(analyzed.commitment_count(), 2);
+        assert_eq!(analyzed.constant_count(), 1);
+        let ids = ["A.x", "B.y"].map(|name| analyzed.definitions[name].0.id);
+        assert_eq!(ids, [0, 1]);
+        assert_eq!(
+            analyzed.identities.iter().join("\n"),
+            "A.x = B.y * Common.ONE;\nB.y = A.x;"
+        );
+    }
+
+    #[test]
+    fn multiple_files_name_collision() {
+        let dir = std::env::temp_dir().join(format!("pil_name_collision_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let a = dir.join("a.pil");
+        let b = dir.join("b.pil");
+        std::fs::write(&a, "namespace N(4);\ncol witness x;\n").unwrap();
+        std::fs::write(&b, "namespace N(4);\ncol witness x;\n").unwrap();
+        let result =
+            analyze_files::<GoldilocksField>(&[a.as_path(), b.as_path()], &Default::default());
+        std::fs::remove_dir_all(&dir).unwrap();
+        let Err(AnalysisError::DuplicateDefinition(duplicate, original)) = result else {
+            panic!()
+        };
+        assert_eq!(duplicate.message(), "Duplicate symbol definition: N.x");
+        assert_eq!(duplicate.source_ref().file_name.as_deref(), b.to_str());
+        assert_eq!(original.message(), "N.x was first defined here.");
+        assert_eq!(original.source_ref().file_name.as_deref(), a.to_str());
+    }
+
+    #[test]
+    fn duplicate_definitions() {
+        let input = "namespace N(4);\n    let x = 1;\n    col witness y;\n    let x = 2;\n";
+        let err = analyze_string::<GoldilocksField>(input).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "input:4:5: Duplicate symbol definition: N.x\ninput:2:5: N.x was first defined here."
+        );
+
+        let input = "namespace N(4);\n    col witness x;\n    col witness y, x;\n";
+        let Err(AnalysisError::DuplicateDefinition(..)) = analyze_string::<GoldilocksField>(input)
+        else {
+            panic!()
+        };
+
+        // The same name in different namespaces is fine.
+        let input = "namespace N(4);\n    col witness x;\nnamespace M(4);\n    col witness x;\n    x = N.x;\n";
+        analyze_string::<GoldilocksField>(input).unwrap();
+    }
+
+    #[test]
+    fn analyze_constructed_ast() {
+        use crate::ast::parsed::build::*;
+        use crate::ast::parsed::{ArrayExpression, FunctionDefinition, PILFile, PilStatement};
+        use crate::parser_util::SourceRef;
+
+        let pil_file = PILFile(vec![
+            namespace("N", Some(8)),
+            witness_columns(["x", "y"]),
+            fixed_column(
+                "BYTE",
+                FunctionDefinition::Array(ArrayExpression::RepeatedValue(vec![1u32.into()])),
+            ),
+            polynomial_identity(
+                next_reference("x"),
+                direct_reference("x") + direct_reference("y"),
+            ),
+            plookup_identity(
+                selected_expressions(None, vec![direct_reference("y")]),
+                selected_expressions(None, vec![direct_reference("BYTE")]),
+            ),
+        ]);
+        let analyzed = super::analyze_ast::<GoldilocksField>(pil_file).unwrap();
+        assert_eq!(
+            analyzed.to_string(),
+            "namespace N(8);\n    col witness x;\n    col witness y;\n    col fixed BYTE = [1]*;\n    N.x' = N.x + N.y;\n    [N.y] in [N.BYTE];\n"
+        );
+
+        let with_include = PILFile(vec![PilStatement::Include(
+            SourceRef::unknown(),
+            "other.pil".to_string(),
+        )]);
+        let err = super::analyze_ast::<GoldilocksField>(with_include).unwrap_err();
+        assert!(matches!(err, AnalysisError::Parse(_)));
+    }
+
+    #[test]
+    fn sum_and_zip_columns() {
+        let input = r#"
+            namespace std::array;
+                let<T: Add> sum: T[] -> T = [];
+                let<T1, T2, T3> zip: T1[], T2[], (T1, T2 -> T3) -> T3[] = [];
+            namespace N(4);
+                col witness a[3], b[3];
+                std::array::sum(std::array::zip(a, b, |x, y| x * y)) = 0;
+        "#;
+        let analyzed = analyze_string::<GoldilocksField>(input).unwrap();
+        assert_eq!(
+            analyzed.identities[0].to_string(),
+            "N.a[0] * N.b[0] + N.a[1] * N.b[1] + N.a[2] * N.b[2] = 0;"
+        );
+    }
+
+    #[test]
+    fn let_type_annotations() {
+        let input = "let x: int = 5;\nlet f: -> fe = || { let y: fe = 7; y };";
+        let analyzed = analyze_string::<GoldilocksField>(input).unwrap();
+        assert_eq!(analyzed.type_of_symbol("x").ty.to_string(), "int");
+
+        for input in [
+            "let x: int = \"five\";",
+            "let f: -> fe = || { let y: int = 7; y };",
+            "let f = || { let y: fe = 7; let z: int = y; z };",
+        ] {
+            let err = analyze_string::<GoldilocksField>(input).unwrap_err();
+            assert!(matches!(err, AnalysisError::Type(_)), "{input}: {err}");
+        }
+    }
+
+    #[test]
+    fn compile_time_require() {
+        let input = r#"
+            namespace std::check;
+                let require: bool, string -> () = [];
+            namespace std::convert;
+                let<T: FromLiteral> expr: T -> expr = [];
+            namespace N(4);
+                let K: int = 3;
+                std::check::require(K < 4, "K too large");
+                col witness x;
+                x = std::convert::expr(K);
+        "#;
+        let analyzed = analyze_string::<GoldilocksField>(input).unwrap();
+        assert_eq!(analyzed.identities.len(), 1);
+    }
+
+    #[test]
+    fn compile_time_require_failure() {
+        let input = r#"
+            namespace std::check;
+                let require: bool, string -> () = [];
+            namespace N(4);
+                let K: int = 5;
+                std::check::require(K < 4, "K too large");
+        "#;
+        let err = analyze_string::<GoldilocksField>(input).unwrap_err();
+        let AnalysisError::Evaluation(errors) = &err else {
+            panic!("Expected evaluation error, got: {err}");
+        };
+        let [e] = &errors[..] else { panic!() };
+        assert_eq!(
+            e.source_ref().start,
+            input.find("std::check::require(K").unwrap()
+        );
+        assert!(
+            e.message().contains(r#"FailedAssertion("K too large")"#),
+            "{}",
+            e.message()
+        );
+    }
+
+    #[test]
+    fn invalid_operations() {
+        let declarations = r#"
+            namespace std::convert;
+                let<T: FromLiteral> expr: T -> expr = [];
+            namespace std::prelude;
+                let challenge: int, int -> expr = [];
+            namespace N(4);
+                col witness x, y;
+        "#;
+        for (code, message) in [
+            (
+                "x = std::convert::expr(2 / 0);",
+                r#"DivisionByZero("2 / 0")"#,
+            ),
+            (
+                "x = std::convert::expr(2 % 0);",
+                r#"DivisionByZero("2 % 0")"#,
+            ),
+            (
+                "x = std::convert::expr(1 << 100000000000000000000);",
+                "The right operand in 1 << 100000000000000000000 has to be non-negative",
+            ),
+            (
+                "let p: int = 2 ** -1; x = std::convert::expr(p);",
+                "The right operand in 2 ** -1 has to be non-negative",
+            ),
+            (
+                "x = x ** 18446744069414584322;",
+                "Exponent in N.x**18446744069414584322 is too large for the field.",
+            ),
+            (
+                "x = std::prelude::challenge(-1, 0);",
+                "Invalid challenge stage -1 or id 0",
+            ),
+            (
+                "x = if x == y { 1 } else { 0 };",
+                "Binary operator == not allowed in algebraic expression. Expression: N.x == N.y",
+            ),
+        ] {
+            let input = format!("{declarations}    {code}");
+            let err = analyze_string::<GoldilocksField>(&input).unwrap_err();
+            let AnalysisError::Evaluation(errors) = &err else {
+                panic!("Expected evaluation error for {code}, got: {err}");
+            };
+            assert!(errors[0].message().contains(message), "{}", errors[0]);
+        }
+    }
+
+    #[test]
+    fn array_contains_on_columns() {
+        let input = r#"
+            namespace std::array;
+                let<T: Eq> contains: T[], T -> bool = [];
+            namespace N(4);
+                col witness x, y;
+                x = if std::array::contains([x, y], x) { 1 } else { 0 };
+        "#;
+        let err = analyze_string::<GoldilocksField>(input).unwrap_err();
+        let AnalysisError::Evaluation(errors) = &err else {
+            panic!("Expected evaluation error, got: {err}");
+        };
+        assert!(
+            errors[0].message().contains(
+                "Binary operator == not allowed in algebraic expression. Expression: N.x == N.x"
+            ),
+            "{}",
+            errors[0]
+        );
+    }
+
+    #[test]
+    fn structured_type_errors() {
+        let type_errors = |input: &str| match analyze_string::<GoldilocksField>(input) {
+            Err(AnalysisError::Type(errors)) => errors,
+            r => panic!("Expected type error, but got {r:?}"),
+        };
+
+        let input = "let x: int = \"five\";";
+        let [TypeError::Mismatch {
+            expected,
+            found,
+            span,
+        }] = &type_errors(input)[..]
+        else {
+            panic!()
+        };
+        assert_eq!(
+            (expected.as_ref(), found.as_ref()),
+            (&Type::Int, &Type::String)
+        );
+        assert_eq!(&input[span.start..span.end], "\"five\"");
+
+        let input = "let x: string = 1;";
+        let [TypeError::UnboundBound { var, bound, span }] = &type_errors(input)[..] else {
+            panic!()
+        };
+        assert_eq!((var, bound.as_str()), (&Type::String, "FromLiteral"));
+        assert_eq!(&input[span.start..span.end], "1");
+
+        let input = "let f = |x| x(x);";
+        let [TypeError::Recursive { var, ty, span }] = &type_errors(input)[..] else {
+            panic!()
+        };
+        assert!(ty.contains_type_var(var));
+        assert_eq!(&input[span.start..span.end], "x");
+
+        let input = "let f = |x| f((x, x));";
+        let [TypeError::Recursive { var, ty, .. }] = &type_errors(input)[..] else {
+            panic!()
+        };
+        assert_eq!(ty.to_string(), format!("({var}, {var})"));
+    }
+
+    #[test]
+    fn degree_ranges() {
+        let input = r#"namespace std::prover;
+    let degree: -> int = [];
+    let min_degree: -> int = [];
+    let max_degree: -> int = [];
+let N: int = 32;
+namespace A(8..N);
+    col witness x, y;
+    (if std::prover::min_degree() == 8 { x } else { y }) = 0;
+    (if std::prover::max_degree() == 8 { x } else { y }) = 0;
+namespace B(4);
+    col witness z;
+    (if std::prover::min_degree() + std::prover::max_degree() == 8 { z } else { 1 }) = 0;
+"#;
+        let analyzed = analyze_string::<GoldilocksField>(input).unwrap();
+        assert_eq!(
+            analyzed.definitions["A.x"].0.degree,
+            Some(DegreeRange { min: 8, max: 32 })
+        );
+        assert_eq!(analyzed.definitions["B.z"].0.degree, Some(4.into()));
+        assert_eq!(analyzed.common_degree(), None);
+        assert_eq!(
+            analyzed.identities.iter().join("\n"),
+            "A.x = 0;\nA.y = 0;\nB.z = 0;"
+        );
+        assert!(analyzed.to_string().contains("namespace A(8..32);"));
+        crate::pil_analyzer::test_utils::assert_roundtrip(input);
+        let json = analyzed.to_json_value(&Default::default()).unwrap();
+        let degree = &json["definitions"]["A.x"][0]["degree"];
+        assert_eq!(degree.to_string(), r#"{"max":32,"min":8}"#);
+
+        let err = analyze_string::<GoldilocksField>("namespace A(16..8);").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "input:1:1: The minimum degree 16 is larger than the maximum degree 8."
+        );
+        let err = analyze_string::<GoldilocksField>("namespace A(8..16);\ncol fixed F = [1]*;")
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "input:2:1: Array definition of A.F requires a single degree, but the degree of the namespace is the range 8..16."
+        );
+    }
+
+    #[test]
+    fn degree_of_degree_range() {
+        let input = r#"namespace std::prover;
+    let degree: -> int = [];
+namespace A(8..16);
+    col witness x;
+    (if std::prover::degree() == 8 { x } else { 0 }) = 0;
+"#;
+        let err = analyze_string::<GoldilocksField>(input).unwrap_err();
+        assert!(matches!(err, AnalysisError::Evaluation(_)), "{err}");
+        assert!(err
+            .to_string()
+            .contains("Cannot evaluate degree, the degree of namespace A is the range 8..16."));
+    }
+
+    #[test]
+    fn invalid_namespace_degree() {
+        let error = |input: &str| match analyze_string::<GoldilocksField>(input).unwrap_err() {
+            AnalysisError::UnresolvedReference(errors) => errors.iter().join("\n"),
+            err => panic!("Expected unresolved reference, got: {err}"),
+        };
+        assert_eq!(
+            error("namespace N(-4);"),
+            "input:1:13: Namespace degree -4 evaluates to -4, which is not a valid degree."
+        );
+        assert_eq!(
+            error("let d = \"4\";\nnamespace N(d);"),
+            "input:2:13: Error evaluating namespace degree d: Type error: Expected integer but got \"4\": string"
+        );
+    }
+
+    #[test]
+    fn target_degree() {
+        let input = r#"namespace std::prover;
+    let degree: -> int = [];
+namespace A;
+    col witness x, y;
+    (if std::prover::degree() == 16 { x } else { y }) = 0;
+namespace B(8..32);
+    col witness z;
+namespace C(16);
+    col witness w;
+"#;
+        let target_degree = |degree| AnalysisOptions {
+            target_degree: Some(degree),
+            ..Default::default()
+        };
+        let (analyzed, _) =
+            analyze_string_with_options::<GoldilocksField>("input", input, &target_degree(16))
+                .unwrap();
+        for name in ["A.x", "B.z", "C.w"] {
+            assert_eq!(analyzed.definitions[name].0.degree, Some(16.into()));
+        }
+        assert_eq!(analyzed.common_degree(), Some(16));
+        assert_eq!(analyzed.identities.iter().join("\n"), "A.x = 0;");
+
+        let err =
+            analyze_string_with_options::<GoldilocksField>("input", input, &target_degree(32))
+                .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "input:8:1: The degree 16 of namespace C conflicts with the target degree 32."
+        );
+    }
+
+    #[test]
+    fn namespace_blocks() {
+        let input = r#"
+let N: int = 8;
+namespace A(N) {
+    col witness x;
+    let double: expr -> expr = |e| e * 2;
+    namespace B {
+        col witness y;
+        // References are resolved walking outwards.
+        y = double(x);
+        namespace C(4) with max_identities: 1 {
+            col witness z;
+            z = B::y + A::x;
+        }
+        y' = y;
+    }
+    x' = B::y + B::C::z;
+}
+namespace D(2);
+    col witness w;
+"#;
+        let analyzed = analyze_string::<GoldilocksField>(input).unwrap();
+        let degrees = ["A.x", "A::B::y", "A::B::C::z", "D.w"]
+            .map(|name| analyzed.definitions[name].0.degree.unwrap().min)
+            .to_vec();
+        assert_eq!(degrees, [8, 8, 4, 2]);
+        assert_eq!(
+            analyzed.identities.iter().join("\n"),
+            "A::B::y = A.x * 2;\nA::B::C::z = A::B::y + A.x;\nA::B::y' = A::B::y;\nA.x' = A::B::y + A::B::C::z;"
+        );
+        crate::pil_analyzer::test_utils::assert_roundtrip(input);
+
+        let input = "namespace A(4) { col witness x; namespace B { col witness y; } y = x; }";
+        let err = analyze_string::<GoldilocksField>(input).unwrap_err();
+        assert_eq!(err.to_string(), "input:1:64: Value symbol not found: y");
+
+        let input = "namespace A(4) with max_identities: 1 { col witness x; x = 0; x' = x; }";
+        assert!(matches!(
+            analyze_string::<GoldilocksField>(input),
+            Err(AnalysisError::BudgetExceeded(_))
+        ));
+    }
+
+    #[test]
+    fn namespace_templates() {
+        let input = r#"
+namespace Adder<N>(N) {
+    col witness a, b;
+    let SIZE: int = 2 * N;
+    a' = a + b;
+}
+namespace Main(16) {
+    let N: int = 4;
+    col witness x;
+    namespace Small = Adder(8);
+    // `N` refers to the outer `N`, not to the parameter.
+    namespace Big = Adder(N * 8);
+    x = Small::a + Big::a;
+}
+"#;
+        let analyzed = analyze_string::<GoldilocksField>(input).unwrap();
+        let degrees = ["Main.x", "Main::Small::a", "Main::Big::b"]
+            .map(|name| analyzed.definitions[name].0.degree.unwrap().min)
+            .to_vec();
+        assert_eq!(degrees, [16, 8, 32]);
+        assert!(analyzed.definitions.contains_key("Main::Big::SIZE"));
+        assert!(!analyzed.definitions.contains_key("Adder.a"));
+        assert_eq!(
+            analyzed.identities.iter().join("\n"),
+            "Main::Small::a' = Main::Small::a + Main::Small::b;\n\
+             Main::Big::a' = Main::Big::a + Main::Big::b;\n\
+             Main.x = Main::Small::a + Main::Big::a;"
+        );
+        crate::pil_analyzer::test_utils::assert_roundtrip(input);
+
+        let input = "namespace A<N>(N) { col witness x; } namespace B = C(2);";
+        let err = analyze_string::<GoldilocksField>(input).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "input:1:38: Namespace template not found: C"
+        );
+        let input = "namespace A<N>(N) { col witness x; } namespace B = A(2, 3);";
+        let err = analyze_string::<GoldilocksField>(input).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "input:1:38: Namespace template A expects 1 arguments, got 2."
+        );
+    }
+
+    #[test]
+    fn multiple_bounds() {
+        let declaration = r#"
+            let<T: Add + Ord, U: Eq> pick: T, T, U, U -> T = |a, b, c, d| if c == d && a < b { a + b } else { b };
+        "#;
+        let input = format!("{declaration}let x: int = pick(1, 2, \"a\", \"b\");");
+        analyze_string::<GoldilocksField>(&input).unwrap();
+
+        let type_errors = |input: &str| match analyze_string::<GoldilocksField>(input) {
+            Err(AnalysisError::Type(errors)) => errors.iter().map(|e| e.message()).join("\n"),
+            r => panic!("Expected type error, but got {r:?}"),
+        };
+        assert_eq!(
+            type_errors(&format!(
+                "{declaration}let y: string = pick(\"a\", \"b\", 1, 2);"
+            )),
+            "Type string does not satisfy trait Ord."
+        );
+        assert_eq!(
+            type_errors(&format!(
+                "{declaration}let z: int = pick(1, 2, || \"a\", || \"b\");"
+            )),
+            "Type -> string does not satisfy trait Eq."
+        );
+        assert_eq!(
+            type_errors("let<T: Add> bad: T, T -> bool = |a, b| a + b < b;"),
+            "Inferred type scheme for symbol bad does not match the declared type.\nInferred: let<T: Add + Ord> bad: T, T -> bool\nDeclared: let<T: Add> bad: T, T -> bool"
+        );
+    }
+
+    #[test]
+    fn compare_field_elements() {
+        let input = "let a: fe = 1;\nlet b: fe = 2;\nlet c: bool = a + b <= b;";
+        let Err(AnalysisError::Type(errors)) = analyze_string::<GoldilocksField>(input) else {
+            panic!()
+        };
+        let [error] = &errors[..] else { panic!() };
+        assert_eq!(
+            error.message(),
+            "Operator <= cannot be applied to fe: field elements are not ordered; convert to int first."
+        );
+        assert_eq!(&input[error.span().start..error.span().end], "a + b <= b");
+
+        let input = "namespace std::convert;\nlet<T: FromLiteral> int: T -> int = [];\nlet a: fe = 1;\nlet c: bool = int(a) < 2;";
+        analyze_string::<GoldilocksField>(input).unwrap();
+    }
+
+    #[test]
+    fn mix_int_and_fe() {
+        let message = |input: &str| {
+            let Err(AnalysisError::Type(errors)) = analyze_string::<GoldilocksField>(input) else {
+                panic!()
+            };
+            let [error] = &errors[..] else { panic!() };
+            (
+                error.message(),
+                input[error.span().start..error.span().end].to_string(),
+            )
+        };
+        let declarations = "let i: int = 1;\nlet f: fe = 2;\n";
+        assert_eq!(
+            message(&format!("{declarations}let x: int = i + f;")),
+            (
+                "Operator + cannot be applied to int and fe; use fe(...) or int(...) to convert."
+                    .to_string(),
+                "i + f".to_string()
+            )
+        );
+        assert_eq!(
+            message(&format!("{declarations}let x: fe = f * (i - 1);")),
+            (
+                "Operator * cannot be applied to fe and int; use fe(...) or int(...) to convert."
+                    .to_string(),
+                "f * (i - 1)".to_string()
+            )
+        );
+        // Errors inside the operands are not attributed to the operator.
+        assert_eq!(
+            message(&format!(
+                "{declarations}let g: int -> int = |x| x;\nlet x: int = i + g(f);"
+            ))
+            .0,
+            "Expected type int but got type fe."
+        );
+    }
+
+    #[test]
+    fn nested_tuple_patterns() {
+        let input = r#"
+            let f: -> (int, (int, fe)) = || (1, (2, 3));
+            let g: -> fe = || { let (a, (b, c)) = f(); c };
+        "#;
+        analyze_string::<GoldilocksField>(input).unwrap();
+
+        let input = r#"
+            let f: -> (int, (int, fe)) = || (1, (2, 3));
+            let g: -> fe = || { let (a, (b, c, d)) = f(); c };
+        "#;
+        let Err(AnalysisError::Type(errors)) = analyze_string::<GoldilocksField>(input) else {
+            panic!()
+        };
+        let [error] = &errors[..] else { panic!() };
+        let span = error.span();
+        assert_eq!(&input[span.start..span.end], "(b, c, d)");
+        assert_eq!(
+            error.message(),
+            "Tuple pattern (b, c, d) has 3 items, but the value has type (int, fe) with 2 items."
+        );
+    }
+
+    #[test]
+    fn if_expression_with_expr_branches() {
+        let input = r#"
+            namespace N(4);
+                let use_x: bool = std::prover::degree() > 2;
+                col witness x, y, sel;
+                sel * (if use_x { x } else { y }) = 0;
+                (if !use_x { x } else { y })' = x;
+        "#;
+        let input = format!("namespace std::prover;\nlet degree: -> int = [];\n{input}");
+        let analyzed = analyze_string::<GoldilocksField>(&input).unwrap();
+        assert_eq!(
+            analyzed.identities.iter().join("\n"),
+            "N.sel * N.x = 0;\nN.y' = N.x;"
+        );
+
+        for input in [
+            "namespace N(4);\ncol witness x, y;\n(if x { x } else { y }) = 0;",
+            "namespace N(4);\ncol witness x;\nlet y: int = 2;\n(if 1 == 1 { x } else { y }) = 0;",
+        ] {
+            let err = analyze_string::<GoldilocksField>(input).unwrap_err();
+            assert!(matches!(err, AnalysisError::Type(_)), "{input}: {err}");
+        }
+    }
+
+    #[test]
+    fn array_new_constraints() {
+        let input = r#"
+            namespace std::array;
+                let<T> new: int, (int -> T) -> T[] = [];
+            namespace N(4);
+                col witness x[3];
+                std::array::new(3, |i| x[i] = std::convert::expr(i));
+        "#;
+        let input =
+            format!("namespace std::convert;\nlet<T: FromLiteral> expr: T -> expr = [];\n{input}");
+        let analyzed = analyze_string::<GoldilocksField>(&input).unwrap();
+        assert_eq!(
+            analyzed.identities.iter().join("\n"),
+            "N.x[0] = 0;\nN.x[1] = 1;\nN.x[2] = 2;"
+        );
+    }
+
+    #[test]
+    fn struct_declarations() {
+        let input = r#"
+            namespace N(4);
+                struct Gate<T> {
+                    sel: expr,
+                    inputs: T[],
+                }
+                let mul: Gate<expr>, expr -> Constr = |g, out| match g {
+                    Gate::new(s, [a, b]) => s * (a * b - out) = 0,
+                };
+                col witness s, x, y, z;
+                let g: Gate<expr> = Gate::new(s, [x, y]);
+                mul(g, z);
+                Gate::sel(g) * (1 - Gate::sel(g)) = 0;
+                let second: expr = match Gate::inputs(g) {
+                    [_, b] => b,
+                };
+        "#;
+        let analyzed = analyze_string::<GoldilocksField>(input).unwrap();
+        assert_eq!(
+            analyzed.identities.iter().join("\n"),
+            "N.s * (N.x * N.y - N.z) = 0;\nN.s * (1 - N.s) = 0;"
+        );
+        let printed = analyzed.to_string();
+        assert!(
+            printed.contains("enum Gate<T> {\n        new(expr, T[]),\n    }"),
+            "{printed}"
+        );
+        assert!(
+            printed.contains(
+                "let<T> inputs: N::Gate<T> -> T[] = (|s| match s {\n        N::Gate::new(_, v) => v,\n    });"
+            ),
+            "{printed}"
+        );
+        let reanalyzed = analyze_string::<GoldilocksField>(&printed).unwrap();
+        assert_eq!(reanalyzed.to_string(), printed);
+
+        let err = analyze_string::<GoldilocksField>(
+            "struct P { x: int, y: fe }\nlet p: P = P::new(1, 2, 3);",
+        )
+        .unwrap_err();
+        assert!(matches!(err, AnalysisError::Type(_)), "{err}");
+    }
+
+    #[test]
+    fn trait_implementations() {
+        let input = r#"
+            namespace N(4);
+                struct Pair {
+                    a: expr,
+                    b: expr,
+                }
+                impl Mul<Pair> {
+                    mul: |p, q| Pair::new(Pair::a(p) * Pair::a(q), Pair::b(p) * Pair::b(q)),
+                }
+                col witness x, y;
+                let p: Pair = Pair::new(x, y) * Pair::new(y, x);
+                Pair::a(p) = Pair::b(p);
+        "#;
+        let analyzed = analyze_string::<GoldilocksField>(input).unwrap();
+        assert_eq!(
+            analyzed.identities.iter().join("\n"),
+            "N.x * N.y = N.y * N.x;"
+        );
+        let printed = analyzed.to_string();
+        assert!(
+            printed.contains("\n    let mul: N::Pair, N::Pair -> N::Pair = (|p, q| "),
+            "{printed}"
+        );
+        let reanalyzed = analyze_string::<GoldilocksField>(&printed).unwrap();
+        assert_eq!(reanalyzed.to_string(), printed);
+
+        let type_errors = |input: &str| match analyze_string::<GoldilocksField>(input) {
+            Err(AnalysisError::Type(errors)) => errors.iter().map(|e| e.message()).join("\n"),
+            r => panic!("Expected type error, but got {r:?}"),
+        };
+        assert_eq!(
+            type_errors("struct P { x: int }\nlet p: P = P::new(1) + P::new(2);"),
+            "Type P does not satisfy trait Add."
+        );
+        // The implementation has to have the type of the operator.
+        assert_eq!(
+            type_errors("struct P { x: int }\nimpl Neg<P> { neg: |p| \"p\" }"),
+            "Expected type P but got type string."
+        );
+
+        let err = analyze_string::<GoldilocksField>(
+            "struct P { x: int }\nimpl Eq<P> { eq: |a, b| true }",
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "input:2:1: Trait Eq cannot be implemented. Only the traits of overloadable operators can be implemented: Add, Mul, Neg, Pow, Sub"
+        );
+        let err = analyze_string::<GoldilocksField>(
+            "struct P { x: int }\nimpl Add<P> { plus: |a, b| a }",
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "input:2:1: Expected exactly the function add in the implementation of Add<P>."
+        );
+    }
+
+    #[test]
+    fn type_aliases() {
+        let input = r#"namespace M(4);
+    type Word = int[];
+    type Pair = (Word, fe);
+    let first: Pair -> Word = |p| match p { (w, _) => w };
+    let x: Word = [1, 2, 3, 4];
+    let f = || {
+        let y: Pair = (x, 1);
+        first(y)
+    };
+namespace K(4);
+    let g: M::Word -> int = |w| w[0];
+"#;
+        let analyzed = analyze_string::<GoldilocksField>(input).unwrap();
+        let printed = analyzed.to_string();
+        assert!(
+            printed.contains("let first: (int[], fe) -> int[] ="),
+            "{printed}"
+        );
+        assert!(
+            printed.contains("let y: (int[], fe) = (M.x, 1);"),
+            "{printed}"
+        );
+        assert!(printed.contains("let g: int[] -> int ="), "{printed}");
+        assert!(!printed.contains("Word"), "{printed}");
+        let reanalyzed = analyze_string::<GoldilocksField>(&printed).unwrap();
+        assert_eq!(reanalyzed.to_string(), printed);
+
+        let error = |input: &str| {
+            analyze_string::<GoldilocksField>(input)
+                .unwrap_err()
+                .to_string()
+        };
+        assert_eq!(
+            error("type A = A[];"),
+            "input:1:1: Type alias A is used before its declaration. Type aliases have to be declared before they are used and cannot be recursive."
+        );
+        assert_eq!(
+            error("type A = B;\ntype B = A;"),
+            "input:1:1: Type alias B is used before its declaration. Type aliases have to be declared before they are used and cannot be recursive."
+        );
+        assert_eq!(
+            error("type A = int;\nlet x: A<int> = 1;"),
+            "input:2:1: Type alias A does not take type arguments."
+        );
+    }
+
+    #[test]
+    fn field_literal_out_of_range() {
+        let input = "let x: int = 18446744069414584321000;\nlet y: fe = 18446744069414584320;";
+        analyze_string::<GoldilocksField>(input).unwrap();
+
+        let input = r#"namespace N(4);
+            let x: fe = 18446744069414584321;
+            col witness w;
+            w = 18446744069414584322;
+        "#;
+        let Err(AnalysisError::Type(errors)) = analyze_string::<GoldilocksField>(input) else {
+            panic!()
+        };
+        let mut messages = errors
+            .iter()
+            .map(|e| (&input[e.span().start..e.span().end], e.message()))
+            .collect::<Vec<_>>();
+        messages.sort();
+        assert_eq!(
+            messages,
+            [
+                (
+                    "18446744069414584321",
+                    "Number literal 18446744069414584321 is too large for a field element of the goldilocks field (modulus 18446744069414584321).".to_string()
+                ),
+                (
+                    "18446744069414584322",
+                    "Number literal 18446744069414584322 is too large for a field element of the goldilocks field (modulus 18446744069414584321).".to_string()
+                )
+            ]
+        );
+    }
+
+    #[test]
+    fn field_literal_wrap() {
+        let input = r#"namespace N(4);
+    let x: expr = 18446744069414584322;
+    let<T: FromLiteral> big: T = 18446744069414584323;
+    col witness w;
+    w = 18446744069414584322 + x;
+    w' = big;
+"#;
+        let fe_overflow = |fe_overflow| AnalysisOptions {
+            fe_overflow,
+            ..Default::default()
+        };
+        let (analyzed, _) = analyze_string_with_options::<GoldilocksField>(
+            "input",
+            input,
+            &fe_overflow(FeOverflow::Wrap),
+        )
+        .unwrap();
+        assert_eq!(
+            analyzed.identities.iter().join("\n"),
+            "N.w = 1 + N.x;\nN.w' = 2;"
+        );
+        assert!(matches!(
+            analyze_string_with_options::<GoldilocksField>(
+                "input",
+                input,
+                &fe_overflow(FeOverflow::Error)
+            ),
+            Err(AnalysisError::Type(errors)) if errors.len() == 2
+        ));
+
+        assert_eq!("wrap".parse(), Ok(FeOverflow::Wrap));
+        assert_eq!(FeOverflow::Error.to_string().parse(), Ok(FeOverflow::Error));
+        assert!("saturate".parse::<FeOverflow>().is_err());
+    }
+
+    #[test]
+    fn unused_symbol_warnings() {
+        let input = r#"namespace N(4);
+    struct Point { x: int, y: int }
+    col witness a, b, unused_col, p;
+    col fixed f = [0]*;
+    col sq = a * a;
+    col unused_inter = b * b;
+    let unused_fn: int -> int = |i| if i == 0 { 0 } else { unused_fn(i - 1) };
+    let add_constr = constr |x, y| x = y;
+    let pick: int -> expr = |i| match i { 0 => a, 1 => b };
+    add_constr(sq, f);
+    pick(1) = b';
+    public out = p(2);
+"#;
+        let mut options = AnalysisOptions {
+            report_lints: true,
+            ..Default::default()
+        };
+        let (_, warnings) =
+            analyze_string_with_options::<GoldilocksField>("input", input, &options).unwrap();
+        let messages = warnings.iter().map(|w| w.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            [
+                "input:9:33: Match expression is not exhaustive, consider adding a catch-all arm (\"_ => ...\").",
+                "input:3:5: Column N.unused_col is never used.",
+                "input:6:5: Column N.unused_inter is never used.",
+                "input:7:5: Definition N.unused_fn is never used.",
+                "input:11:5: Identity N.b = N.b'; uses the next row of column N.b without a selector, so it also applies to the wrap-around from the last to the first row."
+            ]
+        );
+
+        options.deny_warnings = true;
+        let Err(AnalysisError::Warnings(errors)) =
+            analyze_string_with_options::<GoldilocksField>("input", input, &options)
+        else {
+            panic!()
+        };
+        assert_eq!(errors.len(), 5);
+
+        let input = "namespace N(4);\ncol witness x;\nx = x * x;";
+        let (_, warnings) =
+            analyze_string_with_options::<GoldilocksField>("input", input, &options).unwrap();
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn array_slices() {
+        let input = r#"namespace std::array;
+    let<T> len: T[] -> int = [];
+namespace N(4);
+    col witness x[4];
+    col witness y[2];
+    let window = x[1..3];
+    [y[0], y[1]] in [window[0], window[1]];
+    let n: int = std::array::len(x[2..4]);
+"#;
+        analyze_string::<GoldilocksField>(input).unwrap();
+
+        let input = r#"namespace N(4);
+    col witness x[4];
+    let a = x[2..5];
+    let b: int[] = [1, 2][1..0];
+    let c = |i| x[0..i];
+"#;
+        let Err(AnalysisError::Type(errors)) = analyze_string::<GoldilocksField>(input) else {
+            panic!()
+        };
+        let messages = errors
+            .iter()
+            .map(|e| e.message().to_string())
+            .sorted()
+            .collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            [
+                "Slice N.x[2..5] is out of range for an array of length 4.",
+                "Slice [1, 2][1..0] is out of range for an array of length 2."
+            ]
+        );
+    }
+
+    #[test]
+    fn index_bounds() {
+        let input = r#"namespace N(4);
+    col witness x[4];
+    x[3] = x[0];
+    x[4] = x[-1];
+    let a: int = [1, 2][-2];
+    let b: int -> int = |i| [1, 2][i - 1];
+    let c = x[-1..2];
+"#;
+        let Err(AnalysisError::Type(errors)) = analyze_string::<GoldilocksField>(input) else {
+            panic!()
+        };
+        let messages = errors
+            .iter()
+            .map(|e| e.message().to_string())
+            .sorted()
+            .collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            [
+                "Bound -1 of slice N.x[-1..2] is negative, but array indices have to be non-negative.",
+                "Index -1 in N.x[-1] is negative, but array indices have to be non-negative.",
+                "Index -2 in [1, 2][-2] is negative, but array indices have to be non-negative.",
+                "Index 4 in N.x[4] is out of range for an array of length 4."
+            ]
+        );
+    }
+
+    #[test]
+    fn new_witness_col_builtin() {
+        let input = r#"namespace std::prover;
+    let new_witness_col: string -> expr = [];
+namespace N(4);
+    col witness x;
+    let copy = constr |e| {
+        let c = std::prover::new_witness_col("copy");
+        c = e;
+        c
+    };
+    copy(x) = copy(x');
+"#;
+        let analyzed = analyze_string::<GoldilocksField>(input).unwrap();
+        assert_eq!(analyzed.commitment_count(), 3);
+        let expected = r#"namespace std::prover;
+    let new_witness_col: string -> expr = [];
+namespace N(4);
+    col witness x;
+    let copy: expr -> expr = (constr |e| {
+        let c: expr = std::prover::new_witness_col("copy");
+        c = e;
+        c
+    });
+    col witness copy_1;
+    col witness copy_2;
+    N.copy_1 = N.x;
+    N.copy_2 = N.x';
+    N.copy_1 = N.copy_2;
+"#;
+        assert_eq!(analyzed.to_string(), expected);
+    }
+
+    #[test]
+    fn new_witness_col_outside_constr() {
+        let input = r#"namespace std::prover;
+    let new_witness_col: string -> expr = [];
+namespace N(4);
+    let make = || std::prover::new_witness_col("c");
+"#;
+        let err = analyze_string::<GoldilocksField>(input).unwrap_err();
+        let AnalysisError::SideEffect(errors) = &err else {
+            panic!("Expected side effect error, got: {err}");
+        };
+        let [e] = &errors[..] else { panic!() };
+        assert_eq!(
+            e.to_string(),
+            "input:4:5: Error checking side-effects of N.make: Referenced a constr function inside a pure context: std::prover::new_witness_col"
+        );
+    }
+
+    #[test]
+    fn invalid_patterns() {
+        let error = |input: &str| match analyze_string::<GoldilocksField>(input).unwrap_err() {
+            AnalysisError::UnresolvedReference(errors) => errors.iter().join("\n"),
+            err => panic!("Expected unresolved reference, got: {err}"),
+        };
+        assert_eq!(
+            error("let f: int, int -> int = |x, x| x;"),
+            "input:1:30: Variable already defined: x"
+        );
+        assert_eq!(
+            error("let f: int -> int = |x| { let (a, a) = (x, x); a };"),
+            "input:1:35: Variable already defined: a"
+        );
+        assert_eq!(
+            error("let f: int -> int = |x| { let 1 = x; x };"),
+            "input:1:25: Let statement requires an irrefutable pattern, but 1 is refutable."
+        );
+        assert_eq!(
+            error("let f: int -> int = |x| { let (a, b); x };"),
+            "input:1:25: Let statement without value requires a single variable, but got (a, b)."
+        );
+        assert_eq!(
+            error("let f: int -> int = |1| 2;"),
+            "input:1:22: Function parameters must be irrefutable, but 1 is refutable."
+        );
+        assert_eq!(
+            error("let f: int[] -> int = |[.., ..]| 2;"),
+            "input:1:24: Only one \"..\"-item allowed in array pattern"
+        );
+    }
+
+    #[test]
+    fn invalid_witness_column_types() {
+        let error = |input: &str| match analyze_string::<GoldilocksField>(input).unwrap_err() {
+            AnalysisError::UnresolvedReference(errors) => errors.iter().join("\n"),
+            err => panic!("Expected unresolved reference, got: {err}"),
+        };
+        assert_eq!(
+            error("namespace N(4);\nlet x: int;"),
+            "input:2:1: Symbol x is declared without value and thus must be a witness column, but its type is int instead of col."
+        );
+        assert_eq!(
+            error("namespace N(4);\nlet x: int[2];"),
+            "input:2:1: Symbol x is declared without value and thus must be a witness column array, but its type is int[2] instead of col[]."
+        );
+        assert_eq!(
+            error("namespace N(4);\nlet x: col[];"),
+            "input:2:1: Explicit array length required for column x: col[]"
+        );
+        assert_eq!(
+            error("namespace N(4);\nlet<T> x: int = 1;"),
+            "input:2:1: Unused type variable(s) in declaration: T\nlet<T> x: int"
+        );
+        assert_eq!(
+            error("namespace N(4);\nlet s = \"a\";\ncol witness x[s];"),
+            "input:3:1: Error evaluating length of array of witness columns x:\nType error: Expected integer but got \"a\": string"
+        );
+    }
+
+    #[test]
+    fn capture_constraints() {
+        let input = r#"namespace std::prover;
+    let capture_constraints: (-> ()) -> Constr[] = [];
+    let new_witness_col: string -> expr = [];
+namespace N(4);
+    col witness x, y;
+    let gadget = constr || std::prover::capture_constraints(constr || {
+        let c = std::prover::new_witness_col("c");
+        c = x;
+        [x = y, y = c];
+    });
+    let outer = constr || std::prover::capture_constraints(constr || {
+        gadget();
+        x = 1;
+    });
+    let apply_if: int, Constr[] -> Constr[] = constr |flag, cs| if flag == 1 { cs } else { [] };
+    apply_if(1, gadget());
+    apply_if(0, gadget());
+    apply_if(1, outer());
+"#;
+        let analyzed = analyze_string::<GoldilocksField>(input).unwrap();
+        // Columns created while capturing are kept even if the constraints are dropped.
+        assert_eq!(analyzed.commitment_count(), 5);
+        assert_eq!(
+            analyzed.identities.iter().join("\n"),
+            "N.c = N.x;\nN.x = N.y;\nN.y = N.c;\nN.c_2 = N.x;\nN.x = N.y;\nN.y = N.c_2;\nN.x = 1;"
+        );
+    }
 }
@@ -107,17 +107,46 @@ impl<'a> SideEffectChecker<'a> {
 
 lazy_static! {
     static ref BUILTIN_KINDS: HashMap<&'static str, FunctionKind> = [
+        ("std::array::contains", FunctionKind::Pure),
+        ("std::array::find", FunctionKind::Pure),
+        ("std::array::flatten", FunctionKind::Pure),
+        ("std::array::fold", FunctionKind::Pure),
+        ("std::array::from_tuple2", FunctionKind::Pure),
+        ("std::array::from_tuple3", FunctionKind::Pure),
+        ("std::array::from_tuple4", FunctionKind::Pure),
         ("std::array::len", FunctionKind::Pure),
+        ("std::array::map", FunctionKind::Pure),
+        ("std::array::new", FunctionKind::Pure),
+        ("std::array::reverse", FunctionKind::Pure),
+        ("std::array::sum", FunctionKind::Pure),
+        ("std::array::to_tuple2", FunctionKind::Pure),
+        ("std::array::to_tuple3", FunctionKind::Pure),
+        ("std::array::to_tuple4", FunctionKind::Pure),
+        ("std::array::zip", FunctionKind::Pure),
         ("std::check::panic", FunctionKind::Pure),
+        ("std::check::require", FunctionKind::Pure),
+        ("std::convert::bool", FunctionKind::Pure),
         ("std::convert::expr", FunctionKind::Pure),
         ("std::convert::fe", FunctionKind::Pure),
         ("std::convert::int", FunctionKind::Pure),
+        ("std::convert::to_bits", FunctionKind::Pure),
+        ("std::convert::to_string", FunctionKind::Pure),
         ("std::convert::expr", FunctionKind::Pure),
         ("std::debug::print", FunctionKind::Pure),
+        ("std::field::bits", FunctionKind::Pure),
         ("std::field::modulus", FunctionKind::Pure),
+        ("std::fmt::format", FunctionKind::Pure),
+        ("std::math::ff::inv_field", FunctionKind::Pure),
         ("std::prelude::challenge", FunctionKind::Constr), // strictly, only new_challenge would need "constr"
+        ("std::prover::capture_constraints", FunctionKind::Constr),
         ("std::prover::degree", FunctionKind::Pure),
+        ("std::prover::min_degree", FunctionKind::Pure),
+        ("std::prover::max_degree", FunctionKind::Pure),
         ("std::prover::eval", FunctionKind::Query),
+        ("std::prover::new_witness_col", FunctionKind::Constr),
+        ("std::utils::cartesian_product", FunctionKind::Pure),
+        ("std::utils::is_power_of_two", FunctionKind::Pure),
+        ("std::utils::next_power_of_two", FunctionKind::Pure),
     ]
     .into_iter()
     .collect();
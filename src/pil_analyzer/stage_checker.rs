@@ -0,0 +1,105 @@
+use crate::ast::analyzed::{AlgebraicExpression, Analyzed};
+use crate::ast::parsed::visitor::AllChildren;
+use crate::number::FieldElement;
+use crate::pil_analyzer::AnalysisError;
+
+/// Checks that intermediate columns only use challenges of earlier stages,
+/// where the stage of a column is computed as in [`Analyzed::column_stages`].
+/// A challenge of stage `s` is only available from stage `s + 1` on,
+/// so in particular, columns of stage 0 cannot use any challenges.
+/// Returns an error for every offending use of a challenge.
+pub fn check_stages<T: FieldElement>(pil: &Analyzed<T>) -> Result<(), AnalysisError> {
+    let stages = pil.column_stages();
+    let errors = pil
+        .intermediate_polys_in_source_order()
+        .into_iter()
+        .flat_map(|(symbol, values)| {
+            symbol
+                .array_elements()
+                .zip(values)
+                .map(move |((name, _), value)| (symbol, name, value))
+        })
+        .flat_map(|(symbol, name, value)| {
+            let stage = stages[&name];
+            value
+                .all_children()
+                .filter_map(move |e| match e {
+                    AlgebraicExpression::Challenge(challenge) if challenge.stage >= stage => {
+                        Some(symbol.source.with_error(format!(
+                            "Intermediate column {name} is in stage {stage} but uses challenge {} of stage {}, which is only available from stage {} on.",
+                            challenge.id,
+                            challenge.stage,
+                            challenge.stage + 1
+                        )))
+                    }
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(AnalysisError::StageOrder(errors))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ast::analyzed::JsonExportOptions;
+    use crate::number::GoldilocksField;
+    use crate::pil_analyzer::{analyze_string, AnalysisError};
+
+    #[test]
+    fn challenges_of_earlier_stages() {
+        let input = r#"namespace N(4);
+    col witness x;
+    col witness stage(1) inv;
+    let alpha = std::prelude::challenge(0, 1);
+    col x2 = x * x;
+    col prod = (alpha - x2) * inv;
+    prod = 1;
+"#;
+        let analyzed = analyze_string::<GoldilocksField>(input).unwrap();
+        assert_eq!(
+            analyzed.column_stages().into_iter().collect::<Vec<_>>(),
+            [
+                ("N.inv".to_string(), 1),
+                ("N.prod".to_string(), 1),
+                ("N.x".to_string(), 0),
+                ("N.x2".to_string(), 0)
+            ]
+        );
+        let json = analyzed
+            .to_json(&JsonExportOptions {
+                stages: true,
+                ..Default::default()
+            })
+            .unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["stages"]["N.prod"], 1);
+        assert_eq!(value["stages"]["N.x2"], 0);
+    }
+
+    #[test]
+    fn challenge_in_stage_zero() {
+        let input = r#"namespace N(4);
+    col witness x;
+    col witness stage(1) inv;
+    col diff = std::prelude::challenge(1, 2) - x;
+    col late = std::prelude::challenge(1, 3) * inv;
+    diff * late = 1;
+"#;
+        let Err(AnalysisError::StageOrder(errors)) = analyze_string::<GoldilocksField>(input)
+        else {
+            panic!()
+        };
+        assert_eq!(
+            errors.iter().map(|e| e.message()).collect::<Vec<_>>(),
+            [
+                "Intermediate column N.diff is in stage 0 but uses challenge 2 of stage 1, which is only available from stage 2 on.",
+                "Intermediate column N.late is in stage 1 but uses challenge 3 of stage 1, which is only available from stage 2 on."
+            ]
+        );
+    }
+}
@@ -7,19 +7,23 @@ use itertools::Itertools;
 use crate::ast::analyzed::TypedExpression;
 use crate::ast::parsed::{
     self,
-    types::{ArrayType, Type, TypeScheme},
+    asm::{Part, SymbolPath},
+    types::{ArrayType, FunctionType, Type, TypeScheme},
     ArrayLiteral, EnumDeclaration, EnumVariant, FunctionDefinition, FunctionKind, LambdaExpression,
-    PilStatement, PolynomialName, SelectedExpressions, TraitDeclaration, TraitFunction,
+    MatchArm, MatchExpression, NamespacedPolynomialReference, Pattern, PilStatement,
+    PolynomialName, SelectedExpressions, StructDeclaration, TraitDeclaration, TraitFunction,
+    TraitImplementation, STRUCT_CONSTRUCTOR_NAME,
 };
 
 use crate::number::DegreeType;
-use crate::parser_util::SourceRef;
+use crate::parser_util::{Error, SourceRef};
 
 use crate::ast::analyzed::{
-    Expression, FunctionValueDefinition, Identity, IdentityKind, PolynomialType, PublicDeclaration,
-    Symbol, SymbolKind,
+    DegreeRange, Expression, FunctionValueDefinition, Identity, IdentityKind, PolynomialType,
+    PublicDeclaration, Symbol, SymbolKind,
 };
 
+use crate::pil_analyzer::type_builtins::{operator_trait_function, operator_traits};
 use crate::pil_analyzer::type_processor::TypeProcessor;
 use crate::pil_analyzer::{untyped_evaluator, AnalysisDriver};
 
@@ -29,6 +33,8 @@ pub enum PILItem {
     Definition(Symbol, Option<FunctionValueDefinition>),
     PublicDeclaration(PublicDeclaration),
     Identity(Identity<SelectedExpressions<Expression>>),
+    /// A type alias with its absolute name and the aliased type.
+    TypeAlias(String, Type),
 }
 
 pub struct Counters {
@@ -101,14 +107,14 @@ impl Counters {
 pub struct StatementProcessor<'a, D> {
     driver: D,
     counters: &'a mut Counters,
-    degree: Option<DegreeType>,
+    degree: Option<DegreeRange>,
 }
 
 impl<'a, D> StatementProcessor<'a, D>
 where
     D: AnalysisDriver,
 {
-    pub fn new(driver: D, counters: &'a mut Counters, degree: Option<DegreeType>) -> Self {
+    pub fn new(driver: D, counters: &'a mut Counters, degree: Option<DegreeRange>) -> Self {
         StatementProcessor {
             driver,
             counters,
@@ -116,12 +122,14 @@ where
         }
     }
 
-    pub fn handle_statement(&mut self, statement: PilStatement) -> Vec<PILItem> {
+    /// Processes a statement into PIL items.
+    /// Returns an error if a reference inside the statement cannot be resolved.
+    pub fn handle_statement(&mut self, statement: PilStatement) -> Result<Vec<PILItem>, Error> {
         match statement {
             PilStatement::Include(_, _) => {
                 panic!("Includes must be handled outside the statement processor.")
             }
-            PilStatement::Namespace(_, _, _) => {
+            PilStatement::Namespace(_, _, _, _) => {
                 panic!("Namespaces must be handled outside the statement processor.")
             }
             PilStatement::PolynomialDefinition(source, name, value) => self
@@ -143,15 +151,31 @@ where
                     polynomials,
                     PolynomialType::Constant,
                 ),
-            PilStatement::PolynomialConstantDefinition(source, name, definition) => self
-                .handle_symbol_definition(
+            PilStatement::PolynomialConstantDefinition(source, name, mut definition) => {
+                // `col fixed f(i) { e }` and `let f: col = |i| e;` are printed the same way,
+                // so we remove the block to analyze them to the same value.
+                if let FunctionDefinition::Expression(parsed::Expression::LambdaExpression(
+                    _,
+                    LambdaExpression { body, .. },
+                )) = &mut definition
+                {
+                    if let parsed::Expression::BlockExpression(_, block) = body.as_mut() {
+                        if block.statements.is_empty() {
+                            if let Some(expr) = block.expr.take() {
+                                *body = expr;
+                            }
+                        }
+                    }
+                }
+                self.handle_symbol_definition(
                     source,
                     name,
                     SymbolKind::Poly(PolynomialType::Constant),
                     None,
                     Some(Type::Col.into()),
                     Some(definition),
-                ),
+                )
+            }
             PilStatement::PolynomialCommitDeclaration(source, stage, polynomials, None) => self
                 .handle_polynomial_declarations(
                     source,
@@ -167,7 +191,7 @@ where
             ) => {
                 assert!(polynomials.len() == 1);
                 let (name, ty) =
-                    self.name_and_type_from_polynomial_name(polynomials.pop().unwrap());
+                    self.name_and_type_from_polynomial_name(&source, polynomials.pop().unwrap())?;
 
                 self.handle_symbol_definition(
                     source,
@@ -192,6 +216,9 @@ where
                         enum_declaration.clone(),
                     )),
                 ),
+            PilStatement::StructDeclaration(source, struct_decl) => {
+                self.handle_struct_declaration(source, struct_decl)
+            }
             PilStatement::TraitDeclaration(source, trait_decl) => self.handle_symbol_definition(
                 source,
                 trait_decl.name.clone(),
@@ -200,34 +227,48 @@ where
                 None,
                 Some(FunctionDefinition::TraitDeclaration(trait_decl.clone())),
             ),
+            PilStatement::TraitImplementation(source, trait_impl) => {
+                self.handle_trait_implementation(source, trait_impl)
+            }
+            PilStatement::TypeAlias(source, name, ty) => {
+                let ty = self
+                    .type_processor(&Default::default())
+                    .process_type(ty)
+                    .map_err(|e| source.with_error(e))?;
+                Ok(vec![PILItem::TypeAlias(
+                    self.driver.resolve_decl(&name),
+                    ty,
+                )])
+            }
             _ => self.handle_identity_statement(statement),
         }
     }
 
     fn name_and_type_from_polynomial_name(
         &mut self,
+        source: &SourceRef,
         PolynomialName { name, array_size }: PolynomialName,
-    ) -> (String, Option<Type>) {
+    ) -> Result<(String, Option<Type>), Error> {
         let ty = Some(match array_size {
             None => Type::Col,
             Some(len) => {
                 let length = untyped_evaluator::evaluate_expression_to_int(self.driver, len)
-                    .map(|length| {
-                        length
-                            .try_into()
-                            .unwrap_or_else(|_| panic!("Array length too large."))
-                    })
                     .map_err(|e| {
-                        panic!("Error evaluating length of array of witness columns {name}:\n{e}")
-                    })
-                    .ok();
+                        source.with_error(format!(
+                            "Error evaluating length of array of witness columns {name}:\n{e}"
+                        ))
+                    })?
+                    .try_into()
+                    .map_err(|_| {
+                        source.with_error(format!("Array length of columns {name} too large."))
+                    })?;
                 Type::Array(ArrayType {
                     base: Box::new(Type::Col),
-                    length,
+                    length: Some(length),
                 })
             }
         });
-        (name, ty)
+        Ok((name, ty))
     }
 
     fn handle_generic_definition(
@@ -236,30 +277,35 @@ where
         name: String,
         type_scheme: Option<TypeScheme<parsed::Expression>>,
         value: Option<parsed::Expression>,
-    ) -> Vec<PILItem> {
-        let type_scheme = type_scheme.map(|ts| {
-            let vars = ts.vars;
-            let duplicates = vars.vars().duplicates().collect::<Vec<_>>();
-            if !duplicates.is_empty() {
-                panic!(
-                    "Duplicate type variables in declaration of \"{name}\":\n{}",
-                    duplicates.iter().format(", ")
-                );
-            }
-            let declared_type_vars = vars.vars().collect::<HashSet<_>>();
-            let ty = self.type_processor(&declared_type_vars).process_type(ts.ty);
-            let contained_type_vars = ty.contained_type_vars().collect::<HashSet<_>>();
-            if contained_type_vars != declared_type_vars {
-                assert!(contained_type_vars.is_subset(&declared_type_vars));
-                panic!(
-                    "Unused type variable(s) in declaration: {}\nlet<{vars}> {name}: {ty}",
-                    declared_type_vars
-                        .difference(&contained_type_vars)
-                        .format(", ")
-                );
-            };
-            TypeScheme { vars, ty }
-        });
+    ) -> Result<Vec<PILItem>, Error> {
+        let type_scheme = type_scheme
+            .map(|ts| {
+                let vars = ts.vars;
+                let duplicates = vars.vars().duplicates().collect::<Vec<_>>();
+                if !duplicates.is_empty() {
+                    return Err(source.with_error(format!(
+                        "Duplicate type variables in declaration of \"{name}\":\n{}",
+                        duplicates.iter().format(", ")
+                    )));
+                }
+                let declared_type_vars = vars.vars().collect::<HashSet<_>>();
+                let ty = self
+                    .type_processor(&declared_type_vars)
+                    .process_type(ts.ty)
+                    .map_err(|e| source.with_error(e))?;
+                let contained_type_vars = ty.contained_type_vars().collect::<HashSet<_>>();
+                if contained_type_vars != declared_type_vars {
+                    assert!(contained_type_vars.is_subset(&declared_type_vars));
+                    return Err(source.with_error(format!(
+                        "Unused type variable(s) in declaration: {}\nlet<{vars}> {name}: {ty}",
+                        declared_type_vars
+                            .difference(&contained_type_vars)
+                            .format(", ")
+                    )));
+                };
+                Ok(TypeScheme { vars, ty })
+            })
+            .transpose()?;
 
         match value {
             None => {
@@ -270,16 +316,17 @@ where
                         let ty = ts.ty;
                         if let Type::Array(ArrayType { base, length }) = &ty {
                             if base.as_ref() != &Type::Col {
-                                panic!("Symbol {name} is declared without value and thus must be a witness column array, but its type is {ty} instead of col[].");
+                                return Err(source.with_error(format!("Symbol {name} is declared without value and thus must be a witness column array, but its type is {ty} instead of col[].")));
                             }
                             if length.is_none() {
-                                panic!("Explicit array length required for column {name}: {ty}");
+                                return Err(source.with_error(format!("Explicit array length required for column {name}: {ty}")));
                             }
                         } else if ty != Type::Col {
-                            panic!("Symbol {name} is declared without value and thus must be a witness column, but its type is {ty} instead of col.");
+                            return Err(source.with_error(format!("Symbol {name} is declared without value and thus must be a witness column, but its type is {ty} instead of col.")));
                         }
-                        ty
+                        Ok(ty)
                     })
+                    .transpose()?
                     .unwrap_or(Type::Col);
                 self.handle_symbol_definition(
                     source,
@@ -308,6 +355,164 @@ where
         }
     }
 
+    /// Structs are analyzed as enums with a single variant `new` (the constructor),
+    /// and for each field, an accessor function `Name::field` is added,
+    /// which is defined as `|s| match s { Name::new(_, .., v, .., _) => v }`.
+    fn handle_struct_declaration(
+        &mut self,
+        source: SourceRef,
+        struct_decl: StructDeclaration<parsed::Expression>,
+    ) -> Result<Vec<PILItem>, Error> {
+        let name = struct_decl.name.clone();
+        let mut items = self.handle_symbol_definition(
+            source.clone(),
+            name.clone(),
+            SymbolKind::Other(),
+            None,
+            None,
+            Some(FunctionDefinition::TypeDeclaration(
+                struct_decl.to_enum_declaration(),
+            )),
+        )?;
+
+        let type_vars = struct_decl.type_vars.vars().collect::<HashSet<_>>();
+        let generic_args = (!struct_decl.type_vars.is_empty()).then(|| {
+            struct_decl
+                .type_vars
+                .vars()
+                .map(|v| Type::NamedType(SymbolPath::from_identifier(v.clone()), None))
+                .collect::<Vec<_>>()
+        });
+        let struct_type = Type::NamedType(SymbolPath::from_identifier(name.clone()), generic_args);
+        let constructor = SymbolPath::from_parts(
+            [&name, &*STRUCT_CONSTRUCTOR_NAME]
+                .into_iter()
+                .map(|p| Part::Named(p.clone())),
+        );
+        for (index, field) in struct_decl.fields.iter().enumerate() {
+            let ty = Type::Function(FunctionType {
+                params: vec![struct_type.clone()],
+                value: Box::new(field.ty.clone()),
+            });
+            let ty = self
+                .type_processor(&type_vars)
+                .process_type(ty)
+                .map_err(|e| source.with_error(e))?;
+            let field_patterns = (0..struct_decl.fields.len())
+                .map(|i| {
+                    if i == index {
+                        Pattern::Variable(source.clone(), "v".to_string())
+                    } else {
+                        Pattern::CatchAll(source.clone())
+                    }
+                })
+                .collect();
+            let accessor = parsed::Expression::LambdaExpression(
+                source.clone(),
+                LambdaExpression {
+                    kind: FunctionKind::Pure,
+                    params: vec![Pattern::Variable(source.clone(), "s".to_string())],
+                    body: Box::new(parsed::Expression::MatchExpression(
+                        source.clone(),
+                        MatchExpression {
+                            scrutinee: Box::new(parsed::Expression::Reference(
+                                source.clone(),
+                                NamespacedPolynomialReference::from_identifier("s".to_string()),
+                            )),
+                            arms: vec![MatchArm {
+                                pattern: Pattern::Enum(
+                                    source.clone(),
+                                    constructor.clone(),
+                                    Some(field_patterns),
+                                ),
+                                value: parsed::Expression::Reference(
+                                    source.clone(),
+                                    NamespacedPolynomialReference::from_identifier("v".to_string()),
+                                ),
+                            }],
+                        },
+                    )),
+                    outer_var_references: Default::default(),
+                },
+            );
+            let symbol = Symbol {
+                id: self.counters.dispense_symbol_id(SymbolKind::Other(), None),
+                source: source.clone(),
+                absolute_name: self
+                    .driver
+                    .resolve_namespaced_decl(&[&name, &field.name])
+                    .to_dotted_string(),
+                stage: None,
+                kind: SymbolKind::Other(),
+                length: None,
+                degree: self.degree,
+            };
+            let value = FunctionValueDefinition::Expression(TypedExpression {
+                e: self
+                    .expression_processor(&type_vars)
+                    .process_expression(accessor)?,
+                type_scheme: Some(TypeScheme {
+                    vars: struct_decl.type_vars.clone(),
+                    ty,
+                }),
+            });
+            items.push(PILItem::Definition(symbol, Some(value)));
+        }
+        Ok(items)
+    }
+
+    /// A trait implementation `impl Add<Point> { add: f }` is analyzed into the function
+    /// `Point::add` with value `f` and the declared type `Point, Point -> Point`.
+    /// Type inference uses these functions to decide which named types satisfy
+    /// the trait, and the evaluator calls them when the operator is applied.
+    fn handle_trait_implementation(
+        &mut self,
+        source: SourceRef,
+        trait_impl: TraitImplementation<parsed::Expression>,
+    ) -> Result<Vec<PILItem>, Error> {
+        let TraitImplementation {
+            name: trait_name,
+            type_name,
+            mut functions,
+        } = trait_impl;
+        let named_type = Type::NamedType(SymbolPath::from_identifier(type_name.clone()), None);
+        let Some((function_name, ty)) = operator_trait_function(&trait_name, &named_type) else {
+            return Err(source.with_error(format!(
+                "Trait {trait_name} cannot be implemented. Only the traits of overloadable operators can be implemented: {}",
+                operator_traits().sorted().format(", ")
+            )));
+        };
+        if functions.len() != 1 || functions[0].0 != function_name {
+            return Err(source.with_error(format!(
+                "Expected exactly the function {function_name} in the implementation of {trait_name}<{type_name}>."
+            )));
+        }
+        let (_, value) = functions.pop().unwrap();
+        let ty = self
+            .type_processor(&Default::default())
+            .process_number_type(ty)
+            .map_err(|e| source.with_error(e))?;
+        let symbol = Symbol {
+            id: self.counters.dispense_symbol_id(SymbolKind::Other(), None),
+            source: source.clone(),
+            absolute_name: self
+                .driver
+                .resolve_namespaced_decl(&[&type_name, &function_name.to_string()])
+                .to_dotted_string(),
+            stage: None,
+            kind: SymbolKind::Other(),
+            length: None,
+            degree: self.degree,
+        };
+        let value = FunctionValueDefinition::Expression(TypedExpression {
+            e: self
+                .expression_processor(&Default::default())
+                .process_expression(value)?,
+            type_scheme: Some(ty.into()),
+        });
+        Ok(vec![PILItem::Definition(symbol, Some(value))])
+    }
+
     fn symbol_kind_from_type(ts: &TypeScheme) -> SymbolKind {
         if !ts.vars.is_empty() {
             return SymbolKind::Other();
@@ -327,15 +532,26 @@ where
         }
     }
 
-    fn handle_identity_statement(&mut self, statement: PilStatement) -> Vec<PILItem> {
+    fn handle_identity_statement(
+        &mut self,
+        statement: PilStatement,
+    ) -> Result<Vec<PILItem>, Error> {
         let (source, kind, left, right) = match statement {
+            PilStatement::LabeledIdentity(_, label, identity) => {
+                let mut items = self.handle_identity_statement(*identity)?;
+                let [PILItem::Identity(identity)] = &mut items[..] else {
+                    unreachable!()
+                };
+                identity.label = Some(label);
+                return Ok(items);
+            }
             PilStatement::Expression(source, expression) => (
                 source,
                 IdentityKind::Polynomial,
                 SelectedExpressions {
                     selector: Some(
                         self.expression_processor(&Default::default())
-                            .process_expression(expression),
+                            .process_expression(expression)?,
                     ),
                     expressions: Box::new(ArrayLiteral { items: vec![] }.into()),
                 },
@@ -345,25 +561,25 @@ where
                 source,
                 IdentityKind::Plookup,
                 self.expression_processor(&Default::default())
-                    .process_selected_expressions(key),
+                    .process_selected_expressions(key)?,
                 self.expression_processor(&Default::default())
-                    .process_selected_expressions(haystack),
+                    .process_selected_expressions(haystack)?,
             ),
             PilStatement::PermutationIdentity(source, left, right) => (
                 source,
                 IdentityKind::Permutation,
                 self.expression_processor(&Default::default())
-                    .process_selected_expressions(left),
+                    .process_selected_expressions(left)?,
                 self.expression_processor(&Default::default())
-                    .process_selected_expressions(right),
+                    .process_selected_expressions(right)?,
             ),
             PilStatement::ConnectIdentity(source, left, right) => (
                 source,
                 IdentityKind::Connect,
                 self.expression_processor(&Default::default())
-                    .process_vec_into_selected_expression(left),
+                    .process_vec_into_selected_expression(left)?,
                 self.expression_processor(&Default::default())
-                    .process_vec_into_selected_expression(right),
+                    .process_vec_into_selected_expression(right)?,
             ),
             // TODO at some point, these should all be caught by the type checker.
             _ => {
@@ -371,13 +587,14 @@ where
             }
         };
 
-        vec![PILItem::Identity(Identity {
+        Ok(vec![PILItem::Identity(Identity {
             id: self.counters.dispense_identity_id(),
             kind,
             source,
+            label: None,
             left,
             right,
-        })]
+        })])
     }
 
     fn handle_polynomial_declarations(
@@ -386,11 +603,11 @@ where
         stage: Option<u32>,
         polynomials: Vec<PolynomialName>,
         polynomial_type: PolynomialType,
-    ) -> Vec<PILItem> {
+    ) -> Result<Vec<PILItem>, Error> {
         polynomials
             .into_iter()
-            .flat_map(|poly_name| {
-                let (name, ty) = self.name_and_type_from_polynomial_name(poly_name);
+            .map(|poly_name| {
+                let (name, ty) = self.name_and_type_from_polynomial_name(&source, poly_name)?;
                 self.handle_symbol_definition(
                     source.clone(),
                     name,
@@ -400,6 +617,7 @@ where
                     None,
                 )
             })
+            .flatten_ok()
             .collect()
     }
 
@@ -411,19 +629,19 @@ where
         stage: Option<u32>,
         type_scheme: Option<TypeScheme>,
         value: Option<FunctionDefinition>,
-    ) -> Vec<PILItem> {
-        let length = type_scheme.as_ref().and_then(|t| {
-            if symbol_kind == SymbolKind::Other() {
-                None
-            } else if let Type::Array(ArrayType { length, base: _ }) = t.ty {
-                if length.is_none() && symbol_kind != SymbolKind::Other() {
-                    panic!("Explicit array length required for column {name}.");
+    ) -> Result<Vec<PILItem>, Error> {
+        let length = match type_scheme.as_ref().map(|t| &t.ty) {
+            Some(Type::Array(ArrayType { length, base: _ }))
+                if symbol_kind != SymbolKind::Other() =>
+            {
+                if length.is_none() {
+                    return Err(source
+                        .with_error(format!("Explicit array length required for column {name}.")));
                 }
-                length
-            } else {
-                None
+                *length
             }
-        });
+            _ => None,
+        };
         assert!(stage.is_none() || symbol_kind == SymbolKind::Poly(PolynomialType::Committed));
 
         let id = self.counters.dispense_symbol_id(symbol_kind, length);
@@ -443,7 +661,9 @@ where
             // For enums, we add PILItems both for the enum itself and also for all
             // its type constructors.
             assert_eq!(symbol_kind, SymbolKind::Other());
-            let enum_decl = self.process_enum_declaration(enum_decl);
+            let enum_decl = self
+                .process_enum_declaration(enum_decl)
+                .map_err(|e| source.with_error(e))?;
             let shared_enum_decl = Arc::new(enum_decl.clone());
             let var_items = enum_decl.variants.iter().map(|variant| {
                 let var_symbol = Symbol {
@@ -464,14 +684,16 @@ where
                 );
                 PILItem::Definition(var_symbol, Some(value))
             });
-            return iter::once(PILItem::Definition(
+            return Ok(iter::once(PILItem::Definition(
                 symbol,
                 Some(FunctionValueDefinition::TypeDeclaration(enum_decl.clone())),
             ))
             .chain(var_items)
-            .collect();
+            .collect());
         } else if let Some(FunctionDefinition::TraitDeclaration(trait_decl)) = value {
-            let trait_decl = self.process_trait_declaration(trait_decl);
+            let trait_decl = self
+                .process_trait_declaration(trait_decl)
+                .map_err(|e| source.with_error(e))?;
             let shared_trait_decl = Arc::new(trait_decl.clone());
             let trait_functions = trait_decl.functions.iter().map(|function| {
                 let f_symbol = Symbol {
@@ -492,60 +714,69 @@ where
                 );
                 PILItem::Definition(f_symbol, Some(value))
             });
-            return iter::once(PILItem::Definition(
+            return Ok(iter::once(PILItem::Definition(
                 symbol,
                 Some(FunctionValueDefinition::TraitDeclaration(
                     trait_decl.clone(),
                 )),
             ))
             .chain(trait_functions)
-            .collect();
+            .collect());
         }
 
-        let value = value.map(|v| match v {
-            FunctionDefinition::Expression(expr) => {
-                if symbol_kind == SymbolKind::Poly(PolynomialType::Committed) {
-                    // The only allowed value for a witness column is a query function.
-                    assert!(matches!(
-                        expr,
-                        parsed::Expression::LambdaExpression(
-                            _,
-                            LambdaExpression {
-                                kind: FunctionKind::Query,
-                                ..
-                            }
-                        )
-                    ));
+        let value = value
+            .map(|v| match v {
+                FunctionDefinition::Expression(expr) => {
+                    if symbol_kind == SymbolKind::Poly(PolynomialType::Committed) {
+                        // The only allowed value for a witness column is a query function.
+                        assert!(matches!(
+                            expr,
+                            parsed::Expression::LambdaExpression(
+                                _,
+                                LambdaExpression {
+                                    kind: FunctionKind::Query,
+                                    ..
+                                }
+                            )
+                        ));
+                        assert!(type_scheme.is_none() || type_scheme == Some(Type::Col.into()));
+                    }
+                    let type_vars = type_scheme
+                        .as_ref()
+                        .map(|ts| ts.vars.vars().collect())
+                        .unwrap_or_default();
+                    Ok(FunctionValueDefinition::Expression(TypedExpression {
+                        e: self
+                            .expression_processor(&type_vars)
+                            .process_expression(expr)?,
+                        type_scheme,
+                    }))
+                }
+                FunctionDefinition::Array(value) => {
+                    let degree = self.degree.unwrap();
+                    let degree = degree.try_into_unique().ok_or_else(|| {
+                        source.with_error(format!(
+                            "Array definition of {absolute_name} requires a single degree, but the degree of the namespace is the range {degree}."
+                        ))
+                    })?;
+                    let size = value.solve(degree);
+                    let expression = self
+                        .expression_processor(&Default::default())
+                        .process_array_expression(value, size)?;
+                    assert_eq!(
+                        expression.iter().map(|e| e.size()).sum::<DegreeType>(),
+                        degree
+                    );
                     assert!(type_scheme.is_none() || type_scheme == Some(Type::Col.into()));
+                    Ok(FunctionValueDefinition::Array(expression))
                 }
-                let type_vars = type_scheme
-                    .as_ref()
-                    .map(|ts| ts.vars.vars().collect())
-                    .unwrap_or_default();
-                FunctionValueDefinition::Expression(TypedExpression {
-                    e: self
-                        .expression_processor(&type_vars)
-                        .process_expression(expr),
-                    type_scheme,
-                })
-            }
-            FunctionDefinition::Array(value) => {
-                let size = value.solve(self.degree.unwrap());
-                let expression = self
-                    .expression_processor(&Default::default())
-                    .process_array_expression(value, size);
-                assert_eq!(
-                    expression.iter().map(|e| e.size()).sum::<DegreeType>(),
-                    self.degree.unwrap()
-                );
-                assert!(type_scheme.is_none() || type_scheme == Some(Type::Col.into()));
-                FunctionValueDefinition::Array(expression)
-            }
-            FunctionDefinition::TypeDeclaration(_) | FunctionDefinition::TraitDeclaration(_) => {
-                unreachable!()
-            }
-        });
-        vec![PILItem::Definition(symbol, value)]
+                FunctionDefinition::TypeDeclaration(_)
+                | FunctionDefinition::TraitDeclaration(_) => {
+                    unreachable!()
+                }
+            })
+            .transpose()?;
+        Ok(vec![PILItem::Definition(symbol, value)])
     }
 
     fn handle_public_declaration(
@@ -555,11 +786,12 @@ where
         poly: parsed::NamespacedPolynomialReference,
         array_index: Option<parsed::Expression>,
         index: parsed::Expression,
-    ) -> Vec<PILItem> {
+    ) -> Result<Vec<PILItem>, Error> {
         let id = self.counters.dispense_public_id();
         let polynomial = self
             .expression_processor(&Default::default())
-            .process_namespaced_polynomial_reference(poly);
+            .process_namespaced_polynomial_reference(poly)
+            .map_err(|e| source.with_error(e))?;
         let array_index = array_index.map(|i| {
             let index: u64 = untyped_evaluator::evaluate_expression_to_int(self.driver, i)
                 .unwrap()
@@ -568,17 +800,17 @@ where
             assert!(index <= usize::MAX as u64);
             index as usize
         });
-        vec![PILItem::PublicDeclaration(PublicDeclaration {
+        Ok(vec![PILItem::PublicDeclaration(PublicDeclaration {
             id,
             source,
-            name: name.to_string(),
+            name: self.driver.resolve_decl(&name),
             polynomial,
             array_index,
             index: untyped_evaluator::evaluate_expression_to_int(self.driver, index)
                 .unwrap()
                 .try_into()
                 .unwrap(),
-        })]
+        })])
     }
 
     fn expression_processor<'b>(
@@ -595,52 +827,57 @@ where
     fn process_enum_declaration(
         &self,
         enum_decl: EnumDeclaration<parsed::Expression>,
-    ) -> EnumDeclaration {
+    ) -> Result<EnumDeclaration, String> {
         let type_vars = enum_decl.type_vars.vars().collect();
         let variants = enum_decl
             .variants
             .into_iter()
             .map(|v| self.process_enum_variant(v, &type_vars))
-            .collect();
-        EnumDeclaration {
+            .collect::<Result<_, _>>()?;
+        Ok(EnumDeclaration {
             name: self.driver.resolve_decl(&enum_decl.name),
             type_vars: enum_decl.type_vars,
             variants,
-        }
+        })
     }
 
     fn process_enum_variant(
         &self,
         enum_variant: EnumVariant<parsed::Expression>,
         type_vars: &HashSet<&String>,
-    ) -> EnumVariant {
-        EnumVariant {
+    ) -> Result<EnumVariant, String> {
+        Ok(EnumVariant {
             name: enum_variant.name,
-            fields: enum_variant.fields.map(|f| {
-                f.into_iter()
-                    .map(|ty| self.type_processor(type_vars).process_type(ty))
-                    .collect()
-            }),
-        }
+            fields: enum_variant
+                .fields
+                .map(|f| {
+                    f.into_iter()
+                        .map(|ty| self.type_processor(type_vars).process_type(ty))
+                        .collect()
+                })
+                .transpose()?,
+        })
     }
 
     fn process_trait_declaration(
         &self,
         trait_decl: parsed::TraitDeclaration<parsed::Expression>,
-    ) -> TraitDeclaration {
+    ) -> Result<TraitDeclaration, String> {
         let type_vars = trait_decl.type_vars.iter().collect();
         let functions = trait_decl
             .functions
             .into_iter()
-            .map(|f| TraitFunction {
-                name: f.name,
-                ty: self.type_processor(&type_vars).process_type(f.ty),
+            .map(|f| {
+                Ok(TraitFunction {
+                    name: f.name,
+                    ty: self.type_processor(&type_vars).process_type(f.ty)?,
+                })
             })
-            .collect();
-        TraitDeclaration {
+            .collect::<Result<_, String>>()?;
+        Ok(TraitDeclaration {
             name: self.driver.resolve_decl(&trait_decl.name),
             type_vars: trait_decl.type_vars,
             functions,
-        }
+        })
     }
 }
@@ -0,0 +1,85 @@
+use crate::number::GoldilocksField;
+use crate::pil_analyzer::analyze_string;
+
+/// Analyzes `src`, prints the result, analyzes the printed code again and asserts
+/// that both analyzed files are equal (ignoring source references).
+/// This guards against the printer and the analyzer drifting apart.
+///
+/// # Panics
+///
+/// Panics if either of the analysis steps fails or if the results differ.
+pub fn assert_roundtrip(src: &str) {
+    let analyzed = analyze_string::<GoldilocksField>(src)
+        .unwrap_or_else(|e| panic!("Failed to analyze input:\n{e}"));
+    let printed = analyzed.to_string();
+    let reanalyzed = analyze_string::<GoldilocksField>(&printed)
+        .unwrap_or_else(|e| panic!("Failed to analyze printed code:\n{e}\n{printed}"));
+    assert!(
+        analyzed == reanalyzed,
+        "Re-analyzed code differs from the original.\nPrinted code:\n{printed}\nRe-printed code:\n{reanalyzed}"
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use crate::number::GoldilocksField;
+    use crate::pil_analyzer::analyze_string;
+
+    use super::assert_roundtrip;
+
+    #[test]
+    fn equality_ignores_source_refs() {
+        let analyze = |src| analyze_string::<GoldilocksField>(src).unwrap();
+        let original = analyze("namespace N(4);\n    col witness x;\n    x' = x;");
+        assert!(original == analyze("namespace N(4);  col witness x;  x'=x;"));
+        assert!(original != analyze("namespace N(4);\n    col witness x;\n    x' = x + 1;"));
+    }
+
+    #[test]
+    fn roundtrip_columns_and_identities() {
+        assert_roundtrip(
+            r#"let N: int = 8;
+namespace Main(N);
+    col fixed FIRST = [1] + [0]*;
+    col fixed BYTE(i) { i % 256 };
+    let ODD: col = |i| i % 2;
+    col witness x, y[2];
+    col witness stage(1) z;
+    let sum = x + y[0];
+    col sq = x * x;
+    let inter: expr[2] = [x + 1, y[1]];
+    FIRST * (x - 1) = 0;
+    x' = sq + sum + inter[0];
+    [x] in [BYTE];
+    ODD $ [y[0], y[1]] is [x, sq];
+    z = std::prelude::challenge(0, 1) * x;
+    #[label("bool_x")]
+    x * (1 - x) = 0;
+    public out = x(N - 1);
+"#,
+        );
+    }
+
+    #[test]
+    fn roundtrip_functions_and_types() {
+        assert_roundtrip(
+            r#"namespace N(16);
+    enum Op { Add, Mul(int) }
+    let apply: Op, int -> int = |op, x| match op {
+        Op::Add => x + 1,
+        Op::Mul(k) => x * k,
+    };
+    let<T: Add> double: T -> T = |x| x + x;
+    col fixed F(i) { apply(Op::Mul(3), double(i)) };
+    col witness w;
+    w = F;
+"#,
+        );
+    }
+
+    #[test]
+    #[should_panic = "Failed to analyze input"]
+    fn roundtrip_invalid_input() {
+        assert_roundtrip("namespace N(16);\n    col witness x;\n    x = y;");
+    }
+}
@@ -38,17 +38,55 @@ pub fn type_for_reference(declared: &Type) -> Type {
 
 lazy_static! {
     static ref BUILTIN_SCHEMES: HashMap<String, TypeScheme> = [
+        ("std::array::contains", ("T: Eq", "T[], T -> bool")),
+        ("std::array::find", ("T", "T[], (T -> bool) -> int")),
+        ("std::array::flatten", ("T", "T[][] -> T[]")),
+        (
+            "std::array::fold",
+            ("T1, T2", "T1[], T2, (T2, T1 -> T2) -> T2")
+        ),
+        ("std::array::from_tuple2", ("T", "(T, T) -> T[]")),
+        ("std::array::from_tuple3", ("T", "(T, T, T) -> T[]")),
+        ("std::array::from_tuple4", ("T", "(T, T, T, T) -> T[]")),
         ("std::array::len", ("T", "T[] -> int")),
+        ("std::array::map", ("T1, T2", "T1[], (T1 -> T2) -> T2[]")),
+        ("std::array::new", ("T", "int, (int -> T) -> T[]")),
+        ("std::array::reverse", ("T", "T[] -> T[]")),
+        ("std::array::sum", ("T: Add", "T[] -> T")),
+        ("std::array::to_tuple2", ("T", "T[] -> (T, T)")),
+        ("std::array::to_tuple3", ("T", "T[] -> (T, T, T)")),
+        ("std::array::to_tuple4", ("T", "T[] -> (T, T, T, T)")),
+        (
+            "std::array::zip",
+            ("T1, T2, T3", "T1[], T2[], (T1, T2 -> T3) -> T3[]")
+        ),
         ("std::check::panic", ("", "string -> !")),
+        ("std::check::require", ("", "bool, string -> ()")),
+        ("std::convert::bool", ("", "int -> bool")),
         ("std::convert::expr", ("T: FromLiteral", "T -> expr")),
         ("std::convert::fe", ("T: FromLiteral", "T -> fe")),
         ("std::convert::int", ("T: FromLiteral", "T -> int")),
+        ("std::convert::to_bits", ("", "int, int -> int[]")),
+        ("std::convert::to_string", ("T: ToString", "T -> string")),
         ("std::convert::expr", ("T: FromLiteral", "T -> expr")),
         ("std::debug::print", ("T: ToString", "T -> ()")),
+        ("std::field::bits", ("", "-> int")),
         ("std::field::modulus", ("", "-> int")),
+        ("std::fmt::format", ("T: ToString", "string, T[] -> string")),
+        ("std::math::ff::inv_field", ("", "fe -> fe")),
         ("std::prelude::challenge", ("", "int, int -> expr")),
+        (
+            "std::prover::capture_constraints",
+            ("", "(-> ()) -> std::prelude::Constr[]")
+        ),
         ("std::prover::degree", ("", "-> int")),
+        ("std::prover::min_degree", ("", "-> int")),
+        ("std::prover::max_degree", ("", "-> int")),
         ("std::prover::eval", ("", "expr -> fe")),
+        ("std::prover::new_witness_col", ("", "string -> expr")),
+        ("std::utils::cartesian_product", ("T, U", "T[], U[] -> (T, U)[]")),
+        ("std::utils::is_power_of_two", ("", "int -> bool")),
+        ("std::utils::next_power_of_two", ("", "int -> int")),
     ]
     .into_iter()
     .map(|(name, (vars, ty))| { (name.to_string(), parse_type_scheme(vars, ty)) })
@@ -84,11 +122,25 @@ lazy_static! {
     static ref UNARY_OPERATOR_SCHEMES: HashMap<UnaryOperator, TypeScheme> = [
         (UnaryOperator::Minus, ("T: Neg", "T -> T")),
         (UnaryOperator::LogicalNot, ("", "bool -> bool")),
+        (UnaryOperator::BinaryNot, ("", "int -> int")),
         (UnaryOperator::Next, ("", "expr -> expr")),
     ]
     .into_iter()
     .map(|(op, (vars, ty))| (op, parse_type_scheme(vars, ty)))
     .collect();
+    /// The traits that user-defined named types can implement to overload operators,
+    /// with the name and type of the function implementing the operator,
+    /// where `T` stands for the implementing type.
+    static ref OPERATOR_TRAIT_FUNCTIONS: HashMap<&'static str, (&'static str, Type)> = [
+        ("Add", ("add", "T, T -> T")),
+        ("Sub", ("sub", "T, T -> T")),
+        ("Mul", ("mul", "T, T -> T")),
+        ("Pow", ("pow", "T, int -> T")),
+        ("Neg", ("neg", "T -> T")),
+    ]
+    .into_iter()
+    .map(|(name, (function, ty))| (name, (function, parse_type_scheme("T", ty).ty)))
+    .collect();
     static ref CONSTR_FUNCTION_STATEMENT_TYPE: ExpectedType = ExpectedType {
         ty: Type::NamedType(SymbolPath::from_str("std::prelude::Constr").unwrap(), None),
         allow_array: true,
@@ -108,6 +160,45 @@ pub fn unary_operator_scheme(op: UnaryOperator) -> TypeScheme {
     UNARY_OPERATOR_SCHEMES[&op].clone()
 }
 
+/// If the trait `trait_name` can be implemented by user-defined named types,
+/// returns the name of the function implementing the operator and its type
+/// for the implementing type `ty`.
+pub fn operator_trait_function(trait_name: &str, ty: &Type) -> Option<(&'static str, Type)> {
+    OPERATOR_TRAIT_FUNCTIONS
+        .get(trait_name)
+        .map(|(function, function_type)| {
+            let mut function_type = function_type.clone();
+            function_type.substitute_type_vars(&[("T".to_string(), ty.clone())].into());
+            (*function, function_type)
+        })
+}
+
+/// Returns the names of all traits that user-defined named types can implement.
+pub fn operator_traits() -> impl Iterator<Item = &'static str> {
+    OPERATOR_TRAIT_FUNCTIONS.keys().copied()
+}
+
+/// Returns the trait that has to be implemented by user-defined named types
+/// to use them with the binary operator `op`, if it can be overloaded.
+pub fn binary_operator_trait(op: BinaryOperator) -> Option<&'static str> {
+    match op {
+        BinaryOperator::Add => Some("Add"),
+        BinaryOperator::Sub => Some("Sub"),
+        BinaryOperator::Mul => Some("Mul"),
+        BinaryOperator::Pow => Some("Pow"),
+        _ => None,
+    }
+}
+
+/// Returns the trait that has to be implemented by user-defined named types
+/// to use them with the unary operator `op`, if it can be overloaded.
+pub fn unary_operator_trait(op: UnaryOperator) -> Option<&'static str> {
+    match op {
+        UnaryOperator::Minus => Some("Neg"),
+        _ => None,
+    }
+}
+
 /// Returns the type allowed at statement level in `constr` functions.
 pub fn constr_function_statement_type() -> ExpectedType {
     CONSTR_FUNCTION_STATEMENT_TYPE.clone()
@@ -153,6 +244,7 @@ pub fn elementary_type_bounds(ty: &Type) -> &'static [&'static str] {
             "Eq",
         ],
         Type::Col => &[],
+        // Adding arrays concatenates them, see `std::array::add` for elementwise addition.
         Type::Array(_) => &["Add"],
         Type::Tuple(_) => &[],
         Type::Function(_) => &[],
@@ -1,26 +1,28 @@
 use std::collections::{BTreeSet, HashMap};
+use std::str::FromStr;
 
 use itertools::Itertools;
 use crate::ast::{
     analyzed::{Expression, PolynomialReference, Reference},
     parsed::{
+        asm::SymbolPath,
         display::format_type_scheme_around_name,
         types::{ArrayType, FunctionType, TupleType, Type, TypeBounds, TypeScheme},
         visitor::ExpressionVisitable,
-        ArrayLiteral, BinaryOperation, BlockExpression, FunctionCall, FunctionKind, IndexAccess,
-        LambdaExpression, LetStatementInsideBlock, MatchArm, MatchExpression, Number, Pattern,
-        SourceReference, StatementInsideBlock, UnaryOperation,
+        ArrayLiteral, BinaryOperation, BinaryOperator, BlockExpression, FunctionCall, FunctionKind,
+        IndexAccess, LambdaExpression, LetStatementInsideBlock, MatchArm, MatchExpression, Number,
+        Pattern, SliceAccess, SourceReference, StatementInsideBlock, UnaryOperation,
     },
 };
-use crate::parser_util::{Error, SourceRef};
+use crate::parser_util::SourceRef;
 
 use crate::pil_analyzer::{
     call_graph::sort_called_first,
     type_builtins::{
         binary_operator_scheme, builtin_schemes, constr_function_statement_type,
-        type_for_reference, unary_operator_scheme,
+        operator_trait_function, operator_traits, type_for_reference, unary_operator_scheme,
     },
-    type_unifier::Unifier,
+    type_unifier::{TypeError, Unifier},
 };
 
 /// Infers types on all definitions and checks type-correctness for isolated
@@ -31,7 +33,7 @@ use crate::pil_analyzer::{
 pub fn infer_types(
     definitions: HashMap<String, (Option<TypeScheme>, Option<&mut Expression>)>,
     expressions: &mut [(&mut Expression, ExpectedType)],
-) -> Result<Vec<(String, Type)>, Vec<Error>> {
+) -> Result<Vec<(String, Type)>, Vec<TypeError>> {
     TypeChecker::new().infer_types(definitions, expressions)
 }
 
@@ -89,10 +91,8 @@ impl TypeChecker {
         mut self,
         mut definitions: HashMap<String, (Option<TypeScheme>, Option<&mut Expression>)>,
         expressions: &mut [(&mut Expression, ExpectedType)],
-    ) -> Result<Vec<(String, Type)>, Vec<Error>> {
-        let type_var_mapping = self
-            .infer_types_inner(&mut definitions, expressions)
-            .map_err(|e| vec![e])?;
+    ) -> Result<Vec<(String, Type)>, Vec<TypeError>> {
+        let type_var_mapping = self.infer_types_inner(&mut definitions, expressions)?;
         self.update_type_args(&mut definitions, expressions, &type_var_mapping)?;
         Ok(definitions
             .into_iter()
@@ -109,11 +109,13 @@ impl TypeChecker {
 
     /// Returns, for each name declared with a type scheme, a mapping from
     /// the type variables used by the type checker to those used in the declaration.
+    /// If the type of a symbol or expression cannot be derived, the others are still
+    /// checked, so that all independent errors are returned together.
     fn infer_types_inner(
         &mut self,
         definitions: &mut HashMap<String, (Option<TypeScheme>, Option<&mut Expression>)>,
         expressions: &mut [(&mut Expression, ExpectedType)],
-    ) -> Result<HashMap<String, HashMap<String, Type>>, Error> {
+    ) -> Result<HashMap<String, HashMap<String, Type>>, Vec<TypeError>> {
         // TODO in order to fix type inference on recursive functions, we need to:
         // - collect all groups of functions that call each other recursively
         // - analyze each such group in an environment, where their type schemes
@@ -127,11 +129,13 @@ impl TypeChecker {
         );
 
         self.setup_declared_types(definitions);
+        self.setup_named_type_bounds();
 
         // These are the inferred types for symbols that are declared
         // as type schemes. They are compared to the declared types
         // at the end.
         let mut inferred_types: HashMap<String, Type> = Default::default();
+        let mut errors = vec![];
 
         // Now go through all symbols and derive types for the expressions.
         // While analyzing a symbol, we ignore its declared type (unless the
@@ -144,9 +148,11 @@ impl TypeChecker {
             };
 
             let (_, declared_type) = self.declared_types[&name].clone();
-            if declared_type.vars.is_empty() {
+            // An error inside a lambda leaves its parameters behind.
+            self.local_var_types.clear();
+            let result = if declared_type.vars.is_empty() {
                 self.declared_type_vars.clear();
-                self.process_concrete_symbol(declared_type.ty.clone(), value)?;
+                self.process_concrete_symbol(declared_type.ty.clone(), value)
             } else {
                 self.declared_type_vars = declared_type
                     .vars
@@ -155,34 +161,49 @@ impl TypeChecker {
                     .collect();
                 self.infer_type_of_expression(value).map(|ty| {
                     inferred_types.insert(name.to_string(), ty);
-                })?;
-            }
+                })
+            };
+            errors.extend(result.err());
         }
         self.declared_type_vars.clear();
 
-        self.check_expressions(expressions)?;
+        for (e, expected_type) in expressions {
+            self.local_var_types.clear();
+            errors.extend(self.expect_type_with_flexibility(expected_type, e).err());
+        }
+        // The remaining checks would mostly report consequences of these errors.
+        if !errors.is_empty() {
+            return Err(errors);
+        }
 
         // From this point on, the substitutions are fixed.
 
         // Now we check for all symbols that are not declared as a type scheme that they
         // can resolve to a concrete type.
-        for (name, (source_ref, declared_type)) in &self.declared_types {
+        for (name, (source_ref, declared_type)) in
+            self.declared_types.iter().sorted_by_key(|(name, _)| *name)
+        {
             if declared_type.vars.is_empty() {
                 // It is not a type scheme, see if we were able to derive a concrete type.
                 let inferred = self.type_into_substituted(declared_type.ty.clone());
                 if !inferred.is_concrete_type() {
                     let inferred_scheme = self.to_type_scheme(inferred);
-                    return Err(source_ref.with_error(
+                    errors.push(TypeError::other(
+                        source_ref,
                         format!(
                             "Could not derive a concrete type for symbol {name}.\nInferred type scheme: {}\n",
                             format_type_scheme_around_name(
                                 name,
                                 &Some(inferred_scheme),
                             )
-                        )));
+                        ),
+                    ));
                 }
             }
         }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
 
         // We check type schemes last, because only at this point do we know
         // that other types that should be concrete do not occur as type variables in the
@@ -240,12 +261,40 @@ impl TypeChecker {
         }
     }
 
+    /// Registers the operator traits implemented by user-defined named types:
+    /// A named type `N` implements an operator trait if there is a symbol `N::<function>`
+    /// declared with the type of the function implementing the operator (see
+    /// [`operator_trait_function`]). Symbols like this are created by `impl` statements.
+    fn setup_named_type_bounds(&mut self) {
+        for (name, (_, scheme)) in &self.declared_types {
+            if !scheme.vars.is_empty() {
+                continue;
+            }
+            let path = SymbolPath::from_str(name).unwrap();
+            let (Some(function_name), true) = (path.try_last_part(), path.parts().len() >= 2)
+            else {
+                continue;
+            };
+            let type_path =
+                SymbolPath::from_parts(path.parts().take(path.parts().len() - 1).cloned());
+            let ty = Type::NamedType(type_path.clone(), None);
+            for trait_name in operator_traits() {
+                if operator_trait_function(trait_name, &ty)
+                    == Some((function_name.as_str(), scheme.ty.clone()))
+                {
+                    self.unifier
+                        .add_named_type_bound(type_path.to_dotted_string(), trait_name.to_string());
+                }
+            }
+        }
+    }
+
     /// Processes the definition of a symbol that is expected to have a concrete type.
     fn process_concrete_symbol(
         &mut self,
         declared_type: Type,
         value: &mut Expression,
-    ) -> Result<(), Error> {
+    ) -> Result<(), TypeError> {
         match &declared_type {
             Type::Col => {
                 // This is a column. It means we prefer `int -> fe`, but `int -> int`
@@ -295,7 +344,7 @@ impl TypeChecker {
         expected_type: &Type,
         expr: &mut Expression,
         flexible_var: &str,
-    ) -> Result<(), Error> {
+    ) -> Result<(), TypeError> {
         self.expect_type(expected_type, expr)?;
         match self.type_into_substituted(Type::TypeVar(flexible_var.to_string())) {
             Type::Int => Ok(()),
@@ -309,12 +358,16 @@ impl TypeChecker {
                         self.type_into_substituted(t)
                     };
 
-                    expr.source_reference().with_error(format!(
-                        "Expected either {} or {}, but got: {}.\n{err}",
-                        substitute_flexible(Type::Int),
-                        substitute_flexible(Type::Fe),
-                        substitute_flexible(t)
-                    ))
+                    TypeError::other(
+                        expr.source_reference(),
+                        format!(
+                            "Expected either {} or {}, but got: {}.\n{}",
+                            substitute_flexible(Type::Int),
+                            substitute_flexible(Type::Fe),
+                            substitute_flexible(t),
+                            err.message()
+                        ),
+                    )
                 }),
         }
     }
@@ -327,7 +380,7 @@ impl TypeChecker {
         definitions: &mut HashMap<String, (Option<TypeScheme>, Option<&mut Expression>)>,
         expressions: &mut [(&mut Expression, ExpectedType)],
         type_var_mapping: &HashMap<String, HashMap<String, Type>>,
-    ) -> Result<(), Vec<Error>> {
+    ) -> Result<(), Vec<TypeError>> {
         let mut errors = vec![];
         definitions
             .iter_mut()
@@ -362,7 +415,7 @@ impl TypeChecker {
         &self,
         e: &mut Expression,
         type_var_mapping: &HashMap<String, Type>,
-    ) -> Result<(), Error> {
+    ) -> Result<(), TypeError> {
         match e {
             Expression::Number(
                 source_ref,
@@ -380,9 +433,10 @@ impl TypeChecker {
                         .contained_type_vars()
                         .all(|tv| type_var_mapping.contains_key(tv))
                     {
-                        return Err(source_ref.with_error(format!(
-                            "Unable to derive concrete type for literal {n}."
-                        )));
+                        return Err(TypeError::other(
+                            source_ref,
+                            format!("Unable to derive concrete type for literal {n}."),
+                        ));
                     }
                     // Rename type vars (hopefully just a single one) to match the declaration scheme.
                     ty.substitute_type_vars(type_var_mapping);
@@ -409,9 +463,12 @@ impl TypeChecker {
             ) => {
                 for ty in type_args.as_mut().unwrap() {
                     if !self.update_local_type(ty, type_var_mapping) {
-                        return Err(source_ref.with_error(format!(
+                        return Err(TypeError::other(
+                            source_ref,
+                            format!(
                             "Unable to derive concrete type for reference to generic symbol {name}"
-                        )));
+                        ),
+                        ));
                     }
                 }
             }
@@ -431,9 +488,12 @@ impl TypeChecker {
                         }) => {
                             if !self.update_local_type(ty.as_mut().unwrap(), type_var_mapping) {
                                 // TODO better source ref
-                                return Err(source_ref.with_error(format!(
+                                return Err(TypeError::other(
+                                    source_ref,
+                                    format!(
                                     "Unable to derive concrete type for local declaration {pattern}"
-                                )));
+                                ),
+                                ));
                             }
                         }
                         StatementInsideBlock::Expression(_) => {}
@@ -460,22 +520,12 @@ impl TypeChecker {
     }
 
     /// Type-checks the isolated expressions.
-    fn check_expressions(
-        &mut self,
-        expressions: &mut [(&mut Expression, ExpectedType)],
-    ) -> Result<(), Error> {
-        for (e, expected_type) in expressions {
-            self.expect_type_with_flexibility(expected_type, e)?;
-        }
-        Ok(())
-    }
-
     /// Process an expression, inferring its type and expecting either a certain type or potentially an array of that type.
     fn expect_type_with_flexibility(
         &mut self,
         expected_type: &ExpectedType,
         expr: &mut Expression,
-    ) -> Result<(), Error> {
+    ) -> Result<(), TypeError> {
         update_type_if_literal(expr, &expected_type.ty);
 
         let ty = self.infer_type_of_expression(expr)?;
@@ -493,17 +543,11 @@ impl TypeChecker {
 
         self.unifier
             .unify_types(ty.clone(), expected_type.clone())
-            .map_err(|err| {
-                expr.source_reference().with_error(format!(
-                    "Expected type {} but got type {}.\n{err}",
-                    self.format_type_with_bounds(expected_type),
-                    self.format_type_with_bounds(ty),
-                ))
-            })
+            .map_err(|err| err.with_span(expr.source_reference()))
     }
 
     /// Process an expression and return the type of the expression.
-    fn infer_type_of_expression(&mut self, e: &mut Expression) -> Result<Type, Error> {
+    fn infer_type_of_expression(&mut self, e: &mut Expression) -> Result<Type, TypeError> {
         Ok(match e {
             Expression::Reference(_, Reference::LocalVar(id, _name)) => self.local_var_type(*id),
             Expression::Reference(
@@ -517,18 +561,21 @@ impl TypeChecker {
                 let (ty, args) = self.instantiate_scheme(self.declared_types[name].1.clone());
                 if let Some(requested_type_args) = type_args {
                     if requested_type_args.len() != args.len() {
-                        return Err(source_ref.with_error(format!(
-                            "Expected {} type arguments for symbol {name}, but got {}: {}",
-                            args.len(),
-                            requested_type_args.len(),
-                            requested_type_args.iter().join(", ")
-                        )));
+                        return Err(TypeError::other(
+                            source_ref,
+                            format!(
+                                "Expected {} type arguments for symbol {name}, but got {}: {}",
+                                args.len(),
+                                requested_type_args.len(),
+                                requested_type_args.iter().join(", ")
+                            ),
+                        ));
                     }
                     for (requested, inferred) in requested_type_args.iter_mut().zip(&args) {
                         requested.substitute_type_vars(&self.declared_type_vars);
                         self.unifier
                             .unify_types(requested.clone(), inferred.clone())
-                            .map_err(|err| source_ref.with_error(err))?;
+                            .map_err(|err| err.with_span(source_ref))?;
                     }
                 }
                 *type_args = Some(args);
@@ -556,7 +603,7 @@ impl TypeChecker {
                 };
                 self.unifier
                     .ensure_bound(&ty, "FromLiteral".to_string())
-                    .map_err(|err| e.source_reference().with_error(err))?;
+                    .map_err(|err| err.with_span(e.source_reference()))?;
                 ty
             }
             Expression::String(_, _) => Type::String,
@@ -608,12 +655,15 @@ impl TypeChecker {
             Expression::BinaryOperation(source_ref, BinaryOperation { left, op, right }) => {
                 // TODO at some point, also store the generic args for operators
                 let fun_type = self.instantiate_scheme(binary_operator_scheme(*op)).0;
+                let operand_spans = [&left, &right].map(|e| e.source_reference().clone());
                 self.infer_type_of_function_call(
                     fun_type,
                     [left, right].into_iter().map(AsMut::as_mut),
-                    || format!("applying operator {op}"),
                     source_ref,
-                )?
+                )
+                .map_err(|err| {
+                    explain_binary_operator_error(*op, err, source_ref, &operand_spans)
+                })?
             }
             Expression::UnaryOperation(source_ref, UnaryOperation { op, expr: inner }) => {
                 // TODO at some point, also store the generic args for operators
@@ -621,7 +671,6 @@ impl TypeChecker {
                 self.infer_type_of_function_call(
                     fun_type,
                     [inner].into_iter().map(AsMut::as_mut),
-                    || format!("applying unary {op}"),
                     source_ref,
                 )?
             }
@@ -638,6 +687,18 @@ impl TypeChecker {
                 self.expect_type(&Type::Int, index)?;
                 result
             }
+            Expression::SliceAccess(_, SliceAccess { array, start, end }) => {
+                // Array lengths are not tracked in types, so the slice
+                // has the same type as the array.
+                let result = Type::Array(ArrayType {
+                    base: Box::new(self.new_type_var()),
+                    length: None,
+                });
+                self.expect_type(&result, array)?;
+                self.expect_type(&Type::Int, start)?;
+                self.expect_type(&Type::Int, end)?;
+                result
+            }
             Expression::FunctionCall(
                 source_ref,
                 FunctionCall {
@@ -646,14 +707,13 @@ impl TypeChecker {
                 },
             ) => {
                 let ft = self.infer_type_of_expression(function)?;
-                self.infer_type_of_function_call(
-                    ft,
-                    arguments.iter_mut(),
-                    || format!("calling function {function}"),
-                    source_ref,
-                )?
+                self.infer_type_of_function_call(ft, arguments.iter_mut(), source_ref)?
             }
             Expression::FreeInput(_, _) => todo!(),
+            Expression::ArrayComprehension(_, _) => {
+                unreachable!("Array comprehensions are desugared during analysis.")
+            }
+            Expression::Cast(_, _) => unreachable!("Casts are desugared during analysis."),
             Expression::MatchExpression(_, MatchExpression { scrutinee, arms }) => {
                 let scrutinee_type = self.infer_type_of_expression(scrutinee)?;
                 let result = self.new_type_var();
@@ -689,7 +749,7 @@ impl TypeChecker {
                                 (Some(ty), None) => {
                                     if *ty != Type::Col {
                                         // TODO better source ref
-                                        return Err(source_ref.with_error("Let-declared variables without value must have type 'col'.".to_string()));
+                                        return Err(TypeError::other(source_ref, "Let-declared variables without value must have type 'col'.".to_string()));
                                     }
                                 }
                                 (None, Some(value)) => {
@@ -732,9 +792,8 @@ impl TypeChecker {
         &mut self,
         function_type: Type,
         arguments: impl ExactSizeIterator<Item = &'b mut Expression>,
-        error_message: impl FnOnce() -> String,
         source_ref: &SourceRef,
-    ) -> Result<Type, Error> {
+    ) -> Result<Type, TypeError> {
         let arguments = arguments.collect::<Vec<_>>();
         let params = (0..arguments.len())
             .map(|_| self.new_type_var())
@@ -745,16 +804,8 @@ impl TypeChecker {
             value: Box::new(result_type.clone()),
         });
         self.unifier
-            .unify_types(function_type.clone(), expected_function_type.clone())
-            .map_err(|err| {
-                source_ref.with_error(format!(
-                    "Expected function of type `{}`, but got `{}` when {} on ({}):\n{err}",
-                    self.format_type_with_bounds(expected_function_type),
-                    self.format_type_with_bounds(function_type),
-                    error_message(),
-                    arguments.iter().format(", ")
-                ))
-            })?;
+            .unify_types(function_type, expected_function_type)
+            .map_err(|err| err.with_span(source_ref))?;
 
         for (arg, param) in arguments.into_iter().zip(params) {
             self.expect_type(&param, arg)?;
@@ -765,19 +816,17 @@ impl TypeChecker {
     /// Process the expression and unify it with the given type.
     /// This function should be preferred over `infer_type_of_expression` if an expected type is known
     /// because we can create better error messages.
-    fn expect_type(&mut self, expected_type: &Type, expr: &mut Expression) -> Result<(), Error> {
+    fn expect_type(
+        &mut self,
+        expected_type: &Type,
+        expr: &mut Expression,
+    ) -> Result<(), TypeError> {
         update_type_if_literal(expr, expected_type);
 
         let inferred_type = self.infer_type_of_expression(expr)?;
         self.unifier
             .unify_types(inferred_type.clone(), expected_type.clone())
-            .map_err(|err| {
-                expr.source_reference().with_error(format!(
-                    "Expected type: {}\nInferred type: {}\n{err}",
-                    self.format_type_with_bounds(expected_type.clone()),
-                    self.format_type_with_bounds(inferred_type)
-                ))
-            })
+            .map_err(|err| err.with_span(expr.source_reference()))
     }
 
     /// Type-checks a pattern and adds local variables.
@@ -785,21 +834,41 @@ impl TypeChecker {
         &mut self,
         expected_type: &Type,
         pattern: &Pattern,
-    ) -> Result<(), Error> {
+    ) -> Result<(), TypeError> {
+        // If the expected type is already known to be a tuple, match the items
+        // one by one so that errors point to the innermost pattern.
+        if let Pattern::Tuple(source_ref, items) = pattern {
+            if let Type::Tuple(TupleType {
+                items: expected_items,
+            }) = self.type_into_substituted(expected_type.clone())
+            {
+                if items.len() != expected_items.len() {
+                    return Err(TypeError::other(
+                        source_ref,
+                        format!(
+                            "Tuple pattern {pattern} has {} items, but the value has type {} with {} items.",
+                            items.len(),
+                            Type::Tuple(TupleType {
+                                items: expected_items.clone()
+                            }),
+                            expected_items.len()
+                        ),
+                    ));
+                }
+                return items
+                    .iter()
+                    .zip(&expected_items)
+                    .try_for_each(|(item, ty)| self.expect_type_of_pattern(ty, item));
+            }
+        }
         let inferred_type = self.infer_type_of_pattern(pattern)?;
         self.unifier
             .unify_types(inferred_type.clone(), expected_type.clone())
-            .map_err(|err| {
-                pattern.source_reference().with_error(format!(
-                    "Error checking pattern:\nExpected type: {}\nInferred type: {}\n{err}",
-                    self.format_type_with_bounds(expected_type.clone()),
-                    self.format_type_with_bounds(inferred_type)
-                ))
-            })
+            .map_err(|err| err.with_span(pattern.source_reference()))
     }
 
     /// Type-checks a pattern and adds local variables.
-    fn infer_type_of_pattern(&mut self, pattern: &Pattern) -> Result<Type, Error> {
+    fn infer_type_of_pattern(&mut self, pattern: &Pattern) -> Result<Type, TypeError> {
         Ok(match pattern {
             Pattern::Ellipsis(_) => unreachable!("Should be handled higher up."),
             Pattern::CatchAll(_) => self.new_type_var(),
@@ -807,7 +876,7 @@ impl TypeChecker {
                 let ty = self.new_type_var();
                 self.unifier
                     .ensure_bound(&ty, "FromLiteral".to_string())
-                    .map_err(|e| source_ref.with_error(e))?;
+                    .map_err(|err| err.with_span(source_ref))?;
                 ty
             }
             Pattern::String(_, _) => Type::String,
@@ -844,19 +913,23 @@ impl TypeChecker {
                 match data {
                     Some(data) => {
                         let Type::Function(FunctionType { params, value }) = ty else {
-                            return Err(source_ref.with_error(if matches!(ty, Type::NamedType(_, _)) {
-                                format!("Enum variant {name} does not have fields, but is used with parentheses in pattern.")
-                            } else {
-                                format!("Expected enum variant for pattern but got {ty}")
-                            }));
+                            return Err(TypeError::other(
+                                source_ref,
+                                if matches!(ty, Type::NamedType(_, _)) {
+                                    format!("Enum variant {name} does not have fields, but is used with parentheses in pattern.")
+                                } else {
+                                    format!("Expected enum variant for pattern but got {ty}")
+                                },
+                            ));
                         };
                         if !matches!(value.as_ref(), Type::NamedType(_, _)) {
-                            return Err(source_ref.with_error(format!(
-                                "Expected enum variant for pattern but got {value}"
-                            )));
+                            return Err(TypeError::other(
+                                source_ref,
+                                format!("Expected enum variant for pattern but got {value}"),
+                            ));
                         }
                         if params.len() != data.len() {
-                            return Err(source_ref.with_error(format!(
+                            return Err(TypeError::other(source_ref, format!(
                                 "Invalid number of data fields for enum variant {name}. Expected {} but got {}.",
                                 params.len(),
                                 data.len()
@@ -872,13 +945,14 @@ impl TypeChecker {
                         if let Type::NamedType(_, _) = ty {
                             ty
                         } else if matches!(ty, Type::Function(_)) {
-                            return Err(source_ref.with_error(format!(
+                            return Err(TypeError::other(source_ref, format!(
                                 "Expected enum variant for pattern but got {ty} - maybe you forgot the parentheses?"
                             )));
                         } else {
-                            return Err(source_ref.with_error(format!(
-                                "Expected enum variant for pattern but got {ty}"
-                            )));
+                            return Err(TypeError::other(
+                                source_ref,
+                                format!("Expected enum variant for pattern but got {ty}"),
+                            ));
                         }
                     }
                 }
@@ -891,14 +965,14 @@ impl TypeChecker {
     fn verify_type_schemes(
         &self,
         inferred_types: HashMap<String, Type>,
-    ) -> Result<HashMap<String, HashMap<String, Type>>, Error> {
-        inferred_types.into_iter().map(|(name, inferred_type)| {
+    ) -> Result<HashMap<String, HashMap<String, Type>>, Vec<TypeError>> {
+        let (mappings, errors): (HashMap<_, _>, Vec<_>) = inferred_types.into_iter().sorted_by(|(a, _), (b, _)| a.cmp(b)).map(|(name, inferred_type)| {
             let (source_ref, declared_type) = self.declared_types[&name].clone();
             let inferred_type = self.type_into_substituted(inferred_type.clone());
             let inferred = self.to_type_scheme(inferred_type.clone());
             let declared = declared_type.clone().simplify_type_vars();
             if inferred != declared {
-                return Err(source_ref.with_error(format!(
+                return Err(TypeError::other(&source_ref, format!(
                     "Inferred type scheme for symbol {name} does not match the declared type.\nInferred: let{}\nDeclared: let{}",
                     format_type_scheme_around_name(&name, &Some(inferred)),
                     format_type_scheme_around_name(&name, &Some(declared_type),
@@ -913,7 +987,12 @@ impl TypeChecker {
                     .zip(declared_type_vars.into_iter().map(|tv| Type::TypeVar(tv.clone())))
                     .collect(),
             ))
-        }).collect::<Result<_, Error>>()
+        }).partition_result();
+        if errors.is_empty() {
+            Ok(mappings)
+        } else {
+            Err(errors)
+        }
     }
 
     fn type_into_substituted(&self, mut ty: Type) -> Type {
@@ -948,17 +1027,6 @@ impl TypeChecker {
         (ty, vars)
     }
 
-    fn format_type_with_bounds(&self, ty: Type) -> String {
-        let scheme = self.to_type_scheme(ty);
-        if scheme.vars.is_empty() {
-            format!("{}", scheme.ty)
-        } else if let Type::TypeVar(_) = &scheme.ty {
-            format!("{}", scheme.vars)
-        } else {
-            format!("{}, {}", scheme.ty, scheme.vars)
-        }
-    }
-
     fn new_type_var_name(&mut self) -> String {
         self.last_type_var += 1;
         format!("T{}", self.last_type_var)
@@ -1013,3 +1081,59 @@ fn update_type_if_literal(
         };
     }
 }
+
+/// Replaces errors that are common mistakes when using the binary operator `op`
+/// by a more helpful error located at the whole operation. Only errors located
+/// at one of the operands (given by `operand_spans`) are replaced, since errors
+/// further inside the operands are unrelated to the operator.
+fn explain_binary_operator_error(
+    op: BinaryOperator,
+    err: TypeError,
+    span: &SourceRef,
+    operand_spans: &[SourceRef; 2],
+) -> TypeError {
+    if !operand_spans.contains(err.span()) {
+        return err;
+    }
+    match (op, &err) {
+        (
+            BinaryOperator::Less
+            | BinaryOperator::LessEqual
+            | BinaryOperator::Greater
+            | BinaryOperator::GreaterEqual,
+            TypeError::UnboundBound {
+                var: Type::Fe,
+                bound,
+                ..
+            },
+        ) if bound == "Ord" => TypeError::other(
+            span,
+            format!("Operator {op} cannot be applied to fe: field elements are not ordered; convert to int first."),
+        ),
+        (
+            BinaryOperator::Add | BinaryOperator::Sub | BinaryOperator::Mul,
+            TypeError::Mismatch {
+                expected,
+                found,
+                span: err_span,
+            },
+        ) if matches!(
+            (expected.as_ref(), found.as_ref()),
+            (Type::Int, Type::Fe) | (Type::Fe, Type::Int)
+        ) =>
+        {
+            // The type of the left operand is determined first, so it is the expected
+            // type if the error is located at the right operand.
+            let (left, right) = if err_span == &operand_spans[1] {
+                (expected, found)
+            } else {
+                (found, expected)
+            };
+            TypeError::other(
+                span,
+                format!("Operator {op} cannot be applied to {left} and {right}; use fe(...) or int(...) to convert."),
+            )
+        }
+        _ => err,
+    }
+}
@@ -21,7 +21,8 @@ impl<'a, D: AnalysisDriver> TypeProcessor<'a, D> {
 
     /// Processes a type name by evaluating array lengths, changing named type references to type
     /// variables to actual type variables and resolving references to named types.
-    pub fn process_type(&self, ty: Type<Expression>) -> Type {
+    /// Returns an error if a named type cannot be resolved.
+    pub fn process_type(&self, ty: Type<Expression>) -> Result<Type, String> {
         let ty = self.evaluate_array_lengths(ty.clone())
             .map_err(|e| panic!("Error evaluating expressions in type name \"{ty}\" to reduce it to a type:\n{e})"))
             .unwrap();
@@ -29,14 +30,39 @@ impl<'a, D: AnalysisDriver> TypeProcessor<'a, D> {
     }
 
     /// Processes a type name by changing named type references to type variables to actual type
-    /// variables and resolving references to named types.
-    pub fn process_number_type(&self, mut ty: Type<u64>) -> Type {
+    /// variables, resolving references to named types and replacing type aliases by the
+    /// aliased types.
+    /// Returns an error if a named type cannot be resolved.
+    pub fn process_number_type(&self, mut ty: Type<u64>) -> Result<Type, String> {
         ty.map_to_type_vars(self.type_vars);
-        ty.contained_named_types_mut().for_each(|n| {
-            let name = self.driver.resolve_type_ref(n);
+        ty.contained_named_types_mut().try_for_each(|n| {
+            let name = self.driver.resolve_type_ref(n)?;
             *n = SymbolPath::from_str(&name).unwrap();
-        });
-        ty
+            Ok::<_, String>(())
+        })?;
+        self.expand_type_aliases(&mut ty)?;
+        Ok(ty)
+    }
+
+    /// Replaces all references to type aliases by the aliased types. Since aliases
+    /// have to be declared before they are used, the aliased types do not contain
+    /// aliases themselves and aliases cannot be recursive.
+    fn expand_type_aliases(&self, ty: &mut Type) -> Result<(), String> {
+        if let Type::NamedType(name, args) = ty {
+            if let Some(alias) = self.driver.type_alias(&name.to_dotted_string()) {
+                let Some(alias) = alias else {
+                    return Err(format!(
+                        "Type alias {name} is used before its declaration. Type aliases have to be declared before they are used and cannot be recursive."
+                    ));
+                };
+                if args.is_some() {
+                    return Err(format!("Type alias {name} does not take type arguments."));
+                }
+                *ty = alias.clone();
+                return Ok(());
+            }
+        }
+        Children::<Type>::children_mut(ty).try_for_each(|t| self.expand_type_aliases(t))
     }
 
     /// Turns a Type<Expression> to a Type<u64> by evaluating the array length expressions.
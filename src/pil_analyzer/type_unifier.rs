@@ -1,6 +1,8 @@
 use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Display, Formatter};
 
 use crate::ast::parsed::{types::Type, visitor::Children};
+use crate::parser_util::SourceRef;
 
 use crate::pil_analyzer::type_builtins::elementary_type_bounds;
 
@@ -13,12 +15,96 @@ use crate::pil_analyzer::type_builtins::elementary_type_bounds;
 // This way we can exit early if we know that all contained type vars are substituted.
 // This might be especially useful inside add_substitution.
 
+/// An error found during type inference.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeError {
+    /// The type `found` was encountered where a type compatible with `expected`
+    /// was required. `span` is the innermost expression whose type does not match.
+    Mismatch {
+        expected: Box<Type>,
+        found: Box<Type>,
+        span: SourceRef,
+    },
+    /// The type `var` does not satisfy the trait bound `bound`.
+    UnboundBound {
+        var: Type,
+        bound: String,
+        span: SourceRef,
+    },
+    /// The type variable `var` would have to be substituted by `ty`,
+    /// which contains `var` itself.
+    Recursive {
+        var: String,
+        ty: Type,
+        span: SourceRef,
+    },
+    /// Any other error, for example an unknown enum variant.
+    Other { message: String, span: SourceRef },
+}
+
+impl TypeError {
+    pub fn other(span: &SourceRef, message: String) -> Self {
+        TypeError::Other {
+            message,
+            span: span.clone(),
+        }
+    }
+
+    pub fn span(&self) -> &SourceRef {
+        match self {
+            TypeError::Mismatch { span, .. }
+            | TypeError::UnboundBound { span, .. }
+            | TypeError::Recursive { span, .. }
+            | TypeError::Other { span, .. } => span,
+        }
+    }
+
+    /// Returns the same error, but located at `source_ref`.
+    pub fn with_span(mut self, source_ref: &SourceRef) -> Self {
+        match &mut self {
+            TypeError::Mismatch { span, .. }
+            | TypeError::UnboundBound { span, .. }
+            | TypeError::Recursive { span, .. }
+            | TypeError::Other { span, .. } => *span = source_ref.clone(),
+        }
+        self
+    }
+
+    /// Returns the error message without source reference.
+    pub fn message(&self) -> String {
+        match self {
+            TypeError::Mismatch {
+                expected, found, ..
+            } => format!("Expected type {expected} but got type {found}."),
+            TypeError::UnboundBound { var, bound, .. } => {
+                format!("Type {var} does not satisfy trait {bound}.")
+            }
+            TypeError::Recursive { var, ty, .. } => {
+                format!("Cannot unify types {ty} and {var}: They depend on each other")
+            }
+            TypeError::Other { message, .. } => message.clone(),
+        }
+    }
+
+    pub fn output_to_stderr(&self) {
+        self.span().with_error(self.message()).output_to_stderr()
+    }
+}
+
+impl Display for TypeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.span(), self.message())
+    }
+}
+
 #[derive(Default, Clone)]
 pub struct Unifier {
     /// Inferred type constraints (traits) on type variables.
     type_var_bounds: HashMap<String, HashSet<String>>,
     /// Substitutions for type variables
     substitutions: HashMap<String, Type>,
+    /// Traits implemented by user-defined named types, by absolute type name.
+    named_type_bounds: HashMap<String, HashSet<String>>,
 }
 
 impl Unifier {
@@ -33,7 +119,16 @@ impl Unifier {
             .unwrap_or_default()
     }
 
-    pub fn ensure_bound(&mut self, ty: &Type, bound: String) -> Result<(), String> {
+    /// Records that the named type `type_name` (without type arguments) satisfies `bound`.
+    pub fn add_named_type_bound(&mut self, type_name: String, bound: String) {
+        self.named_type_bounds
+            .entry(type_name)
+            .or_default()
+            .insert(bound);
+    }
+
+    /// Ensures that `ty` satisfies `bound`. The span of a returned error is unknown.
+    pub fn ensure_bound(&mut self, ty: &Type, bound: String) -> Result<(), TypeError> {
         let ty = (if let Type::TypeVar(n) = ty {
             self.substitutions.get(n)
         } else {
@@ -51,21 +146,34 @@ impl Unifier {
                     self.ensure_bound(c, "ToString".to_string())?;
                 }
             }
-            Type::NamedType(n, _) => {
-                // Change this as soon as we support user-implemented traits.
-                return Err(format!("Type {n} does not satisfy trait {bound}."));
+            Type::NamedType(name, None)
+                if self
+                    .named_type_bounds
+                    .get(&name.to_dotted_string())
+                    .is_some_and(|bounds| bounds.contains(&bound)) => {}
+            Type::NamedType(..) => {
+                return Err(TypeError::UnboundBound {
+                    var: ty.clone(),
+                    bound,
+                    span: SourceRef::unknown(),
+                });
             }
             _ => {
                 let bounds = elementary_type_bounds(ty);
                 if !bounds.contains(&bound.as_str()) {
-                    return Err(format!("Type {ty} does not satisfy trait {bound}."));
+                    return Err(TypeError::UnboundBound {
+                        var: ty.clone(),
+                        bound,
+                        span: SourceRef::unknown(),
+                    });
                 }
             }
         }
         Ok(())
     }
 
-    pub fn unify_types(&mut self, mut inner: Type, mut expected: Type) -> Result<(), String> {
+    /// Unifies `inner` with `expected`. The span of a returned error is unknown.
+    pub fn unify_types(&mut self, mut inner: Type, mut expected: Type) -> Result<(), TypeError> {
         inner.substitute_type_vars(&self.substitutions);
         expected.substitute_type_vars(&self.substitutions);
 
@@ -82,26 +190,22 @@ impl Unifier {
             }
             (Type::Function(f1), Type::Function(f2)) => {
                 if f1.params.len() != f2.params.len() {
-                    return Err(format!(
-                        "Function types have different number of parameters: {f1} and {f2}"
-                    ));
+                    return Err(mismatch(Type::Function(f2), Type::Function(f1)));
                 }
-                for (p1, p2) in f1.params.into_iter().zip(f2.params.into_iter()) {
+                for (p1, p2) in f1.params.into_iter().zip(f2.params) {
                     self.unify_types(p1, p2)?;
                 }
                 self.unify_types(*f1.value, *f2.value)
             }
             (Type::Array(a1), Type::Array(a2)) => {
                 if a1.length != a2.length {
-                    return Err(format!("Array types have different lengths: {a1} and {a2}"));
+                    return Err(mismatch(Type::Array(a2), Type::Array(a1)));
                 }
                 self.unify_types(*a1.base, *a2.base)
             }
             (Type::Tuple(t1), Type::Tuple(t2)) => {
                 if t1.items.len() != t2.items.len() {
-                    return Err(format!(
-                        "Tuple types have different number of items: {t1} and {t2}"
-                    ));
+                    return Err(mismatch(Type::Tuple(t2), Type::Tuple(t1)));
                 }
                 t1.items
                     .into_iter()
@@ -116,7 +220,7 @@ impl Unifier {
                     .zip(args2)
                     .try_for_each(|(a1, a2)| self.unify_types(a1, a2))
             }
-            (ty1, ty2) => Err(format!("Cannot unify types {ty1} and {ty2}")),
+            (ty1, ty2) => Err(mismatch(ty2, ty1)),
         }
     }
 
@@ -127,11 +231,13 @@ impl Unifier {
             .insert(bound);
     }
 
-    fn add_substitution(&mut self, type_var: String, ty: Type) -> Result<(), String> {
+    fn add_substitution(&mut self, type_var: String, ty: Type) -> Result<(), TypeError> {
         if ty.contains_type_var(&type_var) {
-            return Err(format!(
-                "Cannot unify types {ty} and {type_var}: They depend on each other"
-            ));
+            return Err(TypeError::Recursive {
+                var: type_var,
+                ty,
+                span: SourceRef::unknown(),
+            });
         }
         let subs = [(type_var.clone(), ty.clone())].into();
 
@@ -146,3 +252,11 @@ impl Unifier {
         Ok(())
     }
 }
+
+fn mismatch(expected: Type, found: Type) -> TypeError {
+    TypeError::Mismatch {
+        expected: Box::new(expected),
+        found: Box::new(found),
+        span: SourceRef::unknown(),
+    }
+}
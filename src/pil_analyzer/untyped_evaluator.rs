@@ -1,8 +1,12 @@
-use crate::ast::parsed;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::ast::analyzed::{FunctionValueDefinition, Symbol};
+use crate::ast::parsed::{self, types::Type};
 use crate::number::{BigInt, GoldilocksField};
 
 use crate::pil_analyzer::{
-    evaluator::{self, EvalError},
+    evaluator::{self, Definitions, EvalError, SymbolLookup, Value},
     expression_processor::ExpressionProcessor,
     AnalysisDriver,
 };
@@ -12,13 +16,37 @@ use crate::pil_analyzer::{
 
 /// Evaluates an expression that has not been type-checked.
 /// This is mainly used to evaluate array lengths in types and namespace degrees.
+/// Since such expressions can be evaluated several times and independently of
+/// the order of the statements, output of `std::debug::print` is suppressed.
 pub fn evaluate_expression_to_int(
     driver: impl AnalysisDriver,
     expr: parsed::Expression,
 ) -> Result<BigInt, EvalError> {
-    evaluator::evaluate_expression::<GoldilocksField>(
-        &ExpressionProcessor::new(driver, &Default::default()).process_expression(expr),
-        driver.definitions(),
-    )?
-    .try_to_integer()
+    let expr = ExpressionProcessor::new(driver, &Default::default())
+        .process_expression(expr)
+        .map_err(|e| EvalError::SymbolNotFound(e.message().to_string()))?;
+    evaluator::evaluate::<GoldilocksField>(&expr, &mut SilentDefinitions(driver.definitions()))?
+        .try_to_integer()
+}
+
+/// Symbol lookup like [`Definitions`] that ignores output of `std::debug::print`.
+struct SilentDefinitions<'a>(&'a HashMap<String, (Symbol, Option<FunctionValueDefinition>)>);
+
+impl<'a> SymbolLookup<'a, GoldilocksField> for SilentDefinitions<'a> {
+    fn lookup(
+        &mut self,
+        name: &str,
+        type_args: Option<Vec<Type>>,
+    ) -> Result<Arc<Value<'a, GoldilocksField>>, EvalError> {
+        Definitions::lookup_with_symbols(self.0, name, type_args, self)
+    }
+
+    fn lookup_public_reference(
+        &self,
+        name: &str,
+    ) -> Result<Arc<Value<'a, GoldilocksField>>, EvalError> {
+        Definitions(self.0).lookup_public_reference(name)
+    }
+
+    fn print(&mut self, _message: &str) {}
 }
@@ -4,7 +4,8 @@
 let<T> len: T[] -> int = [];
 
 /// Evaluates to the array [f(0), f(1), ..., f(length - 1)].
-let<T> new: int, (int -> T) -> T[] = |length, f| std::utils::fold(length, f, [], |acc, e| (acc + [e]));
+/// This is a built-in function, the actual semantics are overridden.
+let<T> new: int, (int -> T) -> T[] = [];
 
 /// Returns a new array equal to arr except that the element at index i is x.
 let<T> set_element: T[], int, T -> T[] = |arr, i, x| {
@@ -16,25 +17,74 @@ let<T> set_element: T[], int, T -> T[] = |arr, i, x| {
 let<T> sub_array: T[], int, int -> T[] = |arr, start, l| std::array::new(l, |i| arr[start + i]);
 
 /// Evaluates to the array [f(arr[0]), f(arr[1]), ..., f(arr[len(arr) - 1])].
-let<T1, T2> map: T1[], (T1 -> T2) -> T2[] = |arr, f| new(len(arr), |i| f(arr[i]));
+/// This is a built-in function, the actual semantics are overridden.
+let<T1, T2> map: T1[], (T1 -> T2) -> T2[] = [];
 
 /// Evaluates to the array [f(0, arr[0]), f(1, arr[1]), ..., f(len(arr) - 1, arr[len(arr) - 1])].
 let<T1, T2> map_enumerated: T1[], (int, T1 -> T2) -> T2[] = |arr, f| new(len(arr), |i| f(i, arr[i]));
 
 /// Computes folder(...folder(folder(initial, arr[0]), arr[1]) ..., arr[len(arr) - 1])
-let<T1, T2> fold: T1[], T2, (T2, T1 -> T2) -> T2 = |arr, initial, folder| std::utils::fold(len(arr), |i| arr[i], initial, folder);
+/// This is a built-in function, the actual semantics are overridden.
+let<T1, T2> fold: T1[], T2, (T2, T1 -> T2) -> T2 = [];
 
-/// Returns the sum of the array elements.
-// TODO: Should make use of the Default or Zero trait instead of FromLiteral (then we can also
-// use this function to flatten an array of arrays.
-let<T: Add + FromLiteral> sum: T[] -> T = |arr| fold(arr, 0, |a, b| a + b);
+/// Returns the sum of the array elements. The array must not be empty, which is
+/// checked statically where its length is known. Use `sum_with_zero` for possibly empty arrays.
+/// This is a built-in function, the actual semantics are overridden.
+let<T: Add> sum: T[] -> T = [];
+
+/// Returns the sum of `zero` and the array elements, i.e. `zero` for an empty array.
+let<T: Add> sum_with_zero: T[], T -> T = |arr, zero| fold(arr, zero, |a, b| a + b);
 
 /// Returns the product of the array elements.
 let<T: Mul + FromLiteral> product: T[] -> T = |arr| fold(arr, 1, |a, b| a * b);
 
-/// Zips two arrays
-/// TODO: Assert that lengths are equal when expressions are supported.
-let<T1, T2, T3> zip: T1[], T2[], (T1, T2 -> T3) -> T3[] = |array1, array2, fn| new(len(array1), |i| fn(array1[i], array2[i]));
+/// Zips two arrays of the same length.
+/// This is a built-in function, the actual semantics are overridden.
+let<T1, T2, T3> zip: T1[], T2[], (T1, T2 -> T3) -> T3[] = [];
+
+/// Returns the elementwise sum [a[0] + b[0], ..., a[len(a) - 1] + b[len(b) - 1]]
+/// of two arrays of the same length. Note that `a + b` concatenates the arrays instead.
+/// Fails if the arrays have different lengths.
+let<T: Add> add: T[], T[] -> T[] = |a, b| zip(a, b, |x, y| x + y);
+
+/// Returns the elementwise difference [a[0] - b[0], ..., a[len(a) - 1] - b[len(b) - 1]]
+/// of two arrays of the same length. Fails if the arrays have different lengths.
+let<T: Sub> sub: T[], T[] -> T[] = |a, b| zip(a, b, |x, y| x - y);
+
+/// Returns the elementwise product [a[0] * b[0], ..., a[len(a) - 1] * b[len(b) - 1]]
+/// of two arrays of the same length. Fails if the arrays have different lengths.
+let<T: Mul> mul: T[], T[] -> T[] = |a, b| zip(a, b, |x, y| x * y);
+
+/// Returns true if one of the elements of the array is equal to x.
+/// This is a built-in function, the actual semantics are overridden.
+let<T: Eq> contains: T[], T -> bool = [];
+
+/// Returns the first index i where f(arr[i]) is true, or -1 if no such i exists.
+/// Unlike find_index, this stops calling f once it returned true.
+/// This is a built-in function, the actual semantics are overridden.
+let<T> find: T[], (T -> bool) -> int = [];
+
+/// Concatenates the arrays arr[0], arr[1], ..., arr[len(arr) - 1] into a single array.
+/// This is a built-in function, the actual semantics are overridden.
+let<T> flatten: T[][] -> T[] = [];
+
+/// Returns the array [arr[len(arr) - 1], ..., arr[1], arr[0]].
+/// This is a built-in function, the actual semantics are overridden.
+let<T> reverse: T[] -> T[] = [];
+
+/// Converts an array of length 2 (3, 4) into a tuple with the same elements, i.e.
+/// to_tuple3([a, b, c]) is (a, b, c). Fails if the array has a different length.
+/// This is a built-in function, the actual semantics are overridden.
+let<T> to_tuple2: T[] -> (T, T) = [];
+let<T> to_tuple3: T[] -> (T, T, T) = [];
+let<T> to_tuple4: T[] -> (T, T, T, T) = [];
+
+/// Converts a tuple into an array with the same elements, i.e.
+/// from_tuple3((a, b, c)) is [a, b, c].
+/// This is a built-in function, the actual semantics are overridden.
+let<T> from_tuple2: (T, T) -> T[] = [];
+let<T> from_tuple3: (T, T, T) -> T[] = [];
+let<T> from_tuple4: (T, T, T, T) -> T[] = [];
 
 /// Returns f(i, arr[i]) for the first i where this is not None, or None if no such i exists.
 let<T1, T2> find_map_enumerated: T1[], (int, T1 -> Option<T2>) -> Option<T2> =
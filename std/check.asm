@@ -9,4 +9,11 @@ let panic: string -> ! = [];
 /// prover-internal consistency.
 /// The panic message is obtained by calling the function `reason`.
 /// Returns an empty array on success, which allows it to be used at statement level.
-let assert: bool, (-> string) -> () = |condition, reason| if !condition { panic(reason()) } else { () };
\ No newline at end of file
+let assert: bool, (-> string) -> () = |condition, reason| if !condition { panic(reason()) } else { () };
+
+/// This is a built-in function taking a condition and a string argument.
+/// Like `assert`, it terminates evaluation unsuccessfully with the string as
+/// explanation if the condition is false, but the message is passed directly.
+/// Returns an empty tuple on success, which allows it to be used at statement level.
+/// This symbol is not an empty array, the actual semantics are overridden.
+let require: bool, string -> () = [];
\ No newline at end of file
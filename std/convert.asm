@@ -1,14 +1,36 @@
 /// A function that takes a field element or integer and converts
 /// it to a (non-negative) integer.
+/// `e as int` is a shorthand for `std::convert::int(e)`.
 /// The actual implementation is replaced by a built-in function.
 let int = [];
 
-/// A function that takes a field element or integer and converts
+/// A function that takes a field element, integer or expression and converts
 /// it to a field element.
 /// Panics if the input is negative or larger or equal to the field modulus.
+/// Expressions are evaluated at analysis time and have to be constant,
+/// i.e. they cannot reference columns, public values or challenges.
+/// `e as fe` is a shorthand for `std::convert::fe(e)`.
 /// The actual implementation is replaced by a built-in function.
 let fe = [];
 
 /// A function that takes an integer or a field element and converts it to an expression.
+/// `e as expr` is a shorthand for `std::convert::expr(e)`.
+/// The actual implementation is replaced by a built-in function.
+let expr = [];
+
+/// A function that takes an integer and converts it to a bool:
+/// Zero is converted to false and all other values to true.
+/// This is the inverse of `|b| if b { 1 } else { 0 }` on the values 0 and 1.
+/// The actual implementation is replaced by a built-in function.
+let bool: int -> bool = [];
+
+/// Returns the bit decomposition of a non-negative integer as an array of
+/// the given length, least significant bit first.
+/// Panics if the value does not fit into the given number of bits.
+/// The actual implementation is replaced by a built-in function.
+let to_bits: int, int -> int[] = [];
+
+/// A function that converts a value to a string, in the same way as `std::debug::print`
+/// prints it. Strings are returned unchanged.
 /// The actual implementation is replaced by a built-in function.
-let expr = [];
\ No newline at end of file
+let<T: ToString> to_string: T -> string = [];
@@ -2,6 +2,14 @@
 /// The actual implementation is replaced by a built-in function.
 let modulus: -> int = [];
 
+/// Returns the characteristic of the field, which is the same as its modulus.
+let characteristic: -> int = modulus;
+
+/// A function that returns the number of bits of the field modulus,
+/// i.e. the smallest `n` such that `modulus() <= 2**n`.
+/// The actual implementation is replaced by a built-in function.
+let bits: -> int = [];
+
 let GOLDILOCKS_PRIME: int = 0xffffffff00000001;
 let BN254_PRIME: int = 0x30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001;
 
@@ -0,0 +1,7 @@
+/// Replaces each occurrence of `{}` in the template by the next element of the
+/// array, converted to a string as by `std::convert::to_string`.
+/// Fails if the number of placeholders and arguments differ.
+/// Values of different types can be combined by converting them first:
+/// `format("{} at row {}", [name, std::convert::to_string(i)])`.
+/// The actual implementation is replaced by a built-in function.
+let<T: ToString> format: string, T[] -> string = [];
@@ -12,6 +12,10 @@ let inverse = |x, modulus|
         reduce(r, modulus)
     };
 
+/// Inverts the non-zero field element `x` in the native field.
+/// This is a built-in function, the actual semantics are overridden.
+let inv_field: fe -> fe = [];
+
 /// Computes `x + y` modulo the modulus.
 let add = |x, y, modulus| reduce(x + y, modulus);
 
@@ -4,6 +4,7 @@ mod check;
 mod convert;
 mod debug;
 mod field;
+mod fmt;
 mod machines;
 mod math;
 mod prelude;
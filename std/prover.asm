@@ -23,10 +23,30 @@ enum Query {
 let challenge: int, int -> expr = constr |st, id| std::prelude::challenge(st, id);
 
 /// Returns the current number of rows, sometimes known as the "degree".
+/// Fails if the namespace declares a range of degrees.
 let degree: -> int = [];
 
+/// Returns the smallest degree of the current namespace.
+/// This is the same as `degree()` unless the namespace declares a range of degrees.
+let min_degree: -> int = [];
+
+/// Returns the largest degree of the current namespace.
+/// This is the same as `degree()` unless the namespace declares a range of degrees.
+let max_degree: -> int = [];
+
 /// Asserts that the current degree or row count is at least m.
-let require_min_degree: int -> () = |m| std::check::assert(degree() >= m, || "Degree too small.");
+let require_min_degree: int -> () = |m| std::check::assert(min_degree() >= m, || "Degree too small.");
 
 /// Asserts that the current degree or row count is at most m;
-let require_max_degree: int -> () = |m| std::check::assert(degree() <= m, || "Degree too large.");
\ No newline at end of file
+let require_max_degree: int -> () = |m| std::check::assert(max_degree() <= m, || "Degree too large.");
+
+/// Creates a new witness column in the current namespace and returns a reference to it.
+/// The column is named after the argument, with a suffix added if the name is already taken.
+/// The actual implementation is replaced by a built-in function.
+let new_witness_col: string -> expr = [];
+
+/// Calls the given function and returns the constraints it emits as an array instead of
+/// adding them, so that they can be applied conditionally or more than once.
+/// Columns created by the function are added as usual.
+/// The actual implementation is replaced by a built-in function.
+let capture_constraints: (-> ()) -> Constr[] = [];
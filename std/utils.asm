@@ -70,4 +70,18 @@ let cross_product_internal: int, int, int[] -> (int -> int)[] = |cycle_len, pos,
     } else {
         [|i| (i / cycle_len) % sizes[pos]] +
             cross_product_internal(cycle_len * sizes[pos], pos + 1, sizes)
-    };
\ No newline at end of file
+    };
+
+/// Returns the array of all pairs `(a, b)` with `a` an element of `left` and `b` an element
+/// of `right`, in row-major order, i.e. `(left[0], right[0]), (left[0], right[1]), ...`.
+/// This is useful to generate the contents of lookup tables.
+/// This is a built-in function, the actual semantics are overridden.
+let<T, U> cartesian_product: T[], U[] -> (T, U)[] = [];
+
+/// Returns true if `n` is a power of two, i.e. `n == 2**k` for some `k >= 0`.
+/// This is a built-in function, the actual semantics are overridden.
+let is_power_of_two: int -> bool = [];
+
+/// Returns the smallest power of two that is at least `n`, i.e. `1` for all `n <= 1`.
+/// This is a built-in function, the actual semantics are overridden.
+let next_power_of_two: int -> int = [];